@@ -0,0 +1,453 @@
+use crate::BoxError;
+use bytes::Bytes;
+use futures_core::Stream;
+use std::{
+    fmt,
+    fs::File,
+    future,
+    io::{self, Read, Seek, SeekFrom},
+    ops::Range,
+    path::{Path, PathBuf},
+    pin::Pin,
+    sync::{Arc, Mutex, PoisonError},
+    task::{Context, Poll},
+};
+
+// How much of a file is read into memory at a time as it's sent.
+const FILE_CHUNK_SIZE: usize = 64 * 1024;
+
+type BoxStream = Pin<Box<dyn Stream<Item = Result<Bytes, BoxError>> + Send>>;
+
+/// The body of a request or a response, as connectors send and receive it: bytes in memory,
+/// part of a file, read a chunk at a time as it's sent, or a stream of chunks, e.g. of a
+/// response as it comes in on its connection. It's a `Stream` of its chunks.
+///
+/// Clones of bodies in memory or in files read them from where they're up to on their own.
+/// Streams can only be read once, so clones of them share them: each chunk goes to whichever
+/// clone asks for it first.
+#[derive(Clone, Default)]
+pub struct Body {
+    inner: Inner,
+}
+
+#[derive(Clone)]
+enum Inner {
+    Bytes(Bytes),
+    File(FileRegion),
+    Stream(Arc<Mutex<SharedStream>>),
+}
+
+impl Default for Inner {
+    fn default() -> Self {
+        Inner::Bytes(Bytes::new())
+    }
+}
+
+// The part of a file that's left to be read, which is opened once it's first read.
+struct FileRegion {
+    path: PathBuf,
+    offset: u64,
+    remaining: u64,
+    file: Option<File>,
+}
+
+// Clones open the file again, so they can read it from where they are.
+impl Clone for FileRegion {
+    fn clone(&self) -> Self {
+        FileRegion {
+            path: self.path.clone(),
+            offset: self.offset,
+            remaining: self.remaining,
+            file: None,
+        }
+    }
+}
+
+impl FileRegion {
+    fn read(&mut self) -> io::Result<Option<Bytes>> {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+        let file = match &mut self.file {
+            Some(file) => file,
+            None => {
+                let mut file = File::open(&self.path)?;
+                file.seek(SeekFrom::Start(self.offset))?;
+                self.file.insert(file)
+            }
+        };
+        let mut chunk = vec![0; FILE_CHUNK_SIZE.min(self.remaining as usize)];
+        let read = file.read(&mut chunk)?;
+        if read == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                format!("`{}` ended before the body did", self.path.display()),
+            ));
+        }
+        chunk.truncate(read);
+        self.offset += read as u64;
+        self.remaining -= read as u64;
+        Ok(Some(Bytes::from(chunk)))
+    }
+}
+
+// A stream, and how many bytes it has left if it's known.
+struct SharedStream {
+    stream: BoxStream,
+    remaining: Option<u64>,
+}
+
+impl Body {
+    pub fn empty() -> Self {
+        Body::default()
+    }
+
+    /// The contents of the file at `path`, as they are when they're read.
+    pub fn from_path(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref();
+        let metadata = path.metadata()?;
+        if !metadata.is_file() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("`{}` isn't a file", path.display()),
+            ));
+        }
+        Ok(Body::from_file_region(
+            path.to_path_buf(),
+            0..metadata.len(),
+        ))
+    }
+
+    // The bytes of the file at `path` in `range`, which has to still be there when they're
+    // read.
+    pub(crate) fn from_file_region(path: PathBuf, range: Range<u64>) -> Self {
+        Body {
+            inner: Inner::File(FileRegion {
+                path,
+                offset: range.start,
+                remaining: range.end.saturating_sub(range.start),
+                file: None,
+            }),
+        }
+    }
+
+    /// The chunks of `stream`, which has `len` bytes in all if that's known, e.g. from a
+    /// response's `Content-Length`. A stream that doesn't have as many as it says fails.
+    pub fn from_stream<S, E>(stream: S, len: Option<u64>) -> Self
+    where
+        S: Stream<Item = Result<Bytes, E>> + Send + 'static,
+        E: Into<BoxError>,
+    {
+        let stream = MapErr(Box::pin(stream));
+        Body {
+            inner: Inner::Stream(Arc::new(Mutex::new(SharedStream {
+                stream: Box::pin(stream),
+                remaining: len,
+            }))),
+        }
+    }
+
+    /// The body's bytes, if they're in memory, e.g. to hash them.
+    pub fn bytes(&self) -> Option<&[u8]> {
+        match &self.inner {
+            Inner::Bytes(bytes) => Some(bytes),
+            _ => None,
+        }
+    }
+
+    /// How many bytes are left to be read, if that's known.
+    pub fn content_length(&self) -> Option<u64> {
+        match &self.inner {
+            Inner::Bytes(bytes) => Some(bytes.len() as u64),
+            Inner::File(region) => Some(region.remaining),
+            Inner::Stream(stream) => lock(stream).remaining,
+        }
+    }
+
+    /// A copy of the body that's read on its own, e.g. to send a request again, unless it's a
+    /// stream, which can only be read once.
+    pub fn try_clone(&self) -> Option<Body> {
+        match &self.inner {
+            Inner::Stream(_) => None,
+            _ => Some(self.clone()),
+        }
+    }
+
+    // The bytes in `range` of what's left, without reading them, unless it's a stream or the
+    // range goes past its end.
+    pub(crate) fn slice(&self, range: Range<u64>) -> Option<Body> {
+        if range.start > range.end || range.end > self.content_length()? {
+            return None;
+        }
+        match &self.inner {
+            Inner::Bytes(bytes) => Some(Body::from(
+                bytes.slice(range.start as usize..range.end as usize),
+            )),
+            Inner::File(region) => Some(Body::from_file_region(
+                region.path.clone(),
+                region.offset + range.start..region.offset + range.end,
+            )),
+            Inner::Stream(_) => None,
+        }
+    }
+
+    // How large the body is and where it's read from, for `Debug`.
+    pub(crate) fn describe(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.inner {
+            Inner::Bytes(bytes) => write!(f, "{} bytes", bytes.len()),
+            Inner::File(region) => write!(f, "{} bytes from {:?}", region.remaining, region.path),
+            Inner::Stream(stream) => match lock(stream).remaining {
+                Some(len) => write!(f, "stream of {} bytes", len),
+                None => write!(f, "stream"),
+            },
+        }
+    }
+
+    /// The rest of the bytes, read into memory.
+    pub async fn collect(mut self) -> Result<Vec<u8>, BoxError> {
+        if let Inner::Bytes(bytes) = self.inner {
+            return Ok(bytes.to_vec());
+        }
+        let mut out = Vec::with_capacity(self.content_length().unwrap_or(0) as usize);
+        while let Some(chunk) = future::poll_fn(|cx| Pin::new(&mut self).poll_next(cx)).await {
+            out.extend_from_slice(&chunk?);
+        }
+        Ok(out)
+    }
+}
+
+fn lock(stream: &Mutex<SharedStream>) -> std::sync::MutexGuard<'_, SharedStream> {
+    stream.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+impl Stream for Body {
+    type Item = Result<Bytes, BoxError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        match &mut self.get_mut().inner {
+            Inner::Bytes(bytes) if bytes.is_empty() => Poll::Ready(None),
+            Inner::Bytes(bytes) => Poll::Ready(Some(Ok(std::mem::take(bytes)))),
+            Inner::File(region) => Poll::Ready(region.read().map_err(Into::into).transpose()),
+            Inner::Stream(stream) => {
+                let mut stream = lock(stream);
+                let stream = &mut *stream;
+                let chunk = stream.stream.as_mut().poll_next(cx);
+                match &chunk {
+                    Poll::Ready(Some(Ok(chunk))) => {
+                        if let Some(remaining) = &mut stream.remaining {
+                            *remaining = remaining.saturating_sub(chunk.len() as u64);
+                        }
+                    }
+                    Poll::Ready(None) if stream.remaining.is_some_and(|left| left > 0) => {
+                        // Only once: a stream that's cut off ends after saying so.
+                        stream.remaining = Some(0);
+                        return Poll::Ready(Some(Err("the body ended early".into())));
+                    }
+                    _ => {}
+                }
+                chunk
+            }
+        }
+    }
+}
+
+// The stream `from_stream` is given, with its errors boxed.
+struct MapErr<S>(Pin<Box<S>>);
+
+impl<S, E> Stream for MapErr<S>
+where
+    S: Stream<Item = Result<Bytes, E>>,
+    E: Into<BoxError>,
+{
+    type Item = Result<Bytes, BoxError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.0
+            .as_mut()
+            .poll_next(cx)
+            .map(|chunk| chunk.map(|chunk| chunk.map_err(Into::into)))
+    }
+}
+
+#[cfg(feature = "hyper")]
+impl http_body::Body for Body {
+    type Data = Bytes;
+    type Error = BoxError;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<http_body::Frame<Bytes>, BoxError>>> {
+        self.poll_next(cx)
+            .map(|chunk| chunk.map(|chunk| chunk.map(http_body::Frame::data)))
+    }
+
+    fn size_hint(&self) -> http_body::SizeHint {
+        match self.content_length() {
+            Some(len) => http_body::SizeHint::with_exact(len),
+            None => http_body::SizeHint::default(),
+        }
+    }
+}
+
+impl From<Bytes> for Body {
+    fn from(bytes: Bytes) -> Self {
+        Body {
+            inner: Inner::Bytes(bytes),
+        }
+    }
+}
+
+impl From<Vec<u8>> for Body {
+    fn from(bytes: Vec<u8>) -> Self {
+        Body::from(Bytes::from(bytes))
+    }
+}
+
+impl From<&[u8]> for Body {
+    fn from(bytes: &[u8]) -> Self {
+        Body::from(Bytes::copy_from_slice(bytes))
+    }
+}
+
+impl From<String> for Body {
+    fn from(string: String) -> Self {
+        Body::from(Bytes::from(string))
+    }
+}
+
+impl From<&str> for Body {
+    fn from(string: &str) -> Self {
+        Body::from(string.as_bytes())
+    }
+}
+
+// Bodies in memory are equal if their bytes are, and in files if they're the same part of
+// the same file. Streams are only equal to their clones.
+impl PartialEq for Body {
+    fn eq(&self, other: &Self) -> bool {
+        match (&self.inner, &other.inner) {
+            (Inner::Bytes(a), Inner::Bytes(b)) => a == b,
+            (Inner::File(a), Inner::File(b)) => {
+                (&a.path, a.offset, a.remaining) == (&b.path, b.offset, b.remaining)
+            }
+            (Inner::Stream(a), Inner::Stream(b)) => Arc::ptr_eq(a, b),
+            _ => false,
+        }
+    }
+}
+
+impl Eq for Body {}
+
+// Bodies are often too large to be worth printing, so only their size and where they're read
+// from are.
+impl fmt::Debug for Body {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Body(")?;
+        self.describe(f)?;
+        f.write_str(")")
+    }
+}
+
+/// A copy of `request` that's sent on its own, e.g. for another attempt at it, unless its body
+/// can't be read again.
+pub fn try_clone_request(request: &http::Request<Body>) -> Option<http::Request<Body>> {
+    let mut copy = http::Request::new(request.body().try_clone()?);
+    *copy.method_mut() = request.method().clone();
+    *copy.uri_mut() = request.uri().clone();
+    *copy.version_mut() = request.version();
+    *copy.headers_mut() = request.headers().clone();
+    *copy.extensions_mut() = request.extensions().clone();
+    Some(copy)
+}
+
+/// `response`, with the rest of its body read into memory, e.g. to deserialize it.
+pub async fn read_response(
+    response: http::Response<Body>,
+) -> Result<http::Response<Vec<u8>>, BoxError> {
+    let (parts, body) = response.into_parts();
+    Ok(http::Response::from_parts(parts, body.collect().await?))
+}
+
+#[test]
+fn reads_bodies() -> Result<(), BoxError> {
+    use crate::blocking::block_on;
+
+    let body = Body::from("hello, world");
+    assert_eq!(body.bytes(), Some(&b"hello, world"[..]));
+    assert_eq!(body.content_length(), Some(12));
+    assert_eq!(format!("{:?}", body), "Body(12 bytes)");
+    assert_eq!(body.try_clone(), Some(body.clone()));
+    assert_eq!(block_on(body.collect())?, b"hello, world");
+    assert_eq!(block_on(Body::empty().collect())?, b"");
+
+    let path = std::env::temp_dir().join("aws-sdk-rust-runtime-reads-bodies");
+    let contents = (0..FILE_CHUNK_SIZE * 2 + 10)
+        .map(|i| i as u8)
+        .collect::<Vec<_>>();
+    std::fs::write(&path, &contents)?;
+    let body = Body::from_path(&path)?;
+    assert_eq!(body.bytes(), None);
+    assert_eq!(body.content_length(), Some(contents.len() as u64));
+    let clone = body.try_clone().ok_or("files can be read again")?;
+    assert_eq!(block_on(body.collect())?, contents);
+    assert_eq!(block_on(clone.collect())?, contents);
+    let region = Body::from_file_region(path.clone(), 5..10);
+    assert_eq!(block_on(region.collect())?, &contents[5..10]);
+    let slice = Body::from_path(&path)?
+        .slice(2..4)
+        .ok_or("files can be sliced")?;
+    assert_eq!(block_on(slice.collect())?, &contents[2..4]);
+    assert!(Body::from("abc").slice(2..4).is_none());
+    // A file that's shorter than it was fails.
+    let region = Body::from_file_region(path.clone(), 5..contents.len() as u64 + 1);
+    assert!(block_on(region.collect()).is_err());
+    std::fs::remove_file(&path)?;
+    assert!(Body::from_path(std::env::temp_dir()).is_err());
+
+    let chunks = vec![Bytes::from("a"), Bytes::from("bc")];
+    let stream = Body::from_stream(Chunks(chunks.clone()), Some(3));
+    assert_eq!(format!("{:?}", stream), "Body(stream of 3 bytes)");
+    assert!(stream.try_clone().is_none());
+    let mut shared = stream.clone();
+    assert_eq!(
+        block_on(future::poll_fn(|cx| Pin::new(&mut shared).poll_next(cx)))
+            .transpose()?
+            .as_deref(),
+        Some(&b"a"[..])
+    );
+    assert_eq!(stream.content_length(), Some(2));
+    assert_eq!(block_on(stream.collect())?, b"bc");
+    let short = Body::from_stream(Chunks(chunks), Some(4));
+    assert!(block_on(short.collect()).is_err());
+
+    let request = http::Request::builder()
+        .uri("https://example.com/")
+        .header("x-test", "1")
+        .body(Body::from("hi"))?;
+    let copy = try_clone_request(&request).ok_or("bytes can be read again")?;
+    assert_eq!(copy.uri(), request.uri());
+    assert_eq!(copy.headers(), request.headers());
+    assert_eq!(copy.body(), request.body());
+    let request = request.map(|_| Body::from_stream(Chunks(Vec::new()), None));
+    assert!(try_clone_request(&request).is_none());
+
+    Ok(())
+}
+
+// A stream of chunks that are already there, for tests.
+#[cfg(test)]
+pub(crate) struct Chunks(pub(crate) Vec<Bytes>);
+
+#[cfg(test)]
+impl Stream for Chunks {
+    type Item = Result<Bytes, io::Error>;
+
+    fn poll_next(mut self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Poll::Ready(if self.0.is_empty() {
+            None
+        } else {
+            Some(Ok(self.0.remove(0)))
+        })
+    }
+}
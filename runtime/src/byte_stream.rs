@@ -0,0 +1,155 @@
+use crate::{body::Body, BoxError};
+use bytes::Bytes;
+use futures_core::Stream;
+use std::{
+    fmt, io,
+    ops::Range,
+    path::Path,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// A streaming blob, e.g. an S3 object's body or a Lambda function's payload: bytes in
+/// memory, a file that's read a chunk at a time as the request it's the body of is sent, or
+/// a response's body as it comes in on its connection. It's a `Stream` of its chunks, and
+/// clones share it as `Body`'s do.
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct ByteStream {
+    body: Body,
+}
+
+impl ByteStream {
+    pub fn new(bytes: impl Into<Vec<u8>>) -> Self {
+        ByteStream::from(Body::from(bytes.into()))
+    }
+
+    /// The contents of the file at `path`, as they are when they're read.
+    pub fn from_path(path: impl AsRef<Path>) -> io::Result<Self> {
+        Body::from_path(path).map(ByteStream::from)
+    }
+
+    /// How many bytes are left to be read, if that's known, e.g. from a response's
+    /// `Content-Length`.
+    pub fn content_length(&self) -> Option<u64> {
+        self.body.content_length()
+    }
+
+    /// Whether there are known to be no bytes left.
+    pub fn is_empty(&self) -> bool {
+        self.content_length() == Some(0)
+    }
+
+    /// The bytes, if they're in memory.
+    pub fn bytes(&self) -> Option<&[u8]> {
+        self.body.bytes()
+    }
+
+    /// The rest of the bytes, read into memory.
+    pub async fn collect(self) -> Result<Vec<u8>, BoxError> {
+        self.body.collect().await
+    }
+
+    /// The bytes in `range`, e.g. for a part of a multipart upload, which are only read when
+    /// the slice is. Fails if the range goes past the end, or for a response's stream, which
+    /// can only be read in order.
+    pub fn slice(&self, range: Range<u64>) -> io::Result<ByteStream> {
+        if self.body.try_clone().is_none() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "streams can't be sliced",
+            ));
+        }
+        self.body
+            .slice(range)
+            .map(ByteStream::from)
+            .ok_or_else(|| io::ErrorKind::UnexpectedEof.into())
+    }
+}
+
+impl Stream for ByteStream {
+    type Item = Result<Bytes, BoxError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Pin::new(&mut self.body).poll_next(cx)
+    }
+}
+
+impl From<Body> for ByteStream {
+    fn from(body: Body) -> Self {
+        ByteStream { body }
+    }
+}
+
+impl From<ByteStream> for Body {
+    fn from(stream: ByteStream) -> Self {
+        stream.body
+    }
+}
+
+impl From<Vec<u8>> for ByteStream {
+    fn from(bytes: Vec<u8>) -> Self {
+        ByteStream::new(bytes)
+    }
+}
+
+impl From<&[u8]> for ByteStream {
+    fn from(bytes: &[u8]) -> Self {
+        ByteStream::new(bytes)
+    }
+}
+
+impl From<String> for ByteStream {
+    fn from(string: String) -> Self {
+        ByteStream::new(string)
+    }
+}
+
+impl From<&str> for ByteStream {
+    fn from(string: &str) -> Self {
+        ByteStream::new(string)
+    }
+}
+
+// Streams are often too large to be worth printing, so only their size and where they're
+// read from are.
+impl fmt::Debug for ByteStream {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ByteStream(")?;
+        self.body.describe(f)?;
+        f.write_str(")")
+    }
+}
+
+#[test]
+fn streams_bytes() -> Result<(), crate::BoxError> {
+    use crate::blocking::block_on;
+
+    let stream = ByteStream::from("hello, world");
+    assert_eq!(stream.content_length(), Some(12));
+    assert_eq!(stream.bytes(), Some(&b"hello, world"[..]));
+    assert_eq!(format!("{:?}", stream), "ByteStream(12 bytes)");
+    assert_eq!(block_on(stream.clone().collect())?, b"hello, world");
+    assert_eq!(block_on(stream.slice(7..12)?.collect())?, b"world");
+    assert!(stream.slice(7..13).is_err());
+    assert!(ByteStream::default().is_empty());
+
+    let path = std::env::temp_dir().join("aws-sdk-rust-runtime-streams-bytes");
+    std::fs::write(&path, b"from a file")?;
+    let stream = ByteStream::from_path(&path)?;
+    assert_eq!(stream.content_length(), Some(11));
+    assert_eq!(stream.bytes(), None);
+    assert_eq!(block_on(stream.clone().collect())?, b"from a file");
+    assert_eq!(block_on(stream.slice(2..6)?.collect())?, b"om a");
+    assert!(stream.slice(7..12).is_err());
+    std::fs::remove_file(&path)?;
+    assert!(block_on(stream.collect()).is_err());
+    assert!(ByteStream::from_path(std::env::temp_dir()).is_err());
+
+    let chunks = crate::body::Chunks(vec![Bytes::from("a"), Bytes::from("b")]);
+    let stream = ByteStream::from(Body::from_stream(chunks, None));
+    assert!(!stream.is_empty());
+    assert!(stream.slice(0..1).is_err());
+    assert_eq!(block_on(stream.collect())?, b"ab");
+
+    Ok(())
+}
@@ -0,0 +1,512 @@
+use crate::{
+    body::Body,
+    encoding::base64_encode,
+    hash::{Crc, Sha1, Sha256},
+    BoxError,
+};
+use bytes::Bytes;
+use futures_core::Stream;
+use http::{header, HeaderMap, HeaderValue, Request, Response};
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+// How many bytes of a body each chunk of an `aws-chunked` one has, but its last.
+const CHUNK_SIZE: u64 = 64 * 1024;
+
+/// An algorithm operations with flexible checksums can check bodies with, which a request
+/// names in its `x-amz-sdk-checksum-algorithm` header and carries the checksum of in its
+/// `x-amz-checksum-` one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ChecksumAlgorithm {
+    Crc32,
+    Crc32c,
+    Sha1,
+    Sha256,
+}
+
+impl ChecksumAlgorithm {
+    /// The algorithm a model's name, e.g. `CRC32C`, is for, in any case.
+    pub fn from_name(name: &str) -> Option<Self> {
+        [
+            ChecksumAlgorithm::Crc32,
+            ChecksumAlgorithm::Crc32c,
+            ChecksumAlgorithm::Sha1,
+            ChecksumAlgorithm::Sha256,
+        ]
+        .iter()
+        .copied()
+        .find(|algorithm| algorithm.name().eq_ignore_ascii_case(name))
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ChecksumAlgorithm::Crc32 => "CRC32",
+            ChecksumAlgorithm::Crc32c => "CRC32C",
+            ChecksumAlgorithm::Sha1 => "SHA1",
+            ChecksumAlgorithm::Sha256 => "SHA256",
+        }
+    }
+
+    /// The header requests and responses carry checksums with this algorithm in, e.g.
+    /// `x-amz-checksum-crc32c`.
+    pub fn header_name(&self) -> &'static str {
+        match self {
+            ChecksumAlgorithm::Crc32 => "x-amz-checksum-crc32",
+            ChecksumAlgorithm::Crc32c => "x-amz-checksum-crc32c",
+            ChecksumAlgorithm::Sha1 => "x-amz-checksum-sha1",
+            ChecksumAlgorithm::Sha256 => "x-amz-checksum-sha256",
+        }
+    }
+
+    /// The checksum of `body` as headers carry it: its big-endian bytes, base64-encoded.
+    pub fn checksum(&self, body: &[u8]) -> String {
+        let mut hasher = self.hasher();
+        hasher.update(body);
+        hasher.finish()
+    }
+
+    /// A checksum of bytes that come a chunk at a time, e.g. a body's as it's sent.
+    pub fn hasher(&self) -> Hasher {
+        Hasher(match self {
+            ChecksumAlgorithm::Crc32 => HasherState::Crc(Crc::crc32()),
+            ChecksumAlgorithm::Crc32c => HasherState::Crc(Crc::crc32c()),
+            ChecksumAlgorithm::Sha1 => HasherState::Sha1(Sha1::new()),
+            ChecksumAlgorithm::Sha256 => HasherState::Sha256(Sha256::new()),
+        })
+    }
+
+    // How long checksums are once they're base64-encoded.
+    fn encoded_len(&self) -> u64 {
+        match self {
+            ChecksumAlgorithm::Crc32 | ChecksumAlgorithm::Crc32c => 8,
+            ChecksumAlgorithm::Sha1 => 28,
+            ChecksumAlgorithm::Sha256 => 44,
+        }
+    }
+}
+
+/// A checksum that's being computed, as `ChecksumAlgorithm::hasher` makes them.
+#[derive(Debug, Clone)]
+pub struct Hasher(HasherState);
+
+#[derive(Debug, Clone)]
+enum HasherState {
+    Crc(Crc),
+    Sha1(Sha1),
+    Sha256(Sha256),
+}
+
+impl Hasher {
+    pub fn update(&mut self, data: &[u8]) {
+        match &mut self.0 {
+            HasherState::Crc(crc) => crc.update(data),
+            HasherState::Sha1(sha1) => sha1.update(data),
+            HasherState::Sha256(sha256) => sha256.update(data),
+        }
+    }
+
+    /// The checksum of everything so far, as `ChecksumAlgorithm::checksum` encodes it.
+    pub fn finish(self) -> String {
+        match self.0 {
+            HasherState::Crc(crc) => base64_encode(&crc.finish().to_be_bytes()),
+            HasherState::Sha1(sha1) => base64_encode(&sha1.finish()),
+            HasherState::Sha256(sha256) => base64_encode(&sha256.finish()),
+        }
+    }
+}
+
+/// The algorithm a request is checksummed with: the one the caller picked in its input, by
+/// name, or else the config's, or else CRC-32 if the operation requires a checksum. `None`
+/// if it's sent without one. Algorithms this crate doesn't implement are an error.
+pub fn request_algorithm(
+    requested: Option<&str>,
+    configured: Option<ChecksumAlgorithm>,
+    required: bool,
+) -> Result<Option<ChecksumAlgorithm>, BoxError> {
+    match requested {
+        Some(name) => ChecksumAlgorithm::from_name(name)
+            .map(Some)
+            .ok_or_else(|| format!("checksum algorithm `{}` isn't supported", name).into()),
+        None if configured.is_some() => Ok(configured),
+        None if required => Ok(Some(ChecksumAlgorithm::Crc32)),
+        None => Ok(None),
+    }
+}
+
+/// Adds the checksum of a request's body with `algorithm`, unless it already has one, e.g.
+/// because the caller computed it themselves. Bodies in memory carry it in a header. Others,
+/// e.g. files, are sent `aws-chunked`, with it in the trailer after their last chunk, so
+/// they're only read once.
+pub fn add_checksum(request: &mut Request<Body>, algorithm: ChecksumAlgorithm) {
+    let headers = request.headers();
+    if headers
+        .keys()
+        .any(|name| name.as_str().starts_with("x-amz-checksum-"))
+    {
+        return;
+    }
+    let checksum = request.body().bytes().map(|body| algorithm.checksum(body));
+    let headers = request.headers_mut();
+    headers.insert(
+        "x-amz-sdk-checksum-algorithm",
+        HeaderValue::from_static(algorithm.name()),
+    );
+    match checksum {
+        // Base64 is always a valid header value.
+        Some(checksum) => {
+            if let Ok(value) = HeaderValue::from_str(&checksum) {
+                headers.insert(algorithm.header_name(), value);
+            }
+        }
+        None => send_chunked(request, algorithm),
+    }
+}
+
+// Sends a request's body `aws-chunked`, with its checksum in a trailer: each chunk's length in
+// hex and the chunk, then an empty chunk, and the trailer, with CRLFs after each. S3 takes
+// its lengths from `x-amz-decoded-content-length` rather than `Content-Length`.
+fn send_chunked(request: &mut Request<Body>, algorithm: ChecksumAlgorithm) {
+    let body = std::mem::take(request.body_mut());
+    let decoded_len = body.content_length();
+    let encoded_len = decoded_len.map(|len| {
+        let chunk = |len: u64| format!("{:x}", len).len() as u64 + len + 4;
+        let (full, last) = (len / CHUNK_SIZE, len % CHUNK_SIZE);
+        full * chunk(CHUNK_SIZE)
+            + if last > 0 { chunk(last) } else { 0 }
+            + 3
+            + algorithm.header_name().len() as u64
+            + 1
+            + algorithm.encoded_len()
+            + 4
+    });
+    let headers = request.headers_mut();
+    let encoding = match headers
+        .get(header::CONTENT_ENCODING)
+        .and_then(|encoding| encoding.to_str().ok())
+    {
+        Some(encoding) => format!("{}, aws-chunked", encoding),
+        None => "aws-chunked".to_string(),
+    };
+    if let Ok(encoding) = HeaderValue::from_str(&encoding) {
+        headers.insert(header::CONTENT_ENCODING, encoding);
+    }
+    headers.insert(
+        "x-amz-trailer",
+        HeaderValue::from_static(algorithm.header_name()),
+    );
+    match (decoded_len, encoded_len) {
+        (Some(decoded_len), Some(encoded_len)) => {
+            headers.insert("x-amz-decoded-content-length", decoded_len.into());
+            headers.insert(header::CONTENT_LENGTH, encoded_len.into());
+        }
+        _ => {
+            headers.remove(header::CONTENT_LENGTH);
+        }
+    }
+    let chunked = Chunked {
+        body,
+        algorithm,
+        hasher: Some(algorithm.hasher()),
+        pending: Vec::new(),
+    };
+    *request.body_mut() = Body::from_stream(chunked, encoded_len);
+}
+
+// A body, `aws-chunked`, with its chunks gathered to `CHUNK_SIZE` so that their lengths add
+// up to what `send_chunked` says they do.
+struct Chunked {
+    body: Body,
+    algorithm: ChecksumAlgorithm,
+    // Until the trailer's been sent.
+    hasher: Option<Hasher>,
+    pending: Vec<u8>,
+}
+
+impl Stream for Chunked {
+    type Item = Result<Bytes, BoxError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        loop {
+            if this.pending.len() as u64 >= CHUNK_SIZE {
+                let rest = this.pending.split_off(CHUNK_SIZE as usize);
+                let data = std::mem::replace(&mut this.pending, rest);
+                return Poll::Ready(Some(Ok(chunk(&data, b""))));
+            }
+            if this.hasher.is_none() {
+                return Poll::Ready(None);
+            }
+            match Pin::new(&mut this.body).poll_next(cx) {
+                Poll::Ready(Some(Ok(data))) => {
+                    if let Some(hasher) = &mut this.hasher {
+                        hasher.update(&data);
+                    }
+                    this.pending.extend_from_slice(&data);
+                }
+                Poll::Ready(Some(Err(error))) => return Poll::Ready(Some(Err(error))),
+                Poll::Ready(None) => {
+                    let checksum = this.hasher.take().map(Hasher::finish).unwrap_or_default();
+                    let trailer =
+                        format!("0\r\n{}:{}\r\n\r\n", this.algorithm.header_name(), checksum);
+                    let data = std::mem::take(&mut this.pending);
+                    let last = if data.is_empty() {
+                        Bytes::from(trailer)
+                    } else {
+                        chunk(&data, trailer.as_bytes())
+                    };
+                    return Poll::Ready(Some(Ok(last)));
+                }
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+// An `aws-chunked` chunk of `data`, and what follows it.
+fn chunk(data: &[u8], then: &[u8]) -> Bytes {
+    let mut out = format!("{:x}\r\n", data.len()).into_bytes();
+    out.extend_from_slice(data);
+    out.extend_from_slice(b"\r\n");
+    out.extend_from_slice(then);
+    Bytes::from(out)
+}
+
+/// Checks a response's body against the checksum it carries with the first of `algorithms`,
+/// the operation's in order of preference, that it has one for. Responses without one pass,
+/// as do multipart objects', e.g. `Hp/Uyw==-3`, which are of their parts' checksums rather
+/// than of their bodies.
+pub fn validate_response(
+    response: &Response<Vec<u8>>,
+    algorithms: &[ChecksumAlgorithm],
+) -> Result<(), BoxError> {
+    match expected_checksum(response.headers(), algorithms)? {
+        Some((algorithm, expected)) => {
+            compare(algorithm, algorithm.checksum(response.body()), &expected)
+        }
+        None => Ok(()),
+    }
+}
+
+/// Checks a response's body, which is read as it streams in, e.g. an object's, against its
+/// checksum as `validate_response` does, once all of it has been. The body fails at its end
+/// if it doesn't match.
+pub fn validate_body(
+    headers: &HeaderMap,
+    body: Body,
+    algorithms: &[ChecksumAlgorithm],
+) -> Result<Body, BoxError> {
+    match expected_checksum(headers, algorithms)? {
+        Some((algorithm, expected)) => {
+            let len = body.content_length();
+            let validated = Validated {
+                body,
+                algorithm,
+                hasher: Some(algorithm.hasher()),
+                expected,
+            };
+            Ok(Body::from_stream(validated, len))
+        }
+        None => Ok(body),
+    }
+}
+
+// The algorithm and checksum a response's body is checked with, if any.
+fn expected_checksum(
+    headers: &HeaderMap,
+    algorithms: &[ChecksumAlgorithm],
+) -> Result<Option<(ChecksumAlgorithm, String)>, BoxError> {
+    for algorithm in algorithms {
+        let expected = match headers.get(algorithm.header_name()) {
+            Some(expected) => expected.to_str()?,
+            None => continue,
+        };
+        if expected.contains('-') {
+            return Ok(None);
+        }
+        return Ok(Some((*algorithm, expected.to_string())));
+    }
+    Ok(None)
+}
+
+fn compare(algorithm: ChecksumAlgorithm, actual: String, expected: &str) -> Result<(), BoxError> {
+    if actual != expected {
+        return Err(format!(
+            "the response's body has a {} checksum of `{}`, not the `{}` it came with",
+            algorithm.name(),
+            actual,
+            expected
+        )
+        .into());
+    }
+    Ok(())
+}
+
+// A body that's hashed as it's read, and fails at its end if its checksum's wrong.
+struct Validated {
+    body: Body,
+    algorithm: ChecksumAlgorithm,
+    // Until the body's ended.
+    hasher: Option<Hasher>,
+    expected: String,
+}
+
+impl Stream for Validated {
+    type Item = Result<Bytes, BoxError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        if this.hasher.is_none() {
+            return Poll::Ready(None);
+        }
+        let chunk = Pin::new(&mut this.body).poll_next(cx);
+        match &chunk {
+            Poll::Ready(Some(Ok(data))) => {
+                if let Some(hasher) = &mut this.hasher {
+                    hasher.update(data);
+                }
+            }
+            Poll::Ready(None) => {
+                let actual = this.hasher.take().map(Hasher::finish).unwrap_or_default();
+                if let Err(error) = compare(this.algorithm, actual, &this.expected) {
+                    return Poll::Ready(Some(Err(error)));
+                }
+            }
+            _ => {}
+        }
+        chunk
+    }
+}
+
+#[test]
+fn checksums_bodies() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    assert_eq!(ChecksumAlgorithm::Crc32.checksum(b"hello"), "NhCmhg==");
+    assert_eq!(ChecksumAlgorithm::Crc32c.checksum(b"hello"), "mnG7TA==");
+    assert_eq!(
+        ChecksumAlgorithm::Sha1.checksum(b"hello"),
+        "qvTGHdzF6KLavt4PO0gs2a6pQ00="
+    );
+    assert_eq!(
+        ChecksumAlgorithm::Sha256.checksum(b"hello"),
+        "LPJNul+wow4m6DsqxbninhsWHlwfp0JecwQzYpOLmCQ="
+    );
+    assert_eq!(
+        ChecksumAlgorithm::from_name("crc32c"),
+        Some(ChecksumAlgorithm::Crc32c)
+    );
+    assert_eq!(ChecksumAlgorithm::from_name("MD5"), None);
+
+    assert_eq!(
+        request_algorithm(Some("SHA256"), Some(ChecksumAlgorithm::Crc32), true)?,
+        Some(ChecksumAlgorithm::Sha256)
+    );
+    assert_eq!(
+        request_algorithm(None, Some(ChecksumAlgorithm::Sha1), false)?,
+        Some(ChecksumAlgorithm::Sha1)
+    );
+    assert_eq!(
+        request_algorithm(None, None, true)?,
+        Some(ChecksumAlgorithm::Crc32)
+    );
+    assert_eq!(request_algorithm(None, None, false)?, None);
+    assert!(request_algorithm(Some("MD5"), None, false).is_err());
+
+    let mut hasher = ChecksumAlgorithm::Sha1.hasher();
+    hasher.update(b"hel");
+    hasher.update(b"lo");
+    assert_eq!(hasher.finish(), "qvTGHdzF6KLavt4PO0gs2a6pQ00=");
+
+    let mut request = Request::new(Body::from("hello"));
+    add_checksum(&mut request, ChecksumAlgorithm::Crc32c);
+    assert_eq!(request.headers()["x-amz-checksum-crc32c"], "mnG7TA==");
+    assert_eq!(request.headers()["x-amz-sdk-checksum-algorithm"], "CRC32C");
+    // The caller's own checksum is kept.
+    let mut request = Request::new(Body::from("hello"));
+    request
+        .headers_mut()
+        .insert("x-amz-checksum-sha1", HeaderValue::from_static("mine"));
+    add_checksum(&mut request, ChecksumAlgorithm::Crc32);
+    assert_eq!(request.headers().len(), 1);
+
+    Ok(())
+}
+
+#[test]
+fn sends_checksums_in_trailers() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    use crate::blocking::block_on;
+
+    let path = std::env::temp_dir().join("aws-sdk-rust-runtime-sends-checksums-in-trailers");
+    let contents = vec![b'a'; CHUNK_SIZE as usize + 5];
+    std::fs::write(&path, &contents)?;
+    let mut request = Request::new(Body::from_path(&path)?);
+    add_checksum(&mut request, ChecksumAlgorithm::Crc32);
+    let headers = request.headers();
+    assert_eq!(headers[header::CONTENT_ENCODING], "aws-chunked");
+    assert_eq!(headers["x-amz-trailer"], "x-amz-checksum-crc32");
+    assert_eq!(
+        headers["x-amz-decoded-content-length"],
+        contents.len().to_string().as_str()
+    );
+    let len = headers[header::CONTENT_LENGTH].to_str()?.parse::<u64>()?;
+    assert_eq!(request.body().content_length(), Some(len));
+    let body = block_on(std::mem::take(request.body_mut()).collect())?;
+    std::fs::remove_file(&path)?;
+    assert_eq!(body.len() as u64, len);
+    let mut expected = b"10000\r\n".to_vec();
+    expected.extend_from_slice(&contents[..CHUNK_SIZE as usize]);
+    expected.extend_from_slice(b"\r\n5\r\naaaaa\r\n0\r\nx-amz-checksum-crc32:");
+    expected.extend_from_slice(ChecksumAlgorithm::Crc32.checksum(&contents).as_bytes());
+    expected.extend_from_slice(b"\r\n\r\n");
+    assert_eq!(body, expected);
+
+    Ok(())
+}
+
+#[test]
+fn validates_response_checksums() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let response = |headers: &[(&'static str, &'static str)]| {
+        let mut response = Response::new(b"hello".to_vec());
+        for (name, value) in headers {
+            response
+                .headers_mut()
+                .insert(*name, HeaderValue::from_static(value));
+        }
+        response
+    };
+    let algorithms = [ChecksumAlgorithm::Crc32c, ChecksumAlgorithm::Crc32];
+    validate_response(&response(&[]), &algorithms)?;
+    validate_response(
+        &response(&[("x-amz-checksum-crc32", "NhCmhg==")]),
+        &algorithms,
+    )?;
+    // Only the preferred algorithm's checked.
+    assert!(validate_response(
+        &response(&[
+            ("x-amz-checksum-crc32c", "AAAAAA=="),
+            ("x-amz-checksum-crc32", "NhCmhg==")
+        ]),
+        &algorithms
+    )
+    .is_err());
+    // Algorithms the operation doesn't list aren't.
+    validate_response(
+        &response(&[("x-amz-checksum-sha1", "AAAAAA==")]),
+        &algorithms,
+    )?;
+    validate_response(
+        &response(&[("x-amz-checksum-crc32c", "AAAAAA==-2")]),
+        &algorithms,
+    )?;
+
+    // Streamed bodies are checked once they've been read.
+    let body = |checksum| {
+        let response = response(&[("x-amz-checksum-crc32", checksum)]);
+        let body = validate_body(response.headers(), Body::from("hello"), &algorithms)?;
+        crate::blocking::block_on(body.collect())
+    };
+    assert_eq!(body("NhCmhg==")?, b"hello");
+    assert!(body("AAAAAA==").is_err());
+
+    Ok(())
+}
@@ -0,0 +1,224 @@
+use crate::{body::Body, hash::crc32};
+use http::{header, HeaderValue, Request};
+
+/// The smallest body, in bytes, that requests for operations with request compression are
+/// gzipped from, unless the config has another: 10 KiB, as other SDKs have it.
+pub const DEFAULT_MIN_COMPRESSION_SIZE: usize = 10_240;
+
+// How far back matches can be, and how many earlier positions with the same hash are tried
+// for each, which trades how small bodies get for how long compressing them takes.
+const WINDOW: usize = 32 * 1024;
+const MAX_CHAIN: usize = 64;
+const MAX_MATCH: usize = 258;
+
+// The lengths and distances DEFLATE's length and distance codes start at, and how many extra
+// bits they're followed by.
+const LENGTH_BASES: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+const LENGTH_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+const DISTANCE_BASES: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DISTANCE_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+
+/// Gzips a request's body, for operations whose `requestcompression` trait allows it, if it's
+/// at least `min_size` bytes, and says so in its `Content-Encoding`, after any encodings it
+/// already has. Its `Content-Length`, if it has one, is the compressed body's. Only bodies in
+/// memory are: streams are sent as they are.
+pub fn compress_request(request: &mut Request<Body>, min_size: usize) {
+    let body = match request.body().bytes() {
+        Some(body) if body.len() >= min_size => gzip(body),
+        _ => return,
+    };
+    let encoding = match request
+        .headers()
+        .get(header::CONTENT_ENCODING)
+        .and_then(|encoding| encoding.to_str().ok())
+    {
+        Some(encoding) => format!("{}, gzip", encoding),
+        None => "gzip".to_string(),
+    };
+    let headers = request.headers_mut();
+    if let Ok(encoding) = HeaderValue::from_str(&encoding) {
+        headers.insert(header::CONTENT_ENCODING, encoding);
+    }
+    if headers.contains_key(header::CONTENT_LENGTH) {
+        headers.insert(header::CONTENT_LENGTH, HeaderValue::from(body.len()));
+    }
+    *request.body_mut() = Body::from(body);
+}
+
+/// Compresses `data` as a gzip member (RFC 1952): a single DEFLATE block with the fixed
+/// Huffman codes, of matches found with a hash chain, which is most of what dynamic codes
+/// would save on the text bodies that are compressed.
+pub fn gzip(data: &[u8]) -> Vec<u8> {
+    // No file name or modification time, from an unknown OS.
+    let mut out = BitWriter {
+        out: vec![0x1f, 0x8b, 8, 0, 0, 0, 0, 0, 0, 0xff],
+        bits: 0,
+        count: 0,
+    };
+    // The last block, with the fixed codes.
+    out.write(1, 1);
+    out.write(1, 2);
+
+    let hash = |i: usize| {
+        (usize::from(data[i]) << 10 ^ usize::from(data[i + 1]) << 5 ^ usize::from(data[i + 2]))
+            & 0x7fff
+    };
+    // The last position with each hash, and the one before each position with the same hash,
+    // as far back as the window goes.
+    let mut head = vec![usize::MAX; 1 << 15];
+    let mut prev = vec![usize::MAX; WINDOW];
+    let mut i = 0;
+    while i < data.len() {
+        let (mut length, mut distance) = (0, 0);
+        if i + 3 <= data.len() {
+            let max = (data.len() - i).min(MAX_MATCH);
+            let mut candidate = head[hash(i)];
+            let mut chain = 0;
+            while candidate != usize::MAX && i - candidate <= WINDOW && chain < MAX_CHAIN {
+                let matched = data[candidate..]
+                    .iter()
+                    .zip(&data[i..i + max])
+                    .take_while(|(a, b)| a == b)
+                    .count();
+                if matched > length {
+                    length = matched;
+                    distance = i - candidate;
+                    if matched == max {
+                        break;
+                    }
+                }
+                candidate = prev[candidate % WINDOW];
+                chain += 1;
+            }
+        }
+        let step = if length >= 3 {
+            out.write_match(length, distance);
+            length
+        } else {
+            out.write_symbol(u16::from(data[i]));
+            1
+        };
+        for j in i..(i + step).min(data.len().saturating_sub(2)) {
+            let h = hash(j);
+            prev[j % WINDOW] = head[h];
+            head[h] = j;
+        }
+        i += step;
+    }
+    out.write_symbol(256);
+
+    let mut out = out.finish();
+    out.extend_from_slice(&crc32(data).to_le_bytes());
+    out.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    out
+}
+
+// Writes DEFLATE's bits, which are packed from each byte's least significant bit.
+struct BitWriter {
+    out: Vec<u8>,
+    bits: u64,
+    count: u32,
+}
+
+impl BitWriter {
+    fn write(&mut self, value: u32, len: u32) {
+        self.bits |= u64::from(value) << self.count;
+        self.count += len;
+        while self.count >= 8 {
+            self.out.push(self.bits as u8);
+            self.bits >>= 8;
+            self.count -= 8;
+        }
+    }
+
+    // Huffman codes are packed from their most significant bit, unlike everything else.
+    fn write_code(&mut self, code: u32, len: u32) {
+        self.write(code.reverse_bits() >> (32 - len), len);
+    }
+
+    // A literal byte, the end of the block, or a length code, in the fixed codes.
+    fn write_symbol(&mut self, symbol: u16) {
+        let symbol = u32::from(symbol);
+        match symbol {
+            0..=143 => self.write_code(0x30 + symbol, 8),
+            144..=255 => self.write_code(0x190 + symbol - 144, 9),
+            256..=279 => self.write_code(symbol - 256, 7),
+            _ => self.write_code(0xc0 + symbol - 280, 8),
+        }
+    }
+
+    fn write_match(&mut self, length: usize, distance: usize) {
+        let code = LENGTH_BASES
+            .iter()
+            .rposition(|base| usize::from(*base) <= length)
+            .unwrap_or_default();
+        self.write_symbol(257 + code as u16);
+        self.write(
+            (length - usize::from(LENGTH_BASES[code])) as u32,
+            u32::from(LENGTH_EXTRA[code]),
+        );
+        let code = DISTANCE_BASES
+            .iter()
+            .rposition(|base| usize::from(*base) <= distance)
+            .unwrap_or_default();
+        self.write_code(code as u32, 5);
+        self.write(
+            (distance - usize::from(DISTANCE_BASES[code])) as u32,
+            u32::from(DISTANCE_EXTRA[code]),
+        );
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.count > 0 {
+            self.out.push(self.bits as u8);
+        }
+        self.out
+    }
+}
+
+#[test]
+fn gzips_bytes() {
+    assert_eq!(
+        gzip(b""),
+        [0x1f, 0x8b, 8, 0, 0, 0, 0, 0, 0, 0xff, 3, 0, 0, 0, 0, 0, 0, 0, 0, 0]
+    );
+    // `hello ` in literals, and the rest as a match of it.
+    assert_eq!(
+        gzip(b"hello hello hello"),
+        [
+            0x1f, 0x8b, 8, 0, 0, 0, 0, 0, 0, 0xff, 0xcb, 0x48, 0xcd, 0xc9, 0xc9, 0x57, 0x40, 0x22,
+            0x01, 0x80, 0x88, 0xf9, 0xe5, 0x11, 0, 0, 0
+        ]
+    );
+    let body = "Action=PutMetricData&MetricData.member.1.Value=1".repeat(100);
+    assert!(gzip(body.as_bytes()).len() < body.len() / 10);
+}
+
+#[test]
+fn compresses_large_request_bodies() {
+    let mut request = Request::new(Body::from("small"));
+    compress_request(&mut request, 10);
+    assert_eq!(request.body().bytes(), Some(&b"small"[..]));
+    assert!(request.headers().is_empty());
+
+    let mut request = Request::builder()
+        .header(header::CONTENT_ENCODING, "custom")
+        .header(header::CONTENT_LENGTH, 17)
+        .body(Body::from("hello hello hello"))
+        .unwrap();
+    compress_request(&mut request, 10);
+    assert_eq!(request.body(), &Body::from(gzip(b"hello hello hello")));
+    assert_eq!(request.headers()[header::CONTENT_ENCODING], "custom, gzip");
+    assert_eq!(request.headers()[header::CONTENT_LENGTH], "27");
+}
@@ -0,0 +1,126 @@
+use super::{ConnectorFuture, HttpConnector, HttpRequest};
+use crate::{
+    body::Body,
+    timeout::{read_timeout, timeout, ConnectorTimeouts},
+    BoxError,
+};
+use http_body::Body as _;
+use http_body_util::BodyExt;
+use hyper_rustls::{HttpsConnector, HttpsConnectorBuilder};
+use hyper_util::{
+    client::legacy::{connect::HttpConnector as HttpConnection, Client},
+    rt::TokioExecutor,
+};
+use std::sync::OnceLock;
+use tokio::runtime::{self, Handle, Runtime};
+
+/// The default connector: a pool of hyper connections, with HTTPS over rustls and the
+/// webpki roots, and HTTP/2 where servers offer it. Off a tokio runtime, e.g. in a blocking
+/// client, requests are sent on a runtime of the connector's own. Bodies are streamed both
+/// ways: requests' as hyper polls them, and responses' as they're read. hyper's pool connects
+/// inside the request, so each is given as long as its connect and read timeouts add up to
+/// for its response's head to come in, and then the read timeout for each chunk of its body.
+#[derive(Debug, Clone)]
+pub struct HyperConnector {
+    client: Client<HttpsConnector<HttpConnection>, Body>,
+}
+
+impl Default for HyperConnector {
+    fn default() -> Self {
+        let https = HttpsConnectorBuilder::new()
+            .with_webpki_roots()
+            .https_or_http()
+            .enable_http1()
+            .enable_http2()
+            .build();
+        HyperConnector::with_client(Client::builder(TokioExecutor::new()).build(https))
+    }
+}
+
+impl HyperConnector {
+    pub fn new() -> Self {
+        HyperConnector::default()
+    }
+
+    /// A connector that sends requests with `client`, e.g. one with a pool configured for
+    /// a particular workload.
+    pub fn with_client(client: Client<HttpsConnector<HttpConnection>, Body>) -> Self {
+        HyperConnector { client }
+    }
+}
+
+impl HttpConnector for HyperConnector {
+    fn call(&self, request: HttpRequest) -> ConnectorFuture<'_> {
+        let timeouts = request
+            .extensions()
+            .get::<ConnectorTimeouts>()
+            .copied()
+            .unwrap_or_default();
+        let response = timeout(timeouts.attempt(), self.client.request(request));
+        let response = async move {
+            let (parts, body) = response.await??.into_parts();
+            let len = body.size_hint().exact();
+            let body = Body::from_stream(body.into_data_stream(), len);
+            Ok(http::Response::from_parts(
+                parts,
+                read_timeout(body, timeouts.read),
+            ))
+        };
+        if Handle::try_current().is_ok() {
+            return Box::pin(response);
+        }
+        Box::pin(async move {
+            let response = background_runtime()?.spawn(response);
+            response.await?
+        })
+    }
+}
+
+// The runtime that requests sent off tokio run on, started for the first of them.
+fn background_runtime() -> Result<&'static Runtime, BoxError> {
+    static RUNTIME: OnceLock<Result<Runtime, String>> = OnceLock::new();
+    let runtime = RUNTIME.get_or_init(|| {
+        runtime::Builder::new_multi_thread()
+            .worker_threads(1)
+            .thread_name("aws-sdk-hyper")
+            .enable_all()
+            .build()
+            .map_err(|error| format!("can't start hyper's runtime: {}", error))
+    });
+    runtime.as_ref().map_err(|error| error.clone().into())
+}
+
+#[test]
+fn times_out_stalled_connections() -> Result<(), BoxError> {
+    use super::{get_with_timeouts, stalled_server};
+    use crate::timeout::TimedOut;
+    use std::time::Duration;
+
+    let timed_out = |error: BoxError| error.downcast_ref::<TimedOut>().map(TimedOut::after);
+    let millis = Duration::from_millis;
+    let connector = HyperConnector::new();
+
+    // A connect timeout bounds attempts on its own, until the response's head comes in.
+    let url = stalled_server(b"")?;
+    let timeouts = ConnectorTimeouts {
+        connect: Some(millis(100)),
+        read: None,
+    };
+    let error = get_with_timeouts(&connector, &url, timeouts).unwrap_err();
+    assert_eq!(timed_out(error), Some(millis(100)));
+
+    // The read timeout bounds each chunk of the body.
+    let url = stalled_server(b"HTTP/1.1 200 OK\r\ncontent-length: 10\r\n\r\nhello")?;
+    let timeouts = ConnectorTimeouts {
+        connect: None,
+        read: Some(millis(100)),
+    };
+    let error = get_with_timeouts(&connector, &url, timeouts).unwrap_err();
+    assert_eq!(timed_out(error), Some(millis(100)));
+
+    // Without either, the body's read whole.
+    let url = stalled_server(b"HTTP/1.1 200 OK\r\ncontent-length: 5\r\n\r\nhello")?;
+    let body = get_with_timeouts(&connector, &url, ConnectorTimeouts::default())?;
+    assert_eq!(body, b"hello");
+    Ok(())
+}
@@ -0,0 +1,185 @@
+use crate::{body::Body, BoxError};
+use std::{
+    collections::VecDeque,
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Mutex, PoisonError},
+};
+
+#[cfg(feature = "hyper")]
+mod hyper;
+#[cfg(feature = "reqwest")]
+mod reqwest;
+
+#[cfg(feature = "hyper")]
+pub use self::hyper::HyperConnector;
+#[cfg(feature = "reqwest")]
+pub use self::reqwest::ReqwestConnector;
+
+/// A request as connectors send it, with its body streamed to the connection as it's read.
+pub type HttpRequest = http::Request<Body>;
+/// A response as connectors return it, once its head has come in: its body is read from the
+/// connection as it's polled.
+pub type HttpResponse = http::Response<Body>;
+
+/// A response, or why there isn't one, from an `HttpConnector`.
+pub type ConnectorFuture<'a> =
+    Pin<Box<dyn Future<Output = Result<HttpResponse, BoxError>> + Send + 'a>>;
+
+/// Sends requests and returns their responses, e.g. over a pool of HTTP connections. Clients
+/// are generic over theirs, so they can use whichever HTTP stack, or none at all in tests.
+pub trait HttpConnector {
+    fn call(&self, request: HttpRequest) -> ConnectorFuture<'_>;
+}
+
+impl<C: HttpConnector + ?Sized> HttpConnector for Arc<C> {
+    fn call(&self, request: HttpRequest) -> ConnectorFuture<'_> {
+        (**self).call(request)
+    }
+}
+
+impl<C: HttpConnector + ?Sized> HttpConnector for Box<C> {
+    fn call(&self, request: HttpRequest) -> ConnectorFuture<'_> {
+        (**self).call(request)
+    }
+}
+
+/// A connector for tests, which answers requests with canned responses, in order, and keeps
+/// the requests for the test to look at, with their bodies read into memory. Its clones share
+/// them, so a test can hand one to a client and keep another.
+#[derive(Debug, Clone, Default)]
+pub struct TestConnector {
+    state: Arc<Mutex<TestState>>,
+}
+
+#[derive(Debug, Default)]
+struct TestState {
+    responses: VecDeque<Result<HttpResponse, BoxError>>,
+    requests: Vec<HttpRequest>,
+}
+
+impl TestConnector {
+    pub fn new() -> Self {
+        TestConnector::default()
+    }
+
+    /// Answers the next request that hasn't been answered with `response`.
+    pub fn with_response(self, response: http::Response<impl Into<Body>>) -> Self {
+        self.push(Ok(response.map(Into::into)));
+        self
+    }
+
+    /// Fails the next request that hasn't been answered with `error`, as if it couldn't be
+    /// sent.
+    pub fn with_error(self, error: impl Into<BoxError>) -> Self {
+        self.push(Err(error.into()));
+        self
+    }
+
+    /// The requests that have been sent, in the order they were.
+    pub fn requests(&self) -> Vec<HttpRequest> {
+        self.state().requests.clone()
+    }
+
+    fn push(&self, response: Result<HttpResponse, BoxError>) {
+        self.state().responses.push_back(response);
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, TestState> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl HttpConnector for TestConnector {
+    fn call(&self, request: HttpRequest) -> ConnectorFuture<'_> {
+        Box::pin(async move {
+            let (parts, body) = request.into_parts();
+            let request = http::Request::from_parts(parts, Body::from(body.collect().await?));
+            let mut state = self.state();
+            state.requests.push(request);
+            state
+                .responses
+                .pop_front()
+                .unwrap_or_else(|| Err("the test connector has no responses left".into()))
+        })
+    }
+}
+
+#[test]
+fn replays_responses() -> Result<(), BoxError> {
+    use std::task::{Context, Poll, Waker};
+
+    let call = |connector: &dyn HttpConnector, uri: &str| {
+        let request = http::Request::builder().uri(uri).body(Body::empty())?;
+        let mut future = connector.call(request);
+        match future
+            .as_mut()
+            .poll(&mut Context::from_waker(Waker::noop()))
+        {
+            Poll::Ready(response) => response,
+            Poll::Pending => Err("the test connector didn't answer straight away".into()),
+        }
+    };
+    let connector = TestConnector::new()
+        .with_response(http::Response::builder().status(503).body(Vec::new())?)
+        .with_error("connection reset")
+        .with_response(http::Response::new(b"ok".to_vec()));
+    let client = Arc::new(connector.clone());
+    assert_eq!(call(&client, "https://example.com/1")?.status(), 503);
+    assert_eq!(
+        call(&client, "https://example.com/2")
+            .unwrap_err()
+            .to_string(),
+        "connection reset"
+    );
+    assert_eq!(
+        call(&client, "https://example.com/3")?.body().bytes(),
+        Some(&b"ok"[..])
+    );
+    assert!(call(&client, "https://example.com/4").is_err());
+    assert_eq!(
+        connector
+            .requests()
+            .iter()
+            .map(|request| request.uri().path())
+            .collect::<Vec<_>>(),
+        vec!["/1", "/2", "/3", "/4"]
+    );
+    Ok(())
+}
+
+// A server for connector tests, which answers the first request it's sent with `response` and
+// then stalls, keeping the connection open without sending anything else. It's at the URL
+// returned.
+#[cfg(all(test, any(feature = "hyper", feature = "reqwest")))]
+pub(crate) fn stalled_server(response: &'static [u8]) -> std::io::Result<String> {
+    use std::io::{Read, Write};
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0")?;
+    let url = format!("http://{}/", listener.local_addr()?);
+    std::thread::spawn(move || -> std::io::Result<()> {
+        let (mut connection, _) = listener.accept()?;
+        // The request's head, which is all a `GET` has.
+        let _ = connection.read(&mut [0; 1024])?;
+        connection.write_all(response)?;
+        std::thread::sleep(std::time::Duration::from_secs(5));
+        Ok(())
+    });
+    Ok(url)
+}
+
+// Sends a `GET` of `url` with `connector`, on a tokio runtime, with `timeouts`, and reads
+// the whole of its response's body.
+#[cfg(all(test, any(feature = "hyper", feature = "reqwest")))]
+pub(crate) fn get_with_timeouts(
+    connector: &dyn HttpConnector,
+    url: &str,
+    timeouts: crate::timeout::ConnectorTimeouts,
+) -> Result<Vec<u8>, BoxError> {
+    let mut request = http::Request::builder().uri(url).body(Body::empty())?;
+    request.extensions_mut().insert(timeouts);
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?
+        .block_on(async { connector.call(request).await?.into_body().collect().await })
+}
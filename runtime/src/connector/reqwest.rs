@@ -0,0 +1,82 @@
+use super::{ConnectorFuture, HttpConnector, HttpRequest};
+use crate::{
+    body::Body,
+    timeout::{read_timeout, timeout, ConnectorTimeouts},
+};
+use std::convert::TryFrom;
+
+/// A connector that sends requests with a `reqwest::Client`, for programs that already have
+/// one. reqwest only has a connect timeout for a whole client, so each request is given as
+/// long as its connect and read timeouts add up to for its response's head to come in, and
+/// then the read timeout for each chunk of its body.
+#[derive(Debug, Clone, Default)]
+pub struct ReqwestConnector {
+    client: reqwest::Client,
+}
+
+impl ReqwestConnector {
+    pub fn new(client: reqwest::Client) -> Self {
+        ReqwestConnector { client }
+    }
+}
+
+impl HttpConnector for ReqwestConnector {
+    fn call(&self, request: HttpRequest) -> ConnectorFuture<'_> {
+        let timeouts = request
+            .extensions()
+            .get::<ConnectorTimeouts>()
+            .copied()
+            .unwrap_or_default();
+        Box::pin(async move {
+            // reqwest can't tell how long a stream is, so it's told.
+            let (mut parts, body) = request.into_parts();
+            if let Some(len) = body.content_length() {
+                parts
+                    .headers
+                    .entry(http::header::CONTENT_LENGTH)
+                    .or_insert(len.into());
+            }
+            let body = reqwest::Body::wrap_stream(body);
+            let request = reqwest::Request::try_from(http::Request::from_parts(parts, body))?;
+            let response = timeout(timeouts.attempt(), self.client.execute(request)).await??;
+            let mut out = http::Response::new(Body::empty());
+            *out.status_mut() = response.status();
+            *out.version_mut() = response.version();
+            *out.headers_mut() = response.headers().clone();
+            let len = response.content_length();
+            let body = Body::from_stream(response.bytes_stream(), len);
+            *out.body_mut() = read_timeout(body, timeouts.read);
+            Ok(out)
+        })
+    }
+}
+
+#[test]
+fn times_out_stalled_connections() -> Result<(), crate::BoxError> {
+    use super::{get_with_timeouts, stalled_server};
+    use crate::timeout::TimedOut;
+    use std::time::Duration;
+
+    let timed_out = |error: crate::BoxError| error.downcast_ref::<TimedOut>().map(TimedOut::after);
+    let millis = Duration::from_millis;
+    let connector = ReqwestConnector::default();
+
+    // A connect timeout bounds attempts on its own, until the response's head comes in.
+    let url = stalled_server(b"")?;
+    let timeouts = ConnectorTimeouts {
+        connect: Some(millis(100)),
+        read: None,
+    };
+    let error = get_with_timeouts(&connector, &url, timeouts).unwrap_err();
+    assert_eq!(timed_out(error), Some(millis(100)));
+
+    // The read timeout bounds each chunk of the body.
+    let url = stalled_server(b"HTTP/1.1 200 OK\r\ncontent-length: 10\r\n\r\nhello")?;
+    let timeouts = ConnectorTimeouts {
+        connect: None,
+        read: Some(millis(100)),
+    };
+    let error = get_with_timeouts(&connector, &url, timeouts).unwrap_err();
+    assert_eq!(timed_out(error), Some(millis(100)));
+    Ok(())
+}
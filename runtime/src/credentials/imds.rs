@@ -0,0 +1,237 @@
+use super::{CredentialsCache, CredentialsFuture, ProvideCredentials};
+use crate::{json, sigv4::Credentials, time::parse_iso8601, BoxError};
+use std::{
+    env, future,
+    io::{Read, Write},
+    net::{TcpStream, ToSocketAddrs},
+    time::Duration,
+};
+
+// How long to wait to connect to IMDS, and then for each read and write. Off EC2, there's
+// nothing to connect to, so this is how long the default chain takes to give up on it.
+const TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Credentials for the role of the EC2 instance the program runs on, from its instance
+/// metadata service (IMDS), with IMDSv2's session tokens. They're cached until shortly before
+/// they expire.
+///
+/// IMDS is talked to over HTTP with blocking I/O, which doesn't take long either way: IMDS
+/// answers within milliseconds on an instance, and connecting to it times out after a second
+/// off of one.
+#[derive(Debug)]
+pub struct ImdsProvider {
+    // The URI IMDS is at, e.g. `http://169.254.169.254`.
+    endpoint: String,
+    cache: CredentialsCache,
+}
+
+impl Default for ImdsProvider {
+    fn default() -> Self {
+        ImdsProvider::new()
+    }
+}
+
+impl ImdsProvider {
+    /// A provider for IMDS at `AWS_EC2_METADATA_SERVICE_ENDPOINT`, or the usual
+    /// `http://169.254.169.254` if it isn't set.
+    pub fn new() -> Self {
+        ImdsProvider::with_endpoint(
+            env::var("AWS_EC2_METADATA_SERVICE_ENDPOINT")
+                .unwrap_or_else(|_| "http://169.254.169.254".to_string()),
+        )
+    }
+
+    /// A provider for IMDS at `endpoint`, e.g. `http://[fd00:ec2::254]` for its IPv6 address.
+    pub fn with_endpoint(endpoint: impl Into<String>) -> Self {
+        ImdsProvider {
+            endpoint: endpoint.into(),
+            cache: CredentialsCache::new(),
+        }
+    }
+
+    fn credentials(&self) -> Result<Credentials, BoxError> {
+        if env::var("AWS_EC2_METADATA_DISABLED").is_ok_and(|disabled| disabled == "true") {
+            return Err("IMDS is disabled by AWS_EC2_METADATA_DISABLED".into());
+        }
+        if let Some(credentials) = self.cache.get() {
+            return Ok(credentials);
+        }
+
+        let token = self.send(
+            "PUT",
+            "/latest/api/token",
+            ("x-aws-ec2-metadata-token-ttl-seconds", "21600"),
+        )?;
+        let token = ("x-aws-ec2-metadata-token", token.trim());
+        let roles = self.send("GET", "/latest/meta-data/iam/security-credentials/", token)?;
+        let role = roles
+            .lines()
+            .next()
+            .ok_or("the instance doesn't have a role")?;
+        let document = self.send(
+            "GET",
+            &format!("/latest/meta-data/iam/security-credentials/{}", role),
+            token,
+        )?;
+        let credentials = parse_credentials(&document)?;
+        self.cache.set(credentials.clone());
+        Ok(credentials)
+    }
+
+    // Sends IMDS a request with a header and returns the body of its response, if it's a 200.
+    fn send(&self, method: &str, path: &str, header: (&str, &str)) -> Result<String, BoxError> {
+        let authority = self
+            .endpoint
+            .strip_prefix("http://")
+            .ok_or_else(|| format!("IMDS's endpoint `{}` isn't an http:// URI", self.endpoint))?
+            .trim_end_matches('/');
+        let address = if authority.ends_with(']') || !authority.contains(':') {
+            format!("{}:80", authority)
+        } else {
+            authority.to_string()
+        };
+        let address = address
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| format!("`{}` doesn't resolve", authority))?;
+
+        let mut stream = TcpStream::connect_timeout(&address, TIMEOUT)?;
+        stream.set_read_timeout(Some(TIMEOUT))?;
+        stream.set_write_timeout(Some(TIMEOUT))?;
+        let request = format!(
+            "{} {} HTTP/1.1\r\nHost: {}\r\nConnection: close\r\nContent-Length: 0\r\n{}: {}\r\n\r\n",
+            method, path, authority, header.0, header.1
+        );
+        stream.write_all(request.as_bytes())?;
+        let mut response = Vec::new();
+        stream.read_to_end(&mut response)?;
+        response_body(&response)
+    }
+}
+
+impl ProvideCredentials for ImdsProvider {
+    fn provide_credentials(&self) -> CredentialsFuture<'_> {
+        Box::pin(future::ready(self.credentials()))
+    }
+}
+
+// The body of an HTTP/1.1 response, if its status is 200.
+fn response_body(response: &[u8]) -> Result<String, BoxError> {
+    let response = std::str::from_utf8(response)?;
+    let (head, body) = response
+        .split_once("\r\n\r\n")
+        .ok_or("IMDS's response was cut off")?;
+    let mut lines = head.split("\r\n");
+    let status = lines
+        .next()
+        .and_then(|line| line.split_whitespace().nth(1))
+        .ok_or("IMDS's response doesn't have a status")?;
+    let chunked = lines.any(|line| {
+        line.split_once(':').is_some_and(|(name, value)| {
+            name.trim().eq_ignore_ascii_case("transfer-encoding")
+                && value.trim().eq_ignore_ascii_case("chunked")
+        })
+    });
+    let body = if chunked {
+        dechunk(body)?
+    } else {
+        body.to_string()
+    };
+    if status != "200" {
+        return Err(format!("IMDS responded with {}: {}", status, body).into());
+    }
+    Ok(body)
+}
+
+// A chunked body's chunks, put back together.
+fn dechunk(mut body: &str) -> Result<String, BoxError> {
+    let mut out = String::new();
+    loop {
+        let (size, rest) = body.split_once("\r\n").ok_or("a chunk was cut off")?;
+        let size = usize::from_str_radix(size.split(';').next().unwrap_or(size).trim(), 16)?;
+        if size == 0 {
+            return Ok(out);
+        }
+        out.push_str(rest.get(..size).ok_or("a chunk was cut off")?);
+        body = rest[size..]
+            .strip_prefix("\r\n")
+            .ok_or("a chunk was cut off")?;
+    }
+}
+
+// The credentials in a role's document, e.g. `{"Code": "Success", "AccessKeyId": ...}`.
+fn parse_credentials(document: &str) -> Result<Credentials, BoxError> {
+    let document = json::parse(document.as_bytes())?;
+    let field = |name| document.get(name).and_then(json::Value::as_str);
+    if let Some(code) = field("Code").filter(|code| *code != "Success") {
+        return Err(format!("IMDS couldn't provide credentials: {}", code).into());
+    }
+    let credentials = match (field("AccessKeyId"), field("SecretAccessKey")) {
+        (Some(access_key_id), Some(secret_access_key)) => Credentials::new(
+            access_key_id,
+            secret_access_key,
+            field("Token").map(str::to_string),
+        ),
+        _ => return Err("IMDS's credentials are missing their keys".into()),
+    };
+    Ok(match field("Expiration") {
+        Some(expiration) => credentials.with_expiry(parse_iso8601(expiration)?),
+        None => credentials,
+    })
+}
+
+#[test]
+fn provides_imds_credentials() -> Result<(), BoxError> {
+    use std::net::TcpListener;
+
+    // An IMDS that answers the three requests fetching credentials take, and then goes away.
+    let listener = TcpListener::bind("127.0.0.1:0")?;
+    let endpoint = format!("http://{}", listener.local_addr()?);
+    let server = std::thread::spawn(move || -> Result<Vec<String>, std::io::Error> {
+        let responses = [
+            "HTTP/1.1 200 OK\r\nContent-Length: 5\r\n\r\ntoken".to_string(),
+            "HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n3\r\nweb\r\n4\r\nrole\r\n0\r\n\r\n"
+                .to_string(),
+            "HTTP/1.1 200 OK\r\n\r\n{\"Code\": \"Success\", \"AccessKeyId\": \"AKID\", \
+             \"SecretAccessKey\": \"secret\", \"Token\": \"session\", \
+             \"Expiration\": \"2100-01-01T00:00:00Z\"}"
+                .to_string(),
+        ];
+        let mut requests = Vec::new();
+        for response in &responses {
+            let (mut stream, _) = listener.accept()?;
+            let mut request = Vec::new();
+            while !request.ends_with(b"\r\n\r\n") {
+                let mut byte = [0];
+                stream.read_exact(&mut byte)?;
+                request.push(byte[0]);
+            }
+            requests.push(String::from_utf8(request).unwrap());
+            stream.write_all(response.as_bytes())?;
+        }
+        Ok(requests)
+    });
+
+    let provider = ImdsProvider::with_endpoint(endpoint);
+    let credentials = super::block_on(provider.provide_credentials())?;
+    assert_eq!(credentials.access_key_id(), "AKID");
+    assert_eq!(credentials.session_token(), Some("session"));
+    let requests = server.join().unwrap()?;
+    assert!(requests[0].starts_with("PUT /latest/api/token HTTP/1.1\r\n"));
+    assert!(requests[0].contains("\r\nx-aws-ec2-metadata-token-ttl-seconds: 21600\r\n"));
+    assert!(requests[2].starts_with("GET /latest/meta-data/iam/security-credentials/webrole "));
+    assert!(requests[2].contains("\r\nx-aws-ec2-metadata-token: token\r\n"));
+    // The server's gone, so these come from the cache.
+    assert_eq!(
+        super::block_on(provider.provide_credentials())?,
+        credentials
+    );
+
+    assert!(response_body(b"HTTP/1.1 404 Not Found\r\n\r\nnope")
+        .unwrap_err()
+        .to_string()
+        .contains("404"));
+    assert!(parse_credentials("{\"Code\": \"AssumeRoleUnauthorizedAccess\"}").is_err());
+
+    Ok(())
+}
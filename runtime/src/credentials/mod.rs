@@ -0,0 +1,398 @@
+use crate::{
+    connector::HttpConnector,
+    endpoint::{EndpointProperties, Partition},
+    profile::{profile_name, Profiles},
+    sigv4::Credentials,
+    BoxError,
+};
+use std::{
+    env, fmt,
+    future::{self, Future},
+    pin::Pin,
+    sync::{Arc, Mutex, PoisonError},
+    time::{Duration, SystemTime},
+};
+
+mod imds;
+mod sso;
+mod sts;
+
+pub use imds::ImdsProvider;
+pub use sso::{SsoProfile, SsoProvider};
+pub use sts::{AssumeRoleProvider, WebIdentityProvider};
+
+// The partitions STS and SSO are called in, which only differ in their DNS suffixes. Regions that aren't
+// China's are `aws`'s.
+const PARTITIONS: &[Partition] = &[
+    Partition {
+        name: "aws",
+        dns_suffix: "amazonaws.com",
+        region_regex: "",
+        regions: &[],
+        defaults: EndpointProperties {
+            hostname: Some("{service}.{region}.{dnsSuffix}"),
+            signing_region: None,
+            signing_name: None,
+        },
+        endpoints: &[],
+        partition_endpoint: None,
+    },
+    Partition {
+        name: "aws-cn",
+        dns_suffix: "amazonaws.com.cn",
+        region_regex: "^cn\\-\\w+\\-\\d+$",
+        regions: &[],
+        defaults: EndpointProperties {
+            hostname: Some("{service}.{region}.{dnsSuffix}"),
+            signing_region: None,
+            signing_name: None,
+        },
+        endpoints: &[],
+        partition_endpoint: None,
+    },
+];
+
+/// Credentials, or why there aren't any, from a `ProvideCredentials`.
+pub type CredentialsFuture<'a> =
+    Pin<Box<dyn Future<Output = Result<Credentials, BoxError>> + Send + 'a>>;
+
+/// Where clients get the credentials they sign requests with. They ask for them for each
+/// request they sign, so providers of temporary credentials can refresh them as they expire.
+pub trait ProvideCredentials: fmt::Debug + Send + Sync {
+    fn provide_credentials(&self) -> CredentialsFuture<'_>;
+}
+
+impl<P: ProvideCredentials + ?Sized> ProvideCredentials for Arc<P> {
+    fn provide_credentials(&self) -> CredentialsFuture<'_> {
+        (**self).provide_credentials()
+    }
+}
+
+// Fixed credentials provide themselves.
+impl ProvideCredentials for Credentials {
+    fn provide_credentials(&self) -> CredentialsFuture<'_> {
+        Box::pin(future::ready(Ok(self.clone())))
+    }
+}
+
+/// Credentials from `AWS_ACCESS_KEY_ID`, `AWS_SECRET_ACCESS_KEY`, and `AWS_SESSION_TOKEN`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct EnvironmentProvider;
+
+impl ProvideCredentials for EnvironmentProvider {
+    fn provide_credentials(&self) -> CredentialsFuture<'_> {
+        Box::pin(future::ready(from_environment(|name| env::var(name).ok())))
+    }
+}
+
+// Credentials from environment variables, as `var` looks them up.
+fn from_environment(var: impl Fn(&str) -> Option<String>) -> Result<Credentials, BoxError> {
+    let var = |name| var(name).filter(|value| !value.is_empty());
+    match (var("AWS_ACCESS_KEY_ID"), var("AWS_SECRET_ACCESS_KEY")) {
+        (Some(access_key_id), Some(secret_access_key)) => Ok(Credentials::new(
+            access_key_id,
+            secret_access_key,
+            var("AWS_SESSION_TOKEN"),
+        )),
+        _ => Err("AWS_ACCESS_KEY_ID and AWS_SECRET_ACCESS_KEY aren't set".into()),
+    }
+}
+
+/// Credentials for a profile in the shared config and credentials files: the role it assumes
+/// with its `web_identity_token_file`, the SSO role it signs in to, the role it assumes with
+/// its `source_profile`'s or `credential_source`'s credentials, or its own `aws_access_key_id`,
+/// `aws_secret_access_key`, and `aws_session_token`. The files are read each time credentials
+/// are asked for, except while temporary ones are cached.
+pub struct ProfileProvider {
+    // The profile `AWS_PROFILE` names, or `default`, if there isn't one.
+    profile: Option<String>,
+    // What requests for temporary credentials are sent with.
+    connector: Arc<dyn HttpConnector + Send + Sync>,
+    cache: CredentialsCache,
+}
+
+impl Default for ProfileProvider {
+    fn default() -> Self {
+        ProfileProvider {
+            profile: None,
+            connector: default_connector(),
+            cache: CredentialsCache::new(),
+        }
+    }
+}
+
+impl ProfileProvider {
+    /// A provider for the profile `AWS_PROFILE` names, or `default`.
+    pub fn new() -> Self {
+        ProfileProvider::default()
+    }
+
+    pub fn with_profile(profile: impl Into<String>) -> Self {
+        ProfileProvider {
+            profile: Some(profile.into()),
+            ..ProfileProvider::default()
+        }
+    }
+
+    /// Sends the requests for profiles' temporary credentials, e.g. to STS, with `connector`,
+    /// in place of the runtime's default one.
+    pub fn with_connector(mut self, connector: impl HttpConnector + Send + Sync + 'static) -> Self {
+        self.connector = Arc::new(connector);
+        self
+    }
+
+    async fn credentials(&self) -> Result<Credentials, BoxError> {
+        if let Some(credentials) = self.cache.get() {
+            return Ok(credentials);
+        }
+        let profile = self.profile.clone().unwrap_or_else(profile_name);
+        let profiles = Profiles::load()?;
+        let credentials = if profiles.get(&profile, "web_identity_token_file").is_some() {
+            WebIdentityProvider::from_profile(&profiles, &profile)?
+                .with_connector(self.connector.clone())
+                .provide_credentials()
+                .await?
+        } else if SsoProfile::is_sso(&profiles, &profile) {
+            SsoProvider::from_profile(&profiles, &profile)?
+                .with_connector(self.connector.clone())
+                .provide_credentials()
+                .await?
+        } else if profiles.get(&profile, "role_arn").is_some() {
+            AssumeRoleProvider::chained_from_profile(&profiles, &profile, &self.connector, 0)?
+                .provide_credentials()
+                .await?
+        } else {
+            profile_credentials(&profiles, &profile)?
+        };
+        if credentials.expiry().is_some() {
+            self.cache.set(credentials.clone());
+        }
+        Ok(credentials)
+    }
+}
+
+// The connector doesn't say anything useful about the provider.
+impl fmt::Debug for ProfileProvider {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProfileProvider")
+            .field("profile", &self.profile)
+            .finish()
+    }
+}
+
+impl ProvideCredentials for ProfileProvider {
+    fn provide_credentials(&self) -> CredentialsFuture<'_> {
+        Box::pin(self.credentials())
+    }
+}
+
+/// The credentials a profile has itself: its `aws_access_key_id`, `aws_secret_access_key`,
+/// and `aws_session_token`.
+pub fn profile_credentials(profiles: &Profiles, profile: &str) -> Result<Credentials, BoxError> {
+    match (
+        profiles.get(profile, "aws_access_key_id"),
+        profiles.get(profile, "aws_secret_access_key"),
+    ) {
+        (Some(access_key_id), Some(secret_access_key)) => Ok(Credentials::new(
+            access_key_id,
+            secret_access_key,
+            profiles
+                .get(profile, "aws_session_token")
+                .map(str::to_string),
+        )),
+        _ if !profiles.contains(profile) => Err(format!("there's no `{}` profile", profile).into()),
+        _ if SsoProfile::is_sso(profiles, profile) => Err(format!(
+            "the `{}` profile signs in with SSO, which takes `SsoProvider`",
+            profile
+        )
+        .into()),
+        _ if profiles.get(profile, "role_arn").is_some() => Err(format!(
+            "the `{}` profile assumes a role, which takes `AssumeRoleProvider`",
+            profile
+        )
+        .into()),
+        _ => Err(format!(
+            "the `{}` profile doesn't have an aws_access_key_id and aws_secret_access_key",
+            profile
+        )
+        .into()),
+    }
+}
+
+/// Temporary credentials a provider holds on to until shortly before they expire.
+#[derive(Debug, Default)]
+pub struct CredentialsCache {
+    cached: Mutex<Option<Credentials>>,
+}
+
+impl CredentialsCache {
+    // How long before they expire credentials are no longer handed out.
+    const REFRESH_BEFORE: Duration = Duration::from_secs(5 * 60);
+
+    pub fn new() -> Self {
+        CredentialsCache::default()
+    }
+
+    /// The cached credentials, unless there aren't any or they're about to expire.
+    pub fn get(&self) -> Option<Credentials> {
+        let cached = self.cached.lock().unwrap_or_else(PoisonError::into_inner);
+        cached
+            .as_ref()
+            .filter(|credentials| {
+                credentials.expiry().is_none_or(|expiry| {
+                    expiry > SystemTime::now() + CredentialsCache::REFRESH_BEFORE
+                })
+            })
+            .cloned()
+    }
+
+    pub fn set(&self, credentials: Credentials) {
+        *self.cached.lock().unwrap_or_else(PoisonError::into_inner) = Some(credentials);
+    }
+}
+
+/// Asks providers for credentials in turn, until one of them has some.
+#[derive(Debug, Default)]
+pub struct CredentialsChain {
+    providers: Vec<Box<dyn ProvideCredentials>>,
+}
+
+impl CredentialsChain {
+    pub fn new() -> Self {
+        CredentialsChain::default()
+    }
+
+    /// Adds a provider to the end of the chain.
+    pub fn or_else(mut self, provider: impl ProvideCredentials + 'static) -> Self {
+        self.providers.push(Box::new(provider));
+        self
+    }
+}
+
+impl ProvideCredentials for CredentialsChain {
+    fn provide_credentials(&self) -> CredentialsFuture<'_> {
+        Box::pin(async move {
+            let mut errors = Vec::new();
+            for provider in &self.providers {
+                match provider.provide_credentials().await {
+                    Ok(credentials) => return Ok(credentials),
+                    Err(error) => errors.push(error.to_string()),
+                }
+            }
+            Err(format!("no provider had credentials: {}", errors.join("; ")).into())
+        })
+    }
+}
+
+/// The chain clients usually get credentials from: the environment, then the role it names
+/// with a web identity token, as it does in EKS, then the current profile in the shared
+/// config and credentials files, then the instance metadata service.
+pub fn default_provider() -> CredentialsChain {
+    let mut chain = CredentialsChain::new().or_else(EnvironmentProvider);
+    if let Ok(provider) = WebIdentityProvider::from_environment() {
+        chain = chain.or_else(provider);
+    }
+    chain
+        .or_else(ProfileProvider::new())
+        .or_else(ImdsProvider::new())
+}
+
+// The connector providers send their requests with unless they're given another: hyper's, or
+// else reqwest's, whichever the runtime's built with.
+#[cfg(feature = "hyper")]
+fn default_connector() -> Arc<dyn HttpConnector + Send + Sync> {
+    Arc::new(crate::connector::HyperConnector::new())
+}
+
+#[cfg(all(feature = "reqwest", not(feature = "hyper")))]
+fn default_connector() -> Arc<dyn HttpConnector + Send + Sync> {
+    Arc::new(crate::connector::ReqwestConnector::default())
+}
+
+#[cfg(not(any(feature = "hyper", feature = "reqwest")))]
+fn default_connector() -> Arc<dyn HttpConnector + Send + Sync> {
+    Arc::new(NoConnector)
+}
+
+// Fails every request, for runtimes built without a connector.
+#[cfg(not(any(feature = "hyper", feature = "reqwest")))]
+struct NoConnector;
+
+#[cfg(not(any(feature = "hyper", feature = "reqwest")))]
+impl HttpConnector for NoConnector {
+    fn call(&self, _: crate::connector::HttpRequest) -> crate::connector::ConnectorFuture<'_> {
+        Box::pin(future::ready(Err(
+            "requesting temporary credentials takes the runtime's `hyper` or `reqwest` feature"
+                .into(),
+        )))
+    }
+}
+
+// Runs a future to completion on the current thread, for tests of providers that talk to
+// local servers or none at all.
+#[cfg(test)]
+fn block_on<F: Future>(future: F) -> F::Output {
+    use std::task::{Context, Poll, Waker};
+
+    let mut future = Box::pin(future);
+    let mut cx = Context::from_waker(Waker::noop());
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+        std::thread::yield_now();
+    }
+}
+
+#[test]
+fn provides_credentials() -> Result<(), BoxError> {
+    let vars = |name: &str| match name {
+        "AWS_ACCESS_KEY_ID" => Some("AKID".to_string()),
+        "AWS_SECRET_ACCESS_KEY" => Some("secret".to_string()),
+        "AWS_SESSION_TOKEN" => Some(String::new()),
+        _ => None,
+    };
+    assert_eq!(
+        from_environment(vars)?,
+        Credentials::new("AKID", "secret", None)
+    );
+    assert!(from_environment(|_| None).is_err());
+
+    let profiles = Profiles::parse(
+        "[profile dev]\naws_access_key_id = AKID\n[profile partial]\naws_access_key_id = AKID\n",
+        "[dev]\naws_secret_access_key = secret\naws_session_token = token\n",
+    );
+    assert_eq!(
+        profile_credentials(&profiles, "dev")?,
+        Credentials::new("AKID", "secret", Some("token".to_string()))
+    );
+    assert!(profile_credentials(&profiles, "partial")
+        .unwrap_err()
+        .to_string()
+        .contains("doesn't have"));
+
+    let chain = CredentialsChain::new()
+        .or_else(ProfileProvider::with_profile(
+            "aws-sdk-rust-runtime-missing",
+        ))
+        .or_else(Credentials::new("AKID", "secret", None));
+    assert_eq!(
+        block_on(chain.provide_credentials())?,
+        Credentials::new("AKID", "secret", None)
+    );
+    let error = block_on(Arc::new(CredentialsChain::new()).provide_credentials()).unwrap_err();
+    assert_eq!(error.to_string(), "no provider had credentials: ");
+
+    let cache = CredentialsCache::new();
+    let credentials = Credentials::new("AKID", "secret", None);
+    cache.set(
+        credentials
+            .clone()
+            .with_expiry(SystemTime::now() + Duration::from_secs(60)),
+    );
+    assert_eq!(cache.get(), None);
+    cache.set(credentials.clone());
+    assert_eq!(cache.get(), Some(credentials));
+
+    Ok(())
+}
@@ -0,0 +1,342 @@
+use super::{
+    default_connector, CredentialsCache, CredentialsFuture, ProvideCredentials, PARTITIONS,
+};
+use crate::{
+    body::{read_response, Body},
+    connector::HttpConnector,
+    encoding::push_query,
+    endpoint::EndpointResolver,
+    hash::{hex_encode, sha1},
+    json,
+    profile::{home_dir, Profiles},
+    sigv4::Credentials,
+    time::parse_iso8601,
+    BoxError,
+};
+use std::{
+    fmt, fs, io,
+    path::Path,
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+const PORTAL: EndpointResolver = EndpointResolver::new("portal.sso", "awsssoportal", PARTITIONS);
+
+/// A profile's IAM Identity Center (SSO) settings: the account and role it signs in to, either
+/// through an `sso_session`'s `sso_start_url` and `sso_region`, or with those in the profile
+/// itself, as before SSO sessions. `SsoProvider` trades the token `aws sso login` cached
+/// for the role's credentials.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SsoProfile {
+    start_url: String,
+    region: String,
+    account_id: String,
+    role_name: String,
+    // The profile's `sso_session`, whose name the token cache is keyed by in place of the
+    // start URL.
+    session: Option<String>,
+}
+
+impl SsoProfile {
+    /// The SSO settings of a profile, with its `sso_account_id` and `sso_role_name`.
+    pub fn from_profile(profiles: &Profiles, profile: &str) -> Result<Self, BoxError> {
+        let setting = |name| -> Result<String, BoxError> {
+            profiles
+                .get(profile, name)
+                .map(str::to_string)
+                .ok_or_else(|| format!("the `{}` profile doesn't have an {}", profile, name).into())
+        };
+        let session = profiles.get(profile, "sso_session");
+        let session_setting = |name| match session {
+            Some(session) => profiles
+                .sso_session(session, name)
+                .map(str::to_string)
+                .ok_or_else(|| -> BoxError {
+                    format!("the `{}` SSO session doesn't have an {}", session, name).into()
+                }),
+            None => setting(name),
+        };
+        Ok(SsoProfile {
+            start_url: session_setting("sso_start_url")?,
+            region: session_setting("sso_region")?,
+            account_id: setting("sso_account_id")?,
+            role_name: setting("sso_role_name")?,
+            session: session.map(str::to_string),
+        })
+    }
+
+    /// Whether a profile signs in with SSO, rather than having credentials of its own.
+    pub fn is_sso(profiles: &Profiles, profile: &str) -> bool {
+        ["sso_session", "sso_start_url"]
+            .iter()
+            .any(|setting| profiles.get(profile, setting).is_some())
+    }
+
+    /// The region SSO's portal is called in, e.g. `us-east-1`.
+    pub fn region(&self) -> &str {
+        &self.region
+    }
+
+    pub fn account_id(&self) -> &str {
+        &self.account_id
+    }
+
+    pub fn role_name(&self) -> &str {
+        &self.role_name
+    }
+
+    /// The access token `aws sso login` cached for the profile's session or start URL, in
+    /// `~/.aws/sso/cache`, unless it's expired.
+    pub fn cached_token(&self) -> Result<String, BoxError> {
+        let home = home_dir().ok_or("there's no home directory for the SSO token cache")?;
+        self.cached_token_in(&home.join(".aws").join("sso").join("cache"))
+    }
+
+    fn cached_token_in(&self, cache: &Path) -> Result<String, BoxError> {
+        let key = self.session.as_ref().unwrap_or(&self.start_url);
+        let path = cache.join(format!("{}.json", hex_encode(&sha1(key.as_bytes()))));
+        let login = || format!("run `aws sso login` for `{}`", key);
+        let contents = match fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => {
+                return Err(format!("there's no cached SSO token; {}", login()).into())
+            }
+            Err(error) => return Err(format!("couldn't read {}: {}", path.display(), error).into()),
+        };
+        let document = json::parse(contents.as_bytes())?;
+        let field = |name| document.get(name).and_then(json::Value::as_str);
+        let (token, expires_at) = match (field("accessToken"), field("expiresAt")) {
+            (Some(token), Some(expires_at)) => (token, parse_iso8601(expires_at)?),
+            _ => return Err(format!("{} isn't an SSO token", path.display()).into()),
+        };
+        if expires_at <= SystemTime::now() {
+            return Err(format!("the cached SSO token has expired; {}", login()).into());
+        }
+        Ok(token.to_string())
+    }
+}
+
+/// Credentials for an IAM Identity Center (SSO) role, from SSO's `GetRoleCredentials` with
+/// the access token `aws sso login` cached for a profile, cached until shortly before they
+/// expire. The token isn't refreshed, so once it expires, `aws sso login` has to be run again.
+pub struct SsoProvider {
+    profile: SsoProfile,
+    connector: Arc<dyn HttpConnector + Send + Sync>,
+    cache: CredentialsCache,
+}
+
+impl SsoProvider {
+    /// A provider for `profile`'s role, calling SSO with the runtime's default connector.
+    pub fn new(profile: SsoProfile) -> Self {
+        SsoProvider {
+            profile,
+            connector: default_connector(),
+            cache: CredentialsCache::new(),
+        }
+    }
+
+    /// A provider for the role a profile in the shared config and credentials files signs in
+    /// to; see `SsoProfile::from_profile`.
+    pub fn from_profile(profiles: &Profiles, profile: &str) -> Result<Self, BoxError> {
+        Ok(SsoProvider::new(SsoProfile::from_profile(
+            profiles, profile,
+        )?))
+    }
+
+    /// Sends SSO's requests with `connector`, in place of the runtime's default one.
+    pub fn with_connector(mut self, connector: impl HttpConnector + Send + Sync + 'static) -> Self {
+        self.connector = Arc::new(connector);
+        self
+    }
+
+    async fn role_credentials(&self) -> Result<Credentials, BoxError> {
+        if let Some(credentials) = self.cache.get() {
+            return Ok(credentials);
+        }
+        let credentials = self
+            .get_role_credentials(&self.profile.cached_token()?)
+            .await?;
+        self.cache.set(credentials.clone());
+        Ok(credentials)
+    }
+
+    // Trades `token` for the role's credentials with `GetRoleCredentials`, a `GET` the token
+    // authorizes in place of a signature.
+    async fn get_role_credentials(&self, token: &str) -> Result<Credentials, BoxError> {
+        let endpoint = PORTAL.resolve(&self.profile.region)?;
+        let mut uri = format!("https://{}/federation/credentials", endpoint.hostname);
+        push_query(&mut uri, "account_id", &self.profile.account_id);
+        push_query(&mut uri, "role_name", &self.profile.role_name);
+        let request = http::Request::builder()
+            .uri(uri)
+            .header("x-amz-sso_bearer_token", token)
+            .body(Body::empty())?;
+        let response = read_response(self.connector.call(request).await?).await?;
+        let document = json::parse(response.body())
+            .map_err(|error| format!("SSO's response isn't JSON: {}", error))?;
+        if !response.status().is_success() {
+            let code = response
+                .headers()
+                .get("x-amzn-errortype")
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.split(':').next())
+                .unwrap_or("UnknownError");
+            return Err(format!(
+                "SSO responded with {}: {}: {}",
+                response.status().as_u16(),
+                code,
+                document
+                    .get("message")
+                    .and_then(json::Value::as_str)
+                    .unwrap_or_default()
+            )
+            .into());
+        }
+        let credentials = document
+            .get("roleCredentials")
+            .ok_or("SSO didn't return credentials for the role")?;
+        let field = |name| {
+            credentials
+                .get(name)
+                .ok_or_else(|| format!("SSO's credentials are missing their {}", name))
+        };
+        let text = |name| {
+            field(name)?
+                .as_str()
+                .map(str::to_string)
+                .ok_or_else(|| format!("SSO's credentials are missing their {}", name))
+        };
+        let expiration = field("expiration")?
+            .as_f64()
+            .and_then(|millis| Duration::try_from_secs_f64(millis / 1000.0).ok())
+            .and_then(|expiration| UNIX_EPOCH.checked_add(expiration))
+            .ok_or("SSO's credentials have an invalid expiration")?;
+        Ok(Credentials::new(
+            text("accessKeyId")?,
+            text("secretAccessKey")?,
+            Some(text("sessionToken")?),
+        )
+        .with_expiry(expiration))
+    }
+}
+
+// The connector doesn't say anything useful about the provider.
+impl fmt::Debug for SsoProvider {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SsoProvider")
+            .field("profile", &self.profile)
+            .finish()
+    }
+}
+
+impl ProvideCredentials for SsoProvider {
+    fn provide_credentials(&self) -> CredentialsFuture<'_> {
+        Box::pin(self.role_credentials())
+    }
+}
+
+#[test]
+fn reads_sso_profiles() -> Result<(), BoxError> {
+    let profiles = Profiles::parse(
+        "[profile dev]\n\
+         sso_session = corp\n\
+         sso_account_id = 123456789012\n\
+         sso_role_name = Admin\n\
+         [profile legacy]\n\
+         sso_start_url = https://legacy.awsapps.com/start\n\
+         sso_region = eu-west-1\n\
+         sso_account_id = 123456789012\n\
+         sso_role_name = ReadOnly\n\
+         [profile missing]\n\
+         sso_session = nope\n\
+         [sso-session corp]\n\
+         sso_start_url = https://corp.awsapps.com/start\n\
+         sso_region = us-east-2\n",
+        "",
+    );
+    let dev = SsoProfile::from_profile(&profiles, "dev")?;
+    assert_eq!(dev.region(), "us-east-2");
+    assert_eq!(dev.role_name(), "Admin");
+    assert!(SsoProfile::is_sso(&profiles, "legacy"));
+    let legacy = SsoProfile::from_profile(&profiles, "legacy")?;
+    assert_eq!(legacy.region(), "eu-west-1");
+    assert!(SsoProfile::from_profile(&profiles, "missing")
+        .unwrap_err()
+        .to_string()
+        .contains("`nope` SSO session doesn't have an sso_start_url"));
+
+    let cache = std::env::temp_dir().join(format!("aws-sdk-rust-sso-{}", std::process::id()));
+    fs::create_dir_all(&cache)?;
+    // The cache's files are named for the SHA-1 of the session's name, or the start URL.
+    fs::write(
+        cache.join(format!("{}.json", hex_encode(&sha1(b"corp")))),
+        "{\"accessToken\": \"token\", \"expiresAt\": \"2100-01-01T00:00:00Z\"}",
+    )?;
+    fs::write(
+        cache.join(format!(
+            "{}.json",
+            hex_encode(&sha1(b"https://legacy.awsapps.com/start"))
+        )),
+        "{\"accessToken\": \"old\", \"expiresAt\": \"2000-01-01T00:00:00Z\"}",
+    )?;
+    let token = dev.cached_token_in(&cache);
+    let expired = legacy.cached_token_in(&cache);
+    fs::remove_dir_all(&cache)?;
+    assert_eq!(token?, "token");
+    assert!(expired.unwrap_err().to_string().contains("aws sso login"));
+
+    Ok(())
+}
+
+#[test]
+fn gets_sso_role_credentials() -> Result<(), BoxError> {
+    use crate::connector::TestConnector;
+
+    let connector = TestConnector::new()
+        .with_response(http::Response::new(
+            "{\"roleCredentials\": {\"accessKeyId\": \"ASIA\", \"secretAccessKey\": \"secret\", \
+             \"sessionToken\": \"session\", \"expiration\": 4102444800000}}",
+        ))
+        .with_response(
+            http::Response::builder()
+                .status(401)
+                .header(
+                    "x-amzn-ErrorType",
+                    "UnauthorizedException:http://internal.amazon.com/",
+                )
+                .body("{\"message\": \"Session token not found or invalid\"}")?,
+        );
+    let profiles = Profiles::parse(
+        "[profile dev]\n\
+         sso_start_url = https://corp.awsapps.com/start\n\
+         sso_region = eu-west-1\n\
+         sso_account_id = 123456789012\n\
+         sso_role_name = Admin\n",
+        "",
+    );
+    let provider = SsoProvider::from_profile(&profiles, "dev")?.with_connector(connector.clone());
+    let credentials = super::block_on(provider.get_role_credentials("token"))?;
+    assert_eq!(credentials.access_key_id(), "ASIA");
+    assert_eq!(credentials.session_token(), Some("session"));
+    assert_eq!(
+        credentials.expiry(),
+        Some(UNIX_EPOCH + Duration::from_secs(4_102_444_800))
+    );
+    let error = super::block_on(provider.get_role_credentials("stale")).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "SSO responded with 401: UnauthorizedException: Session token not found or invalid"
+    );
+
+    let requests = connector.requests();
+    assert_eq!(requests[0].method(), "GET");
+    assert_eq!(
+        requests[0].uri(),
+        "https://portal.sso.eu-west-1.amazonaws.com/federation/credentials\
+         ?account_id=123456789012&role_name=Admin"
+    );
+    assert_eq!(requests[0].headers()["x-amz-sso_bearer_token"], "token");
+    assert!(!requests[0].headers().contains_key("authorization"));
+
+    Ok(())
+}
@@ -0,0 +1,652 @@
+use super::{
+    default_connector, profile_credentials, CredentialsCache, CredentialsFuture,
+    EnvironmentProvider, ImdsProvider, ProvideCredentials, PARTITIONS,
+};
+use crate::{
+    body::{read_response, Body},
+    connector::HttpConnector,
+    encoding::push_form,
+    endpoint::EndpointResolver,
+    profile::Profiles,
+    sigv4::{self, Algorithm, Credentials, Payload, SigningParams},
+    time::parse_iso8601,
+    xml, BoxError,
+};
+use std::{
+    env, fmt, fs,
+    path::PathBuf,
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+const STS: EndpointResolver = EndpointResolver::new("sts", "sts", PARTITIONS);
+
+// How many profiles a profile's role can be assumed through, by way of their
+// `source_profile`s, before it's taken to be a cycle.
+const MAX_CHAINED_PROFILES: usize = 8;
+
+/// Temporary credentials for a role, from STS's `AssumeRole`, signed with the credentials of
+/// another provider, and cached until shortly before they expire.
+pub struct AssumeRoleProvider {
+    sts: Sts,
+    role_arn: String,
+    session_name: Option<String>,
+    external_id: Option<String>,
+    duration: Option<Duration>,
+    mfa_serial: Option<String>,
+    mfa_token: Option<Arc<dyn Fn() -> Result<String, BoxError> + Send + Sync>>,
+    cache: CredentialsCache,
+}
+
+impl AssumeRoleProvider {
+    /// A provider that assumes `role_arn` with `source`'s credentials, calling STS in
+    /// `us-east-1` with the runtime's default connector.
+    pub fn new(source: impl ProvideCredentials + 'static, role_arn: impl Into<String>) -> Self {
+        let mut sts = Sts::new(None);
+        sts.credentials = Some(Arc::new(source));
+        AssumeRoleProvider {
+            sts,
+            role_arn: role_arn.into(),
+            session_name: None,
+            external_id: None,
+            duration: None,
+            mfa_serial: None,
+            mfa_token: None,
+            cache: CredentialsCache::new(),
+        }
+    }
+
+    /// A provider for the role a profile in the shared config and credentials files assumes:
+    /// its `role_arn`, with its `role_session_name`, `external_id`, `duration_seconds`, and
+    /// `mfa_serial`, assumed with the credentials of its `source_profile`, which may assume a
+    /// role of its own, or of its `credential_source`, `Environment` or `Ec2InstanceMetadata`.
+    /// STS is called in the profile's `region`, or `us-east-1`.
+    pub fn from_profile(profiles: &Profiles, profile: &str) -> Result<Self, BoxError> {
+        AssumeRoleProvider::chained_from_profile(profiles, profile, &default_connector(), 0)
+    }
+
+    // The profile's provider, with `connector` for its requests and those of the providers
+    // its source profiles make.
+    pub(super) fn chained_from_profile(
+        profiles: &Profiles,
+        profile: &str,
+        connector: &Arc<dyn HttpConnector + Send + Sync>,
+        depth: usize,
+    ) -> Result<Self, BoxError> {
+        if depth == MAX_CHAINED_PROFILES {
+            return Err(format!(
+                "the `{}` profile's `source_profile`s go in a cycle",
+                profile
+            )
+            .into());
+        }
+        let setting = |name| profiles.get(profile, name);
+        let role_arn = setting("role_arn")
+            .ok_or_else(|| format!("the `{}` profile doesn't have a role_arn", profile))?;
+        let source: Arc<dyn ProvideCredentials> =
+            match (setting("source_profile"), setting("credential_source")) {
+                // A profile can have the keys its role is assumed with itself.
+                (Some(source), None) if source == profile => {
+                    Arc::new(profile_credentials(profiles, source)?)
+                }
+                (Some(source), None)
+                    if profiles.get(source, "web_identity_token_file").is_some() =>
+                {
+                    let mut provider = WebIdentityProvider::from_profile(profiles, source)?;
+                    provider.sts.connector = connector.clone();
+                    Arc::new(provider)
+                }
+                (Some(source), None) if profiles.get(source, "role_arn").is_some() => {
+                    Arc::new(AssumeRoleProvider::chained_from_profile(
+                        profiles,
+                        source,
+                        connector,
+                        depth + 1,
+                    )?)
+                }
+                (Some(source), None) => Arc::new(profile_credentials(profiles, source)?),
+                (None, Some("Environment")) => Arc::new(EnvironmentProvider),
+                (None, Some("Ec2InstanceMetadata")) => Arc::new(ImdsProvider::new()),
+                (None, Some(source)) => {
+                    return Err(
+                        format!("the `{}` credential_source isn't supported", source).into(),
+                    )
+                }
+                (Some(_), Some(_)) => {
+                    return Err(format!(
+                        "the `{}` profile has both a source_profile and a credential_source",
+                        profile
+                    )
+                    .into())
+                }
+                (None, None) => {
+                    return Err(format!(
+                        "the `{}` profile has neither a source_profile nor a credential_source",
+                        profile
+                    )
+                    .into())
+                }
+            };
+
+        let mut provider = AssumeRoleProvider::new(source, role_arn);
+        provider.sts.region = setting("region").map(str::to_string);
+        provider.sts.connector = connector.clone();
+        if let Some(session_name) = setting("role_session_name") {
+            provider = provider.with_session_name(session_name);
+        }
+        if let Some(external_id) = setting("external_id") {
+            provider = provider.with_external_id(external_id);
+        }
+        if let Some(duration) = setting("duration_seconds") {
+            let seconds = duration.parse().map_err(|_| {
+                format!(
+                    "the `{}` profile's duration_seconds isn't a number",
+                    profile
+                )
+            })?;
+            provider = provider.with_duration(Duration::from_secs(seconds));
+        }
+        if let Some(serial) = setting("mfa_serial") {
+            provider = provider.with_mfa_serial(serial);
+        }
+        Ok(provider)
+    }
+
+    /// Names the role's sessions, which CloudTrail logs them by. Otherwise they're named for
+    /// when they start.
+    pub fn with_session_name(mut self, session_name: impl Into<String>) -> Self {
+        self.session_name = Some(session_name.into());
+        self
+    }
+
+    /// Sets the external ID the role's trust policy requires, if it requires one.
+    pub fn with_external_id(mut self, external_id: impl Into<String>) -> Self {
+        self.external_id = Some(external_id.into());
+        self
+    }
+
+    /// Sets how long the credentials last, from 15 minutes to the role's maximum session
+    /// duration. STS's default is an hour.
+    pub fn with_duration(mut self, duration: Duration) -> Self {
+        self.duration = Some(duration);
+        self
+    }
+
+    /// Sets the serial number or ARN of the MFA device the role's trust policy requires.
+    /// Its token codes come from `with_mfa_token`.
+    pub fn with_mfa_serial(mut self, serial: impl Into<String>) -> Self {
+        self.mfa_serial = Some(serial.into());
+        self
+    }
+
+    /// Sets where the MFA device's current token code comes from, e.g. a prompt, for roles
+    /// with an MFA serial. It's asked each time the role is assumed.
+    pub fn with_mfa_token(
+        mut self,
+        token_code: impl Fn() -> Result<String, BoxError> + Send + Sync + 'static,
+    ) -> Self {
+        self.mfa_token = Some(Arc::new(token_code));
+        self
+    }
+
+    /// Calls STS in `region`, e.g. `eu-west-1`.
+    pub fn with_region(mut self, region: impl Into<String>) -> Self {
+        self.sts.region = Some(region.into());
+        self
+    }
+
+    /// Sends STS's requests with `connector`, in place of the runtime's default one. Providers
+    /// made for source profiles keep theirs.
+    pub fn with_connector(mut self, connector: impl HttpConnector + Send + Sync + 'static) -> Self {
+        self.sts.connector = Arc::new(connector);
+        self
+    }
+
+    async fn assume_role(&self) -> Result<Credentials, BoxError> {
+        if let Some(credentials) = self.cache.get() {
+            return Ok(credentials);
+        }
+        let session_name = session_name(&self.session_name);
+        let duration = self.duration.map(|duration| duration.as_secs().to_string());
+        let token_code = match &self.mfa_serial {
+            Some(_) => {
+                let token_code = self.mfa_token.as_ref().ok_or_else(|| {
+                    format!("assuming `{}` takes an MFA token code", self.role_arn)
+                })?;
+                Some(token_code()?)
+            }
+            None => None,
+        };
+        let mut params = vec![
+            ("RoleArn", self.role_arn.as_str()),
+            ("RoleSessionName", &session_name),
+        ];
+        if let Some(external_id) = &self.external_id {
+            params.push(("ExternalId", external_id));
+        }
+        if let Some(duration) = &duration {
+            params.push(("DurationSeconds", duration));
+        }
+        if let (Some(serial), Some(token_code)) = (&self.mfa_serial, &token_code) {
+            params.push(("SerialNumber", serial));
+            params.push(("TokenCode", token_code));
+        }
+        let credentials = self.sts.assume_role("AssumeRole", &params).await?;
+        self.cache.set(credentials.clone());
+        Ok(credentials)
+    }
+}
+
+// The source provider and MFA token function don't say anything useful about the provider.
+impl fmt::Debug for AssumeRoleProvider {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AssumeRoleProvider")
+            .field("role_arn", &self.role_arn)
+            .field("session_name", &self.session_name)
+            .field("external_id", &self.external_id)
+            .field("duration", &self.duration)
+            .field("mfa_serial", &self.mfa_serial)
+            .field("region", &self.sts.region)
+            .finish()
+    }
+}
+
+impl ProvideCredentials for AssumeRoleProvider {
+    fn provide_credentials(&self) -> CredentialsFuture<'_> {
+        Box::pin(self.assume_role())
+    }
+}
+
+/// Temporary credentials for a role, from STS's `AssumeRoleWithWebIdentity` with an OpenID
+/// Connect token read from a file, as EKS gives pods whose service accounts have IAM roles.
+/// The token is read again each time the role is assumed, since it's rotated, and the
+/// credentials are cached until shortly before they expire. The request isn't signed, since
+/// the token stands in for credentials.
+pub struct WebIdentityProvider {
+    sts: Sts,
+    role_arn: String,
+    token_file: PathBuf,
+    session_name: Option<String>,
+    cache: CredentialsCache,
+}
+
+impl WebIdentityProvider {
+    /// A provider that assumes a role with the token in `token_file`, calling STS in
+    /// `us-east-1` with the runtime's default connector.
+    pub fn new(role_arn: impl Into<String>, token_file: impl Into<PathBuf>) -> Self {
+        WebIdentityProvider {
+            sts: Sts::new(None),
+            role_arn: role_arn.into(),
+            token_file: token_file.into(),
+            session_name: None,
+            cache: CredentialsCache::new(),
+        }
+    }
+
+    /// A provider for `AWS_ROLE_ARN` and `AWS_WEB_IDENTITY_TOKEN_FILE`, as EKS sets them, with
+    /// sessions named `AWS_ROLE_SESSION_NAME` if it's set. STS is called in `AWS_REGION` or
+    /// `AWS_DEFAULT_REGION`, or `us-east-1`.
+    pub fn from_environment() -> Result<Self, BoxError> {
+        let var = |name| env::var(name).ok().filter(|value| !value.is_empty());
+        let (role_arn, token_file) = match (var("AWS_ROLE_ARN"), var("AWS_WEB_IDENTITY_TOKEN_FILE"))
+        {
+            (Some(role_arn), Some(token_file)) => (role_arn, token_file),
+            _ => return Err("AWS_ROLE_ARN and AWS_WEB_IDENTITY_TOKEN_FILE aren't set".into()),
+        };
+        let mut provider = WebIdentityProvider::new(role_arn, token_file);
+        provider.sts = Sts::new(var("AWS_REGION").or_else(|| var("AWS_DEFAULT_REGION")));
+        Ok(match var("AWS_ROLE_SESSION_NAME") {
+            Some(session_name) => provider.with_session_name(session_name),
+            None => provider,
+        })
+    }
+
+    /// A provider for the role a profile in the shared config and credentials files assumes
+    /// with a web identity: its `role_arn` and `web_identity_token_file`, with its
+    /// `role_session_name`. STS is called in the profile's `region`, or `us-east-1`.
+    pub fn from_profile(profiles: &Profiles, profile: &str) -> Result<Self, BoxError> {
+        let setting = |name| profiles.get(profile, name);
+        let (role_arn, token_file) = match (setting("role_arn"), setting("web_identity_token_file"))
+        {
+            (Some(role_arn), Some(token_file)) => (role_arn, token_file),
+            _ => {
+                return Err(format!(
+                    "the `{}` profile doesn't have a role_arn and web_identity_token_file",
+                    profile
+                )
+                .into())
+            }
+        };
+        let mut provider = WebIdentityProvider::new(role_arn, token_file);
+        provider.sts = Sts::new(setting("region").map(str::to_string));
+        Ok(match setting("role_session_name") {
+            Some(session_name) => provider.with_session_name(session_name),
+            None => provider,
+        })
+    }
+
+    /// Names the role's sessions, which CloudTrail logs them by. Otherwise they're named for
+    /// when they start.
+    pub fn with_session_name(mut self, session_name: impl Into<String>) -> Self {
+        self.session_name = Some(session_name.into());
+        self
+    }
+
+    /// Calls STS in `region`, e.g. `eu-west-1`.
+    pub fn with_region(mut self, region: impl Into<String>) -> Self {
+        self.sts.region = Some(region.into());
+        self
+    }
+
+    /// Sends STS's requests with `connector`, in place of the runtime's default one.
+    pub fn with_connector(mut self, connector: impl HttpConnector + Send + Sync + 'static) -> Self {
+        self.sts.connector = Arc::new(connector);
+        self
+    }
+
+    async fn assume_role(&self) -> Result<Credentials, BoxError> {
+        if let Some(credentials) = self.cache.get() {
+            return Ok(credentials);
+        }
+        let token = fs::read_to_string(&self.token_file)
+            .map_err(|error| format!("couldn't read {}: {}", self.token_file.display(), error))?;
+        let credentials = self
+            .sts
+            .assume_role(
+                "AssumeRoleWithWebIdentity",
+                &[
+                    ("RoleArn", &self.role_arn),
+                    ("RoleSessionName", &session_name(&self.session_name)),
+                    ("WebIdentityToken", token.trim()),
+                ],
+            )
+            .await?;
+        self.cache.set(credentials.clone());
+        Ok(credentials)
+    }
+}
+
+// The connector doesn't say anything useful about the provider.
+impl fmt::Debug for WebIdentityProvider {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WebIdentityProvider")
+            .field("role_arn", &self.role_arn)
+            .field("token_file", &self.token_file)
+            .field("session_name", &self.session_name)
+            .field("region", &self.sts.region)
+            .finish()
+    }
+}
+
+impl ProvideCredentials for WebIdentityProvider {
+    fn provide_credentials(&self) -> CredentialsFuture<'_> {
+        Box::pin(self.assume_role())
+    }
+}
+
+// Where STS is called for a provider's role: a region, or `us-east-1`, the connector its
+// requests are sent with, and the credentials they're signed with, if they're signed.
+struct Sts {
+    region: Option<String>,
+    connector: Arc<dyn HttpConnector + Send + Sync>,
+    credentials: Option<Arc<dyn ProvideCredentials>>,
+}
+
+impl Sts {
+    fn new(region: Option<String>) -> Self {
+        Sts {
+            region,
+            connector: default_connector(),
+            credentials: None,
+        }
+    }
+
+    // Calls one of STS's `AssumeRole` actions with `params`, in the query protocol's form,
+    // and returns the credentials in the `{action}Result` of its response.
+    async fn assume_role(
+        &self,
+        action: &str,
+        params: &[(&str, &str)],
+    ) -> Result<Credentials, BoxError> {
+        let endpoint = STS.resolve(self.region.as_deref().unwrap_or("us-east-1"))?;
+        let mut form = String::new();
+        push_form(&mut form, "Action", action);
+        push_form(&mut form, "Version", "2011-06-15");
+        for (name, value) in params {
+            push_form(&mut form, name, value);
+        }
+        let uri = format!("https://{}/", endpoint.hostname);
+        let headers = vec![
+            (
+                "content-type".to_string(),
+                "application/x-www-form-urlencoded".to_string(),
+            ),
+            ("content-length".to_string(), form.len().to_string()),
+        ];
+        let signature = match &self.credentials {
+            Some(source) => {
+                let credentials = source.provide_credentials().await?;
+                let params = SigningParams {
+                    credentials: &credentials,
+                    algorithm: Algorithm::V4,
+                    region: &endpoint.signing_region,
+                    service: &endpoint.signing_name,
+                    time: SystemTime::now(),
+                    s3: false,
+                };
+                sigv4::sign(
+                    "POST",
+                    &uri,
+                    &headers,
+                    Payload::Bytes(form.as_bytes()),
+                    &params,
+                )?
+            }
+            None => Vec::new(),
+        };
+        let mut request = http::Request::builder().method("POST").uri(uri);
+        for (name, value) in headers.iter().chain(&signature) {
+            request = request.header(name.as_str(), value.as_str());
+        }
+        let request = request.body(Body::from(form))?;
+        let response = read_response(self.connector.call(request).await?).await?;
+        let root = xml::parse(response.body())
+            .map_err(|error| format!("STS's response isn't XML: {}", error))?;
+        if !response.status().is_success() {
+            let error = root.child("Error");
+            let text = |name| {
+                error
+                    .and_then(|error| error.child(name))
+                    .map(|child| child.text.as_str())
+            };
+            return Err(format!(
+                "STS responded with {}: {}: {}",
+                response.status().as_u16(),
+                text("Code").unwrap_or("UnknownError"),
+                text("Message").unwrap_or_default()
+            )
+            .into());
+        }
+        let credentials = root
+            .child(&format!("{}Result", action))
+            .and_then(|result| result.child("Credentials"))
+            .ok_or("STS didn't return credentials for the role")?;
+        let text = |name| {
+            credentials
+                .child(name)
+                .map(|child| child.text.clone())
+                .ok_or_else(|| format!("STS's credentials are missing their {}", name))
+        };
+        Ok(Credentials::new(
+            text("AccessKeyId")?,
+            text("SecretAccessKey")?,
+            Some(text("SessionToken")?),
+        )
+        .with_expiry(parse_iso8601(&text("Expiration")?)?))
+    }
+}
+
+// A role session's name, or one for when it starts if it isn't given one.
+fn session_name(name: &Option<String>) -> String {
+    name.clone().unwrap_or_else(|| {
+        let started = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        format!("aws-sdk-rust-{}", started.as_millis())
+    })
+}
+
+#[test]
+fn assumes_roles_with_web_identities() -> Result<(), BoxError> {
+    use crate::connector::TestConnector;
+
+    let token_file =
+        std::env::temp_dir().join(format!("aws-sdk-rust-web-identity-{}", std::process::id()));
+    fs::write(&token_file, "oidc-token\n")?;
+    let connector = TestConnector::new()
+        .with_response(http::Response::new(
+            "<AssumeRoleWithWebIdentityResponse xmlns=\"https://sts.amazonaws.com/doc/2011-06-15/\">\
+             <AssumeRoleWithWebIdentityResult><Credentials>\
+             <AccessKeyId>ASIA</AccessKeyId><SecretAccessKey>secret</SecretAccessKey>\
+             <SessionToken>session</SessionToken><Expiration>2100-01-01T00:00:00Z</Expiration>\
+             </Credentials></AssumeRoleWithWebIdentityResult></AssumeRoleWithWebIdentityResponse>",
+        ))
+        .with_response(
+            http::Response::builder().status(400).body(
+                "<ErrorResponse><Error><Type>Sender</Type><Code>InvalidIdentityToken</Code>\
+                 <Message>expired</Message></Error></ErrorResponse>",
+            )?,
+        );
+    let provider = WebIdentityProvider::new("arn:aws:iam::123456789012:role/pods", &token_file)
+        .with_session_name("pod")
+        .with_region("cn-north-1")
+        .with_connector(connector.clone());
+    let credentials = super::block_on(provider.provide_credentials());
+    fs::remove_file(&token_file)?;
+    let credentials = credentials?;
+    assert_eq!(credentials.access_key_id(), "ASIA");
+    assert_eq!(credentials.session_token(), Some("session"));
+    assert!(credentials.expiry().is_some());
+    // They're cached, so STS isn't called again.
+    assert_eq!(
+        super::block_on(provider.provide_credentials())?,
+        credentials
+    );
+
+    let requests = connector.requests();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].method(), "POST");
+    assert_eq!(
+        requests[0].uri(),
+        "https://sts.cn-north-1.amazonaws.com.cn/"
+    );
+    assert!(!requests[0].headers().contains_key("authorization"));
+    assert_eq!(
+        requests[0].body().bytes(),
+        Some(
+            &b"Action=AssumeRoleWithWebIdentity&Version=2011-06-15\
+               &RoleArn=arn%3Aaws%3Aiam%3A%3A123456789012%3Arole%2Fpods\
+               &RoleSessionName=pod&WebIdentityToken=oidc-token"[..]
+        )
+    );
+
+    let sts = Sts {
+        region: None,
+        connector: Arc::new(connector.clone()),
+        credentials: None,
+    };
+    let error = super::block_on(sts.assume_role("AssumeRoleWithWebIdentity", &[])).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "STS responded with 400: InvalidIdentityToken: expired"
+    );
+    assert_eq!(
+        connector.requests()[1].uri(),
+        "https://sts.us-east-1.amazonaws.com/"
+    );
+
+    let profiles = Profiles::parse(
+        "[profile eks]\nrole_arn = arn:aws:iam::123456789012:role/pods\n\
+         web_identity_token_file = /var/run/token\nregion = eu-west-1\n\
+         [profile partial]\nrole_arn = arn:aws:iam::123456789012:role/pods\n",
+        "",
+    );
+    let provider = WebIdentityProvider::from_profile(&profiles, "eks")?;
+    assert_eq!(provider.sts.region.as_deref(), Some("eu-west-1"));
+    assert_eq!(provider.token_file, PathBuf::from("/var/run/token"));
+    assert!(WebIdentityProvider::from_profile(&profiles, "partial").is_err());
+
+    Ok(())
+}
+
+#[test]
+fn assumes_roles() -> Result<(), BoxError> {
+    use crate::connector::TestConnector;
+
+    let connector = TestConnector::new().with_response(http::Response::new(
+        "<AssumeRoleResponse xmlns=\"https://sts.amazonaws.com/doc/2011-06-15/\">\
+         <AssumeRoleResult><Credentials>\
+         <AccessKeyId>ASIA</AccessKeyId><SecretAccessKey>secret</SecretAccessKey>\
+         <SessionToken>session</SessionToken><Expiration>2100-01-01T00:00:00Z</Expiration>\
+         </Credentials></AssumeRoleResult></AssumeRoleResponse>",
+    ));
+    let provider = AssumeRoleProvider::new(
+        Credentials::new("AKID", "secret", None),
+        "arn:aws:iam::123456789012:role/admin",
+    )
+    .with_session_name("admin")
+    .with_external_id("corp")
+    .with_duration(Duration::from_secs(900))
+    .with_mfa_serial("arn:aws:iam::123456789012:mfa/me")
+    .with_mfa_token(|| Ok("123456".to_string()))
+    .with_connector(connector.clone());
+    let credentials = super::block_on(provider.provide_credentials())?;
+    assert_eq!(credentials.access_key_id(), "ASIA");
+    assert_eq!(credentials.session_token(), Some("session"));
+
+    let requests = connector.requests();
+    assert_eq!(requests[0].uri(), "https://sts.us-east-1.amazonaws.com/");
+    // The request is signed with the source's credentials.
+    let authorization = requests[0].headers()["authorization"].to_str()?;
+    assert!(authorization.starts_with("AWS4-HMAC-SHA256 Credential=AKID/"));
+    assert!(authorization.contains("/us-east-1/sts/aws4_request"));
+    assert_eq!(
+        requests[0].body().bytes(),
+        Some(
+            &b"Action=AssumeRole&Version=2011-06-15\
+               &RoleArn=arn%3Aaws%3Aiam%3A%3A123456789012%3Arole%2Fadmin\
+               &RoleSessionName=admin&ExternalId=corp&DurationSeconds=900\
+               &SerialNumber=arn%3Aaws%3Aiam%3A%3A123456789012%3Amfa%2Fme&TokenCode=123456"[..]
+        )
+    );
+
+    let profiles = Profiles::parse(
+        "[profile admin]\nrole_arn = arn:aws:iam::123456789012:role/admin\n\
+         source_profile = dev\nregion = eu-west-1\nduration_seconds = 900\n\
+         [profile dev]\nrole_arn = arn:aws:iam::123456789012:role/dev\n\
+         source_profile = base\n\
+         [profile base]\naws_access_key_id = AKID\naws_secret_access_key = secret\n\
+         [profile ec2]\nrole_arn = arn:aws:iam::123456789012:role/ec2\n\
+         credential_source = Ec2InstanceMetadata\n\
+         [profile loop]\nrole_arn = arn:aws:iam::123456789012:role/loop\n\
+         source_profile = other\n\
+         [profile other]\nrole_arn = arn:aws:iam::123456789012:role/other\n\
+         source_profile = loop\n\
+         [profile orphan]\nrole_arn = arn:aws:iam::123456789012:role/orphan\n",
+        "",
+    );
+    let admin = AssumeRoleProvider::from_profile(&profiles, "admin")?;
+    assert_eq!(admin.sts.region.as_deref(), Some("eu-west-1"));
+    assert_eq!(admin.duration, Some(Duration::from_secs(900)));
+    assert!(AssumeRoleProvider::from_profile(&profiles, "ec2").is_ok());
+    assert!(AssumeRoleProvider::from_profile(&profiles, "loop")
+        .unwrap_err()
+        .to_string()
+        .contains("go in a cycle"));
+    assert!(AssumeRoleProvider::from_profile(&profiles, "orphan")
+        .unwrap_err()
+        .to_string()
+        .contains("neither a source_profile nor a credential_source"));
+
+    Ok(())
+}
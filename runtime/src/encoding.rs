@@ -0,0 +1,292 @@
+use crate::BoxError;
+use std::fmt::Display;
+
+// The characters RFC 3986 leaves unencoded everywhere.
+fn is_unreserved(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || b"-_.~".contains(&byte)
+}
+
+fn encode(value: &str, keep: impl Fn(u8) -> bool) -> String {
+    let mut out = String::with_capacity(value.len());
+    for &byte in value.as_bytes() {
+        if keep(byte) {
+            out.push(byte as char);
+        } else {
+            out.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    out
+}
+
+/// Percent-encodes everything but unreserved characters, for URI labels, query parameter
+/// names and values, and anything else that has to be a single path segment.
+pub fn percent_encode(value: &str) -> String {
+    encode(value, is_unreserved)
+}
+
+/// Decodes a URL-encoded value, with `+`s for spaces, e.g. a key S3 listed with
+/// `encoding-type=url` because XML can't hold some of its characters.
+pub fn url_decode(value: &str) -> Result<String, BoxError> {
+    let mut out = Vec::with_capacity(value.len());
+    let mut bytes = value.bytes();
+    while let Some(byte) = bytes.next() {
+        out.push(match byte {
+            b'+' => b' ',
+            b'%' => {
+                let hex = [bytes.next(), bytes.next()];
+                match hex {
+                    [Some(high), Some(low)] => std::str::from_utf8(&[high, low])
+                        .ok()
+                        .and_then(|hex| u8::from_str_radix(hex, 16).ok()),
+                    _ => None,
+                }
+                .ok_or_else(|| format!("`{}` has an invalid percent-encoding", value))?
+            }
+            byte => byte,
+        });
+    }
+    Ok(String::from_utf8(out)?)
+}
+
+/// Percent-encodes a greedy URI label like `{Key+}`, which can span path segments, so its
+/// `/`s are kept.
+pub fn percent_encode_greedy(value: &str) -> String {
+    encode(value, |byte| is_unreserved(byte) || byte == b'/')
+}
+
+/// Adds a query parameter to a URI, percent-encoding its name and value. The URI may already
+/// have a query, e.g. from a request URI like `/{Bucket}?tagging`.
+pub fn push_query(uri: &mut String, name: &str, value: &str) {
+    uri.push(if uri.contains('?') { '&' } else { '?' });
+    uri.push_str(&percent_encode(name));
+    uri.push('=');
+    uri.push_str(&percent_encode(value));
+}
+
+/// Adds a parameter to an `application/x-www-form-urlencoded` body, percent-encoding its name
+/// and value.
+pub fn push_form(form: &mut String, name: &str, value: &str) {
+    if !form.is_empty() {
+        form.push('&');
+    }
+    form.push_str(&percent_encode(name));
+    form.push('=');
+    form.push_str(&percent_encode(value));
+}
+
+/// Formats a float the way AWS protocols send them as text, which JSON does for ones that
+/// aren't finite: as Rust does, but with `NaN`, `Infinity`, and `-Infinity` spelled out.
+pub fn format_float<F: Into<f64> + Display + Copy>(value: F) -> String {
+    let float = value.into();
+    if float.is_nan() {
+        "NaN".to_string()
+    } else if float.is_infinite() {
+        if float > 0.0 { "Infinity" } else { "-Infinity" }.to_string()
+    } else {
+        value.to_string()
+    }
+}
+
+/// Checks that a value can be sent in the named header, which rules out control characters
+/// other than tabs.
+pub fn header_value(name: &str, value: String) -> Result<String, BoxError> {
+    if value
+        .bytes()
+        .any(|byte| (byte < b' ' && byte != b'\t') || byte == 0x7f)
+    {
+        return Err(format!("{:?} can't be sent in the `{}` header", value, name).into());
+    }
+    Ok(value)
+}
+
+/// Joins the items of a list sent in a header with commas. Items with commas or double quotes
+/// in them are double-quoted, with their double quotes and backslashes escaped.
+pub fn header_list(items: impl IntoIterator<Item = String>) -> String {
+    let mut out = String::new();
+    for item in items {
+        if !out.is_empty() {
+            out.push_str(", ");
+        }
+        if item.contains(&[',', '"'][..]) {
+            out.push('"');
+            for c in item.chars() {
+                if c == '"' || c == '\\' {
+                    out.push('\\');
+                }
+                out.push(c);
+            }
+            out.push('"');
+        } else {
+            out.push_str(&item);
+        }
+    }
+    out
+}
+
+/// Splits a list sent in a header at its commas, undoing `header_list`.
+pub fn split_header_list(value: &str) -> Result<Vec<String>, BoxError> {
+    let mut items = Vec::new();
+    let mut chars = value.chars().peekable();
+    loop {
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        let mut item = String::new();
+        if chars.peek() == Some(&'"') {
+            chars.next();
+            loop {
+                match chars.next() {
+                    Some('"') => break,
+                    Some('\\') => item.extend(chars.next()),
+                    Some(c) => item.push(c),
+                    None => return Err(format!("`{}` has an unclosed quote", value).into()),
+                }
+            }
+            while chars.next_if(|c| c.is_whitespace()).is_some() {}
+            match chars.next() {
+                Some(',') | None => {}
+                Some(_) => return Err(format!("`{}` has text after a quoted item", value).into()),
+            }
+        } else {
+            for c in chars.by_ref() {
+                if c == ',' {
+                    break;
+                }
+                item.push(c);
+            }
+            item.truncate(item.trim_end().len());
+        }
+        items.push(item);
+        if chars.peek().is_none() {
+            return Ok(items);
+        }
+    }
+}
+
+/// Splits a list of HTTP dates sent in a header, which aren't quoted even though they have
+/// commas in them, e.g. `Mon, 16 Dec 2019 23:48:18 GMT, Tue, 17 Dec 2019 23:48:18 GMT`.
+pub fn split_http_dates(value: &str) -> Vec<String> {
+    value
+        .split_terminator("GMT")
+        .map(|date| date.trim_start_matches(|c: char| c == ',' || c.is_whitespace()))
+        .filter(|date| !date.is_empty())
+        .map(|date| format!("{}GMT", date))
+        .collect()
+}
+
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Encodes bytes as padded, standard base64, which is how blobs are sent in JSON bodies,
+/// query strings, and headers.
+pub fn base64_encode(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, &byte)| n | u32::from(byte) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(BASE64[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+/// Decodes padded, standard base64. Whitespace is skipped, since some services wrap long
+/// values.
+pub fn base64_decode(encoded: &str) -> Result<Vec<u8>, BoxError> {
+    let digits = encoded
+        .bytes()
+        .filter(|byte| !byte.is_ascii_whitespace())
+        .collect::<Vec<_>>();
+    if digits.len() % 4 != 0 {
+        return Err(format!("base64 `{}` isn't padded to a multiple of 4", encoded).into());
+    }
+    let mut out = Vec::with_capacity(digits.len() / 4 * 3);
+    for chunk in digits.chunks(4) {
+        let padding = chunk.iter().rev().take_while(|&&byte| byte == b'=').count();
+        if padding > 2 {
+            return Err(format!("base64 `{}` has too much padding", encoded).into());
+        }
+        let mut n = 0u32;
+        for (i, &byte) in chunk[..4 - padding].iter().enumerate() {
+            let digit = BASE64
+                .iter()
+                .position(|&digit| digit == byte)
+                .ok_or_else(|| format!("base64 `{}` has a `{}` in it", encoded, byte as char))?;
+            n |= (digit as u32) << (18 - 6 * i);
+        }
+        out.extend_from_slice(&n.to_be_bytes()[1..4 - padding]);
+    }
+    Ok(out)
+}
+
+#[test]
+fn encodes_values() -> Result<(), BoxError> {
+    assert_eq!(
+        percent_encode("my function:$LATEST"),
+        "my%20function%3A%24LATEST"
+    );
+    assert_eq!(percent_encode("a/b~c"), "a%2Fb~c");
+    let mut form = String::new();
+    push_form(&mut form, "Action", "SendMessage");
+    push_form(&mut form, "Tags.member.1.Key", "a b");
+    assert_eq!(form, "Action=SendMessage&Tags.member.1.Key=a%20b");
+    assert_eq!(
+        percent_encode_greedy("photos/2020/ü.jpg"),
+        "photos/2020/%C3%BC.jpg"
+    );
+    assert_eq!(
+        url_decode("photos/my+caf%C3%A9%2B.jpg")?,
+        "photos/my café+.jpg"
+    );
+    assert!(url_decode("100%").is_err());
+    assert!(url_decode("%zz").is_err());
+
+    assert_eq!(format_float(1.5f32), "1.5");
+    assert_eq!(format_float(0.1f32), "0.1");
+    assert_eq!(format_float(f64::NAN), "NaN");
+    assert_eq!(format_float(f64::NEG_INFINITY), "-Infinity");
+    assert_eq!(
+        header_list(vec![
+            "a".to_string(),
+            "b,c".to_string(),
+            "say \"hi\"".to_string()
+        ]),
+        r#"a, "b,c", "say \"hi\"""#
+    );
+    assert_eq!(
+        split_header_list(r#"a, "b,c" ,"say \"hi\"",d"#)?,
+        vec!["a", "b,c", "say \"hi\"", "d"]
+    );
+    assert_eq!(split_header_list("")?, vec![""]);
+    assert!(split_header_list(r#""a"#).is_err());
+    assert_eq!(
+        split_http_dates("Mon, 16 Dec 2019 23:48:18 GMT, Tue, 17 Dec 2019 23:48:18 GMT"),
+        vec![
+            "Mon, 16 Dec 2019 23:48:18 GMT",
+            "Tue, 17 Dec 2019 23:48:18 GMT"
+        ]
+    );
+    assert_eq!(header_value("X-Tab", "a\tb".to_string())?, "a\tb");
+    assert!(header_value("X-Line", "a\r\nX-Injected: b".to_string()).is_err());
+
+    for (decoded, encoded) in &[
+        ("", ""),
+        ("f", "Zg=="),
+        ("fo", "Zm8="),
+        ("foo", "Zm9v"),
+        ("foob", "Zm9vYg=="),
+        ("hello?>", "aGVsbG8/Pg=="),
+    ] {
+        assert_eq!(base64_encode(decoded.as_bytes()), *encoded);
+        assert_eq!(base64_decode(encoded)?, decoded.as_bytes());
+    }
+    assert_eq!(base64_decode("Zm9v\nYg==")?, b"foob");
+    assert!(base64_decode("Zm9").is_err());
+    assert!(base64_decode("Zm9*").is_err());
+
+    Ok(())
+}
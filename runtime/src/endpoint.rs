@@ -0,0 +1,540 @@
+use crate::{
+    profile::{profile_name, Profiles},
+    BoxError,
+};
+use std::{
+    collections::{BTreeMap, HashMap},
+    env,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+/// Where a region's requests to a service go, and how they're signed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Endpoint {
+    pub hostname: String,
+    pub signing_region: String,
+    pub signing_name: String,
+}
+
+/// An endpoint's properties in `endpoints.json`. Anything left unset is inherited from the
+/// service's defaults in its partition, then the partition's.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EndpointProperties {
+    // A template such as `{service}.{region}.{dnsSuffix}`.
+    pub hostname: Option<&'static str>,
+    // The region and service name requests are signed for, from the endpoint's credential
+    // scope, when they aren't the region and the service's own.
+    pub signing_region: Option<&'static str>,
+    pub signing_name: Option<&'static str>,
+}
+
+/// A service's endpoints in one of `endpoints.json`'s partitions, e.g. `aws` or `aws-cn`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Partition {
+    pub name: &'static str,
+    pub dns_suffix: &'static str,
+    // Matches the partition's regions that aren't in `regions` yet, e.g. `^cn\-\w+\-\d+$`.
+    pub region_regex: &'static str,
+    pub regions: &'static [&'static str],
+    // The service's defaults in the partition, over the partition's own.
+    pub defaults: EndpointProperties,
+    // Keyed by region, or by a pseudo-region like `aws-global`.
+    pub endpoints: &'static [(&'static str, EndpointProperties)],
+    // The endpoint every region's requests go to, for services that aren't regionalized, e.g.
+    // IAM's `aws-global`.
+    pub partition_endpoint: Option<&'static str>,
+}
+
+impl Partition {
+    fn endpoint(&self, name: &str) -> Option<&EndpointProperties> {
+        self.endpoints
+            .iter()
+            .find(|(endpoint, _)| *endpoint == name)
+            .map(|(_, properties)| properties)
+    }
+
+    fn has_region(&self, region: &str) -> bool {
+        self.regions.contains(&region) || matches_region_regex(self.region_regex, region)
+    }
+}
+
+// The partition regions fall back to when a service's crate was generated without
+// `endpoints.json`, or a region isn't any partition's.
+const AWS: Partition = Partition {
+    name: "aws",
+    dns_suffix: "amazonaws.com",
+    region_regex: "",
+    regions: &[],
+    defaults: EndpointProperties {
+        hostname: Some("{service}.{region}.{dnsSuffix}"),
+        signing_region: None,
+        signing_name: None,
+    },
+    endpoints: &[],
+    partition_endpoint: None,
+};
+
+/// Resolves a service's endpoint for a region from the partitions in `endpoints.json`, as
+/// they were when its crate was generated. Regions that aren't in any partition, e.g. ones
+/// newer than the crate, resolve in the first partition, `aws`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EndpointResolver {
+    // The service's endpoint prefix, which `endpoints.json` keys it by, e.g. `lambda`.
+    service: &'static str,
+    signing_name: &'static str,
+    partitions: &'static [Partition],
+}
+
+impl EndpointResolver {
+    pub const fn new(
+        service: &'static str,
+        signing_name: &'static str,
+        partitions: &'static [Partition],
+    ) -> Self {
+        EndpointResolver {
+            service,
+            signing_name,
+            partitions,
+        }
+    }
+
+    /// The endpoint for a region, e.g. `us-east-1`.
+    pub fn resolve(&self, region: &str) -> Result<Endpoint, BoxError> {
+        if region.is_empty()
+            || !region
+                .bytes()
+                .all(|byte| byte.is_ascii_alphanumeric() || byte == b'-')
+        {
+            return Err(format!("`{}` isn't a region", region).into());
+        }
+        let listed = self
+            .partitions
+            .iter()
+            .find(|partition| partition.endpoint(region).is_some());
+        let partition = listed
+            .or_else(|| {
+                self.partitions
+                    .iter()
+                    .find(|partition| partition.has_region(region))
+            })
+            .or_else(|| self.partitions.first())
+            .unwrap_or(&AWS);
+        let endpoint_name = match partition.partition_endpoint {
+            Some(endpoint) if listed.is_none() => endpoint,
+            _ => region,
+        };
+        let properties = partition
+            .endpoint(endpoint_name)
+            .copied()
+            .unwrap_or_default();
+        let hostname = properties
+            .hostname
+            .or(partition.defaults.hostname)
+            .or(AWS.defaults.hostname)
+            .unwrap_or_default()
+            .replace("{service}", self.service)
+            .replace("{region}", endpoint_name)
+            .replace("{dnsSuffix}", partition.dns_suffix);
+        Ok(Endpoint {
+            hostname,
+            signing_region: properties
+                .signing_region
+                .or(partition.defaults.signing_region)
+                .unwrap_or(region)
+                .to_string(),
+            signing_name: properties
+                .signing_name
+                .or(partition.defaults.signing_name)
+                .unwrap_or(self.signing_name)
+                .to_string(),
+        })
+    }
+}
+
+/// The region clients are configured for when they aren't given one: `AWS_REGION`, or else
+/// `AWS_DEFAULT_REGION`, or else the current profile's `region` in the shared config file.
+pub fn default_region() -> Result<String, BoxError> {
+    let profile = profile_name();
+    let region = setting_from(
+        |name| env::var(name).ok(),
+        &["AWS_REGION", "AWS_DEFAULT_REGION"],
+        &Profiles::load()?,
+        &profile,
+        "region",
+    );
+    region.ok_or_else(|| {
+        format!(
+            "there's no region: set AWS_REGION, or a region for the `{}` profile",
+            profile
+        )
+        .into()
+    })
+}
+
+/// The URL clients send requests to in place of their resolved endpoints, if there is one:
+/// `AWS_ENDPOINT_URL`, or else the current profile's `endpoint_url`, e.g. for LocalStack.
+pub fn default_endpoint_url() -> Result<Option<String>, BoxError> {
+    Ok(setting_from(
+        |name| env::var(name).ok(),
+        &["AWS_ENDPOINT_URL"],
+        &Profiles::load()?,
+        &profile_name(),
+        "endpoint_url",
+    ))
+}
+
+// A setting from the first of some environment variables that's set, as `var` looks them
+// up, or else from a profile.
+pub(crate) fn setting_from(
+    var: impl Fn(&str) -> Option<String>,
+    vars: &[&str],
+    profiles: &Profiles,
+    profile: &str,
+    setting: &str,
+) -> Option<String> {
+    vars.iter()
+        .find_map(|name| var(name).filter(|value| !value.is_empty()))
+        .or_else(|| profiles.get(profile, setting).map(str::to_string))
+}
+
+/// An S3 request URI with its bucket, the first segment of its path, moved into its host,
+/// e.g. `https://bucket.s3.amazonaws.com/key` for `https://s3.amazonaws.com/bucket/key`.
+/// `None` if the bucket's name can't be in a host, which keeps the request path-style. Names
+/// with dots can be over HTTP, if each of their labels can, but over HTTPS they wouldn't match
+/// S3's wildcard TLS certificate, and names like IP addresses never can.
+pub fn virtual_hosted_uri(uri: &str) -> Option<String> {
+    let (scheme, rest) = uri.split_once("://")?;
+    let (host, path) = rest.split_at(rest.find('/')?);
+    let path = &path[1..];
+    let end = path.find(['/', '?']).unwrap_or(path.len());
+    let (bucket, rest) = path.split_at(end);
+    let label = |label: &str| {
+        !label.is_empty()
+            && label
+                .bytes()
+                .all(|byte| byte.is_ascii_lowercase() || byte.is_ascii_digit() || byte == b'-')
+            && !label.starts_with('-')
+            && !label.ends_with('-')
+    };
+    let dns_compatible = (3..=63).contains(&bucket.len())
+        && if bucket.contains('.') {
+            scheme == "http"
+                && bucket.split('.').all(label)
+                && bucket.parse::<std::net::Ipv4Addr>().is_err()
+        } else {
+            label(bucket)
+        };
+    if !dns_compatible {
+        return None;
+    }
+    let rest = if rest.starts_with('/') {
+        rest.to_string()
+    } else {
+        format!("/{}", rest)
+    };
+    Some(format!("{}://{}.{}{}", scheme, bucket, host, rest))
+}
+
+/// The endpoints a client discovered with its service's endpoint operation, e.g. DynamoDB's
+/// `DescribeEndpoints`, each kept until its cache period passes. Clones share them.
+#[derive(Debug, Clone, Default)]
+pub struct EndpointCache {
+    // By key, with when they expire.
+    endpoints: Arc<Mutex<HashMap<String, (String, Instant)>>>,
+}
+
+impl EndpointCache {
+    pub fn new() -> Self {
+        EndpointCache::default()
+    }
+
+    /// What endpoints for an operation's requests are cached under: the same for every
+    /// operation whose requests have no identifiers, and otherwise the operation and its
+    /// identifiers, e.g. a table name.
+    pub fn key(operation: &str, identifiers: &BTreeMap<String, String>) -> String {
+        if identifiers.is_empty() {
+            return String::new();
+        }
+        let mut key = operation.to_string();
+        for (name, value) in identifiers {
+            key.push_str(&format!(" {}={}", name, value));
+        }
+        key
+    }
+
+    /// The endpoint cached under `key`, unless it's expired.
+    pub fn get(&self, key: &str) -> Option<String> {
+        let mut endpoints = self.endpoints.lock().unwrap();
+        match endpoints.get(key) {
+            Some((endpoint, expires)) if *expires > Instant::now() => Some(endpoint.clone()),
+            Some(_) => {
+                endpoints.remove(key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    /// Caches `endpoint` under `key` for `ttl`.
+    pub fn insert(&self, key: String, endpoint: String, ttl: Duration) {
+        let expires = Instant::now() + ttl;
+        self.endpoints
+            .lock()
+            .unwrap()
+            .insert(key, (endpoint, expires));
+    }
+
+    /// Forgets the endpoint cached under `key`, e.g. once it's sent a request elsewhere.
+    pub fn remove(&self, key: &str) {
+        self.endpoints.lock().unwrap().remove(key);
+    }
+}
+
+// Whether a region matches a partition's `regionRegex`. They're all anchored, and made of
+// literals, escapes like `\-`, `\w` and `\d`, alternations of literals in parentheses, and
+// `+`, e.g. `^(us|eu|ap)\-\w+\-\d+$`, which is all that's supported; anything else matches
+// no regions.
+fn matches_region_regex(regex: &str, region: &str) -> bool {
+    enum Atom {
+        Literal(char),
+        Word,
+        Digit,
+        Group(Vec<String>),
+    }
+
+    let pattern = match regex
+        .strip_prefix('^')
+        .and_then(|pattern| pattern.strip_suffix('$'))
+    {
+        Some(pattern) => pattern,
+        None => return false,
+    };
+    let mut atoms = Vec::new();
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        let atom = match c {
+            '\\' => match chars.next() {
+                Some('w') => Atom::Word,
+                Some('d') => Atom::Digit,
+                Some(c) if !c.is_ascii_alphanumeric() => Atom::Literal(c),
+                _ => return false,
+            },
+            '(' => {
+                let group = chars.by_ref().take_while(|c| *c != ')').collect::<String>();
+                if !group.chars().all(|c| c.is_ascii_alphanumeric() || c == '|') {
+                    return false;
+                }
+                Atom::Group(group.split('|').map(str::to_string).collect())
+            }
+            '+' => {
+                if atoms.last().is_none_or(|(_, repeated)| *repeated) {
+                    return false;
+                }
+                atoms.last_mut().unwrap().1 = true;
+                continue;
+            }
+            c if c.is_ascii_alphanumeric() || c == '-' => Atom::Literal(c),
+            _ => return false,
+        };
+        atoms.push((atom, false));
+    }
+
+    // The lengths of the prefixes of `text` that an atom matches once.
+    fn once(atom: &Atom, text: &str) -> Vec<usize> {
+        let first = text.chars().next();
+        match atom {
+            Atom::Literal(c) => first
+                .filter(|first| first == c)
+                .map(char::len_utf8)
+                .into_iter()
+                .collect(),
+            Atom::Word => first
+                .filter(|c| c.is_ascii_alphanumeric() || *c == '_')
+                .map(|_| 1)
+                .into_iter()
+                .collect(),
+            Atom::Digit => first
+                .filter(char::is_ascii_digit)
+                .map(|_| 1)
+                .into_iter()
+                .collect(),
+            Atom::Group(alternatives) => alternatives
+                .iter()
+                .filter(|alternative| text.starts_with(alternative.as_str()))
+                .map(String::len)
+                .collect(),
+        }
+    }
+    fn matches(atoms: &[(Atom, bool)], text: &str) -> bool {
+        let ((atom, repeated), rest) = match atoms.split_first() {
+            Some(first) => first,
+            None => return text.is_empty(),
+        };
+        once(atom, text).into_iter().any(|length| {
+            let text = &text[length..];
+            matches(rest, text) || *repeated && matches(atoms, text)
+        })
+    }
+    matches(&atoms, region)
+}
+
+#[test]
+fn resolves_endpoints() -> Result<(), BoxError> {
+    const PARTITIONS: &[Partition] = &[
+        Partition {
+            name: "aws",
+            dns_suffix: "amazonaws.com",
+            region_regex: "^(us|eu|ap|sa|ca|me)\\-\\w+\\-\\d+$",
+            regions: &["us-east-1", "eu-west-1"],
+            defaults: EndpointProperties {
+                hostname: Some("{service}.{region}.{dnsSuffix}"),
+                signing_region: None,
+                signing_name: None,
+            },
+            endpoints: &[
+                (
+                    "aws-global",
+                    EndpointProperties {
+                        hostname: Some("iam.amazonaws.com"),
+                        signing_region: Some("us-east-1"),
+                        signing_name: None,
+                    },
+                ),
+                (
+                    "eu-west-1",
+                    EndpointProperties {
+                        hostname: None,
+                        signing_region: None,
+                        signing_name: None,
+                    },
+                ),
+            ],
+            partition_endpoint: Some("aws-global"),
+        },
+        Partition {
+            name: "aws-cn",
+            dns_suffix: "amazonaws.com.cn",
+            region_regex: "^cn\\-\\w+\\-\\d+$",
+            regions: &["cn-north-1"],
+            defaults: EndpointProperties {
+                hostname: Some("{service}.{region}.{dnsSuffix}"),
+                signing_region: None,
+                signing_name: Some("iam-cn"),
+            },
+            endpoints: &[],
+            partition_endpoint: None,
+        },
+    ];
+    let resolver = EndpointResolver::new("iam", "iam", PARTITIONS);
+    assert_eq!(
+        resolver.resolve("us-west-2")?,
+        Endpoint {
+            hostname: "iam.amazonaws.com".to_string(),
+            signing_region: "us-east-1".to_string(),
+            signing_name: "iam".to_string(),
+        }
+    );
+    assert_eq!(
+        resolver.resolve("eu-west-1")?.hostname,
+        "iam.eu-west-1.amazonaws.com"
+    );
+    let china = resolver.resolve("cn-northwest-1")?;
+    assert_eq!(china.hostname, "iam.cn-northwest-1.amazonaws.com.cn");
+    assert_eq!(china.signing_region, "cn-northwest-1");
+    assert_eq!(china.signing_name, "iam-cn");
+    // Regions that aren't any partition's are taken to be `aws`'s.
+    assert_eq!(
+        EndpointResolver::new("lambda", "lambda", &[])
+            .resolve("xx-new-1")?
+            .hostname,
+        "lambda.xx-new-1.amazonaws.com"
+    );
+    assert!(resolver.resolve("us-east-1.evil.com/").is_err());
+
+    assert!(matches_region_regex(
+        "^us\\-gov\\-\\w+\\-\\d+$",
+        "us-gov-west-1"
+    ));
+    assert!(!matches_region_regex(
+        "^us\\-gov\\-\\w+\\-\\d+$",
+        "us-west-1"
+    ));
+    assert!(matches_region_regex(
+        "^(us|eu)\\-\\w+\\-\\d+$",
+        "eu-central-12"
+    ));
+    assert!(!matches_region_regex(
+        "^(us|eu)\\-\\w+\\-\\d+$",
+        "eu-central-"
+    ));
+    assert!(!matches_region_regex("^[a-z]+$", "us"));
+
+    let profiles = Profiles::parse("[profile dev]\nregion = eu-west-1\n", "");
+    let vars = |name: &str| match name {
+        "AWS_REGION" => Some(String::new()),
+        "AWS_DEFAULT_REGION" => Some("us-west-2".to_string()),
+        _ => None,
+    };
+    let region = |var: &dyn Fn(&str) -> Option<String>, profile| {
+        setting_from(
+            var,
+            &["AWS_REGION", "AWS_DEFAULT_REGION"],
+            &profiles,
+            profile,
+            "region",
+        )
+    };
+    assert_eq!(region(&vars, "dev").as_deref(), Some("us-west-2"));
+    assert_eq!(region(&|_| None, "dev").as_deref(), Some("eu-west-1"));
+    assert_eq!(region(&|_| None, "default"), None);
+
+    assert_eq!(
+        virtual_hosted_uri("https://s3.amazonaws.com/bucket/a/key?tagging").as_deref(),
+        Some("https://bucket.s3.amazonaws.com/a/key?tagging")
+    );
+    assert_eq!(
+        virtual_hosted_uri("https://s3.amazonaws.com/bucket?list-type=2").as_deref(),
+        Some("https://bucket.s3.amazonaws.com/?list-type=2")
+    );
+    assert_eq!(
+        virtual_hosted_uri("https://s3.amazonaws.com/my.bucket/key"),
+        None
+    );
+    assert_eq!(
+        virtual_hosted_uri("http://localhost:4566/my.bucket/key").as_deref(),
+        Some("http://my.bucket.localhost:4566/key")
+    );
+    assert_eq!(virtual_hosted_uri("http://localhost:4566/my..bucket"), None);
+    assert_eq!(virtual_hosted_uri("http://localhost:4566/10.0.0.1"), None);
+    assert_eq!(virtual_hosted_uri("https://s3.amazonaws.com/Bucket"), None);
+
+    Ok(())
+}
+
+#[test]
+fn caches_discovered_endpoints() {
+    let mut identifiers = BTreeMap::new();
+    assert_eq!(EndpointCache::key("GetItem", &identifiers), "");
+    identifiers.insert("TableName".to_string(), "t".to_string());
+    assert_eq!(
+        EndpointCache::key("GetItem", &identifiers),
+        "GetItem TableName=t"
+    );
+
+    let cache = EndpointCache::new();
+    cache.insert(
+        String::new(),
+        "https://a".to_string(),
+        Duration::from_secs(60),
+    );
+    cache.insert(
+        "expired".to_string(),
+        "https://b".to_string(),
+        Duration::ZERO,
+    );
+    assert_eq!(cache.clone().get("").as_deref(), Some("https://a"));
+    assert_eq!(cache.get("expired"), None);
+    cache.remove("");
+    assert_eq!(cache.get(""), None);
+}
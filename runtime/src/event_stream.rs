@@ -0,0 +1,746 @@
+use crate::{body::Body, hash::crc32, BoxError};
+use bytes::Bytes;
+use futures_core::Stream;
+use std::{
+    convert::TryFrom,
+    fmt,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+// The total length, the headers' length, and the CRC of the two of them.
+const PRELUDE_LEN: usize = 12;
+// The prelude and the CRC of the whole message after it.
+const MIN_MESSAGE_LEN: usize = PRELUDE_LEN + 4;
+// The limits services hold messages to.
+const MAX_MESSAGE_LEN: usize = 16 * 1024 * 1024;
+const MAX_HEADERS_LEN: usize = 128 * 1024;
+
+/// The value of a message header, typed as the `application/vnd.amazon.eventstream` framing
+/// encodes it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HeaderValue {
+    Bool(bool),
+    Byte(i8),
+    Int16(i16),
+    Int32(i32),
+    Int64(i64),
+    Bytes(Vec<u8>),
+    String(String),
+    /// Encoded as milliseconds since the Unix epoch.
+    Timestamp(SystemTime),
+    Uuid([u8; 16]),
+}
+
+impl HeaderValue {
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            HeaderValue::Bool(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// The value of any of the integer types, if it fits in an `i32`.
+    pub fn as_i32(&self) -> Option<i32> {
+        self.as_i64().and_then(|value| i32::try_from(value).ok())
+    }
+
+    /// The value of any of the integer types.
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            HeaderValue::Byte(value) => Some(i64::from(*value)),
+            HeaderValue::Int16(value) => Some(i64::from(*value)),
+            HeaderValue::Int32(value) => Some(i64::from(*value)),
+            HeaderValue::Int64(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        match self {
+            HeaderValue::Bytes(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            HeaderValue::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn as_timestamp(&self) -> Option<SystemTime> {
+        match self {
+            HeaderValue::Timestamp(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    // The type byte the value is encoded after.
+    fn type_byte(&self) -> u8 {
+        match self {
+            HeaderValue::Bool(true) => 0,
+            HeaderValue::Bool(false) => 1,
+            HeaderValue::Byte(_) => 2,
+            HeaderValue::Int16(_) => 3,
+            HeaderValue::Int32(_) => 4,
+            HeaderValue::Int64(_) => 5,
+            HeaderValue::Bytes(_) => 6,
+            HeaderValue::String(_) => 7,
+            HeaderValue::Timestamp(_) => 8,
+            HeaderValue::Uuid(_) => 9,
+        }
+    }
+}
+
+/// A message of an event stream: its headers, in the order they're encoded, and its payload.
+/// Events say what they are in their `:message-type` and `:event-type` headers.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Message {
+    pub headers: Vec<(String, HeaderValue)>,
+    pub payload: Vec<u8>,
+}
+
+impl Message {
+    pub fn new(payload: impl Into<Vec<u8>>) -> Self {
+        Message {
+            headers: Vec::new(),
+            payload: payload.into(),
+        }
+    }
+
+    pub fn with_header(mut self, name: impl Into<String>, value: HeaderValue) -> Self {
+        self.headers.push((name.into(), value));
+        self
+    }
+
+    /// The value of the first header named `name`.
+    pub fn header(&self, name: &str) -> Option<&HeaderValue> {
+        self.headers
+            .iter()
+            .find(|(header, _)| header == name)
+            .map(|(_, value)| value)
+    }
+
+    /// The name of the event or exception the message is, from its `:event-type` or
+    /// `:exception-type` header. Error messages, which aren't modeled, are an error with their
+    /// `:error-code` and `:error-message`.
+    pub fn event_type(&self) -> Result<&str, BoxError> {
+        let header = |name: &str| self.header(name).and_then(HeaderValue::as_str);
+        let name = match header(":message-type") {
+            Some("event") => ":event-type",
+            Some("exception") => ":exception-type",
+            Some("error") => {
+                return Err(format!(
+                    "{}: {}",
+                    header(":error-code").unwrap_or("UnknownError"),
+                    header(":error-message").unwrap_or("the stream failed")
+                )
+                .into())
+            }
+            Some(other) => return Err(format!("unknown message type `{}`", other).into()),
+            None => return Err("the message has no `:message-type`".into()),
+        };
+        header(name).ok_or_else(|| format!("the message has no `{}`", name).into())
+    }
+
+    /// Whether the message is one of the exceptions a stream's events can include, rather
+    /// than an event.
+    pub fn is_exception(&self) -> bool {
+        self.header(":message-type").and_then(HeaderValue::as_str) == Some("exception")
+    }
+
+    /// The message's frame: its prelude, headers, and payload, and the CRC-32 of them.
+    pub fn encode(&self) -> Result<Vec<u8>, BoxError> {
+        let headers = encode_headers(&self.headers)?;
+        let total_len = MIN_MESSAGE_LEN + headers.len() + self.payload.len();
+        if headers.len() > MAX_HEADERS_LEN || total_len > MAX_MESSAGE_LEN {
+            return Err(format!("a {} byte message is too long", total_len).into());
+        }
+
+        let mut out = Vec::with_capacity(total_len);
+        out.extend_from_slice(&(total_len as u32).to_be_bytes());
+        out.extend_from_slice(&(headers.len() as u32).to_be_bytes());
+        out.extend_from_slice(&crc32(&out).to_be_bytes());
+        out.extend_from_slice(&headers);
+        out.extend_from_slice(&self.payload);
+        out.extend_from_slice(&crc32(&out).to_be_bytes());
+        Ok(out)
+    }
+
+    /// Decodes the message `frame` is exactly, checking its lengths and CRCs.
+    pub fn decode(frame: &[u8]) -> Result<Message, BoxError> {
+        let (total_len, headers_len) = prelude(frame)?;
+        if frame.len() != total_len {
+            return Err(format!(
+                "the message is {} bytes, but its prelude says it's {}",
+                frame.len(),
+                total_len
+            )
+            .into());
+        }
+        let (body, crc) = frame.split_at(total_len - 4);
+        if crc32(body) != read_u32(crc) {
+            return Err("the message's CRC doesn't match it".into());
+        }
+
+        let mut headers = Vec::new();
+        let mut reader = Reader(&body[PRELUDE_LEN..PRELUDE_LEN + headers_len]);
+        while !reader.0.is_empty() {
+            let name_len = reader.take(1)?[0] as usize;
+            let name = String::from_utf8(reader.take(name_len)?.to_vec())?;
+            let value = match reader.take(1)?[0] {
+                0 => HeaderValue::Bool(true),
+                1 => HeaderValue::Bool(false),
+                2 => HeaderValue::Byte(reader.take(1)?[0] as i8),
+                3 => HeaderValue::Int16(i16::from_be_bytes(reader.array()?)),
+                4 => HeaderValue::Int32(i32::from_be_bytes(reader.array()?)),
+                5 => HeaderValue::Int64(i64::from_be_bytes(reader.array()?)),
+                6 => HeaderValue::Bytes(reader.bytes()?.to_vec()),
+                7 => HeaderValue::String(String::from_utf8(reader.bytes()?.to_vec())?),
+                8 => HeaderValue::Timestamp(from_epoch_millis(i64::from_be_bytes(reader.array()?))),
+                9 => HeaderValue::Uuid(reader.array()?),
+                other => return Err(format!("header `{}` has unknown type {}", name, other).into()),
+            };
+            headers.push((name, value));
+        }
+        Ok(Message {
+            headers,
+            payload: body[PRELUDE_LEN + headers_len..].to_vec(),
+        })
+    }
+}
+
+// Headers as they're encoded in a message, one after another, which event signatures sign too.
+pub(crate) fn encode_headers(headers: &[(String, HeaderValue)]) -> Result<Vec<u8>, BoxError> {
+    let mut out = Vec::new();
+    for (name, value) in headers {
+        let name_len =
+            u8::try_from(name.len()).map_err(|_| format!("header name `{}` is too long", name))?;
+        out.push(name_len);
+        out.extend_from_slice(name.as_bytes());
+        out.push(value.type_byte());
+        match value {
+            HeaderValue::Bool(_) => {}
+            HeaderValue::Byte(value) => out.extend_from_slice(&value.to_be_bytes()),
+            HeaderValue::Int16(value) => out.extend_from_slice(&value.to_be_bytes()),
+            HeaderValue::Int32(value) => out.extend_from_slice(&value.to_be_bytes()),
+            HeaderValue::Int64(value) => out.extend_from_slice(&value.to_be_bytes()),
+            HeaderValue::Bytes(bytes) => write_bytes(&mut out, name, bytes)?,
+            HeaderValue::String(string) => write_bytes(&mut out, name, string.as_bytes())?,
+            HeaderValue::Timestamp(time) => {
+                out.extend_from_slice(&epoch_millis(*time).to_be_bytes())
+            }
+            HeaderValue::Uuid(uuid) => out.extend_from_slice(uuid),
+        }
+    }
+    Ok(out)
+}
+
+// Appends a byte array or string header's value, after its length.
+fn write_bytes(out: &mut Vec<u8>, name: &str, bytes: &[u8]) -> Result<(), BoxError> {
+    let len = u16::try_from(bytes.len())
+        .map_err(|_| format!("the value of header `{}` is too long", name))?;
+    out.extend_from_slice(&len.to_be_bytes());
+    out.extend_from_slice(bytes);
+    Ok(())
+}
+
+// The total and header lengths of the message `bytes` starts with, once its prelude's CRC
+// checks out and the lengths are ones a message could have.
+fn prelude(bytes: &[u8]) -> Result<(usize, usize), BoxError> {
+    if bytes.len() < PRELUDE_LEN {
+        return Err("the message is shorter than a prelude".into());
+    }
+    if crc32(&bytes[..8]) != read_u32(&bytes[8..PRELUDE_LEN]) {
+        return Err("the message prelude's CRC doesn't match it".into());
+    }
+    let total_len = read_u32(&bytes[..4]) as usize;
+    let headers_len = read_u32(&bytes[4..8]) as usize;
+    if total_len < MIN_MESSAGE_LEN + headers_len
+        || total_len > MAX_MESSAGE_LEN
+        || headers_len > MAX_HEADERS_LEN
+    {
+        return Err(format!(
+            "a {} byte message can't have {} bytes of headers",
+            total_len, headers_len
+        )
+        .into());
+    }
+    Ok((total_len, headers_len))
+}
+
+fn read_u32(bytes: &[u8]) -> u32 {
+    u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+}
+
+fn epoch_millis(time: SystemTime) -> i64 {
+    match time.duration_since(UNIX_EPOCH) {
+        Ok(since) => since.as_millis() as i64,
+        Err(before) => -(before.duration().as_millis() as i64),
+    }
+}
+
+fn from_epoch_millis(millis: i64) -> SystemTime {
+    let duration = Duration::from_millis(millis.unsigned_abs());
+    if millis < 0 {
+        UNIX_EPOCH - duration
+    } else {
+        UNIX_EPOCH + duration
+    }
+}
+
+// Reads headers from the front of what's left of them.
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], BoxError> {
+        if self.0.len() < len {
+            return Err("a header runs past the end of the headers".into());
+        }
+        let (taken, rest) = self.0.split_at(len);
+        self.0 = rest;
+        Ok(taken)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], BoxError> {
+        let mut array = [0; N];
+        array.copy_from_slice(self.take(N)?);
+        Ok(array)
+    }
+
+    // A byte array or string's bytes, after their length.
+    fn bytes(&mut self) -> Result<&'a [u8], BoxError> {
+        let len = u16::from_be_bytes(self.array()?);
+        self.take(len as usize)
+    }
+}
+
+/// Splits a stream's bytes into messages as they arrive, however they're chunked.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MessageDecoder {
+    buffer: Vec<u8>,
+}
+
+impl MessageDecoder {
+    pub fn new() -> Self {
+        MessageDecoder::default()
+    }
+
+    pub fn push(&mut self, bytes: &[u8]) {
+        self.buffer.extend_from_slice(bytes);
+    }
+
+    /// The next whole message pushed, or `None` until the rest of it is.
+    pub fn next_message(&mut self) -> Result<Option<Message>, BoxError> {
+        if self.buffer.len() < PRELUDE_LEN {
+            return Ok(None);
+        }
+        let (total_len, _) = prelude(&self.buffer)?;
+        if self.buffer.len() < total_len {
+            return Ok(None);
+        }
+        let message = Message::decode(&self.buffer[..total_len])?;
+        self.buffer.drain(..total_len);
+        Ok(Some(message))
+    }
+
+    /// How many bytes have been pushed that aren't part of a message returned yet.
+    pub fn remaining(&self) -> usize {
+        self.buffer.len()
+    }
+}
+
+/// The events of a response's event stream, e.g. the records of an S3 `SelectObjectContent`,
+/// each decoded into a `T` by the function the generated code passes. It's a `Stream` of
+/// them, which decodes each message as soon as all of it has come in on the response's body,
+/// and ends after the first error. Clones share the body, as `Body`'s do.
+pub struct EventReceiver<T> {
+    body: Body,
+    decoder: MessageDecoder,
+    // Whether the body's ended, or the stream's failed.
+    ended: bool,
+    unmarshal: fn(&Message) -> Result<T, BoxError>,
+}
+
+impl<T> EventReceiver<T> {
+    pub fn new(body: impl Into<Body>, unmarshal: fn(&Message) -> Result<T, BoxError>) -> Self {
+        EventReceiver {
+            body: body.into(),
+            decoder: MessageDecoder::new(),
+            ended: false,
+            unmarshal,
+        }
+    }
+
+    /// The next event, or `None` once there aren't any more.
+    pub async fn recv(&mut self) -> Result<Option<T>, BoxError> {
+        std::future::poll_fn(|cx| Pin::new(&mut *self).poll_next(cx))
+            .await
+            .transpose()
+    }
+
+    // Ends the stream with `error`.
+    fn fail(&mut self, error: BoxError) -> Poll<Option<Result<T, BoxError>>> {
+        self.body = Body::empty();
+        self.decoder = MessageDecoder::new();
+        self.ended = true;
+        Poll::Ready(Some(Err(error)))
+    }
+}
+
+impl<T> Stream for EventReceiver<T> {
+    type Item = Result<T, BoxError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            match this.decoder.next_message() {
+                Ok(Some(message)) => {
+                    return match (this.unmarshal)(&message) {
+                        Ok(event) => Poll::Ready(Some(Ok(event))),
+                        Err(error) => this.fail(error),
+                    }
+                }
+                Ok(None) if this.ended && this.decoder.remaining() == 0 => {
+                    return Poll::Ready(None)
+                }
+                Ok(None) if this.ended => {
+                    return this.fail("the event stream ends partway through a message".into())
+                }
+                Ok(None) => {}
+                Err(error) => return this.fail(error),
+            }
+            match Pin::new(&mut this.body).poll_next(cx) {
+                Poll::Ready(Some(Ok(chunk))) => this.decoder.push(&chunk),
+                Poll::Ready(Some(Err(error))) => return this.fail(error),
+                Poll::Ready(None) => this.ended = true,
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+impl<T> Clone for EventReceiver<T> {
+    fn clone(&self) -> Self {
+        EventReceiver {
+            body: self.body.clone(),
+            decoder: self.decoder.clone(),
+            ended: self.ended,
+            unmarshal: self.unmarshal,
+        }
+    }
+}
+
+// Receivers of the same type decode events the same way, so they're equal if they have the
+// same events left.
+impl<T> PartialEq for EventReceiver<T> {
+    fn eq(&self, other: &Self) -> bool {
+        (&self.body, &self.decoder, self.ended) == (&other.body, &other.decoder, other.ended)
+    }
+}
+
+impl<T> fmt::Debug for EventReceiver<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("EventReceiver(")?;
+        self.body.describe(f)?;
+        write!(f, ", {} bytes buffered)", self.decoder.remaining())
+    }
+}
+
+/// The events of a request's event stream, e.g. the audio of a Transcribe
+/// `StartStreamTranscription`, in the order they're sent: a `Stream` of them, each encoded and
+/// signed as the request's connector polls its body for more, so events can be sent while the
+/// rest are still being made. The stream is only sent once, so clones share it, as `Body`'s
+/// do.
+pub struct EventSender<T> {
+    events: Arc<Mutex<Option<EventStream<T>>>>,
+}
+
+type EventStream<T> = Pin<Box<dyn Stream<Item = T> + Send>>;
+
+impl<T: Send + 'static> EventSender<T> {
+    pub fn new(events: impl Stream<Item = T> + Send + 'static) -> Self {
+        EventSender {
+            events: Arc::new(Mutex::new(Some(Box::pin(events)))),
+        }
+    }
+}
+
+impl<T: 'static> EventSender<T> {
+    /// The body the events are sent as, each encoded by `marshal` once it's been polled for.
+    /// Bodies of senders that have already been sent fail.
+    pub fn into_body(self, marshal: fn(&T) -> Result<Message, BoxError>) -> Body {
+        let events = self
+            .events
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .take();
+        match events {
+            Some(events) => Body::from_stream(Encoded { events, marshal }, None),
+            None => Body::from_stream(
+                Iter(std::iter::once(Err::<Bytes, BoxError>(
+                    "the event stream has already been sent".into(),
+                ))),
+                None,
+            ),
+        }
+    }
+}
+
+impl<T: Send + 'static> Default for EventSender<T> {
+    fn default() -> Self {
+        Vec::new().into()
+    }
+}
+
+impl<T: Send + 'static> From<Vec<T>> for EventSender<T> {
+    fn from(events: Vec<T>) -> Self {
+        EventSender::new(Iter(events.into_iter()))
+    }
+}
+
+impl<T: Send + 'static> std::iter::FromIterator<T> for EventSender<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(events: I) -> Self {
+        events.into_iter().collect::<Vec<_>>().into()
+    }
+}
+
+impl<T> Clone for EventSender<T> {
+    fn clone(&self) -> Self {
+        EventSender {
+            events: self.events.clone(),
+        }
+    }
+}
+
+impl<T> PartialEq for EventSender<T> {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.events, &other.events)
+    }
+}
+
+impl<T> fmt::Debug for EventSender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("EventSender")
+    }
+}
+
+// The encoded messages of an `EventSender`'s events.
+struct Encoded<T> {
+    events: EventStream<T>,
+    marshal: fn(&T) -> Result<Message, BoxError>,
+}
+
+impl<T> Stream for Encoded<T> {
+    type Item = Result<Bytes, BoxError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let marshal = self.marshal;
+        self.events
+            .as_mut()
+            .poll_next(cx)
+            .map(|event| event.map(|event| Ok(marshal(&event)?.encode()?.into())))
+    }
+}
+
+// An iterator's items, as a stream that's never pending. The iterator itself is never pinned.
+struct Iter<I>(I);
+
+impl<I> Unpin for Iter<I> {}
+
+impl<I: Iterator> Stream for Iter<I> {
+    type Item = I::Item;
+
+    fn poll_next(mut self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Option<I::Item>> {
+        Poll::Ready(self.0.next())
+    }
+}
+
+#[test]
+fn encodes_messages() -> Result<(), BoxError> {
+    // The empty message from the event stream test suite.
+    let empty = Message::default().encode()?;
+    assert_eq!(
+        crate::hash::hex_encode(&empty),
+        "000000100000000005c248eb7d98c8ff"
+    );
+    assert_eq!(Message::decode(&empty)?, Message::default());
+
+    let message = Message::new(b"{\"foo\":\"bar\"}".to_vec())
+        .with_header(":message-type", HeaderValue::String("event".to_string()))
+        .with_header(":event-type", HeaderValue::String("Records".to_string()))
+        .with_header("flag", HeaderValue::Bool(false))
+        .with_header("byte", HeaderValue::Byte(-1))
+        .with_header("short", HeaderValue::Int16(300))
+        .with_header("int", HeaderValue::Int32(-70_000))
+        .with_header("long", HeaderValue::Int64(1 << 40))
+        .with_header("bytes", HeaderValue::Bytes(vec![0, 1, 2]))
+        .with_header(
+            "time",
+            HeaderValue::Timestamp(UNIX_EPOCH - Duration::from_millis(1500)),
+        )
+        .with_header("id", HeaderValue::Uuid([7; 16]));
+    let frame = message.encode()?;
+    assert_eq!(Message::decode(&frame)?, message);
+    assert_eq!(message.event_type()?, "Records");
+    assert!(!message.is_exception());
+    assert_eq!(
+        message.header("int").and_then(HeaderValue::as_i32),
+        Some(-70_000)
+    );
+    assert_eq!(message.header("long").and_then(HeaderValue::as_i32), None);
+
+    let mut corrupt = frame.clone();
+    corrupt[20] ^= 1;
+    assert!(Message::decode(&corrupt).is_err());
+    corrupt = frame.clone();
+    corrupt[2] ^= 1;
+    assert!(Message::decode(&corrupt)
+        .unwrap_err()
+        .to_string()
+        .contains("prelude"));
+
+    let error = Message::default()
+        .with_header(":message-type", HeaderValue::String("error".to_string()))
+        .with_header(
+            ":error-code",
+            HeaderValue::String("InternalError".to_string()),
+        )
+        .with_header(":error-message", HeaderValue::String("oops".to_string()));
+    assert_eq!(
+        error.event_type().unwrap_err().to_string(),
+        "InternalError: oops"
+    );
+    Ok(())
+}
+
+// A stream of the chunks sent on a channel, which is pending until the next one is, and ends
+// once the channel's closed, for tests.
+#[cfg(test)]
+struct Pending(std::sync::Mutex<std::sync::mpsc::Receiver<Vec<u8>>>);
+
+#[cfg(test)]
+impl Stream for Pending {
+    type Item = Result<bytes::Bytes, BoxError>;
+
+    fn poll_next(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let events = self
+            .0
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        match events.try_recv() {
+            Ok(chunk) => Poll::Ready(Some(Ok(chunk.into()))),
+            Err(std::sync::mpsc::TryRecvError::Empty) => Poll::Pending,
+            Err(std::sync::mpsc::TryRecvError::Disconnected) => Poll::Ready(None),
+        }
+    }
+}
+
+#[test]
+fn receives_events() -> Result<(), BoxError> {
+    let event = |payload: &str| {
+        Message::new(payload)
+            .with_header(":message-type", HeaderValue::String("event".to_string()))
+            .with_header(":event-type", HeaderValue::String("Text".to_string()))
+            .encode()
+    };
+    let mut body = event("hello, ")?;
+    body.extend(event("world")?);
+
+    // However the bytes arrive, the decoder only hands out whole messages.
+    let mut decoder = MessageDecoder::new();
+    for chunk in body.chunks(5) {
+        decoder.push(chunk);
+    }
+    assert!(decoder.next_message()?.is_some());
+    assert!(decoder.next_message()?.is_some());
+    assert_eq!(decoder.next_message()?, None);
+
+    let unmarshal = |message: &Message| -> Result<String, BoxError> {
+        Ok(String::from_utf8(message.payload.clone())?)
+    };
+    let mut receiver = EventReceiver::new(body.clone(), unmarshal);
+    assert_eq!(
+        format!("{:?}", receiver),
+        format!("EventReceiver({} bytes, 0 bytes buffered)", body.len())
+    );
+    let recv = |receiver: &mut EventReceiver<String>| crate::blocking::block_on(receiver.recv());
+    assert_eq!(recv(&mut receiver.clone())?.as_deref(), Some("hello, "));
+    assert_eq!(recv(&mut receiver)?.as_deref(), Some("hello, "));
+    assert_eq!(recv(&mut receiver)?.as_deref(), Some("world"));
+    assert_eq!(recv(&mut receiver)?, None);
+
+    // A stream that's cut off ends with an error.
+    let mut truncated = EventReceiver::new(&body[..body.len() - 1], unmarshal);
+    assert!(recv(&mut truncated).is_ok());
+    assert!(recv(&mut truncated).is_err());
+    assert_eq!(recv(&mut truncated)?, None);
+
+    // Events are decoded as soon as they've come in, before the rest of the body has.
+    let (first, rest) = body.split_at(event("hello, ")?.len());
+    let chunks = [first.to_vec(), rest[..3].to_vec(), rest[3..].to_vec()];
+    let (sender, events) = std::sync::mpsc::channel::<Vec<u8>>();
+    let stream = Pending(std::sync::Mutex::new(events));
+    let mut receiver = EventReceiver::new(Body::from_stream(stream, None), unmarshal);
+    let mut cx = Context::from_waker(std::task::Waker::noop());
+    let mut poll =
+        |receiver: &mut EventReceiver<String>| match Pin::new(receiver).poll_next(&mut cx) {
+            Poll::Ready(event) => event.transpose().map(Some),
+            Poll::Pending => Ok(None),
+        };
+    assert_eq!(poll(&mut receiver)?, None);
+    sender.send(chunks[0].clone())?;
+    assert_eq!(poll(&mut receiver)?, Some(Some("hello, ".to_string())));
+    sender.send(chunks[1].clone())?;
+    assert_eq!(poll(&mut receiver)?, None);
+    sender.send(chunks[2].clone())?;
+    assert_eq!(poll(&mut receiver)?, Some(Some("world".to_string())));
+    drop(sender);
+    assert_eq!(poll(&mut receiver)?, Some(None));
+
+    // A corrupt prelude fails without waiting for the rest of its message.
+    let mut corrupt = event("oops")?;
+    corrupt[0] ^= 1;
+    let mut receiver = EventReceiver::new(corrupt[..PRELUDE_LEN].to_vec(), unmarshal);
+    assert!(poll(&mut receiver).is_err());
+    Ok(())
+}
+
+#[test]
+fn sends_events() -> Result<(), BoxError> {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static ENCODED: AtomicUsize = AtomicUsize::new(0);
+    let marshal = |event: &String| -> Result<Message, BoxError> {
+        ENCODED.fetch_add(1, Ordering::SeqCst);
+        Ok(Message::new(event.as_bytes()))
+    };
+    let sender: EventSender<String> = vec!["hello, ".to_string(), "world".to_string()].into();
+    let clone = sender.clone();
+    assert_eq!(sender, clone);
+    assert_ne!(sender, EventSender::default());
+
+    // Events are only encoded as the body's polled for them.
+    let mut body = sender.into_body(marshal);
+    assert_eq!(body.content_length(), None);
+    assert_eq!(ENCODED.load(Ordering::SeqCst), 0);
+    let mut cx = Context::from_waker(std::task::Waker::noop());
+    let chunk = match Pin::new(&mut body).poll_next(&mut cx) {
+        Poll::Ready(Some(chunk)) => chunk?,
+        _ => return Err("no event".into()),
+    };
+    assert_eq!(Message::decode(&chunk)?, Message::new("hello, "));
+    assert_eq!(ENCODED.load(Ordering::SeqCst), 1);
+    let mut decoder = MessageDecoder::new();
+    decoder.push(&crate::blocking::block_on(body.collect())?);
+    assert_eq!(decoder.next_message()?, Some(Message::new("world")));
+    assert_eq!(decoder.next_message()?, None);
+
+    // Clones share the stream, which can only be sent once.
+    assert!(crate::blocking::block_on(clone.into_body(marshal).collect()).is_err());
+    Ok(())
+}
@@ -0,0 +1,365 @@
+// The SHA-256 round constants: the first 32 bits of the fractional parts of the cube roots of
+// the first 64 primes.
+const K: [u32; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+// Mixes a 64-byte block into the hash state.
+fn compress(state: &mut [u32; 8], block: &[u8]) {
+    let mut w = [0u32; 64];
+    for (i, word) in block.chunks(4).enumerate() {
+        w[i] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+    }
+    for i in 16..64 {
+        let s0 = w[i - 15].rotate_right(7) ^ w[i - 15].rotate_right(18) ^ (w[i - 15] >> 3);
+        let s1 = w[i - 2].rotate_right(17) ^ w[i - 2].rotate_right(19) ^ (w[i - 2] >> 10);
+        w[i] = w[i - 16]
+            .wrapping_add(s0)
+            .wrapping_add(w[i - 7])
+            .wrapping_add(s1);
+    }
+
+    let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = *state;
+    for i in 0..64 {
+        let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+        let ch = (e & f) ^ (!e & g);
+        let t1 = h
+            .wrapping_add(s1)
+            .wrapping_add(ch)
+            .wrapping_add(K[i])
+            .wrapping_add(w[i]);
+        let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+        let maj = (a & b) ^ (a & c) ^ (b & c);
+        let t2 = s0.wrapping_add(maj);
+        h = g;
+        g = f;
+        f = e;
+        e = d.wrapping_add(t1);
+        d = c;
+        c = b;
+        b = a;
+        a = t1.wrapping_add(t2);
+    }
+    for (word, value) in state.iter_mut().zip(&[a, b, c, d, e, f, g, h]) {
+        *word = word.wrapping_add(*value);
+    }
+}
+
+/// The SHA-256 digest of some bytes, which SigV4 hashes requests and payloads with.
+pub fn sha256(data: &[u8]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(data);
+    hasher.finish()
+}
+
+/// SHA-256 of bytes that come a chunk at a time, e.g. a body's as it streams in.
+#[derive(Debug, Clone)]
+pub struct Sha256(Blocks<[u32; 8]>);
+
+impl Sha256 {
+    pub fn new() -> Self {
+        Sha256(Blocks::new(
+            [
+                0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab,
+                0x5be0cd19,
+            ],
+            compress,
+        ))
+    }
+
+    pub fn update(&mut self, data: &[u8]) {
+        self.0.update(data);
+    }
+
+    /// The digest of everything so far.
+    pub fn finish(self) -> [u8; 32] {
+        let mut out = [0; 32];
+        for (bytes, word) in out.chunks_mut(4).zip(&self.0.finish()) {
+            bytes.copy_from_slice(&word.to_be_bytes());
+        }
+        out
+    }
+}
+
+impl Default for Sha256 {
+    fn default() -> Self {
+        Sha256::new()
+    }
+}
+
+/// The SHA-1 digest of some bytes, which names the files the SSO token cache keeps tokens in.
+/// It isn't secure for anything else.
+pub fn sha1(data: &[u8]) -> [u8; 20] {
+    let mut hasher = Sha1::new();
+    hasher.update(data);
+    hasher.finish()
+}
+
+/// SHA-1 of bytes that come a chunk at a time, as `Sha256` is.
+#[derive(Debug, Clone)]
+pub struct Sha1(Blocks<[u32; 5]>);
+
+impl Sha1 {
+    pub fn new() -> Self {
+        Sha1(Blocks::new(
+            [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476, 0xc3d2e1f0],
+            compress_sha1,
+        ))
+    }
+
+    pub fn update(&mut self, data: &[u8]) {
+        self.0.update(data);
+    }
+
+    pub fn finish(self) -> [u8; 20] {
+        let mut out = [0; 20];
+        for (bytes, word) in out.chunks_mut(4).zip(&self.0.finish()) {
+            bytes.copy_from_slice(&word.to_be_bytes());
+        }
+        out
+    }
+}
+
+impl Default for Sha1 {
+    fn default() -> Self {
+        Sha1::new()
+    }
+}
+
+// Mixes a 64-byte block into a SHA-1 state.
+fn compress_sha1(state: &mut [u32; 5], block: &[u8]) {
+    let mut w = [0u32; 80];
+    for (i, word) in block.chunks(4).enumerate() {
+        w[i] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+    }
+    for i in 16..80 {
+        w[i] = (w[i - 3] ^ w[i - 8] ^ w[i - 14] ^ w[i - 16]).rotate_left(1);
+    }
+
+    let [mut a, mut b, mut c, mut d, mut e] = state;
+    for (i, word) in w.iter().enumerate() {
+        let (f, k) = match i {
+            0..=19 => ((b & c) | (!b & d), 0x5a827999),
+            20..=39 => (b ^ c ^ d, 0x6ed9eba1),
+            40..=59 => ((b & c) | (b & d) | (c & d), 0x8f1bbcdc),
+            _ => (b ^ c ^ d, 0xca62c1d6),
+        };
+        let t = a
+            .rotate_left(5)
+            .wrapping_add(f)
+            .wrapping_add(e)
+            .wrapping_add(k)
+            .wrapping_add(*word);
+        e = d;
+        d = c;
+        c = b.rotate_left(30);
+        b = a;
+        a = t;
+    }
+    for (word, value) in state.iter_mut().zip(&[a, b, c, d, e]) {
+        *word = word.wrapping_add(*value);
+    }
+}
+
+// The 64-byte blocks SHA-1 and SHA-256 hash, mixed into their state as they fill up: the
+// data, then a 1 bit, zeros up to 8 bytes short of a block boundary, and the length in bits.
+#[derive(Clone)]
+struct Blocks<S> {
+    state: S,
+    compress: fn(&mut S, &[u8]),
+    partial: Vec<u8>,
+    len: u64,
+}
+
+impl<S> Blocks<S> {
+    fn new(state: S, compress: fn(&mut S, &[u8])) -> Self {
+        Blocks {
+            state,
+            compress,
+            partial: Vec::with_capacity(64),
+            len: 0,
+        }
+    }
+
+    fn update(&mut self, mut data: &[u8]) {
+        self.len += data.len() as u64;
+        if !self.partial.is_empty() {
+            let needed = (64 - self.partial.len()).min(data.len());
+            self.partial.extend_from_slice(&data[..needed]);
+            data = &data[needed..];
+            if self.partial.len() < 64 {
+                return;
+            }
+            (self.compress)(&mut self.state, &self.partial);
+            self.partial.clear();
+        }
+        let mut blocks = data.chunks_exact(64);
+        for block in blocks.by_ref() {
+            (self.compress)(&mut self.state, block);
+        }
+        self.partial.extend_from_slice(blocks.remainder());
+    }
+
+    fn finish(mut self) -> S {
+        let mut tail = std::mem::take(&mut self.partial);
+        tail.push(0x80);
+        while tail.len() % 64 != 56 {
+            tail.push(0);
+        }
+        tail.extend_from_slice(&(self.len * 8).to_be_bytes());
+        for block in tail.chunks(64) {
+            (self.compress)(&mut self.state, block);
+        }
+        self.state
+    }
+}
+
+// Hash states are as secret as what's hashed, so only how much has been is printed.
+impl<S> std::fmt::Debug for Blocks<S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} bytes", self.len)
+    }
+}
+
+/// The HMAC-SHA256 of some bytes under a key, which SigV4 derives signing keys and signs
+/// with.
+pub fn hmac_sha256(key: &[u8], data: &[u8]) -> [u8; 32] {
+    let mut block = [0u8; 64];
+    if key.len() > 64 {
+        block[..32].copy_from_slice(&sha256(key));
+    } else {
+        block[..key.len()].copy_from_slice(key);
+    }
+    let mut inner = block.iter().map(|byte| byte ^ 0x36).collect::<Vec<_>>();
+    inner.extend_from_slice(data);
+    let mut outer = block.iter().map(|byte| byte ^ 0x5c).collect::<Vec<_>>();
+    outer.extend_from_slice(&sha256(&inner));
+    sha256(&outer)
+}
+
+/// Lowercase hex, e.g. `e3b0c442` for `[0xe3, 0xb0, 0xc4, 0x42]`.
+pub fn hex_encode(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// The CRC-32 (as zlib and gzip compute it) of some bytes, which event stream messages are
+/// checked with.
+pub fn crc32(data: &[u8]) -> u32 {
+    let mut crc = Crc::crc32();
+    crc.update(data);
+    crc.finish()
+}
+
+/// The CRC-32C (Castagnoli) of some bytes, one of the checksums S3 can check objects with.
+pub fn crc32c(data: &[u8]) -> u32 {
+    let mut crc = Crc::crc32c();
+    crc.update(data);
+    crc.finish()
+}
+
+/// A reflected CRC-32, CRC-32 itself or CRC-32C, of bytes that come a chunk at a time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Crc {
+    poly: u32,
+    crc: u32,
+}
+
+impl Crc {
+    pub fn crc32() -> Self {
+        Crc {
+            poly: 0xedb8_8320,
+            crc: !0,
+        }
+    }
+
+    pub fn crc32c() -> Self {
+        Crc {
+            poly: 0x82f6_3b78,
+            crc: !0,
+        }
+    }
+
+    // Bit by bit.
+    pub fn update(&mut self, data: &[u8]) {
+        let poly = self.poly;
+        self.crc = data.iter().fold(self.crc, |crc, byte| {
+            (0..8).fold(crc ^ u32::from(*byte), |crc, _| {
+                (crc >> 1) ^ (poly & (crc & 1).wrapping_neg())
+            })
+        });
+    }
+
+    pub fn finish(self) -> u32 {
+        !self.crc
+    }
+}
+
+#[test]
+fn hashes_bytes() {
+    assert_eq!(
+        hex_encode(&sha256(b"")),
+        "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+    );
+    assert_eq!(
+        hex_encode(&sha256(b"abc")),
+        "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+    );
+    // Two blocks once it's padded.
+    assert_eq!(
+        hex_encode(&sha256(
+            b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq"
+        )),
+        "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1"
+    );
+    assert_eq!(
+        hex_encode(&sha1(b"abc")),
+        "a9993e364706816aba3e25717850c26c9cd0d89d"
+    );
+    assert_eq!(
+        hex_encode(&sha1(
+            b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq"
+        )),
+        "84983e441c3bd26ebaae4aa1f95129e5e54670f1"
+    );
+    // RFC 4231's test cases 2 and 6, the latter with a key longer than a block.
+    assert_eq!(
+        hex_encode(&hmac_sha256(b"Jefe", b"what do ya want for nothing?")),
+        "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+    );
+    assert_eq!(
+        hex_encode(&hmac_sha256(
+            &[0xaa; 131],
+            b"Test Using Larger Than Block-Size Key - Hash Key First"
+        )),
+        "60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54"
+    );
+    assert_eq!(crc32(b""), 0);
+    assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+    assert_eq!(crc32c(b""), 0);
+    assert_eq!(crc32c(b"123456789"), 0xe306_9283);
+
+    // However the bytes are split up.
+    let data = (0..200u8).collect::<Vec<_>>();
+    for split in [0, 1, 63, 64, 65, 130, 200] {
+        let (a, b) = data.split_at(split);
+        let mut hasher = Sha256::new();
+        hasher.update(a);
+        hasher.update(b);
+        assert_eq!(hasher.finish(), sha256(&data));
+        let mut hasher = Sha1::new();
+        hasher.update(a);
+        hasher.update(b);
+        assert_eq!(hasher.finish(), sha1(&data));
+        let mut crc = Crc::crc32c();
+        crc.update(a);
+        crc.update(b);
+        assert_eq!(crc.finish(), crc32c(&data));
+    }
+}
@@ -0,0 +1,222 @@
+use crate::BoxError;
+
+// A JSON value, as much of it as credentials providers need to read the documents that
+// IMDS and other credentials services send back.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Value {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Value>),
+    Object(Vec<(String, Value)>),
+}
+
+impl Value {
+    // The value of an object's member, if this is an object and has it.
+    pub(crate) fn get(&self, name: &str) -> Option<&Value> {
+        match self {
+            Value::Object(members) => members
+                .iter()
+                .find(|(member, _)| member == name)
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(text) => Some(text),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_f64(&self) -> Option<f64> {
+        match self {
+            Value::Number(number) => Some(*number),
+            _ => None,
+        }
+    }
+}
+
+// Parses a JSON document.
+pub(crate) fn parse(json: &[u8]) -> Result<Value, BoxError> {
+    let json = std::str::from_utf8(json)?;
+    let mut parser = Parser { json, at: 0 };
+    let value = parser.value()?;
+    parser.skip_whitespace();
+    if parser.at != json.len() {
+        return Err(parser.error("content after the value"));
+    }
+    Ok(value)
+}
+
+struct Parser<'a> {
+    json: &'a str,
+    at: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn rest(&self) -> &'a str {
+        &self.json[self.at..]
+    }
+
+    fn error(&self, problem: &str) -> BoxError {
+        format!("invalid JSON at byte {}: {}", self.at, problem).into()
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.at += rest.len() - rest.trim_start().len();
+    }
+
+    // Skips whitespace and then `token`, failing if it isn't next.
+    fn expect(&mut self, token: &str) -> Result<(), BoxError> {
+        self.skip_whitespace();
+        if !self.rest().starts_with(token) {
+            return Err(self.error(&format!("expected `{}`", token)));
+        }
+        self.at += token.len();
+        Ok(())
+    }
+
+    // Skips whitespace and then `token` if it's next.
+    fn eat(&mut self, token: &str) -> bool {
+        self.skip_whitespace();
+        let found = self.rest().starts_with(token);
+        if found {
+            self.at += token.len();
+        }
+        found
+    }
+
+    fn value(&mut self) -> Result<Value, BoxError> {
+        self.skip_whitespace();
+        let rest = self.rest();
+        if self.eat("null") {
+            Ok(Value::Null)
+        } else if self.eat("true") {
+            Ok(Value::Bool(true))
+        } else if self.eat("false") {
+            Ok(Value::Bool(false))
+        } else if rest.starts_with('"') {
+            self.string().map(Value::String)
+        } else if self.eat("[") {
+            let mut items = Vec::new();
+            if !self.eat("]") {
+                loop {
+                    items.push(self.value()?);
+                    if self.eat("]") {
+                        break;
+                    }
+                    self.expect(",")?;
+                }
+            }
+            Ok(Value::Array(items))
+        } else if self.eat("{") {
+            let mut members = Vec::new();
+            if !self.eat("}") {
+                loop {
+                    self.skip_whitespace();
+                    let name = self.string()?;
+                    self.expect(":")?;
+                    members.push((name, self.value()?));
+                    if self.eat("}") {
+                        break;
+                    }
+                    self.expect(",")?;
+                }
+            }
+            Ok(Value::Object(members))
+        } else {
+            let len = rest
+                .find(|c: char| !matches!(c, '0'..='9' | '-' | '+' | '.' | 'e' | 'E'))
+                .unwrap_or(rest.len());
+            let number = rest[..len]
+                .parse()
+                .map_err(|_| self.error("expected a value"))?;
+            self.at += len;
+            Ok(Value::Number(number))
+        }
+    }
+
+    fn string(&mut self) -> Result<String, BoxError> {
+        self.expect("\"")?;
+        let mut out = String::new();
+        let mut chars = self.rest().char_indices();
+        while let Some((at, c)) = chars.next() {
+            match c {
+                '"' => {
+                    self.at += at + 1;
+                    return Ok(out);
+                }
+                '\\' => match chars.next().map(|(_, c)| c) {
+                    Some('n') => out.push('\n'),
+                    Some('t') => out.push('\t'),
+                    Some('r') => out.push('\r'),
+                    Some('b') => out.push('\u{8}'),
+                    Some('f') => out.push('\u{c}'),
+                    Some('u') => {
+                        let high =
+                            code_unit(&mut chars).ok_or_else(|| self.error("bad `\\u` escape"))?;
+                        // Characters outside the BMP are escaped as surrogate pairs.
+                        let code = if (0xd800..0xdc00).contains(&high) {
+                            let low = match (chars.next(), chars.next()) {
+                                (Some((_, '\\')), Some((_, 'u'))) => code_unit(&mut chars),
+                                _ => None,
+                            }
+                            .ok_or_else(|| self.error("unpaired surrogate"))?;
+                            0x10000 + ((high - 0xd800) << 10) + (low.wrapping_sub(0xdc00) & 0x3ff)
+                        } else {
+                            high
+                        };
+                        out.push(
+                            char::from_u32(code).ok_or_else(|| self.error("bad `\\u` escape"))?,
+                        );
+                    }
+                    Some(c @ ('"' | '\\' | '/')) => out.push(c),
+                    _ => return Err(self.error("bad escape")),
+                },
+                c => out.push(c),
+            }
+        }
+        Err(self.error("unterminated string"))
+    }
+}
+
+// The four hex digits of a `\u` escape.
+fn code_unit(chars: &mut std::str::CharIndices<'_>) -> Option<u32> {
+    let hex = chars.take(4).map(|(_, c)| c).collect::<String>();
+    match hex.len() {
+        4 => u32::from_str_radix(&hex, 16).ok(),
+        _ => None,
+    }
+}
+
+#[test]
+fn parses_json() -> Result<(), BoxError> {
+    let value = parse(
+        br#" {"Code": "Success", "Expiration": 1.5e3, "Nested": {"List": [null, true, false]},
+             "Escaped": "a\"b\\c\n\u00e9\ud83d\ude00"} "#,
+    )?;
+    assert_eq!(value.get("Code").and_then(Value::as_str), Some("Success"));
+    assert_eq!(value.get("Expiration"), Some(&Value::Number(1500.0)));
+    assert_eq!(
+        value.get("Nested").and_then(|nested| nested.get("List")),
+        Some(&Value::Array(vec![
+            Value::Null,
+            Value::Bool(true),
+            Value::Bool(false)
+        ]))
+    );
+    assert_eq!(
+        value.get("Escaped").and_then(Value::as_str),
+        Some("a\"b\\c\né😀")
+    );
+    assert_eq!(parse(b"[]")?, Value::Array(Vec::new()));
+    assert!(parse(b"{\"a\": 1,}").is_err());
+    assert!(parse(b"\"open").is_err());
+    assert!(parse(b"1 2").is_err());
+
+    Ok(())
+}
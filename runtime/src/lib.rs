@@ -0,0 +1,27 @@
+//! Code shared by the crates generated for each service, so it's compiled and tested once
+//! rather than emitted into every one of them.
+
+pub mod blocking;
+pub mod body;
+pub mod byte_stream;
+pub mod checksum;
+pub mod compression;
+pub mod connector;
+pub mod credentials;
+pub mod encoding;
+pub mod endpoint;
+pub mod event_stream;
+pub mod hash;
+mod json;
+pub mod paginator;
+pub mod profile;
+pub mod retry;
+pub mod sigv4;
+pub mod time;
+pub mod timeout;
+pub mod upload;
+pub mod uuid;
+pub mod waiter;
+pub mod xml;
+
+pub type BoxError = Box<dyn std::error::Error + Send + Sync + 'static>;
@@ -0,0 +1,180 @@
+use futures_core::Stream;
+use std::{
+    fmt,
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+type PageFuture<O, E> = Pin<Box<dyn Future<Output = Result<O, E>> + Send>>;
+
+/// The pages of an operation's results, e.g. a Lambda `ListFunctions`' functions, as a
+/// `Stream` of its outputs. Each request after the first is the one before it with the input
+/// the generated code's `next_input` makes from the last output, and the stream ends when it
+/// makes none or after the first error. `items` streams the `T`s on the pages instead.
+pub struct Paginator<I, O, E, T> {
+    // The next page's input, until there isn't one.
+    input: Option<I>,
+    send: Arc<dyn Fn(I) -> PageFuture<O, E> + Send + Sync>,
+    next_input: fn(I, &O) -> Option<I>,
+    items: fn(O) -> Vec<T>,
+    // The request for a page while it's being sent, with its input.
+    page: Option<(I, PageFuture<O, E>)>,
+}
+
+impl<I: Clone + Unpin, O, E, T> Paginator<I, O, E, T> {
+    pub fn new<F, Fut>(
+        input: I,
+        send: F,
+        next_input: fn(I, &O) -> Option<I>,
+        items: fn(O) -> Vec<T>,
+    ) -> Self
+    where
+        F: Fn(I) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<O, E>> + Send + 'static,
+    {
+        Paginator {
+            input: Some(input),
+            send: Arc::new(move |input| Box::pin(send(input))),
+            next_input,
+            items,
+            page: None,
+        }
+    }
+
+    /// The next page, or `None` once there aren't any more.
+    pub async fn next_page(&mut self) -> Option<Result<O, E>> {
+        std::future::poll_fn(|cx| Pin::new(&mut *self).poll_next(cx)).await
+    }
+
+    /// The items on every page, one page after another.
+    pub fn items(self) -> Items<I, O, E, T> {
+        Items {
+            pages: self,
+            items: Vec::new().into_iter(),
+        }
+    }
+}
+
+impl<I: Clone + Unpin, O, E, T> Stream for Paginator<I, O, E, T> {
+    type Item = Result<O, E>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        let (input, mut page) = match (this.page.take(), this.input.take()) {
+            (Some(page), _) => page,
+            (None, Some(input)) => (input.clone(), (this.send)(input)),
+            (None, None) => return Poll::Ready(None),
+        };
+        match page.as_mut().poll(cx) {
+            Poll::Pending => {
+                this.page = Some((input, page));
+                Poll::Pending
+            }
+            Poll::Ready(Ok(output)) => {
+                this.input = (this.next_input)(input, &output);
+                Poll::Ready(Some(Ok(output)))
+            }
+            Poll::Ready(Err(error)) => Poll::Ready(Some(Err(error))),
+        }
+    }
+}
+
+impl<I, O, E, T> fmt::Debug for Paginator<I, O, E, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let done = self.input.is_none() && self.page.is_none();
+        f.debug_struct("Paginator").field("done", &done).finish()
+    }
+}
+
+/// The items on a `Paginator`'s pages, as a `Stream` that ends with its pages.
+pub struct Items<I, O, E, T> {
+    pages: Paginator<I, O, E, T>,
+    // What's left of the last page's items.
+    items: std::vec::IntoIter<T>,
+}
+
+impl<I: Clone + Unpin, O, E, T: Unpin> Items<I, O, E, T> {
+    /// The next item, or `None` once there aren't any more.
+    pub async fn next_item(&mut self) -> Option<Result<T, E>> {
+        std::future::poll_fn(|cx| Pin::new(&mut *self).poll_next(cx)).await
+    }
+}
+
+impl<I: Clone + Unpin, O, E, T: Unpin> Stream for Items<I, O, E, T> {
+    type Item = Result<T, E>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            if let Some(item) = this.items.next() {
+                return Poll::Ready(Some(Ok(item)));
+            }
+            match Pin::new(&mut this.pages).poll_next(cx) {
+                Poll::Pending => return Poll::Pending,
+                Poll::Ready(Some(Ok(output))) => {
+                    this.items = (this.pages.items)(output).into_iter();
+                }
+                Poll::Ready(Some(Err(error))) => return Poll::Ready(Some(Err(error))),
+                Poll::Ready(None) => return Poll::Ready(None),
+            }
+        }
+    }
+}
+
+impl<I, O, E, T> fmt::Debug for Items<I, O, E, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Items")
+            .field("pages", &self.pages)
+            .field("left", &self.items.len())
+            .finish()
+    }
+}
+
+#[test]
+fn paginates() {
+    use crate::blocking::block_on;
+    use std::sync::Mutex;
+
+    // Pages of two numbers, up to 5, whose token is the number to start the next page at.
+    let sent = Arc::new(Mutex::new(Vec::new()));
+    let paginator = {
+        let sent = sent.clone();
+        Paginator::new(
+            0u32,
+            move |start: u32| {
+                sent.lock().unwrap().push(start);
+                async move { Ok::<_, ()>(((start..start + 2).collect(), start + 2)) }
+            },
+            |_, (_, next): &(Vec<u32>, u32)| Some(*next).filter(|next| *next < 6),
+            |(items, _)| items,
+        )
+    };
+    assert_eq!(format!("{:?}", paginator), "Paginator { done: false }");
+    let mut items = paginator.items();
+    let mut collected = Vec::new();
+    while let Some(item) = block_on(items.next_item()) {
+        collected.push(item.unwrap());
+    }
+    assert_eq!(collected, vec![0, 1, 2, 3, 4, 5]);
+    assert_eq!(*sent.lock().unwrap(), vec![0, 2, 4]);
+
+    // The stream ends after an error.
+    let mut pages = Paginator::new(
+        0u32,
+        |start: u32| async move {
+            if start == 0 {
+                Ok(start)
+            } else {
+                Err("no such page")
+            }
+        },
+        |start, _| Some(start + 1),
+        |_| Vec::<()>::new(),
+    );
+    assert_eq!(block_on(pages.next_page()), Some(Ok(0)));
+    assert_eq!(block_on(pages.next_page()), Some(Err("no such page")));
+    assert_eq!(block_on(pages.next_page()), None);
+    assert_eq!(format!("{:?}", pages), "Paginator { done: true }");
+}
@@ -0,0 +1,174 @@
+use crate::BoxError;
+use std::{collections::HashMap, env, fs, io, path::PathBuf};
+
+/// The profiles in the shared config and credentials files, `~/.aws/config` and
+/// `~/.aws/credentials`, with the latter's settings taking precedence.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Profiles {
+    profiles: HashMap<String, HashMap<String, String>>,
+    // The config file's `[sso-session name]` sections, which profiles share SSO settings
+    // through.
+    sso_sessions: HashMap<String, HashMap<String, String>>,
+}
+
+impl Profiles {
+    /// Reads the shared files, or the ones `AWS_CONFIG_FILE` and
+    /// `AWS_SHARED_CREDENTIALS_FILE` name instead. A file that doesn't exist has no profiles.
+    pub fn load() -> Result<Self, BoxError> {
+        let config = read(env::var("AWS_CONFIG_FILE").ok(), "config")?;
+        let credentials = read(env::var("AWS_SHARED_CREDENTIALS_FILE").ok(), "credentials")?;
+        Ok(Profiles::parse(&config, &credentials))
+    }
+
+    /// Parses the contents of a config file and a credentials file. Profiles are
+    /// `[profile name]` sections in the former, except for `[default]`, and `[name]` sections
+    /// in the latter; the former's `[sso-session name]` sections are SSO sessions, and other
+    /// sections and lines that aren't settings are ignored.
+    pub fn parse(config: &str, credentials: &str) -> Self {
+        let mut profiles = Profiles::default();
+        profiles.merge(config, true);
+        profiles.merge(credentials, false);
+        profiles
+    }
+
+    /// A setting of the named profile, e.g. `aws_access_key_id`, if it has one.
+    pub fn get(&self, profile: &str, setting: &str) -> Option<&str> {
+        self.profiles
+            .get(profile)
+            .and_then(|settings| settings.get(setting))
+            .map(String::as_str)
+    }
+
+    /// A setting of the named SSO session, e.g. `sso_start_url`, if it has one.
+    pub fn sso_session(&self, session: &str, setting: &str) -> Option<&str> {
+        self.sso_sessions
+            .get(session)
+            .and_then(|settings| settings.get(setting))
+            .map(String::as_str)
+    }
+
+    /// Whether there's a profile with the name.
+    pub fn contains(&self, profile: &str) -> bool {
+        self.profiles.contains_key(profile)
+    }
+
+    fn merge(&mut self, file: &str, config: bool) {
+        let mut section: Option<&mut HashMap<String, String>> = None;
+        let mut last_setting: Option<String> = None;
+        for line in file.lines() {
+            let trimmed = line.trim();
+            if trimmed.is_empty() || trimmed.starts_with('#') || trimmed.starts_with(';') {
+                continue;
+            }
+            if let Some(header) = trimmed
+                .strip_prefix('[')
+                .and_then(|header| header.split(']').next())
+            {
+                let header = header.trim();
+                let sso_session = header.strip_prefix("sso-session ").filter(|_| config);
+                let profile = match header.strip_prefix("profile ") {
+                    _ if !config => Some(header),
+                    Some(name) => Some(name.trim()),
+                    None if header == "default" => Some(header),
+                    None => None,
+                };
+                section = match (sso_session, profile) {
+                    (Some(name), _) => Some(self.sso_sessions.entry(name.trim().to_string())),
+                    (None, Some(name)) => Some(self.profiles.entry(name.to_string())),
+                    (None, None) => None,
+                }
+                .map(|entry| entry.or_default());
+                last_setting = None;
+                continue;
+            }
+            let settings = match section.as_mut() {
+                Some(settings) => settings,
+                None => continue,
+            };
+            // Indented lines continue the setting before them, e.g. `s3 =` followed by
+            // settings of its own.
+            if line.starts_with(|c: char| c.is_whitespace()) {
+                if let Some(value) = last_setting
+                    .as_ref()
+                    .and_then(|setting| settings.get_mut(setting))
+                {
+                    value.push('\n');
+                    value.push_str(trimmed);
+                }
+                continue;
+            }
+            if let Some((setting, value)) = trimmed.split_once('=') {
+                let setting = setting.trim().to_string();
+                settings.insert(setting.clone(), value.trim().to_string());
+                last_setting = Some(setting);
+            }
+        }
+    }
+}
+
+/// The name of the profile to use: `AWS_PROFILE`, or `default` if it isn't set.
+pub fn profile_name() -> String {
+    env::var("AWS_PROFILE").unwrap_or_else(|_| "default".to_string())
+}
+
+// The user's home directory, which the shared files and caches are under.
+pub(crate) fn home_dir() -> Option<PathBuf> {
+    env::var_os("HOME")
+        .or_else(|| env::var_os("USERPROFILE"))
+        .map(PathBuf::from)
+}
+
+// The contents of a shared file, at `path` if it's given, with a leading `~` for the home
+// directory, or else at `~/.aws/{name}`.
+fn read(path: Option<String>, name: &str) -> Result<String, BoxError> {
+    let path = match (path, home_dir()) {
+        (Some(path), Some(home)) if path.starts_with("~/") => home.join(&path[2..]),
+        (Some(path), _) => PathBuf::from(path),
+        (None, Some(home)) => home.join(".aws").join(name),
+        (None, None) => return Ok(String::new()),
+    };
+    match fs::read_to_string(&path) {
+        Ok(contents) => Ok(contents),
+        Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(String::new()),
+        Err(error) => Err(format!("couldn't read {}: {}", path.display(), error).into()),
+    }
+}
+
+#[test]
+fn parses_profiles() {
+    let profiles = Profiles::parse(
+        "[default]\n\
+         region = us-west-2\n\
+         # a comment\n\
+         [profile dev]\n\
+         region=eu-west-1\n\
+         s3 =\n  \
+         max_concurrent_requests = 10\n\
+         [dev]\n\
+         region = ignored\n\
+         [sso-session corp]\n\
+         sso_region = us-east-1\n",
+        "[dev]\n\
+         aws_access_key_id = AKID\n\
+         region = ap-south-1\n\
+         [profile other]\n\
+         aws_access_key_id = odd\n",
+    );
+    assert_eq!(profiles.get("default", "region"), Some("us-west-2"));
+    assert_eq!(profiles.get("dev", "region"), Some("ap-south-1"));
+    assert_eq!(profiles.get("dev", "aws_access_key_id"), Some("AKID"));
+    assert_eq!(
+        profiles.get("dev", "s3"),
+        Some("\nmax_concurrent_requests = 10")
+    );
+    assert_eq!(
+        profiles.get("profile other", "aws_access_key_id"),
+        Some("odd")
+    );
+    assert!(!profiles.contains("corp"));
+    assert!(!profiles.contains("sso-session corp"));
+    assert_eq!(
+        profiles.sso_session("corp", "sso_region"),
+        Some("us-east-1")
+    );
+}
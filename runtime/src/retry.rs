@@ -0,0 +1,606 @@
+use crate::{
+    endpoint::setting_from,
+    profile::{profile_name, Profiles},
+    BoxError,
+};
+use std::{
+    cmp::{Ordering, Reverse},
+    collections::{hash_map::RandomState, BinaryHeap},
+    env,
+    future::Future,
+    hash::{BuildHasher, Hasher},
+    pin::Pin,
+    str::FromStr,
+    sync::{Arc, Condvar, Mutex, MutexGuard, OnceLock, Weak},
+    task::{Context, Poll, Waker},
+    time::{Duration, Instant},
+};
+
+/// Whether a failed request is worth sending again, and why.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RetryKind {
+    /// The failure may not happen again, e.g. the connection dropped or the service had an
+    /// internal error.
+    Transient,
+    /// The service is limiting how fast the caller can send requests.
+    Throttling,
+    /// Sending the request again would fail the same way.
+    NotRetryable,
+}
+
+impl RetryKind {
+    /// How retryable an error response with `status` is when nothing else is known about it:
+    /// 429s are throttling, other 5xxs are transient, and everything else isn't retryable.
+    pub fn from_status(status: u16) -> Self {
+        match status {
+            429 => RetryKind::Throttling,
+            500..=599 => RetryKind::Transient,
+            _ => RetryKind::NotRetryable,
+        }
+    }
+
+    pub fn is_retryable(self) -> bool {
+        self != RetryKind::NotRetryable
+    }
+}
+
+/// How clients go about retrying requests.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RetryMode {
+    /// Retries back off, and requests are sent as soon as they're made.
+    Standard,
+    /// Retries also draw on a quota that failures drain and successes refill, and once the
+    /// service has throttled the client, requests are sent no faster than the rate the
+    /// service seems to allow.
+    Adaptive,
+}
+
+impl RetryMode {
+    /// The mode `AWS_RETRY_MODE` or the current profile's `retry_mode` names, or else
+    /// `Standard`.
+    pub fn from_environment() -> Result<Self, BoxError> {
+        let mode = setting_from(
+            |name| env::var(name).ok(),
+            &["AWS_RETRY_MODE"],
+            &Profiles::load()?,
+            &profile_name(),
+            "retry_mode",
+        );
+        mode.map_or(Ok(RetryMode::Standard), |mode| mode.parse())
+    }
+}
+
+impl FromStr for RetryMode {
+    type Err = BoxError;
+
+    /// Parses `standard` or `adaptive`. The other SDKs' `legacy` mode is taken as standard.
+    fn from_str(mode: &str) -> Result<Self, BoxError> {
+        match mode {
+            "standard" | "legacy" => Ok(RetryMode::Standard),
+            "adaptive" => Ok(RetryMode::Adaptive),
+            _ => Err(format!(
+                "unknown retry mode `{}`: expected `standard` or `adaptive`",
+                mode
+            )
+            .into()),
+        }
+    }
+}
+
+/// How many times clients send a request, and how long they wait between attempts.
+///
+/// Waits back off exponentially with full jitter: after the `n`th attempt fails, the client
+/// waits a random duration up to `base_delay * 2^(n - 1)`, capped at `max_backoff`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    base_delay: Duration,
+    max_backoff: Duration,
+    mode: RetryMode,
+}
+
+impl RetryPolicy {
+    /// The SDKs' standard policy: three attempts, with up to a second's wait after the first,
+    /// and never more than 20 seconds'.
+    pub fn new() -> Self {
+        RetryPolicy {
+            max_attempts: 3,
+            base_delay: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(20),
+            mode: RetryMode::Standard,
+        }
+    }
+
+    /// The standard policy, in the mode `RetryMode::from_environment` finds.
+    pub fn from_environment() -> Result<Self, BoxError> {
+        Ok(RetryPolicy::new().with_mode(RetryMode::from_environment()?))
+    }
+
+    /// A policy that sends every request once.
+    pub fn disabled() -> Self {
+        RetryPolicy::new().with_max_attempts(1)
+    }
+
+    /// Sets how many times a request is sent in all, including the first. Zero is taken as
+    /// one.
+    pub fn with_max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    /// Sets the longest wait after the first attempt, which doubles after each one after it.
+    pub fn with_base_delay(mut self, base_delay: Duration) -> Self {
+        self.base_delay = base_delay;
+        self
+    }
+
+    /// Sets the longest wait between any two attempts.
+    pub fn with_max_backoff(mut self, max_backoff: Duration) -> Self {
+        self.max_backoff = max_backoff;
+        self
+    }
+
+    pub fn with_mode(mut self, mode: RetryMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    pub fn max_attempts(&self) -> u32 {
+        self.max_attempts
+    }
+
+    pub fn base_delay(&self) -> Duration {
+        self.base_delay
+    }
+
+    pub fn max_backoff(&self) -> Duration {
+        self.max_backoff
+    }
+
+    pub fn mode(&self) -> RetryMode {
+        self.mode
+    }
+
+    /// How long to wait after `attempts` attempts have failed with a `kind` of failure before
+    /// the next one, or `None` if the request shouldn't be sent again.
+    pub fn retry_after(&self, attempts: u32, kind: RetryKind) -> Option<Duration> {
+        if !kind.is_retryable() || attempts >= self.max_attempts {
+            return None;
+        }
+        Some(self.backoff(attempts, random()))
+    }
+
+    /// The wait after `attempts` failed attempts, for a `random` number in `[0, 1)`.
+    pub fn backoff(&self, attempts: u32, random: f64) -> Duration {
+        // 2^31 seconds is far past any cap, so larger exponents don't need computing.
+        let exponent = attempts.saturating_sub(1).min(31);
+        let ceiling = self
+            .base_delay
+            .checked_mul(1 << exponent)
+            .map_or(self.max_backoff, |ceiling| ceiling.min(self.max_backoff));
+        ceiling.mul_f64(random.clamp(0.0, 1.0))
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy::new()
+    }
+}
+
+// The retry quota adaptive clients start with and can't go over, what each retry takes from
+// it, and what a success without retries gives back. A success after retries gives back what
+// they took.
+const RETRY_QUOTA: u32 = 500;
+const RETRY_COST: u32 = 5;
+const SUCCESS_REFUND: u32 = 1;
+
+/// The retries of a client's requests, as its policy has them. In adaptive mode, the
+/// requests share a retry quota and a send rate, which clones of the handler share too.
+#[derive(Debug, Clone)]
+pub struct RetryHandler {
+    policy: RetryPolicy,
+    state: Arc<Mutex<AdaptiveState>>,
+}
+
+#[derive(Debug)]
+struct AdaptiveState {
+    quota: u32,
+    limiter: RateLimiter,
+}
+
+impl RetryHandler {
+    pub fn new(policy: RetryPolicy) -> Self {
+        RetryHandler {
+            policy,
+            state: Arc::new(Mutex::new(AdaptiveState {
+                quota: RETRY_QUOTA,
+                limiter: RateLimiter::new(),
+            })),
+        }
+    }
+
+    pub fn policy(&self) -> RetryPolicy {
+        self.policy
+    }
+
+    /// How long to wait before sending a request, if at all. Only adaptive clients that the
+    /// service has throttled wait, until their send rate allows another request.
+    pub fn send_delay(&self) -> Option<Duration> {
+        if self.policy.mode != RetryMode::Adaptive {
+            return None;
+        }
+        let mut state = self.state();
+        let now = state.limiter.now();
+        state.limiter.acquire(now)
+    }
+
+    /// How long to wait after `attempts` attempts have failed with a `kind` of failure before
+    /// the next one, as the policy's `retry_after` has it. Adaptive clients also slow their
+    /// send rate down when they're throttled, and don't retry once their quota is spent.
+    pub fn retry_after(&self, attempts: u32, kind: RetryKind) -> Option<Duration> {
+        if self.policy.mode != RetryMode::Adaptive {
+            return self.policy.retry_after(attempts, kind);
+        }
+        let mut state = self.state();
+        let now = state.limiter.now();
+        state.limiter.update(now, kind == RetryKind::Throttling);
+        let delay = self.policy.retry_after(attempts, kind)?;
+        state.quota = state.quota.checked_sub(RETRY_COST)?;
+        Some(delay)
+    }
+
+    /// Records that a request succeeded after `attempts` attempts, which refills adaptive
+    /// clients' quota and lets their send rate recover.
+    pub fn succeeded(&self, attempts: u32) {
+        if self.policy.mode != RetryMode::Adaptive {
+            return;
+        }
+        let mut state = self.state();
+        let now = state.limiter.now();
+        state.limiter.update(now, false);
+        let refund = if attempts > 1 {
+            RETRY_COST * (attempts - 1)
+        } else {
+            SUCCESS_REFUND
+        };
+        state.quota = (state.quota + refund).min(RETRY_QUOTA);
+    }
+
+    fn state(&self) -> MutexGuard<'_, AdaptiveState> {
+        self.state.lock().unwrap_or_else(|error| error.into_inner())
+    }
+}
+
+// How much a throttle cuts the send rate by, and how quickly it grows back after one, as the
+// SDKs' CUBIC-like adaptive mode has them, and the slowest rate it's cut to, in requests per
+// second.
+const THROTTLE_FACTOR: f64 = 0.7;
+const SCALE: f64 = 0.4;
+const MIN_RATE: f64 = 0.5;
+
+// A token bucket requests are sent from once the client has been throttled, refilled at a
+// rate that's cut on each throttle and grows back along a cubic curve towards the rate the
+// throttle came at. Times are in seconds since the limiter was made.
+#[derive(Debug)]
+struct RateLimiter {
+    start: Instant,
+    enabled: bool,
+    fill_rate: f64,
+    capacity: f64,
+    tokens: f64,
+    last_refill: Option<f64>,
+    // Requests sent per second, smoothed over half-second buckets.
+    measured_rate: f64,
+    last_bucket: f64,
+    bucket_requests: u32,
+    last_max_rate: f64,
+    last_throttle: f64,
+}
+
+impl RateLimiter {
+    fn new() -> Self {
+        RateLimiter {
+            start: Instant::now(),
+            enabled: false,
+            fill_rate: 0.0,
+            capacity: 0.0,
+            tokens: 0.0,
+            last_refill: None,
+            measured_rate: 0.0,
+            last_bucket: 0.0,
+            bucket_requests: 0,
+            last_max_rate: 0.0,
+            last_throttle: 0.0,
+        }
+    }
+
+    fn now(&self) -> f64 {
+        self.start.elapsed().as_secs_f64()
+    }
+
+    // Takes a token for a request, and how long to wait for it if the bucket's empty.
+    fn acquire(&mut self, now: f64) -> Option<Duration> {
+        if !self.enabled {
+            return None;
+        }
+        self.refill(now);
+        self.tokens -= 1.0;
+        if self.tokens < 0.0 {
+            Some(Duration::from_secs_f64(-self.tokens / self.fill_rate))
+        } else {
+            None
+        }
+    }
+
+    fn refill(&mut self, now: f64) {
+        if let Some(last_refill) = self.last_refill {
+            let tokens = self.tokens + (now - last_refill) * self.fill_rate;
+            self.tokens = tokens.min(self.capacity);
+        }
+        self.last_refill = Some(now);
+    }
+
+    // Adjusts the fill rate after a response, which cuts it if the response was a throttle.
+    fn update(&mut self, now: f64, throttled: bool) {
+        self.measure(now);
+        let rate = if throttled {
+            let rate = if self.enabled {
+                self.measured_rate.min(self.fill_rate)
+            } else {
+                self.measured_rate
+            };
+            self.last_max_rate = rate;
+            self.last_throttle = now;
+            self.enabled = true;
+            rate * THROTTLE_FACTOR
+        } else {
+            let window = (self.last_max_rate * (1.0 - THROTTLE_FACTOR) / SCALE).cbrt();
+            SCALE * (now - self.last_throttle - window).powi(3) + self.last_max_rate
+        };
+        let rate = rate.min(2.0 * self.measured_rate);
+        self.refill(now);
+        self.fill_rate = rate.max(MIN_RATE);
+        self.capacity = rate.max(1.0);
+        self.tokens = self.tokens.min(self.capacity);
+    }
+
+    fn measure(&mut self, now: f64) {
+        let bucket = (now * 2.0).floor() / 2.0;
+        self.bucket_requests += 1;
+        if bucket > self.last_bucket {
+            let rate = f64::from(self.bucket_requests) / (bucket - self.last_bucket);
+            self.measured_rate = rate * 0.8 + self.measured_rate * 0.2;
+            self.bucket_requests = 0;
+            self.last_bucket = bucket;
+        }
+    }
+}
+
+// A random number in [0, 1). Each `RandomState` is seeded differently, so hashing nothing
+// with a new one is enough for jitter and doesn't need a generator to be kept anywhere.
+pub(crate) fn random() -> f64 {
+    let bits = RandomState::new().build_hasher().finish() >> 11;
+    bits as f64 / (1u64 << 53) as f64
+}
+
+/// Waits for `duration`. Waits are kept by a timer thread of the runtime's own, so that they
+/// work whichever executor polls them.
+pub fn sleep(duration: Duration) -> Sleep {
+    Sleep {
+        deadline: Instant::now() + duration,
+        state: None,
+    }
+}
+
+/// The future `sleep` returns.
+#[derive(Debug)]
+pub struct Sleep {
+    deadline: Instant,
+    // Shared with the timer thread, which the wait is handed to on the first poll.
+    state: Option<Arc<Mutex<SleepState>>>,
+}
+
+#[derive(Debug, Default)]
+struct SleepState {
+    done: bool,
+    // The waker of the latest poll, for the timer to wake when the wait is over.
+    waker: Option<Waker>,
+}
+
+impl Future for Sleep {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if Instant::now() >= self.deadline {
+            return Poll::Ready(());
+        }
+        let deadline = self.deadline;
+        let state = self.state.get_or_insert_with(|| {
+            let state = Arc::new(Mutex::new(SleepState::default()));
+            timer().add(deadline, Arc::downgrade(&state));
+            state
+        });
+        let mut state = state.lock().unwrap_or_else(|error| error.into_inner());
+        if state.done {
+            Poll::Ready(())
+        } else {
+            state.waker = Some(cx.waker().clone());
+            Poll::Pending
+        }
+    }
+}
+
+// The waits every `Sleep` hands over, soonest first, and the thread that wakes them. Waits
+// whose `Sleep` has been dropped are skipped.
+struct Timer {
+    waits: Mutex<BinaryHeap<Reverse<Wait>>>,
+    added: Condvar,
+}
+
+struct Wait {
+    deadline: Instant,
+    state: Weak<Mutex<SleepState>>,
+}
+
+impl PartialEq for Wait {
+    fn eq(&self, other: &Self) -> bool {
+        self.deadline == other.deadline
+    }
+}
+
+impl Eq for Wait {}
+
+impl PartialOrd for Wait {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Wait {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.deadline.cmp(&other.deadline)
+    }
+}
+
+fn timer() -> &'static Timer {
+    static TIMER: OnceLock<Timer> = OnceLock::new();
+    TIMER.get_or_init(|| {
+        std::thread::spawn(|| timer().run());
+        Timer {
+            waits: Mutex::new(BinaryHeap::new()),
+            added: Condvar::new(),
+        }
+    })
+}
+
+impl Timer {
+    fn add(&self, deadline: Instant, state: Weak<Mutex<SleepState>>) {
+        let mut waits = self.waits.lock().unwrap_or_else(|error| error.into_inner());
+        waits.push(Reverse(Wait { deadline, state }));
+        self.added.notify_one();
+    }
+
+    fn run(&self) {
+        let mut waits = self.waits.lock().unwrap_or_else(|error| error.into_inner());
+        loop {
+            let now = Instant::now();
+            match waits.peek() {
+                Some(Reverse(wait)) if wait.deadline <= now => {
+                    if let Some(state) = waits.pop().and_then(|Reverse(wait)| wait.state.upgrade())
+                    {
+                        let mut state = state.lock().unwrap_or_else(|error| error.into_inner());
+                        state.done = true;
+                        if let Some(waker) = state.waker.take() {
+                            waker.wake();
+                        }
+                    }
+                }
+                Some(Reverse(wait)) => {
+                    let timeout = wait.deadline - now;
+                    waits = match self.added.wait_timeout(waits, timeout) {
+                        Ok((waits, _)) => waits,
+                        Err(error) => error.into_inner().0,
+                    };
+                }
+                None => {
+                    waits = self
+                        .added
+                        .wait(waits)
+                        .unwrap_or_else(|error| error.into_inner());
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn backs_off_with_jitter() {
+    let policy = RetryPolicy::new();
+    assert_eq!(policy.backoff(1, 0.0), Duration::ZERO);
+    assert_eq!(policy.backoff(1, 0.5), Duration::from_millis(500));
+    assert_eq!(policy.backoff(3, 0.5), Duration::from_secs(2));
+    assert_eq!(policy.backoff(6, 0.5), Duration::from_secs(10));
+    assert_eq!(policy.backoff(u32::MAX, 0.5), Duration::from_secs(10));
+
+    let policy = policy.with_max_attempts(4);
+    for attempts in 1..4 {
+        let delay = policy
+            .retry_after(attempts, RetryKind::Throttling)
+            .expect("attempts are left");
+        assert!(delay < Duration::from_secs(1 << (attempts - 1)));
+    }
+    assert_eq!(policy.retry_after(4, RetryKind::Transient), None);
+    assert_eq!(policy.retry_after(1, RetryKind::NotRetryable), None);
+    assert_eq!(
+        RetryPolicy::disabled().retry_after(1, RetryKind::Transient),
+        None
+    );
+
+    assert_eq!(RetryKind::from_status(429), RetryKind::Throttling);
+    assert_eq!(RetryKind::from_status(503), RetryKind::Transient);
+    assert_eq!(RetryKind::from_status(404), RetryKind::NotRetryable);
+}
+
+#[test]
+fn retries_adaptively() -> Result<(), BoxError> {
+    assert_eq!("adaptive".parse::<RetryMode>()?, RetryMode::Adaptive);
+    assert_eq!("legacy".parse::<RetryMode>()?, RetryMode::Standard);
+    assert!("fast".parse::<RetryMode>().is_err());
+
+    let policy = RetryPolicy::new()
+        .with_max_attempts(u32::MAX)
+        .with_base_delay(Duration::ZERO);
+    let standard = RetryHandler::new(policy);
+    for attempts in 1..=200 {
+        assert!(standard
+            .retry_after(attempts, RetryKind::Throttling)
+            .is_some());
+    }
+    assert_eq!(standard.send_delay(), None);
+
+    // The quota lasts for 100 retries, and a success after one gives back what it took.
+    let adaptive = RetryHandler::new(policy.with_mode(RetryMode::Adaptive));
+    for attempts in 1..=100 {
+        assert!(adaptive
+            .retry_after(attempts, RetryKind::Transient)
+            .is_some());
+    }
+    assert_eq!(adaptive.retry_after(101, RetryKind::Transient), None);
+    adaptive.succeeded(2);
+    assert!(adaptive.retry_after(1, RetryKind::Transient).is_some());
+    assert_eq!(adaptive.retry_after(1, RetryKind::Transient), None);
+
+    // Requests are sent freely until a throttle, at 10 a second, cuts the rate to 7 a second.
+    let mut limiter = RateLimiter::new();
+    for request in 0..20 {
+        let now = f64::from(request) / 10.0;
+        assert_eq!(limiter.acquire(now), None);
+        limiter.update(now, false);
+    }
+    limiter.update(2.0, true);
+    assert!(limiter.fill_rate < 10.0 && limiter.fill_rate > 5.0);
+    let waits = (0..10)
+        .filter_map(|_| limiter.acquire(2.0))
+        .collect::<Vec<_>>();
+    assert!(!waits.is_empty());
+    assert!(waits.windows(2).all(|pair| pair[0] < pair[1]));
+    // Without throttles, the rate grows back past the one the throttle came at.
+    for request in 21..100 {
+        limiter.update(f64::from(request) / 10.0, false);
+    }
+    assert!(limiter.fill_rate > limiter.last_max_rate);
+
+    Ok(())
+}
+
+#[test]
+fn sleeps() {
+    let start = std::time::Instant::now();
+    let mut sleep = sleep(Duration::from_millis(20));
+    let mut cx = Context::from_waker(Waker::noop());
+    while Pin::new(&mut sleep).poll(&mut cx).is_pending() {
+        std::thread::yield_now();
+    }
+    assert!(start.elapsed() >= Duration::from_millis(20));
+}
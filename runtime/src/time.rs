@@ -0,0 +1,325 @@
+use crate::BoxError;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Seconds since the Unix epoch, with any fraction of a second, negative for earlier times.
+/// This is the `unixTimestamp` format JSON protocols use by default.
+pub fn epoch_seconds(time: SystemTime) -> f64 {
+    match time.duration_since(UNIX_EPOCH) {
+        Ok(since) => since.as_secs_f64(),
+        Err(before) => -before.duration().as_secs_f64(),
+    }
+}
+
+// Whole seconds since the epoch, rounded down, and the milliseconds past them.
+fn split(time: SystemTime) -> (i64, u32) {
+    match time.duration_since(UNIX_EPOCH) {
+        Ok(since) => (since.as_secs() as i64, since.subsec_millis()),
+        Err(before) => {
+            let before = before.duration();
+            let millis = before.subsec_millis();
+            if before.subsec_nanos() == 0 {
+                (-(before.as_secs() as i64), 0)
+            } else {
+                (-(before.as_secs() as i64) - 1, (1000 - millis) % 1000)
+            }
+        }
+    }
+}
+
+// The year, month, and day of a count of days since the epoch, from Howard Hinnant's
+// `civil_from_days`.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+// The count of days since the epoch of a date, from Howard Hinnant's `days_from_civil`, or
+// `None` for years too far off to count the days of.
+fn days_from_civil(year: i64, month: u32, day: u32) -> Option<i64> {
+    let year = year.checked_sub(i64::from(month <= 2))?;
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let mp = i64::from((month + 9) % 12);
+    let doy = (153 * mp + 2) / 5 + i64::from(day) - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era.checked_mul(146_097)?.checked_add(doe - 719_468)
+}
+
+// The time some seconds from the epoch, negative for earlier times, if it can be represented.
+fn from_epoch_offset(seconds: i64) -> Option<SystemTime> {
+    let magnitude = Duration::from_secs(seconds.unsigned_abs());
+    if seconds < 0 {
+        UNIX_EPOCH.checked_sub(magnitude)
+    } else {
+        UNIX_EPOCH.checked_add(magnitude)
+    }
+}
+
+// The time at a date and time of day, checking that each part is in range.
+fn from_parts(
+    year: i64,
+    month: u32,
+    day: u32,
+    (hour, minute, second): (u32, u32, u32),
+    nanos: u32,
+) -> Option<SystemTime> {
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+    if hour > 23 || minute > 59 || second > 60 {
+        return None;
+    }
+    let seconds = days_from_civil(year, month, day)?
+        .checked_mul(86_400)?
+        .checked_add(i64::from(hour * 3600 + minute * 60 + second))?;
+    from_epoch_offset(seconds)?.checked_add(Duration::from_nanos(u64::from(nanos)))
+}
+
+// Parses `HH:MM:SS`.
+fn parse_time_of_day(text: &str) -> Option<(u32, u32, u32)> {
+    let mut parts = text.splitn(3, ':').map(|part| part.parse::<u32>().ok());
+    Some((parts.next()??, parts.next()??, parts.next()??))
+}
+
+/// A time from seconds since the epoch, e.g. one `epoch_seconds` returned. Seconds that
+/// aren't finite, or are too far from the epoch for a `SystemTime`, are an error.
+pub fn from_epoch_seconds(seconds: f64) -> Result<SystemTime, BoxError> {
+    let invalid = || -> BoxError { format!("`{}` isn't a time in epoch seconds", seconds).into() };
+    let magnitude = Duration::try_from_secs_f64(seconds.abs()).map_err(|_| invalid())?;
+    if seconds < 0.0 {
+        UNIX_EPOCH.checked_sub(magnitude)
+    } else {
+        UNIX_EPOCH.checked_add(magnitude)
+    }
+    .ok_or_else(invalid)
+}
+
+/// Formats a time as RFC 3339, e.g. `2015-03-31T12:00:00Z`, with milliseconds only when
+/// there are some. This is the `iso8601` format, used by query protocols by default.
+pub fn format_iso8601(time: SystemTime) -> String {
+    let (seconds, millis) = split(time);
+    let (year, month, day) = civil_from_days(seconds.div_euclid(86_400));
+    let of_day = seconds.rem_euclid(86_400);
+    let mut out = format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+        year,
+        month,
+        day,
+        of_day / 3600,
+        of_day / 60 % 60,
+        of_day % 60
+    );
+    if millis != 0 {
+        out.push_str(&format!(".{:03}", millis));
+    }
+    out.push('Z');
+    out
+}
+
+/// Formats a time in ISO 8601's basic format to the second, e.g. `20150830T123600Z`, which is
+/// how SigV4 dates requests.
+pub fn format_amz_date(time: SystemTime) -> String {
+    let (seconds, _) = split(time);
+    let (year, month, day) = civil_from_days(seconds.div_euclid(86_400));
+    let of_day = seconds.rem_euclid(86_400);
+    format!(
+        "{:04}{:02}{:02}T{:02}{:02}{:02}Z",
+        year,
+        month,
+        day,
+        of_day / 3600,
+        of_day / 60 % 60,
+        of_day % 60
+    )
+}
+
+const WEEKDAYS: [&str; 7] = ["Thu", "Fri", "Sat", "Sun", "Mon", "Tue", "Wed"];
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+/// Formats a time as an HTTP date, e.g. `Tue, 31 Mar 2015 12:00:00 GMT`. This is the
+/// `rfc822` format, used for headers by default.
+pub fn format_http_date(time: SystemTime) -> String {
+    let (seconds, _) = split(time);
+    let days = seconds.div_euclid(86_400);
+    let (year, month, day) = civil_from_days(days);
+    let of_day = seconds.rem_euclid(86_400);
+    format!(
+        "{}, {:02} {} {:04} {:02}:{:02}:{:02} GMT",
+        WEEKDAYS[days.rem_euclid(7) as usize],
+        day,
+        MONTHS[month as usize - 1],
+        year,
+        of_day / 3600,
+        of_day / 60 % 60,
+        of_day % 60
+    )
+}
+
+/// Parses an RFC 3339 time, e.g. `2015-03-31T12:00:00.250Z`. Offsets other than `Z` are
+/// allowed, since some services send `+00:00`.
+pub fn parse_iso8601(text: &str) -> Result<SystemTime, BoxError> {
+    let invalid = || -> BoxError { format!("`{}` isn't an ISO 8601 time", text).into() };
+    let (date, rest) = text.split_once(['T', 't']).ok_or_else(invalid)?;
+    let mut date_parts = date.splitn(3, '-');
+    let year = date_parts.next().and_then(|year| year.parse::<i64>().ok());
+    let month = date_parts
+        .next()
+        .and_then(|month| month.parse::<u32>().ok());
+    let day = date_parts.next().and_then(|day| day.parse::<u32>().ok());
+    let (time, offset) = match rest.find(['Z', 'z', '+', '-']) {
+        Some(at) => rest.split_at(at),
+        None => return Err(invalid()),
+    };
+    let (time, fraction) = time.split_once('.').unwrap_or((time, ""));
+    if !fraction.bytes().all(|byte| byte.is_ascii_digit()) {
+        return Err(invalid());
+    }
+    let nanos = format!("{:0<9}", &fraction[..fraction.len().min(9)])
+        .parse::<u32>()
+        .map_err(|_| invalid())?;
+    let offset = match offset {
+        "Z" | "z" => 0,
+        _ => {
+            let (sign, offset) = offset.split_at(1);
+            let (hours, minutes) = offset.split_once(':').ok_or_else(invalid)?;
+            let (hours, minutes) = match (hours.parse::<u32>(), minutes.parse::<u32>()) {
+                (Ok(hours), Ok(minutes)) if hours <= 23 && minutes <= 59 => (hours, minutes),
+                _ => return Err(invalid()),
+            };
+            let seconds = i64::from(hours * 3600 + minutes * 60);
+            if sign == "-" {
+                -seconds
+            } else {
+                seconds
+            }
+        }
+    };
+    let time = match (year, month, day, parse_time_of_day(time)) {
+        (Some(year), Some(month), Some(day), Some(time)) => {
+            from_parts(year, month, day, time, nanos)
+        }
+        _ => None,
+    }
+    .ok_or_else(invalid)?;
+    // A time with an offset ahead of UTC, e.g. `+02:00`, reads later than it would in UTC.
+    let magnitude = Duration::from_secs(offset.unsigned_abs());
+    if offset >= 0 {
+        time.checked_sub(magnitude)
+    } else {
+        time.checked_add(magnitude)
+    }
+    .ok_or_else(invalid)
+}
+
+/// Parses an HTTP date, e.g. `Tue, 31 Mar 2015 12:00:00 GMT`.
+pub fn parse_http_date(text: &str) -> Result<SystemTime, BoxError> {
+    let invalid = || -> BoxError { format!("`{}` isn't an HTTP date", text).into() };
+    let parts = text.split_whitespace().collect::<Vec<_>>();
+    let (day, month, year, time) = match parts.as_slice() {
+        [_, day, month, year, time, "GMT"] => (day, month, year, time),
+        _ => return Err(invalid()),
+    };
+    let month = MONTHS
+        .iter()
+        .position(|name| name == month)
+        .ok_or_else(invalid)? as u32
+        + 1;
+    let (time, fraction) = time.split_once('.').unwrap_or((time, ""));
+    if !fraction.bytes().all(|byte| byte.is_ascii_digit()) {
+        return Err(invalid());
+    }
+    let millis = if fraction.is_empty() {
+        0
+    } else {
+        format!("{:0<3}", &fraction[..fraction.len().min(3)])
+            .parse::<u32>()
+            .map_err(|_| invalid())?
+    };
+    match (day.parse(), year.parse(), parse_time_of_day(time)) {
+        (Ok(day), Ok(year), Some(time)) => from_parts(year, month, day, time, millis * 1_000_000),
+        _ => None,
+    }
+    .ok_or_else(invalid)
+}
+
+#[test]
+fn formats_times() -> Result<(), crate::BoxError> {
+    let time = UNIX_EPOCH + Duration::from_secs(1_427_803_200);
+    assert_eq!(epoch_seconds(time), 1_427_803_200.0);
+    assert_eq!(format_iso8601(time), "2015-03-31T12:00:00Z");
+    assert_eq!(format_http_date(time), "Tue, 31 Mar 2015 12:00:00 GMT");
+    assert_eq!(format_amz_date(time), "20150331T120000Z");
+    assert_eq!(
+        from_epoch_seconds(1_427_803_200.5)?,
+        time + Duration::from_millis(500)
+    );
+    assert_eq!(
+        from_epoch_seconds(-1.5)?,
+        UNIX_EPOCH - Duration::from_millis(1500)
+    );
+    // Times past what a `SystemTime` holds are errors rather than panics.
+    assert!(from_epoch_seconds(f64::NAN).is_err());
+    assert!(from_epoch_seconds(f64::INFINITY).is_err());
+    assert!(from_epoch_seconds(1e300).is_err());
+
+    let time = time + Duration::from_millis(250);
+    assert_eq!(format_iso8601(time), "2015-03-31T12:00:00.250Z");
+    assert_eq!(epoch_seconds(time), 1_427_803_200.25);
+
+    let before = UNIX_EPOCH - Duration::from_millis(1500);
+    assert_eq!(format_iso8601(before), "1969-12-31T23:59:58.500Z");
+    assert_eq!(epoch_seconds(before), -1.5);
+    assert_eq!(
+        format_http_date(UNIX_EPOCH),
+        "Thu, 01 Jan 1970 00:00:00 GMT"
+    );
+    assert_eq!(
+        format_iso8601(UNIX_EPOCH + Duration::from_secs(951_782_400)),
+        "2000-02-29T00:00:00Z"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn parses_times() -> Result<(), crate::BoxError> {
+    let time = UNIX_EPOCH + Duration::from_secs(1_427_803_200);
+    assert_eq!(parse_iso8601("2015-03-31T12:00:00Z")?, time);
+    assert_eq!(
+        parse_iso8601("2015-03-31T12:00:00.25Z")?,
+        time + Duration::from_millis(250)
+    );
+    assert_eq!(parse_iso8601("2015-03-31T14:00:00+02:00")?, time);
+    assert_eq!(parse_iso8601("2015-03-31T10:30:00-01:30")?, time);
+    assert_eq!(parse_http_date("Tue, 31 Mar 2015 12:00:00 GMT")?, time);
+    assert_eq!(
+        parse_iso8601("1969-12-31T23:59:58.500Z")?,
+        UNIX_EPOCH - Duration::from_millis(1500)
+    );
+    assert_eq!(
+        parse_iso8601(&format_iso8601(
+            UNIX_EPOCH + Duration::from_secs(951_782_400)
+        ))?,
+        UNIX_EPOCH + Duration::from_secs(951_782_400)
+    );
+    assert!(parse_iso8601("2015-03-31").is_err());
+    assert!(parse_iso8601("2015-13-31T12:00:00Z").is_err());
+    assert!(parse_http_date("31 Mar 2015 12:00:00").is_err());
+    assert!(parse_http_date("Tue, 31 Mar 2015 12:00:00.éé GMT").is_err());
+    assert!(parse_iso8601("9223372036854775807-12-31T23:59:59Z").is_err());
+    assert!(parse_iso8601("-9223372036854775808-01-01T00:00:00Z").is_err());
+    assert!(parse_iso8601("2015-03-31T12:00:00+9999999999:00").is_err());
+    assert!(parse_http_date("Tue, 31 Mar 99999999999999 12:00:00 GMT").is_err());
+
+    Ok(())
+}
@@ -0,0 +1,228 @@
+use crate::{
+    body::Body,
+    retry::{sleep, Sleep},
+    BoxError,
+};
+use bytes::Bytes;
+use futures_core::Stream;
+use std::{
+    error::Error,
+    fmt,
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
+};
+
+/// The timeouts connectors should apply to the connections they send a request on, which
+/// clients put in each request's extensions. Only connectors can tell connecting from
+/// reading, so only they can apply these separately.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ConnectorTimeouts {
+    // How long connecting, including the TLS handshake, can take.
+    pub connect: Option<Duration>,
+    // How long the response can take once the request has been sent.
+    pub read: Option<Duration>,
+}
+
+impl ConnectorTimeouts {
+    /// How long an attempt to send a request can take until its response's head has come
+    /// in: the connect and read timeouts together, or whichever of them there is. Without
+    /// either, attempts aren't bounded.
+    pub fn attempt(&self) -> Option<Duration> {
+        match (self.connect, self.read) {
+            (Some(connect), Some(read)) => Some(connect + read),
+            (connect, read) => connect.or(read),
+        }
+    }
+}
+
+/// A future that didn't complete within its timeout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimedOut {
+    after: Duration,
+}
+
+impl TimedOut {
+    pub fn after(&self) -> Duration {
+        self.after
+    }
+}
+
+impl fmt::Display for TimedOut {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "timed out after {:?}", self.after)
+    }
+}
+
+impl Error for TimedOut {}
+
+/// Runs `future`, unless it takes longer than `duration`. `None` lets it take as long as it
+/// takes.
+pub fn timeout<F: Future>(duration: Option<Duration>, future: F) -> Timeout<F> {
+    Timeout {
+        future: Box::pin(future),
+        duration,
+        sleep: duration.map(sleep),
+    }
+}
+
+/// The future `timeout` returns.
+pub struct Timeout<F> {
+    future: Pin<Box<F>>,
+    duration: Option<Duration>,
+    sleep: Option<Sleep>,
+}
+
+impl<F: Future> Future for Timeout<F> {
+    type Output = Result<F::Output, TimedOut>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if let Poll::Ready(output) = self.future.as_mut().poll(cx) {
+            return Poll::Ready(Ok(output));
+        }
+        let timed_out = match &mut self.sleep {
+            Some(sleep) => Pin::new(sleep).poll(cx).is_ready(),
+            None => false,
+        };
+        if timed_out {
+            let after = self.duration.unwrap_or_default();
+            Poll::Ready(Err(TimedOut { after }))
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+/// `body`, failing with `TimedOut` once a chunk of it takes longer than `read` to come in,
+/// for connectors to apply the read timeout to responses' bodies with. `None` lets them take
+/// as long as they take.
+pub fn read_timeout(body: Body, read: Option<Duration>) -> Body {
+    match read {
+        Some(read) => {
+            let len = body.content_length();
+            Body::from_stream(
+                ReadTimeout {
+                    body: Some(body),
+                    read,
+                    sleep: None,
+                },
+                len,
+            )
+        }
+        None => body,
+    }
+}
+
+// A body whose chunks each have `read` to come in, which ends once one doesn't.
+struct ReadTimeout {
+    body: Option<Body>,
+    read: Duration,
+    // The wait for the next chunk, from the first poll for it.
+    sleep: Option<Sleep>,
+}
+
+impl Stream for ReadTimeout {
+    type Item = Result<Bytes, BoxError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        let body = match &mut this.body {
+            Some(body) => body,
+            None => return Poll::Ready(None),
+        };
+        if let Poll::Ready(chunk) = Pin::new(body).poll_next(cx) {
+            this.sleep = None;
+            return Poll::Ready(chunk);
+        }
+        let read = this.read;
+        let sleep = this.sleep.get_or_insert_with(|| sleep(read));
+        if Pin::new(sleep).poll(cx).is_pending() {
+            return Poll::Pending;
+        }
+        this.body = None;
+        Poll::Ready(Some(Err(TimedOut { after: read }.into())))
+    }
+}
+
+impl<F> fmt::Debug for Timeout<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Timeout")
+            .field("duration", &self.duration)
+            .finish()
+    }
+}
+
+#[test]
+fn times_out() {
+    use std::task::Waker;
+
+    let block_on = |future: Timeout<Sleep>| {
+        let mut future = Box::pin(future);
+        let mut cx = Context::from_waker(Waker::noop());
+        loop {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                return output;
+            }
+            std::thread::yield_now();
+        }
+    };
+    let millis = Duration::from_millis;
+    assert_eq!(
+        block_on(timeout(Some(millis(50)), sleep(millis(1)))),
+        Ok(())
+    );
+    assert_eq!(
+        block_on(timeout(Some(millis(1)), sleep(millis(50)))),
+        Err(TimedOut { after: millis(1) })
+    );
+    assert_eq!(block_on(timeout(None, sleep(millis(1)))), Ok(()));
+
+    let timeouts = ConnectorTimeouts {
+        connect: Some(Duration::from_secs(1)),
+        read: None,
+    };
+    assert_eq!(timeouts.attempt(), Some(Duration::from_secs(1)));
+    let timeouts = ConnectorTimeouts {
+        read: Some(Duration::from_secs(2)),
+        ..timeouts
+    };
+    assert_eq!(timeouts.attempt(), Some(Duration::from_secs(3)));
+    let timeouts = ConnectorTimeouts {
+        connect: None,
+        ..timeouts
+    };
+    assert_eq!(timeouts.attempt(), Some(Duration::from_secs(2)));
+    assert_eq!(ConnectorTimeouts::default().attempt(), None);
+}
+
+// A body that never sends anything, like a connection that's stalled.
+#[cfg(test)]
+struct Stalled;
+
+#[cfg(test)]
+impl Stream for Stalled {
+    type Item = Result<Bytes, BoxError>;
+
+    fn poll_next(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Poll::Pending
+    }
+}
+
+#[test]
+fn times_out_reads() -> Result<(), BoxError> {
+    use crate::blocking::block_on;
+
+    let millis = Duration::from_millis;
+    let body = read_timeout(Body::from("hello"), Some(millis(10)));
+    assert_eq!(body.content_length(), Some(5));
+    assert_eq!(block_on(body.collect())?, b"hello");
+
+    let body = read_timeout(Body::from_stream(Stalled, None), Some(millis(10)));
+    let error = block_on(body.collect()).unwrap_err();
+    assert_eq!(
+        error.downcast_ref::<TimedOut>(),
+        Some(&TimedOut { after: millis(10) })
+    );
+    Ok(())
+}
@@ -0,0 +1,181 @@
+use std::{future::Future, ops::Range, pin::Pin, task::Poll};
+
+/// The smallest a part of an S3 multipart upload can be, other than its last.
+pub const MIN_PART_SIZE: u64 = 5 * 1024 * 1024;
+
+/// The most parts an S3 multipart upload can have.
+pub const MAX_PARTS: u64 = 10_000;
+
+/// How an `upload` helper sends a large body: in parts, from `part_size`, of a multipart
+/// upload, with up to `concurrency` of them at a time, when it's at least `threshold` bytes.
+/// Smaller bodies are sent with a single request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UploadConfig {
+    threshold: u64,
+    part_size: u64,
+    concurrency: usize,
+}
+
+impl Default for UploadConfig {
+    /// 8 MiB parts, 8 at a time, for bodies of at least 16 MiB.
+    fn default() -> Self {
+        UploadConfig {
+            threshold: 16 * 1024 * 1024,
+            part_size: 8 * 1024 * 1024,
+            concurrency: 8,
+        }
+    }
+}
+
+impl UploadConfig {
+    pub fn with_threshold(mut self, threshold: u64) -> Self {
+        self.threshold = threshold;
+        self
+    }
+
+    /// Sets how large parts are. They're never smaller than `MIN_PART_SIZE`, and larger than
+    /// this if a body would otherwise take more than `MAX_PARTS` of them.
+    pub fn with_part_size(mut self, part_size: u64) -> Self {
+        self.part_size = part_size;
+        self
+    }
+
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency;
+        self
+    }
+
+    pub fn threshold(&self) -> u64 {
+        self.threshold
+    }
+
+    pub fn part_size(&self) -> u64 {
+        self.part_size
+    }
+
+    pub fn concurrency(&self) -> usize {
+        self.concurrency
+    }
+
+    /// Whether a body of `len` bytes is uploaded in parts.
+    pub fn is_multipart(&self, len: u64) -> bool {
+        len >= self.threshold
+    }
+
+    /// The byte ranges of the parts a body of `len` bytes is uploaded in, in order.
+    pub fn parts(&self, len: u64) -> Vec<Range<u64>> {
+        let part_size = self
+            .part_size
+            .max(MIN_PART_SIZE)
+            .max(len.div_ceil(MAX_PARTS));
+        let mut parts = Vec::new();
+        let mut start = 0;
+        while start < len || parts.is_empty() {
+            let end = len.min(start + part_size);
+            parts.push(start..end);
+            start = end;
+        }
+        parts
+    }
+}
+
+/// Runs `futures`, up to `concurrency` of them at a time, until they've all succeeded or one
+/// has failed, e.g. to upload the parts of a multipart upload. Returns their outputs in the
+/// order the futures were in, or the first error, dropping the futures still running.
+/// Futures are only created as they're started, so parts are read as they're uploaded.
+pub async fn try_join<I, F, T, E>(futures: I, concurrency: usize) -> Result<Vec<T>, E>
+where
+    I: IntoIterator<Item = F>,
+    F: Future<Output = Result<T, E>>,
+{
+    let mut pending = futures.into_iter().enumerate();
+    let mut running: Vec<(usize, Pin<Box<F>>)> = Vec::new();
+    let mut outputs = Vec::new();
+    std::future::poll_fn(|cx| loop {
+        while running.len() < concurrency.max(1) {
+            match pending.next() {
+                Some((index, future)) => running.push((index, Box::pin(future))),
+                None => break,
+            }
+        }
+        if running.is_empty() {
+            return Poll::Ready(Ok(()));
+        }
+        let mut finished = false;
+        let mut i = 0;
+        while i < running.len() {
+            match running[i].1.as_mut().poll(cx) {
+                Poll::Ready(Ok(output)) => {
+                    outputs.push((running.swap_remove(i).0, output));
+                    finished = true;
+                }
+                Poll::Ready(Err(error)) => return Poll::Ready(Err(error)),
+                Poll::Pending => i += 1,
+            }
+        }
+        // Futures that finished make room for ones that haven't started, which are polled
+        // straight away so they're woken when they can make progress.
+        if !finished {
+            return Poll::Pending;
+        }
+    })
+    .await?;
+    outputs.sort_by_key(|(index, _)| *index);
+    Ok(outputs.into_iter().map(|(_, output)| output).collect())
+}
+
+#[test]
+fn splits_bodies_into_parts() {
+    let config = UploadConfig::default();
+    assert!(!config.is_multipart(16 * 1024 * 1024 - 1));
+    assert!(config.is_multipart(16 * 1024 * 1024));
+
+    let mib = 1024 * 1024;
+    assert_eq!(
+        config.parts(20 * mib),
+        vec![0..8 * mib, 8 * mib..16 * mib, 16 * mib..20 * mib]
+    );
+    assert_eq!(config.parts(0), vec![0..0]);
+    let config = config.with_part_size(1);
+    assert_eq!(config.parts(6 * mib), vec![0..5 * mib, 5 * mib..6 * mib]);
+    let parts = config.parts(100_000 * mib);
+    assert_eq!(parts.len(), 10_000);
+    assert_eq!(parts[0], 0..10 * mib);
+}
+
+#[test]
+fn joins_futures_concurrently() {
+    use crate::{blocking::block_on, retry::sleep};
+    use std::{
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+        time::Duration,
+    };
+
+    let running = Arc::new(AtomicUsize::new(0));
+    let most = Arc::new(AtomicUsize::new(0));
+    let futures = (0..10u64).map(|i| {
+        let running = running.clone();
+        let most = most.clone();
+        async move {
+            most.fetch_max(running.fetch_add(1, Ordering::SeqCst) + 1, Ordering::SeqCst);
+            sleep(Duration::from_millis(10 - i)).await;
+            running.fetch_sub(1, Ordering::SeqCst);
+            Ok::<_, u64>(i)
+        }
+    });
+    assert_eq!(block_on(try_join(futures, 3)), Ok((0..10).collect()));
+    assert_eq!(most.load(Ordering::SeqCst), 3);
+
+    let futures = (0..10u64).map(|i| async move {
+        sleep(Duration::from_millis(1)).await;
+        if i % 4 == 3 {
+            Err(i)
+        } else {
+            Ok(i)
+        }
+    });
+    assert_eq!(block_on(try_join(futures, 2)), Err(3));
+}
@@ -0,0 +1,38 @@
+use crate::hash::hex_encode;
+use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hasher},
+};
+
+/// A random (version 4) UUID, e.g. `0f8fad5b-d9cb-469f-a165-70867728950e`, which clients fill
+/// idempotency tokens the caller leaves unset with.
+pub fn new_v4() -> String {
+    // Each `RandomState` is seeded differently, so hashing nothing with two of them gives all
+    // the random bits a UUID needs. They're unpredictable enough to tell requests apart, if not
+    // to keep secrets.
+    let mut bytes = [0u8; 16];
+    for half in bytes.chunks_mut(8) {
+        half.copy_from_slice(&RandomState::new().build_hasher().finish().to_be_bytes());
+    }
+    bytes[6] = bytes[6] & 0x0f | 0x40;
+    bytes[8] = bytes[8] & 0x3f | 0x80;
+    let hex = hex_encode(&bytes);
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..]
+    )
+}
+
+#[test]
+fn generates_v4_uuids() {
+    let uuid = new_v4();
+    assert_eq!(uuid.len(), 36);
+    assert_eq!(uuid.matches('-').count(), 4);
+    assert_eq!(&uuid[14..15], "4");
+    assert!(matches!(&uuid[19..20], "8" | "9" | "a" | "b"));
+    assert_ne!(new_v4(), uuid);
+}
@@ -0,0 +1,191 @@
+use crate::retry::{random, sleep};
+use std::{
+    error::Error,
+    fmt,
+    future::Future,
+    time::{Duration, Instant},
+};
+
+/// How often a waiter polls, e.g. for a Lambda function to become active, and for how long.
+/// After the `n`th attempt it waits a random time between `min_delay` and `min_delay * 2^(n - 1)`,
+/// capped at `max_delay`, and it gives up rather than wait past `max_wait` in all.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WaiterConfig {
+    min_delay: Duration,
+    max_delay: Duration,
+    max_wait: Duration,
+}
+
+impl WaiterConfig {
+    /// A model's settings: polling every `delay`, up to `max_attempts` times.
+    pub fn new(delay: Duration, max_attempts: u32) -> Self {
+        WaiterConfig {
+            min_delay: delay,
+            max_delay: delay,
+            max_wait: delay.saturating_mul(max_attempts),
+        }
+    }
+
+    pub fn with_min_delay(mut self, min_delay: Duration) -> Self {
+        self.min_delay = min_delay;
+        self
+    }
+
+    pub fn with_max_delay(mut self, max_delay: Duration) -> Self {
+        self.max_delay = max_delay;
+        self
+    }
+
+    pub fn with_max_wait(mut self, max_wait: Duration) -> Self {
+        self.max_wait = max_wait;
+        self
+    }
+
+    pub fn min_delay(&self) -> Duration {
+        self.min_delay
+    }
+
+    pub fn max_delay(&self) -> Duration {
+        self.max_delay
+    }
+
+    pub fn max_wait(&self) -> Duration {
+        self.max_wait
+    }
+
+    /// The wait after `attempts` attempts, for a `random` number in `[0, 1)`.
+    pub fn delay(&self, attempts: u32, random: f64) -> Duration {
+        let max_delay = self.max_delay.max(self.min_delay);
+        let exponent = attempts.saturating_sub(1).min(31);
+        let ceiling = self
+            .min_delay
+            .checked_mul(1 << exponent)
+            .map_or(max_delay, |ceiling| ceiling.min(max_delay));
+        self.min_delay + (ceiling - self.min_delay).mul_f64(random.clamp(0.0, 1.0))
+    }
+}
+
+/// What a waiter's acceptor makes of an attempt's result.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WaiterState {
+    /// Stop waiting: the resource is as waited for.
+    Success,
+    /// Stop waiting: the resource never will be.
+    Failure,
+    /// Try again.
+    Retry,
+}
+
+/// Why a waiter stopped without succeeding, with `O` and `E` its operation's output and error.
+#[derive(Debug)]
+pub enum WaiterError<O, E> {
+    /// An acceptor matched the result as a failure, e.g. a Lambda function whose state is
+    /// `Failed`.
+    Failure(Result<O, E>),
+    /// The operation failed with an error no acceptor matched.
+    Operation(E),
+    /// Waiting for another attempt would have taken longer than the config's `max_wait`.
+    TimedOut { attempts: u32 },
+}
+
+impl<O, E: fmt::Display> fmt::Display for WaiterError<O, E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WaiterError::Failure(_) => write!(f, "the waiter reached a failure state"),
+            WaiterError::Operation(error) => write!(f, "{}", error),
+            WaiterError::TimedOut { attempts } => {
+                write!(f, "the waiter gave up after {} attempts", attempts)
+            }
+        }
+    }
+}
+
+impl<O: fmt::Debug, E: Error + 'static> Error for WaiterError<O, E> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            WaiterError::Operation(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+/// Makes `attempt`s until `accept` matches one's result as a success or failure, waiting
+/// between them as `config` has it. Results no acceptor matches are retried if they're
+/// outputs, and are the waiter's error otherwise. Succeeds with the last output, or `None` if
+/// an acceptor matched an error as a success.
+pub async fn wait<O, E, F, Fut>(
+    config: WaiterConfig,
+    mut attempt: F,
+    accept: fn(&Result<O, E>) -> Option<WaiterState>,
+) -> Result<Option<O>, WaiterError<O, E>>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<O, E>>,
+{
+    let start = Instant::now();
+    let mut attempts = 0;
+    loop {
+        let result = attempt().await;
+        attempts += 1;
+        match (accept(&result), result) {
+            (Some(WaiterState::Success), result) => return Ok(result.ok()),
+            (Some(WaiterState::Failure), result) => return Err(WaiterError::Failure(result)),
+            (None, Err(error)) => return Err(WaiterError::Operation(error)),
+            (Some(WaiterState::Retry), _) | (None, Ok(_)) => {}
+        }
+        let delay = config.delay(attempts, random());
+        if start.elapsed() + delay > config.max_wait {
+            return Err(WaiterError::TimedOut { attempts });
+        }
+        sleep(delay).await;
+    }
+}
+
+#[test]
+fn backs_off_between_delays() {
+    let config = WaiterConfig::new(Duration::from_secs(5), 60);
+    assert_eq!(config.max_wait(), Duration::from_secs(300));
+    assert_eq!(config.delay(10, 0.9), Duration::from_secs(5));
+
+    let config = config.with_min_delay(Duration::from_secs(2));
+    assert_eq!(config.delay(1, 0.9), Duration::from_secs(2));
+    assert_eq!(config.delay(2, 0.5), Duration::from_secs(3));
+    assert_eq!(config.delay(10, 1.0), Duration::from_secs(5));
+    assert_eq!(config.delay(u32::MAX, 0.0), Duration::from_secs(2));
+}
+
+#[test]
+fn waits() {
+    use crate::blocking::block_on;
+
+    let accept = |result: &Result<u32, &'static str>| match result {
+        Ok(3) => Some(WaiterState::Success),
+        Ok(_) => None,
+        Err("gone") => Some(WaiterState::Failure),
+        Err("missing") => Some(WaiterState::Retry),
+        Err(_) => None,
+    };
+    let config = WaiterConfig::new(Duration::from_millis(1), 10);
+    let poll = |results: Vec<Result<u32, &'static str>>| {
+        let mut results = results.into_iter();
+        block_on(wait(
+            config,
+            move || std::future::ready(results.next().unwrap_or(Ok(0))),
+            accept,
+        ))
+    };
+    assert_eq!(poll(vec![Err("missing"), Ok(1), Ok(3)]).unwrap(), Some(3));
+    assert!(matches!(
+        poll(vec![Ok(1), Err("gone")]),
+        Err(WaiterError::Failure(Err("gone")))
+    ));
+    assert!(matches!(
+        poll(vec![Err("denied")]),
+        Err(WaiterError::Operation("denied"))
+    ));
+    let timed_out = poll(Vec::new()).unwrap_err();
+    assert!(matches!(timed_out, WaiterError::TimedOut { attempts } if attempts <= 10));
+    assert!(timed_out
+        .to_string()
+        .starts_with("the waiter gave up after "));
+}
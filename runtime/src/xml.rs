@@ -0,0 +1,349 @@
+use crate::BoxError;
+
+/// An XML element, as much of it as AWS's XML protocols need: its attributes, child
+/// elements, and text. Comments, processing instructions, and doctypes are dropped.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Element {
+    pub name: String,
+    pub attributes: Vec<(String, String)>,
+    pub children: Vec<Element>,
+    // The element's own text, from all of its text and CDATA nodes.
+    pub text: String,
+}
+
+// A name without its namespace prefix, e.g. `type` for `xsi:type`.
+fn local_name(name: &str) -> &str {
+    name.rsplit(':').next().unwrap_or(name)
+}
+
+impl Element {
+    /// The value of an attribute, matched by local name.
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(attribute, _)| local_name(attribute) == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// The first child element with the given local name.
+    pub fn child(&self, name: &str) -> Option<&Element> {
+        self.children
+            .iter()
+            .find(|child| local_name(&child.name) == name)
+    }
+
+    /// Every child element with the given local name, in document order.
+    pub fn children_named<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Element> {
+        self.children
+            .iter()
+            .filter(move |child| local_name(&child.name) == name)
+    }
+}
+
+/// Escapes text for use in an element or a double-quoted attribute value.
+pub fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '&' => out.push_str("&amp;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            '\r' => out.push_str("&#xD;"),
+            '\n' => out.push_str("&#xA;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Writes an element holding nothing but text.
+pub fn write_element(out: &mut String, name: &str, text: &str) {
+    out.push('<');
+    out.push_str(name);
+    out.push('>');
+    out.push_str(&escape(text));
+    out.push_str("</");
+    out.push_str(name);
+    out.push('>');
+}
+
+/// Writes an attribute, with the space that goes before it, into a start tag.
+pub fn write_attribute(out: &mut String, name: &str, value: &str) {
+    out.push(' ');
+    out.push_str(name);
+    out.push_str("=\"");
+    out.push_str(&escape(value));
+    out.push('"');
+}
+
+/// Parses an XML document into its root element.
+pub fn parse(xml: &[u8]) -> Result<Element, BoxError> {
+    let xml = std::str::from_utf8(xml)?;
+    let mut parser = Parser { xml, at: 0 };
+    parser.skip_misc()?;
+    let root = parser.element()?;
+    parser.skip_misc()?;
+    if parser.at != xml.len() {
+        return Err(parser.error("content after the root element"));
+    }
+    Ok(root)
+}
+
+/// Whether a body is an XML document whose root is an `Error` element, as S3's responses can
+/// be even with a success status, when an operation fails after it started sending them.
+pub fn is_error(body: &[u8]) -> bool {
+    parse(body).is_ok_and(|root| local_name(&root.name) == "Error")
+}
+
+/// An element holding `root`, unless it's empty, for responses like S3's to
+/// `GetBucketLocation`, whose root element is their output's only member rather than the
+/// output, so they can be read like the rest.
+pub fn enclose(root: Element) -> Element {
+    let mut enclosing = Element::default();
+    if !root.text.is_empty() || !root.children.is_empty() {
+        enclosing.children.push(root);
+    }
+    enclosing
+}
+
+struct Parser<'a> {
+    xml: &'a str,
+    at: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn rest(&self) -> &'a str {
+        &self.xml[self.at..]
+    }
+
+    fn error(&self, problem: &str) -> BoxError {
+        format!("invalid XML at byte {}: {}", self.at, problem).into()
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.at += rest.len() - rest.trim_start().len();
+    }
+
+    // Skips past the next `end`, failing if there isn't one.
+    fn skip_past(&mut self, end: &str) -> Result<&'a str, BoxError> {
+        let rest = self.rest();
+        let len = rest
+            .find(end)
+            .ok_or_else(|| self.error(&format!("missing `{}`", end)))?;
+        self.at += len + end.len();
+        Ok(&rest[..len])
+    }
+
+    // Skips whitespace, comments, processing instructions, and doctypes.
+    fn skip_misc(&mut self) -> Result<(), BoxError> {
+        loop {
+            self.skip_whitespace();
+            if self.rest().starts_with("<?") {
+                self.skip_past("?>")?;
+            } else if self.rest().starts_with("<!--") {
+                self.skip_past("-->")?;
+            } else if self.rest().starts_with("<!DOCTYPE") {
+                self.skip_past(">")?;
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    fn name(&mut self) -> Result<String, BoxError> {
+        let rest = self.rest();
+        let len = rest
+            .find(|c: char| c.is_whitespace() || "/>=".contains(c))
+            .unwrap_or(rest.len());
+        if len == 0 {
+            return Err(self.error("expected a name"));
+        }
+        self.at += len;
+        Ok(rest[..len].to_string())
+    }
+
+    fn element(&mut self) -> Result<Element, BoxError> {
+        if !self.rest().starts_with('<') {
+            return Err(self.error("expected an element"));
+        }
+        self.at += 1;
+        let mut element = Element {
+            name: self.name()?,
+            ..Element::default()
+        };
+        loop {
+            self.skip_whitespace();
+            if self.rest().starts_with("/>") {
+                self.at += 2;
+                return Ok(element);
+            }
+            if self.rest().starts_with('>') {
+                self.at += 1;
+                break;
+            }
+            let name = self.name()?;
+            self.skip_whitespace();
+            if !self.rest().starts_with('=') {
+                return Err(self.error("expected `=` after an attribute name"));
+            }
+            self.at += 1;
+            self.skip_whitespace();
+            let quote = match self.rest().chars().next() {
+                Some(quote @ '"') | Some(quote @ '\'') => quote,
+                _ => return Err(self.error("expected a quoted attribute value")),
+            };
+            self.at += 1;
+            let value = self.skip_past(&quote.to_string())?;
+            let value = unescape(value).map_err(|problem| self.error(&problem))?;
+            element.attributes.push((name, value));
+        }
+
+        loop {
+            let rest = self.rest();
+            if rest.starts_with("</") {
+                self.at += 2;
+                let name = self.name()?;
+                if name != element.name {
+                    return Err(
+                        self.error(&format!("`</{}>` doesn't close `<{}>`", name, element.name))
+                    );
+                }
+                self.skip_whitespace();
+                self.skip_past(">")?;
+                return Ok(element);
+            } else if rest.starts_with("<![CDATA[") {
+                self.at += "<![CDATA[".len();
+                let text = self.skip_past("]]>")?;
+                element.text.push_str(text);
+            } else if rest.starts_with("<!--") {
+                self.skip_past("-->")?;
+            } else if rest.starts_with("<?") {
+                self.skip_past("?>")?;
+            } else if rest.starts_with('<') {
+                element.children.push(self.element()?);
+            } else if rest.is_empty() {
+                return Err(self.error(&format!("`<{}>` isn't closed", element.name)));
+            } else {
+                let len = rest.find('<').unwrap_or(rest.len());
+                self.at += len;
+                let text = unescape(&rest[..len]).map_err(|problem| self.error(&problem))?;
+                element.text.push_str(&text);
+            }
+        }
+    }
+}
+
+// Replaces the predefined entities and character references in text.
+fn unescape(text: &str) -> Result<String, String> {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        out.push_str(&rest[..start]);
+        let end = rest[start..]
+            .find(';')
+            .ok_or_else(|| format!("unterminated entity in `{}`", text))?;
+        let entity = &rest[start + 1..start + end];
+        let c = match entity {
+            "lt" => '<',
+            "gt" => '>',
+            "amp" => '&',
+            "quot" => '"',
+            "apos" => '\'',
+            _ => {
+                let code = if let Some(hex) = entity.strip_prefix("#x") {
+                    u32::from_str_radix(hex, 16).ok()
+                } else if let Some(decimal) = entity.strip_prefix('#') {
+                    decimal.parse().ok()
+                } else {
+                    None
+                };
+                code.and_then(std::char::from_u32)
+                    .ok_or_else(|| format!("unknown entity `&{};`", entity))?
+            }
+        };
+        out.push(c);
+        rest = &rest[start + end + 1..];
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+#[test]
+fn parses_xml() -> Result<(), BoxError> {
+    let root = parse(
+        br#"<?xml version="1.0" encoding="UTF-8"?>
+<ListBucketResult xmlns="http://s3.amazonaws.com/doc/2006-03-01/">
+    <!-- A comment. -->
+    <Name>photos &amp; videos</Name>
+    <Contents><Key>a.jpg</Key></Contents>
+    <Contents><Key><![CDATA[<b>.jpg]]></Key></Contents>
+    <Grantee xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" xsi:type='CanonicalUser'/>
+    <Empty></Empty>
+    <Emoji>&#x1F600;&#65;</Emoji>
+</ListBucketResult>"#,
+    )?;
+    assert_eq!(root.name, "ListBucketResult");
+    assert_eq!(
+        root.attribute("xmlns"),
+        Some("http://s3.amazonaws.com/doc/2006-03-01/")
+    );
+    assert_eq!(
+        root.child("Name").map(|name| name.text.as_str()),
+        Some("photos & videos")
+    );
+    let keys = root
+        .children_named("Contents")
+        .filter_map(|contents| contents.child("Key"))
+        .map(|key| key.text.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(keys, vec!["a.jpg", "<b>.jpg"]);
+    assert_eq!(
+        root.child("Grantee")
+            .and_then(|grantee| grantee.attribute("type")),
+        Some("CanonicalUser")
+    );
+    assert_eq!(
+        root.child("Empty").map(|empty| empty.text.as_str()),
+        Some("")
+    );
+    assert_eq!(
+        root.child("Emoji").map(|emoji| emoji.text.as_str()),
+        Some("😀A")
+    );
+
+    assert!(parse(b"<a><b></a>").is_err());
+    assert!(parse(b"<a>").is_err());
+    assert!(parse(b"<a/><b/>").is_err());
+    assert!(parse(b"<a>&bogus;</a>").is_err());
+
+    assert!(is_error(
+        b"<Error><Code>InternalError</Code><Message>Try again.</Message></Error>"
+    ));
+    assert!(!is_error(b"<CompleteMultipartUploadResult/>"));
+    assert!(!is_error(b""));
+    let location = enclose(parse(
+        b"<LocationConstraint>eu-west-1</LocationConstraint>",
+    )?);
+    assert_eq!(
+        location
+            .child("LocationConstraint")
+            .map(|location| location.text.as_str()),
+        Some("eu-west-1")
+    );
+    assert!(enclose(parse(b"<LocationConstraint/>")?)
+        .children
+        .is_empty());
+
+    let mut out = String::new();
+    write_element(&mut out, "Key", "a<b>&\"c\"");
+    assert_eq!(out, "<Key>a&lt;b&gt;&amp;&quot;c&quot;</Key>");
+    assert_eq!(parse(out.as_bytes())?.text, "a<b>&\"c\"");
+    let mut out = "<Grantee".to_string();
+    write_attribute(&mut out, "xsi:type", "Group");
+    assert_eq!(out, "<Grantee xsi:type=\"Group\"");
+
+    Ok(())
+}
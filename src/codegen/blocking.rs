@@ -0,0 +1,134 @@
+use super::{
+    allow_deprecated, deprecated_operation, features::OperationFeatures, indent,
+    operation_uses_deprecated,
+};
+use crate::{
+    docs::{doc_comment, DocLinks},
+    graph::ModelGraph,
+    model::Operation,
+    naming::{field_name, type_name},
+    Err,
+};
+
+/// Generates the service's blocking `Client`, in the `blocking` module of crates generated
+/// with one: a method for each of the async client's operation methods, in name order, that
+/// blocks the calling thread until the async method is done. Presigning, and anything else
+/// without a blocking method, is done with the async client it wraps.
+pub fn generate_blocking_client(
+    graph: &ModelGraph<'_>,
+    links: &DocLinks,
+    features: &OperationFeatures,
+) -> Result<String, Err> {
+    let model = graph.model();
+    let mut methods = String::new();
+    for (_, op) in graph.operations() {
+        methods.push('\n');
+        methods.push_str(&blocking_method(graph, op, links, features));
+    }
+
+    Ok(format!(
+        "/// A client for {service} that blocks the calling thread until each of its requests is\n\
+         /// done, for programs without an async runtime.\n\
+         #[derive(Debug, Clone)]\n\
+         pub struct Client<C> {{\n    \
+         inner: crate::client::Client<C>,\n\
+         }}\n\n\
+         impl<C: crate::connector::HttpConnector + Send + Sync + 'static> Client<C> {{\n    \
+         pub fn new(config: crate::config::Config, connector: C) -> Self {{\n        \
+         Client::from_async(crate::client::Client::new(config, connector))\n    \
+         }}\n\
+         }}\n\n\
+         #[cfg(feature = \"hyper\")]\n\
+         impl Client<crate::connector::HyperConnector> {{\n    \
+         /// A client configured as `Config::from_environment` does, which sends requests\n    \
+         /// with hyper.\n    \
+         pub fn from_environment() -> Result<Self, crate::error::BoxError> {{\n        \
+         crate::client::Client::from_environment().map(Client::from_async)\n    \
+         }}\n\
+         }}\n\n\
+         impl<C> Client<C> {{\n    \
+         /// A blocking client that sends requests with an async one.\n    \
+         pub fn from_async(inner: crate::client::Client<C>) -> Self {{\n        \
+         Client {{ inner }}\n    \
+         }}\n\n    \
+         /// The async client requests are sent with, e.g. to presign them.\n    \
+         pub fn as_async(&self) -> &crate::client::Client<C> {{\n        \
+         &self.inner\n    \
+         }}\n\n    \
+         pub fn config(&self) -> &crate::config::Config {{\n        \
+         self.inner.config()\n    \
+         }}\n\
+         {methods}\
+         }}\n",
+        service = model.metadata.service_full_name,
+        methods = indent(&methods, 1),
+    ))
+}
+
+// The blocking client method for an operation.
+fn blocking_method(
+    graph: &ModelGraph<'_>,
+    op: &Operation,
+    links: &DocLinks,
+    features: &OperationFeatures,
+) -> String {
+    let method = field_name(&op.name);
+    let output = op
+        .output
+        .as_ref()
+        .map(|output| format!("crate::model::{}", type_name(output.shape.as_str())))
+        .unwrap_or_else(|| "()".to_string());
+    let error = format!("{}Error", type_name(&op.name));
+
+    let mut out = doc_comment(&op.documentation, links);
+    for attribute in deprecated_operation(op)
+        .into_iter()
+        .chain(features.cfg_attribute(&op.name))
+    {
+        out.push_str(&attribute);
+        out.push('\n');
+    }
+    out.push_str(allow_deprecated(operation_uses_deprecated(graph, op)));
+    out.push_str(&format!(
+        "pub fn {method}(\n    \
+         &self,\n    \
+         input: crate::model::{input},\n\
+         ) -> Result<{output}, crate::error::SdkError<crate::error::{error}>> {{\n    \
+         aws_sdk_rust_runtime::blocking::block_on(self.inner.{method}(input))\n\
+         }}\n",
+        method = method,
+        input = type_name(op.input.shape.as_str()),
+        output = output,
+        error = error,
+    ));
+    out
+}
+
+#[test]
+fn generates_blocking_clients() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let def = crate::model::BotocoreModel::from_path("test-data/recursive.json")?;
+    let graph = ModelGraph::new(&def);
+    let client = generate_blocking_client(&graph, &DocLinks::new(), &OperationFeatures::default())?;
+    assert!(client.starts_with(
+        "/// A client for Recursive Test Service that blocks the calling thread until each of \
+         its requests is\n"
+    ));
+    assert!(client.contains(
+        "    /// Stores an item.\n    \
+         pub fn put_item(\n        \
+         &self,\n        \
+         input: crate::model::PutItemInput,\n    \
+         ) -> Result<crate::model::PutItemOutput, crate::error::SdkError<crate::error::PutItemError>> {\n        \
+         aws_sdk_rust_runtime::blocking::block_on(self.inner.put_item(input))\n    \
+         }\n"
+    ));
+
+    let def = crate::model::BotocoreModel::from_path("test-data/lambda/2015-03-31/service-2.json")?;
+    let graph = ModelGraph::new(&def);
+    let features = OperationFeatures::new(&graph, &super::features::OperationGroups::ByPrefix)?;
+    let client = generate_blocking_client(&graph, &DocLinks::new(), &features)?;
+    assert!(client.contains("    #[cfg(feature = \"get\")]\n    pub fn get_function(\n"));
+    assert!(!client.contains("presigned_"));
+
+    Ok(())
+}
@@ -0,0 +1,201 @@
+use super::{
+    allow_deprecated, debug, indent,
+    structs::{debug_fields, request_id_fields, struct_fields},
+    uses_deprecated,
+};
+use crate::{
+    docs::{doc_comment, DocLinks},
+    graph::ModelGraph,
+    model::ShapeId,
+    naming::type_name,
+    Err,
+};
+
+/// The error builders return for a missing required field, emitted once into each
+/// generated crate's `error` module.
+pub const BUILD_ERROR: &str = r#"/// A required field wasn't set on a builder.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BuildError {
+    type_name: &'static str,
+    field: &'static str,
+}
+
+impl BuildError {
+    pub fn missing_field(type_name: &'static str, field: &'static str) -> Self {
+        BuildError { type_name, field }
+    }
+}
+
+impl std::fmt::Display for BuildError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "`{}` is required to build `{}`", self.field, self.type_name)
+    }
+}
+
+impl std::error::Error for BuildError {}
+"#;
+
+/// Generates the builder for a structure shape's struct, along with a `builder()` on the
+/// struct to create one.
+///
+/// Each field gets a fluent setter taking anything that converts into it, and a `set_`
+/// setter taking an `Option` for callers that have one already. `build()` fails with a
+/// `BuildError` if a required field is unset.
+pub fn generate_builder(
+    graph: &ModelGraph<'_>,
+    id: ShapeId,
+    links: &DocLinks,
+) -> Result<String, Err> {
+    let (fields, _) = struct_fields(graph, id)?;
+    let type_name = type_name(id.as_str());
+    let builder = format!("{}Builder", type_name);
+
+    let mut body = String::new();
+    let mut setters = String::new();
+    let mut build = String::new();
+    for field in &fields {
+        let name = &field.name;
+        let ident = name.trim_start_matches("r#");
+        let value_type = field.value_type();
+        body.push_str(&format!("{}: Option<{}>,\n", name, value_type));
+
+        let docs = field
+            .documentation
+            .map(|documentation| doc_comment(documentation, links))
+            .unwrap_or_default();
+        let deprecated = field
+            .deprecated
+            .as_ref()
+            .map(|attribute| format!("{}\n", attribute))
+            .unwrap_or_default();
+        setters.push_str(&format!(
+            "{docs}{deprecated}pub fn {name}(mut self, input: impl Into<{ty}>) -> Self {{\n    \
+             self.{name} = Some(input.into());\n    \
+             self\n\
+             }}\n\n\
+             {docs}{deprecated}pub fn set_{ident}(mut self, input: Option<{ty}>) -> Self {{\n    \
+             self.{name} = input;\n    \
+             self\n\
+             }}\n\n",
+            docs = docs,
+            deprecated = deprecated,
+            name = name,
+            ident = ident,
+            ty = value_type,
+        ));
+
+        if field.is_optional() {
+            build.push_str(&format!("{}: self.{},\n", name, name));
+        } else {
+            build.push_str(&format!(
+                "{name}: self\n    \
+                 .{name}\n    \
+                 .ok_or_else(|| crate::error::BuildError::missing_field({ty:?}, {ident:?}))?,\n",
+                name = name,
+                ty = type_name,
+                ident = ident,
+            ));
+        }
+    }
+    for name in request_id_fields(graph, id) {
+        build.push_str(&format!("_{}: None,\n", name));
+    }
+    setters.push_str(&format!(
+        "pub fn build(self) -> Result<{}, crate::error::BuildError> {{\n    \
+         Ok({} {{\n{}    }})\n\
+         }}\n",
+        type_name,
+        type_name,
+        indent(&build, 2)
+    ));
+
+    let redact = fields.iter().any(|field| field.sensitive);
+    // Builders name their struct and set its fields, deprecated or not.
+    let allow = allow_deprecated(uses_deprecated(graph, id));
+    let mut out = format!("/// A builder for [`{}`].\n", type_name);
+    if redact {
+        out.push_str("#[derive(Clone, PartialEq, Default)]\n");
+    } else {
+        out.push_str("#[derive(Debug, Clone, PartialEq, Default)]\n");
+    }
+    out.push_str(allow);
+    out.push_str(&format!("pub struct {} {{\n", builder));
+    out.push_str(&indent(&body, 1));
+    out.push_str("}\n\n");
+    out.push_str(allow);
+    out.push_str(&format!("impl {} {{\n", builder));
+    out.push_str(&indent(&setters, 1));
+    out.push_str("}\n\n");
+    out.push_str(allow);
+    out.push_str(&format!(
+        "impl {} {{\n    \
+         /// Creates a new builder for this type.\n    \
+         pub fn builder() -> {} {{\n        \
+         {}::default()\n    \
+         }}\n\
+         }}\n",
+        type_name, builder, builder
+    ));
+    if redact {
+        out.push('\n');
+        out.push_str(allow);
+        out.push_str(&debug::debug_impl(&builder, &debug_fields(&fields)));
+    }
+    Ok(out)
+}
+
+#[test]
+fn generates_builders() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let def = crate::model::BotocoreModel::from_path("test-data/recursive.json")?;
+    let graph = ModelGraph::new(&def);
+    assert_eq!(
+        generate_builder(&graph, ShapeId::from("PutItemInput"), &DocLinks::new())?,
+        r#"/// A builder for [`PutItemInput`].
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct PutItemInputBuilder {
+    item: Option<std::collections::HashMap<String, AttributeValue>>,
+}
+
+impl PutItemInputBuilder {
+    pub fn item(mut self, input: impl Into<std::collections::HashMap<String, AttributeValue>>) -> Self {
+        self.item = Some(input.into());
+        self
+    }
+
+    pub fn set_item(mut self, input: Option<std::collections::HashMap<String, AttributeValue>>) -> Self {
+        self.item = input;
+        self
+    }
+
+    pub fn build(self) -> Result<PutItemInput, crate::error::BuildError> {
+        Ok(PutItemInput {
+            item: self
+                .item
+                .ok_or_else(|| crate::error::BuildError::missing_field("PutItemInput", "item"))?,
+        })
+    }
+}
+
+impl PutItemInput {
+    /// Creates a new builder for this type.
+    pub fn builder() -> PutItemInputBuilder {
+        PutItemInputBuilder::default()
+    }
+}
+"#
+    );
+
+    let def = crate::model::BotocoreModel::from_path("test-data/lambda/2015-03-31/service-2.json")?;
+    let graph = ModelGraph::new(&def);
+    let code = generate_builder(
+        &graph,
+        ShapeId::from("GetFunctionRequest"),
+        &DocLinks::new(),
+    )?;
+    assert!(code.contains("            qualifier: self.qualifier,\n"));
+    assert!(code.contains("    pub fn set_qualifier(mut self, input: Option<String>) -> Self {\n"));
+    let code = generate_builder(&graph, ShapeId::from("Environment"), &DocLinks::new())?;
+    assert!(code.contains("impl std::fmt::Debug for EnvironmentBuilder {"));
+
+    Ok(())
+}
@@ -0,0 +1,1479 @@
+use super::{
+    allow_deprecated,
+    customizations::{decode_keys, ServiceCustomizations},
+    deprecated_operation,
+    features::OperationFeatures,
+    indent, operation_uses_deprecated,
+    paginators::{expression, paginate_method},
+    protocol::{bindings::streams_payload, operation_deserializer, operation_serializer},
+    structs::{request_id_fields, struct_fields},
+    waiters::waiter_methods,
+};
+use crate::{
+    docs::{doc_comment, DocLinks},
+    graph::ModelGraph,
+    model::{AuthType, Operation, Protocol, Shape, Signature, UriSegment},
+    naming::{field_name, type_name},
+    Err,
+};
+
+/// The errors a client's methods can fail with, emitted once into each generated crate's
+/// `error` module.
+pub const SDK_ERROR: &str = r#"/// `Send + Sync` errors from connectors and (de)serialization.
+pub type BoxError = Box<dyn std::error::Error + Send + Sync + 'static>;
+
+/// Why a client method failed, with `E` the operation's own error type.
+#[derive(Debug)]
+pub enum SdkError<E> {
+    /// The request couldn't be built from the input.
+    Construction(BoxError),
+    /// The request couldn't be sent, or no response came back.
+    Dispatch(BoxError),
+    /// The response couldn't be parsed.
+    Response(BoxError),
+    /// The operation didn't complete within the config's operation timeout, or its last
+    /// attempt didn't within its connect and read timeouts.
+    Timeout(aws_sdk_rust_runtime::timeout::TimedOut),
+    /// The service returned an error.
+    Service {
+        error: E,
+        /// The code, message, and request ID the response carried.
+        meta: ErrorMetadata,
+    },
+}
+
+impl<E> SdkError<E> {
+    /// What the error response said about itself, if the service returned one.
+    pub fn meta(&self) -> Option<&ErrorMetadata> {
+        match self {
+            SdkError::Service { meta, .. } => Some(meta),
+            _ => None,
+        }
+    }
+
+    /// The ID the service gave the request, if it returned an error response with one.
+    pub fn request_id(&self) -> Option<&str> {
+        self.meta().and_then(ErrorMetadata::request_id)
+    }
+
+    /// The ID S3 gave the host that handled the request, if it returned an error response with
+    /// one.
+    pub fn extended_request_id(&self) -> Option<&str> {
+        self.meta().and_then(ErrorMetadata::extended_request_id)
+    }
+}
+
+impl<E: std::error::Error> std::fmt::Display for SdkError<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SdkError::Construction(error) => write!(f, "failed to construct request: {}", error),
+            SdkError::Dispatch(error) => write!(f, "failed to send request: {}", error),
+            SdkError::Response(error) => write!(f, "failed to parse response: {}", error),
+            SdkError::Timeout(error) => write!(f, "request {}", error),
+            SdkError::Service { error, .. } => write!(f, "service error: {}", error),
+        }
+    }
+}
+
+impl<E: std::error::Error + 'static> std::error::Error for SdkError<E> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SdkError::Construction(error) | SdkError::Dispatch(error) | SdkError::Response(error) => {
+                Some(error.as_ref())
+            }
+            SdkError::Timeout(error) => Some(error),
+            SdkError::Service { error, .. } => Some(error),
+        }
+    }
+}
+"#;
+
+/// What clients send requests with: the runtime's connectors, re-exported into each generated
+/// crate's `connector` module.
+pub const CONNECTOR: &str = r#"pub use aws_sdk_rust_runtime::connector::{
+    ConnectorFuture, HttpConnector, HttpRequest, HttpResponse, TestConnector,
+};
+
+#[cfg(feature = "hyper")]
+pub use aws_sdk_rust_runtime::connector::HyperConnector;
+#[cfg(feature = "reqwest")]
+pub use aws_sdk_rust_runtime::connector::ReqwestConnector;
+"#;
+
+/// The configuration clients are created with, emitted into each generated crate's `config`
+/// module.
+pub const CONFIG: &str = r#"/// Configuration for a [`Client`](crate::client::Client).
+#[derive(Debug, Clone)]
+pub struct Config {
+    endpoint: String,
+    region: Option<String>,
+    region_set: Option<String>,
+    signing_name: Option<String>,
+    // Whether S3 requests address buckets in their path rather than in their host.
+    path_style: bool,
+    // Whether operations that can be sent to endpoints discovered with the service's endpoint
+    // operation are.
+    endpoint_discovery: bool,
+    // The algorithm requests for operations with flexible checksums are checksummed with,
+    // unless their input picks one.
+    checksum_algorithm: Option<aws_sdk_rust_runtime::checksum::ChecksumAlgorithm>,
+    // Whether responses' checksums are validated even if their input doesn't ask for it.
+    response_checksum_validation: bool,
+    // Whether request bodies of operations with request compression are gzipped, from
+    // `min_compression_size` bytes.
+    request_compression: bool,
+    min_compression_size: usize,
+    retry_policy: aws_sdk_rust_runtime::retry::RetryPolicy,
+    connector_timeouts: aws_sdk_rust_runtime::timeout::ConnectorTimeouts,
+    operation_timeout: Option<std::time::Duration>,
+    credentials: Option<std::sync::Arc<dyn aws_sdk_rust_runtime::credentials::ProvideCredentials>>,
+}
+
+impl Config {
+    /// Configuration that sends requests to `endpoint`, e.g.
+    /// `https://lambda.us-east-1.amazonaws.com`, with S3's requests path-style.
+    pub fn new(endpoint: impl Into<String>) -> Self {
+        Config {
+            endpoint: endpoint.into(),
+            region: None,
+            region_set: None,
+            signing_name: None,
+            path_style: true,
+            endpoint_discovery: true,
+            checksum_algorithm: None,
+            response_checksum_validation: false,
+            request_compression: true,
+            min_compression_size: aws_sdk_rust_runtime::compression::DEFAULT_MIN_COMPRESSION_SIZE,
+            retry_policy: aws_sdk_rust_runtime::retry::RetryPolicy::new(),
+            connector_timeouts: Default::default(),
+            operation_timeout: None,
+            credentials: None,
+        }
+    }
+
+    /// Configuration for the service's endpoint in `region`, e.g. `us-east-1`, as
+    /// `endpoints.json` has it, with requests signed for the region and service name the
+    /// endpoint is scoped to. S3's requests address buckets in their host, where they can.
+    pub fn for_region(region: &str) -> Result<Self, crate::error::BoxError> {
+        let endpoint = crate::endpoint::RESOLVER.resolve(region)?;
+        Ok(Config::new(format!("https://{}", endpoint.hostname))
+            .with_region(endpoint.signing_region)
+            .with_signing_name(endpoint.signing_name)
+            .with_path_style(false))
+    }
+
+    /// Configuration for the region `AWS_REGION` or `AWS_DEFAULT_REGION` names, or else the
+    /// current profile's, with credentials from the runtime's default chain and the retry mode
+    /// `AWS_RETRY_MODE` or the profile's `retry_mode` names. Requests go to `AWS_ENDPOINT_URL`
+    /// or the profile's `endpoint_url` instead, if there is one.
+    pub fn from_environment() -> Result<Self, crate::error::BoxError> {
+        let region = aws_sdk_rust_runtime::endpoint::default_region()?;
+        let config = Config::for_region(&region)?
+            .with_credentials_provider(aws_sdk_rust_runtime::credentials::default_provider())
+            .with_retry_policy(aws_sdk_rust_runtime::retry::RetryPolicy::from_environment()?);
+        Ok(match aws_sdk_rust_runtime::endpoint::default_endpoint_url()? {
+            Some(endpoint_url) => config.with_endpoint_url(endpoint_url),
+            None => config,
+        })
+    }
+
+    /// Sends requests to `endpoint_url`, e.g. `http://localhost:4566` for LocalStack, rather
+    /// than the endpoint resolved for the region, but still signs them for the region and
+    /// service name. S3's requests are path-style, as MinIO and LocalStack take them, unless
+    /// `with_path_style(false)` follows, and endpoints aren't discovered, unless
+    /// `with_endpoint_discovery(true)` follows.
+    pub fn with_endpoint_url(mut self, endpoint_url: impl Into<String>) -> Self {
+        self.endpoint = endpoint_url.into();
+        self.path_style = true;
+        self.endpoint_discovery = false;
+        self
+    }
+
+    /// Sets whether operations that can be sent to endpoints discovered with the service's
+    /// endpoint operation, e.g. DynamoDB's `DescribeEndpoints`, are. On by default.
+    pub fn with_endpoint_discovery(mut self, endpoint_discovery: bool) -> Self {
+        self.endpoint_discovery = endpoint_discovery;
+        self
+    }
+
+    /// Sets whether S3's requests address buckets in their path, e.g.
+    /// `https://s3.amazonaws.com/bucket/key`, rather than in their host, e.g.
+    /// `https://bucket.s3.amazonaws.com/key`. Buckets whose names can't be in a host are
+    /// always in the path.
+    pub fn with_path_style(mut self, path_style: bool) -> Self {
+        self.path_style = path_style;
+        self
+    }
+
+    /// Sets the algorithm requests for operations with flexible checksums, e.g. S3's
+    /// `PutObject`, are checksummed with when their input doesn't pick one. Without one, only
+    /// operations that require a checksum are sent with one, a CRC-32 unless they pick another.
+    pub fn with_checksum_algorithm(
+        mut self,
+        algorithm: aws_sdk_rust_runtime::checksum::ChecksumAlgorithm,
+    ) -> Self {
+        self.checksum_algorithm = Some(algorithm);
+        self
+    }
+
+    /// Sets whether responses to operations with flexible checksums, e.g. S3's `GetObject`,
+    /// are validated against the checksums they carry even if their input doesn't enable
+    /// checksum validation. Off by default.
+    pub fn with_response_checksum_validation(mut self, validation: bool) -> Self {
+        self.response_checksum_validation = validation;
+        self
+    }
+
+    /// Sets whether request bodies of operations with request compression, e.g. CloudWatch's
+    /// `PutMetricData`, are gzipped when they're large enough. On by default.
+    pub fn with_request_compression(mut self, request_compression: bool) -> Self {
+        self.request_compression = request_compression;
+        self
+    }
+
+    /// Sets how large, in bytes, request bodies of operations with request compression have
+    /// to be to be gzipped: 10 KiB by default.
+    pub fn with_min_compression_size(mut self, min_compression_size: usize) -> Self {
+        self.min_compression_size = min_compression_size;
+        self
+    }
+
+    /// Sets how many times requests that fail retryably are sent, how long to wait between
+    /// attempts, and whether clients retry adaptively, e.g.
+    /// `RetryPolicy::new().with_mode(RetryMode::Adaptive)`. Without one, requests are retried
+    /// with the standard policy, up to three times in all.
+    pub fn with_retry_policy(
+        mut self,
+        retry_policy: aws_sdk_rust_runtime::retry::RetryPolicy,
+    ) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// Sets how long connecting to the endpoint can take. Connectors apply it from the
+    /// `ConnectorTimeouts` in each request's extensions, and it's part of how long each
+    /// attempt can take, with the read timeout if there's one.
+    pub fn with_connect_timeout(mut self, timeout: std::time::Duration) -> Self {
+        self.connector_timeouts.connect = Some(timeout);
+        self
+    }
+
+    /// Sets how long the response to each attempt can take once the connection is made, and
+    /// each chunk of its body after that. Attempts whose responses take longer than it and
+    /// the connect timeout together to start are given up on and retried, even by connectors
+    /// that don't apply these timeouts themselves.
+    pub fn with_read_timeout(mut self, timeout: std::time::Duration) -> Self {
+        self.connector_timeouts.read = Some(timeout);
+        self
+    }
+
+    /// Sets how long an operation can take in all, including its retries and the waits
+    /// between them.
+    pub fn with_operation_timeout(mut self, timeout: std::time::Duration) -> Self {
+        self.operation_timeout = Some(timeout);
+        self
+    }
+
+    /// Sets the region requests are signed for, e.g. `us-east-1`.
+    pub fn with_region(mut self, region: impl Into<String>) -> Self {
+        self.region = Some(region.into());
+        self
+    }
+
+    /// Signs requests with SigV4a for a set of regions, e.g. `us-east-1,us-west-2`, or `*` for
+    /// all of them, as S3 multi-region access points need, rather than with SigV4 for the
+    /// region.
+    pub fn with_region_set(mut self, region_set: impl Into<String>) -> Self {
+        self.region_set = Some(region_set.into());
+        self
+    }
+
+    /// Sets the service name requests are signed for, in place of the service's own.
+    pub fn with_signing_name(mut self, signing_name: impl Into<String>) -> Self {
+        self.signing_name = Some(signing_name.into());
+        self
+    }
+
+    /// Sets the credentials requests are signed with.
+    pub fn with_credentials(self, credentials: aws_sdk_rust_runtime::sigv4::Credentials) -> Self {
+        self.with_credentials_provider(credentials)
+    }
+
+    /// Sets where the credentials requests are signed with come from, e.g.
+    /// `aws_sdk_rust_runtime::credentials::default_provider()`. Without a provider, requests
+    /// are sent unsigned.
+    pub fn with_credentials_provider(
+        mut self,
+        provider: impl aws_sdk_rust_runtime::credentials::ProvideCredentials + 'static,
+    ) -> Self {
+        self.credentials = Some(std::sync::Arc::new(provider));
+        self
+    }
+
+    pub fn endpoint(&self) -> &str {
+        &self.endpoint
+    }
+
+    pub fn region(&self) -> Option<&str> {
+        self.region.as_deref()
+    }
+
+    pub fn region_set(&self) -> Option<&str> {
+        self.region_set.as_deref()
+    }
+
+    pub fn signing_name(&self) -> Option<&str> {
+        self.signing_name.as_deref()
+    }
+
+    pub fn path_style(&self) -> bool {
+        self.path_style
+    }
+
+    pub fn endpoint_discovery(&self) -> bool {
+        self.endpoint_discovery
+    }
+
+    pub fn checksum_algorithm(&self) -> Option<aws_sdk_rust_runtime::checksum::ChecksumAlgorithm> {
+        self.checksum_algorithm
+    }
+
+    pub fn response_checksum_validation(&self) -> bool {
+        self.response_checksum_validation
+    }
+
+    pub fn request_compression(&self) -> bool {
+        self.request_compression
+    }
+
+    pub fn min_compression_size(&self) -> usize {
+        self.min_compression_size
+    }
+
+    pub fn retry_policy(&self) -> aws_sdk_rust_runtime::retry::RetryPolicy {
+        self.retry_policy
+    }
+
+    pub fn connector_timeouts(&self) -> aws_sdk_rust_runtime::timeout::ConnectorTimeouts {
+        self.connector_timeouts
+    }
+
+    pub fn operation_timeout(&self) -> Option<std::time::Duration> {
+        self.operation_timeout
+    }
+
+    pub fn credentials_provider(
+        &self,
+    ) -> Option<&dyn aws_sdk_rust_runtime::credentials::ProvideCredentials> {
+        self.credentials.as_deref()
+    }
+}
+"#;
+
+/// How clients sign requests, emitted into each generated crate's `signing` module.
+pub const SIGNING: &str = r#"use aws_sdk_rust_runtime::{
+    body::Body,
+    sigv4::{presign, sign, Algorithm, EventSigner, Payload, SigningParams},
+};
+
+/// Signs a request with SigV4 for `service`, unless the config has a signing name of its own,
+/// with the config's credentials and region, or with SigV4a if the config has a region set,
+/// or leaves it unsigned if the config has no credentials provider. `signed_body` is whether
+/// the body is part of the signature, `s3` whether it's S3's flavor of SigV4, and `events`
+/// whether the body is an event stream, whose events are signed one by one as they're sent,
+/// starting from the request's signature. Bodies that aren't in memory, e.g. files, can't be hashed before
+/// they're sent, so they're left out of the signature, as are `aws-chunked` ones with a
+/// checksum in their trailer.
+pub(crate) async fn sign_v4(
+    request: http::Request<Body>,
+    config: &crate::config::Config,
+    service: &str,
+    signed_body: bool,
+    s3: bool,
+    events: bool,
+) -> Result<http::Request<Body>, crate::error::BoxError> {
+    let credentials = match config.credentials_provider() {
+        Some(provider) => provider.provide_credentials().await?,
+        None => return Ok(request),
+    };
+    let (algorithm, region) = signing_region(config)?;
+    let headers = signed_headers(request.headers())?;
+    let payload = if events {
+        Payload::StreamingEvents
+    } else if request.headers().contains_key("x-amz-trailer") {
+        Payload::UnsignedTrailer
+    } else {
+        match request.body().bytes() {
+            Some(body) if signed_body => Payload::Bytes(body),
+            _ => Payload::Unsigned,
+        }
+    };
+    let params = SigningParams {
+        credentials: &credentials,
+        algorithm,
+        region,
+        service: config.signing_name().unwrap_or(service),
+        time: std::time::SystemTime::now(),
+        s3,
+    };
+    let signed = sign(
+        request.method().as_str(),
+        &request.uri().to_string(),
+        &headers,
+        payload,
+        &params,
+    )?;
+
+    let (mut parts, mut body) = request.into_parts();
+    if events {
+        body = EventSigner::for_request(&params, &signed)?.sign_stream(body);
+    }
+    for (name, value) in signed {
+        parts.headers.insert(
+            http::header::HeaderName::from_bytes(name.as_bytes())?,
+            http::header::HeaderValue::from_str(&value)?,
+        );
+    }
+    Ok(http::Request::from_parts(parts, body))
+}
+
+/// Presigns a request like `sign_v4` does, with SigV4 or SigV4a, so
+/// that anyone can send it until `expires_in` has passed. S3's payloads are left unsigned.
+/// Other requests are query protocol ones, which are sent as `GET`s with their form in the
+/// query instead.
+// Only clients for those services presign requests.
+#[allow(dead_code)]
+pub(crate) async fn presign_v4(
+    request: http::Request<Body>,
+    config: &crate::config::Config,
+    service: &str,
+    expires_in: std::time::Duration,
+    s3: bool,
+) -> Result<http::Request<Body>, crate::error::BoxError> {
+    let credentials = config
+        .credentials_provider()
+        .ok_or("requests can't be presigned without credentials")?
+        .provide_credentials()
+        .await?;
+    let (algorithm, region) = signing_region(config)?;
+    let (mut parts, mut body) = request.into_parts();
+    let mut uri = parts.uri.to_string();
+    if !s3 {
+        let form = body.bytes().ok_or("only forms can be presigned")?;
+        if !form.is_empty() {
+            uri.push(if uri.contains('?') { '&' } else { '?' });
+            uri.push_str(std::str::from_utf8(form)?);
+        }
+        body = Body::empty();
+        parts.method = http::Method::GET;
+        parts.headers.remove(http::header::CONTENT_TYPE);
+    }
+    let payload = if s3 {
+        Payload::Unsigned
+    } else {
+        Payload::Bytes(b"")
+    };
+    let params = SigningParams {
+        credentials: &credentials,
+        algorithm,
+        region,
+        service: config.signing_name().unwrap_or(service),
+        time: std::time::SystemTime::now(),
+        s3,
+    };
+    let presigned = presign(
+        parts.method.as_str(),
+        &uri,
+        &signed_headers(&parts.headers)?,
+        payload,
+        expires_in,
+        &params,
+    )?;
+    parts.uri = presigned.parse()?;
+    Ok(http::Request::from_parts(parts, body))
+}
+
+// SigV4a and the config's region set if it has one, or SigV4 and its region.
+fn signing_region(
+    config: &crate::config::Config,
+) -> Result<(Algorithm, &str), crate::error::BoxError> {
+    Ok(match config.region_set() {
+        Some(region_set) => (Algorithm::V4a, region_set),
+        None => (
+            Algorithm::V4,
+            config
+                .region()
+                .ok_or("requests can't be signed without a region")?,
+        ),
+    })
+}
+
+fn signed_headers(
+    headers: &http::HeaderMap,
+) -> Result<Vec<(String, String)>, crate::error::BoxError> {
+    let mut signed = Vec::new();
+    for (name, value) in headers {
+        signed.push((name.as_str().to_string(), value.to_str()?.to_string()));
+    }
+    Ok(signed)
+}
+"#;
+
+/// Generates the service's `Client`, with an async method for each of the model's
+/// operations, in name order.
+///
+/// Each method sends an `Operation` through the client's `middleware::Stack`, which resolves
+/// its endpoint, builds its request with the protocol's `serialize_` function for the
+/// operation, signs it as the model says, and sends it with the client's connector, retrying as
+/// the config allows, and under any layers `with_layer` added. Idempotency tokens the caller
+/// leaves unset are filled in with random UUIDs first, so every attempt sends the same ones.
+/// Requests are signed with SigV4 or S3's flavor of it; other signature versions aren't
+/// supported yet, so their requests are sent unsigned. Operations whose endpoints are
+/// discovered are sent to the one the service's endpoint operation responds with, which is
+/// cached for as long as it says. A response with the operation's status code, or 200 if the
+/// model doesn't give one, is parsed as the output by its `deserialize_` function, and any
+/// other as one of the operation's errors by its `deserialize_..._error` function. The whole
+/// operation is bounded by the config's operation timeout. Operations of S3 and query protocol
+/// services also get a `presigned_` method, which builds and presigns the request instead of
+/// sending it, operations with a paginator a `paginate_` method, which streams their pages, and
+/// each waiter `wait_until_` methods, which poll its operation until its acceptors match.
+/// Methods for feature-gated operations are only compiled with their feature.
+///
+/// Methods are `async`, and their futures `Send`, so they can be spawned on tokio's
+/// multithreaded runtime. Only the connector needs an executor, since the runtime's timers
+/// run on a thread of their own: with the `hyper` feature, `Client::from_environment` sends
+/// requests with hyper, on tokio, and other connectors can use other executors.
+pub fn generate_client(
+    graph: &ModelGraph<'_>,
+    links: &DocLinks,
+    features: &OperationFeatures,
+) -> Result<String, Err> {
+    let model = graph.model();
+    let attributes = |op: &Operation| {
+        deprecated_operation(op)
+            .into_iter()
+            .chain(features.cfg_attribute(&op.name))
+            .map(|attribute| format!("{}\n", attribute))
+            .chain(Some(
+                allow_deprecated(operation_uses_deprecated(graph, op)).to_string(),
+            ))
+            .collect::<String>()
+    };
+    let mut methods = String::new();
+    for (_, op) in graph.operations() {
+        if !methods.is_empty() {
+            methods.push('\n');
+        }
+        methods.push_str(&client_method(graph, op, links, features));
+        if let Some(paginator) = model.paginators.get(&op.name) {
+            if let Some(method) = paginate_method(graph, op, paginator, &attributes(op))? {
+                methods.push('\n');
+                methods.push_str(&method);
+            }
+        }
+    }
+    if let Some((_, endpoint_operation)) = model.endpoint_operation() {
+        methods.push('\n');
+        methods.push_str(&discovery_method(graph, endpoint_operation)?);
+    }
+    let mut waiters = model.waiters.iter().collect::<Vec<_>>();
+    waiters.sort_by_key(|(name, _)| *name);
+    for (name, waiter) in waiters {
+        let op = &model.operations[&waiter.operation];
+        if let Some(method) = waiter_methods(graph, name, waiter, &attributes(op))? {
+            methods.push('\n');
+            methods.push_str(&method);
+        }
+    }
+
+    Ok(format!(
+        "/// A client for {service}, which sends requests with a connector of type `C`.\n\
+         pub struct Client<C> {{\n    \
+         config: crate::config::Config,\n    \
+         // The connector, under the layers `with_layer` added.\n    \
+         dispatch: crate::middleware::HttpService,\n    \
+         // The config's retry policy, with the quota and send rate of adaptive retries.\n    \
+         retries: aws_sdk_rust_runtime::retry::RetryHandler,\n    \
+         stack: crate::middleware::Stack,\n    \
+         // The endpoints discovered with the service's endpoint operation, if it has one.\n    \
+         endpoints: aws_sdk_rust_runtime::endpoint::EndpointCache,\n    \
+         connector: std::marker::PhantomData<fn() -> C>,\n\
+         }}\n\n\
+         impl<C: crate::connector::HttpConnector + Send + Sync + 'static> Client<C> {{\n    \
+         pub fn new(config: crate::config::Config, connector: C) -> Self {{\n        \
+         let dispatch = crate::middleware::Dispatch::new(connector);\n        \
+         let retries = aws_sdk_rust_runtime::retry::RetryHandler::new(config.retry_policy());\n        \
+         Client::from_parts(config, crate::middleware::HttpService::new(dispatch), retries)\n    \
+         }}\n\
+         }}\n\n\
+         #[cfg(feature = \"hyper\")]\n\
+         impl Client<crate::connector::HyperConnector> {{\n    \
+         /// A client configured as `Config::from_environment` does, which sends requests\n    \
+         /// with hyper.\n    \
+         pub fn from_environment() -> Result<Self, crate::error::BoxError> {{\n        \
+         let config = crate::config::Config::from_environment()?;\n        \
+         Ok(Client::new(config, crate::connector::HyperConnector::new()))\n    \
+         }}\n\
+         }}\n\n\
+         impl<C> Client<C> {{\n    \
+         fn from_parts(\n        \
+         config: crate::config::Config,\n        \
+         dispatch: crate::middleware::HttpService,\n        \
+         retries: aws_sdk_rust_runtime::retry::RetryHandler,\n    \
+         ) -> Self {{\n        \
+         Client {{\n            \
+         stack: crate::middleware::stack(&config, retries.clone(), dispatch.clone()),\n            \
+         config,\n            \
+         dispatch,\n            \
+         retries,\n            \
+         endpoints: aws_sdk_rust_runtime::endpoint::EndpointCache::new(),\n            \
+         connector: std::marker::PhantomData,\n        \
+         }}\n    \
+         }}\n\n    \
+         pub fn config(&self) -> &crate::config::Config {{\n        \
+         &self.config\n    \
+         }}\n\n    \
+         /// A client like this one but with another config, e.g. to sign some requests with\n    \
+         /// SigV4a. Its adaptive retries have a quota and send rate of their own.\n    \
+         pub fn with_config(&self, config: crate::config::Config) -> Self {{\n        \
+         let retries = aws_sdk_rust_runtime::retry::RetryHandler::new(config.retry_policy());\n        \
+         Client::from_parts(config, self.dispatch.clone(), retries)\n    \
+         }}\n\n    \
+         /// A client like this one with `layer` around its connector, so that every attempt\n    \
+         /// at a request goes through the service it makes, e.g. to log attempts or add\n    \
+         /// headers to them. Headers it adds aren't signed.\n    \
+         pub fn with_layer<L>(&self, layer: L) -> Self\n    \
+         where\n        \
+         L: tower::Layer<crate::middleware::HttpService>,\n        \
+         L::Service: tower::Service<\n                \
+         crate::middleware::HttpRequest,\n                \
+         Response = crate::middleware::HttpResponse,\n                \
+         Error = crate::error::BoxError,\n            \
+         > + Clone\n            \
+         + Send\n            \
+         + Sync\n            \
+         + 'static,\n        \
+         <L::Service as tower::Service<crate::middleware::HttpRequest>>::Future: Send + 'static,\n    \
+         {{\n        \
+         let dispatch = crate::middleware::HttpService::new(layer.layer(self.dispatch.clone()));\n        \
+         Client::from_parts(self.config.clone(), dispatch, self.retries.clone())\n    \
+         }}\n\n\
+         {methods}\
+         }}\n\n\
+         impl<C> Clone for Client<C> {{\n    \
+         fn clone(&self) -> Self {{\n        \
+         Client {{\n            \
+         config: self.config.clone(),\n            \
+         dispatch: self.dispatch.clone(),\n            \
+         retries: self.retries.clone(),\n            \
+         stack: self.stack.clone(),\n            \
+         endpoints: self.endpoints.clone(),\n            \
+         connector: std::marker::PhantomData,\n        \
+         }}\n    \
+         }}\n\
+         }}\n\n\
+         impl<C> std::fmt::Debug for Client<C> {{\n    \
+         fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {{\n        \
+         f.debug_struct(\"Client\")\n            \
+         .field(\"config\", &self.config)\n            \
+         .field(\"retries\", &self.retries)\n            \
+         .finish()\n    \
+         }}\n\
+         }}\n",
+        service = model.metadata.service_full_name,
+        methods = indent(&methods, 1),
+    ))
+}
+
+// The client method for an operation.
+fn client_method(
+    graph: &ModelGraph<'_>,
+    op: &Operation,
+    links: &DocLinks,
+    features: &OperationFeatures,
+) -> String {
+    let metadata = &graph.model().metadata;
+    let method = field_name(&op.name);
+    let input = type_name(op.input.shape.as_str());
+    let output = op
+        .output
+        .as_ref()
+        .map(|output| format!("crate::model::{}", type_name(output.shape.as_str())))
+        .unwrap_or_else(|| "()".to_string());
+    let error = format!("{}Error", type_name(&op.name));
+    let status = op
+        .http
+        .response_code
+        .map(|code| code.as_u16())
+        .unwrap_or(200);
+    let signing_name = metadata
+        .signing_name
+        .as_deref()
+        .unwrap_or(&metadata.endpoint_prefix);
+    let signature = op.signature(metadata.signature_version);
+    let customizations = ServiceCustomizations::for_service(metadata);
+    // S3 requests whose path starts with their bucket can address it in their host instead.
+    let bucket_addressed = customizations.bucket_addressing
+        && matches!(
+            op.http.request_uri.path.as_slice(),
+            [UriSegment::Literal(slash), UriSegment::Label { name, greedy: false }, ..]
+                if slash == "/" && name == "Bucket"
+        );
+    let compressed = op
+        .request_compression
+        .as_ref()
+        .is_some_and(|compression| compression.supports("gzip"));
+    // Event streams sent in the body have their events signed as well.
+    let events = graph
+        .shape(op.input.shape)
+        .and_then(Shape::payload)
+        .and_then(|payload| {
+            graph
+                .neighbors(op.input.shape)
+                .iter()
+                .find(|edge| edge.name == payload)
+        })
+        .is_some_and(|edge| matches!(graph.shape(edge.target), Some(Shape::EventStream { .. })));
+    let signing = match signature {
+        signature @ (Signature::V4 | Signature::S3V4) => format!(
+            "Some(crate::middleware::SigningProperties {{\n    \
+             signing_name: {:?},\n    \
+             signed_body: {},\n    \
+             s3: {},\n    \
+             events: {},\n\
+             }})",
+            signing_name,
+            op.auth_type != Some(AuthType::V4UnsignedBody),
+            signature == Signature::S3V4,
+            events
+        ),
+        _ => "None".to_string(),
+    };
+
+    // Outputs carry the IDs the service gave the request, from the response's headers.
+    let fields = op
+        .output
+        .as_ref()
+        .map(|output| request_id_fields(graph, output.shape))
+        .unwrap_or_default();
+    let (encode_keys, decode_keys) = decode_keys(graph, op).unwrap_or_default();
+    // Outputs whose payload is a stream are returned once the response's head has come in,
+    // with the body still being read. Everything else is read whole first.
+    let streaming = op
+        .output
+        .as_ref()
+        .is_some_and(|output| streams_payload(graph, output.shape));
+    let (checksums, checksum, validation) = checksums(graph, op, streaming);
+    let arguments = if streaming {
+        "&response, stream"
+    } else {
+        "&response"
+    };
+    let mut success = if streaming {
+        "let (parts, stream) = response.into_parts();\n\
+         let response = http::Response::from_parts(parts, Vec::new());\n"
+            .to_string()
+    } else {
+        String::new()
+    };
+    success.push_str(&validation);
+    if fields.is_empty() && decode_keys.is_empty() {
+        success.push_str(&format!(
+            "crate::protocol::{}({}).map_err(crate::error::SdkError::Response)\n",
+            operation_deserializer(&op.name),
+            arguments
+        ));
+    } else {
+        success.push_str(&format!(
+            "let mut output = crate::protocol::{}({})\n    \
+             .map_err(crate::error::SdkError::Response)?;\n",
+            operation_deserializer(&op.name),
+            arguments
+        ));
+        for name in fields {
+            success.push_str(&format!(
+                "output._{name} = crate::error::{name}(response.headers());\n",
+                name = name
+            ));
+        }
+        success.push_str(&decode_keys);
+        success.push_str("Ok(output)\n");
+    }
+
+    let attributes = deprecated_operation(op)
+        .into_iter()
+        .chain(features.cfg_attribute(&op.name))
+        .map(|attribute| format!("{}\n", attribute))
+        .chain(Some(
+            allow_deprecated(operation_uses_deprecated(graph, op)).to_string(),
+        ))
+        .collect::<String>();
+    let tokens = idempotency_tokens(graph, op);
+    let (discovery, route, forget) = endpoint_discovery(graph, op);
+    let errors_in_success = customizations.has_errors_in_success(&op.name);
+    let success_condition = if errors_in_success && !streaming {
+        format!(
+            "response.status().as_u16() == {}\n    \
+             && !aws_sdk_rust_runtime::xml::is_error(response.body())",
+            status
+        )
+    } else {
+        format!("response.status().as_u16() == {}", status)
+    };
+
+    let mut out = doc_comment(&op.documentation, links);
+    out.push_str(&attributes);
+    out.push_str(&format!(
+        "pub async fn {method}(\n    \
+         &self,\n    \
+         input: crate::model::{input},\n\
+         ) -> Result<{output}, crate::error::SdkError<crate::error::{error}>> {{\n\
+         {tokens}{encode_keys}{checksums}{discovery}    \
+         let properties = crate::middleware::OperationProperties {{\n        \
+         name: {name:?},\n        \
+         success_status: {status},\n        \
+         errors_in_success: {errors_in_success},\n        \
+         bucket_addressed: {bucket_addressed},\n        \
+         {checksum},\n        \
+         compressed: {compressed},\n        \
+         signing: {signing},\n    \
+         }};\n    \
+         let {binding} = crate::middleware::Operation::new(properties, move |endpoint| {{\n        \
+         crate::protocol::{serializer}(\n            \
+         http::Request::builder().method({http_method:?}),\n            \
+         &endpoint,\n            \
+         &input,\n        \
+         )\n    \
+         }});\n\
+         {route}    \
+         let response = aws_sdk_rust_runtime::timeout::timeout(\n        \
+         self.config.operation_timeout(),\n        \
+         crate::middleware::{send}(self.stack.clone(), operation),\n    \
+         )\n    \
+         .await\n    \
+         .map_err(crate::error::SdkError::Timeout)?\n    \
+         .map_err(crate::middleware::sdk_error)?;\n\
+         {forget}    \
+         if {success_condition} {{\n        \
+         {success}    \
+         }} else {{\n        \
+         {read}\
+         Err(crate::protocol::{deserializer}_error(&response))\n    \
+         }}\n\
+         }}\n",
+        method = method,
+        send = if streaming { "send" } else { "send_and_read" },
+        read = if streaming {
+            "let response = aws_sdk_rust_runtime::body::read_response(response)\n            \
+             .await\n            \
+             .map_err(crate::middleware::sdk_error)?;\n        "
+        } else {
+            ""
+        },
+        name = op.name,
+        deserializer = operation_deserializer(&op.name),
+        success = indent(&success, 2).trim_start(),
+        serializer = operation_serializer(&op.name),
+        bucket_addressed = bucket_addressed,
+        signing = indent(&signing, 2).trim(),
+        input = input,
+        output = output,
+        error = error,
+        http_method = op.http.method.as_str(),
+        status = status,
+        tokens = indent(&tokens, 1),
+        encode_keys = indent(&encode_keys, 1),
+        checksums = indent(&checksums, 1),
+        checksum = checksum,
+        compressed = compressed,
+        errors_in_success = errors_in_success,
+        success_condition = indent(&success_condition, 1).trim(),
+        discovery = indent(&discovery, 1),
+        binding = if route.is_empty() {
+            "operation"
+        } else {
+            "mut operation"
+        },
+        route = indent(&route, 1),
+        forget = indent(&forget, 1),
+    ));
+
+    let addressing = if bucket_addressed {
+        "let request = crate::endpoint::address_bucket(request, &self.config)\n    \
+         .map_err(crate::error::SdkError::Construction)?;\n"
+    } else {
+        ""
+    };
+    // S3 requests, whose bodies are left out of the signature, and query protocol ones,
+    // whose forms can be sent in the query instead, can be sent by whoever has their URI.
+    let presignable = signature == Signature::S3V4
+        || signature == Signature::V4
+            && matches!(metadata.protocol, Protocol::Query | Protocol::Ec2);
+    if presignable {
+        out.push_str(&format!(
+            "\n/// A presigned `{name}` request, which anyone can send until `expires_in` has passed,\n\
+             /// without credentials of their own, by way of its URI and headers.\n\
+             {attributes}\
+             pub async fn presigned_{method}(\n    \
+             &self,\n    \
+             input: crate::model::{input},\n    \
+             expires_in: std::time::Duration,\n\
+             ) -> Result<\n    \
+             http::Request<aws_sdk_rust_runtime::body::Body>,\n    \
+             crate::error::SdkError<crate::error::{error}>,\n\
+             > {{\n\
+             {tokens}    \
+             let request = crate::protocol::{serializer}(\n        \
+             http::Request::builder().method({http_method:?}),\n        \
+             self.config.endpoint(),\n        \
+             &input,\n    \
+             )\n    \
+             .map_err(crate::error::SdkError::Construction)?;\n\
+             {addressing}    \
+             crate::signing::presign_v4(\n        \
+             request,\n        \
+             &self.config,\n        \
+             {signing_name:?},\n        \
+             expires_in,\n        \
+             {s3},\n    \
+             )\n    \
+             .await\n    \
+             .map_err(crate::error::SdkError::Construction)\n\
+             }}\n",
+            name = op.name,
+            addressing = indent(addressing, 1),
+            attributes = attributes,
+            method = method,
+            input = input,
+            error = error,
+            serializer = operation_serializer(&op.name),
+            http_method = op.http.method.as_str(),
+            signing_name = signing_name,
+            s3 = signature == Signature::S3V4,
+            tokens = indent(&tokens, 1),
+        ));
+    }
+    out
+}
+
+// Fills in the idempotency tokens the caller left unset with random UUIDs, as other SDKs
+// do. That's once per call, so every attempt sends the same ones.
+fn idempotency_tokens(graph: &ModelGraph<'_>, op: &Operation) -> String {
+    let fields = match struct_fields(graph, op.input.shape) {
+        Ok((fields, _)) => fields,
+        Err(_) => return String::new(),
+    };
+    let tokens = fields
+        .iter()
+        .filter(|field| {
+            field.edge.member.idempotency_token
+                && field.is_optional()
+                && field.value_type() == "String"
+        })
+        .map(|field| {
+            format!(
+                "input.{}.get_or_insert_with(aws_sdk_rust_runtime::uuid::new_v4);\n",
+                field.name
+            )
+        })
+        .collect::<String>();
+    if tokens.is_empty() {
+        tokens
+    } else {
+        format!("let mut input = input;\n{}", tokens)
+    }
+}
+
+// For operations with flexible checksums: picking the algorithm the request's body is
+// checksummed with, as `checksum`, and whether the response's checksum is validated, as
+// `validate_checksum`, the property's field, and the code validating the response. Operations
+// that only require a `Content-MD5` get a checksum the same way, which S3 takes in its place.
+fn checksums(
+    graph: &ModelGraph<'_>,
+    op: &Operation,
+    streaming: bool,
+) -> (String, &'static str, String) {
+    let checksum = op.http_checksum.clone().unwrap_or_default();
+    let required = checksum.request_checksum_required || op.http_checksum_required;
+    let mut out = String::new();
+    let mut property = "checksum: None";
+    if required || checksum.request_algorithm_member.is_some() {
+        let requested = checksum
+            .request_algorithm_member
+            .as_deref()
+            .and_then(|member| input_str(graph, op, member))
+            .unwrap_or_else(|| "None".to_string());
+        out.push_str(&format!(
+            "let checksum = aws_sdk_rust_runtime::checksum::request_algorithm(\n    \
+             {},\n    \
+             self.config.checksum_algorithm(),\n    \
+             {},\n\
+             )\n\
+             .map_err(crate::error::SdkError::Construction)?;\n",
+            requested, required
+        ));
+        property = "checksum";
+    }
+
+    let mut validation = String::new();
+    if !checksum.response_algorithms.is_empty() {
+        let requested = checksum
+            .request_validation_mode_member
+            .as_deref()
+            .and_then(|member| input_str(graph, op, member));
+        out.push_str(&match requested {
+            Some(mode) => format!(
+                "let validate_checksum = {} == Some(\"ENABLED\")\n    \
+                 || self.config.response_checksum_validation();\n",
+                mode
+            ),
+            None => {
+                "let validate_checksum = self.config.response_checksum_validation();\n".to_string()
+            }
+        });
+        let algorithms = checksum
+            .response_algorithms
+            .iter()
+            .map(|algorithm| {
+                format!(
+                    "aws_sdk_rust_runtime::checksum::ChecksumAlgorithm::{:?},\n",
+                    algorithm
+                )
+            })
+            .collect::<String>();
+        // Streams are checked as they're read, and fail at their end if they don't match.
+        validation = if streaming {
+            format!(
+                "let stream = if validate_checksum {{\n    \
+                 aws_sdk_rust_runtime::checksum::validate_body(\n        \
+                 response.headers(),\n        \
+                 stream,\n        \
+                 &[\n{}        \
+                 ],\n    \
+                 )\n    \
+                 .map_err(crate::error::SdkError::Response)?\n\
+                 }} else {{\n    \
+                 stream\n\
+                 }};\n",
+                indent(&algorithms, 3)
+            )
+        } else {
+            format!(
+                "if validate_checksum {{\n    \
+                 aws_sdk_rust_runtime::checksum::validate_response(\n        \
+                 &response,\n        \
+                 &[\n{}        \
+                 ],\n    \
+                 )\n    \
+                 .map_err(crate::error::SdkError::Response)?;\n\
+                 }}\n",
+                indent(&algorithms, 3)
+            )
+        };
+    }
+    (out, property, validation)
+}
+
+// An `Option<&str>` of the input's string or enum member `member`, e.g. the checksum algorithm
+// its caller picked. `None` if the input has no such member.
+fn input_str(graph: &ModelGraph<'_>, op: &Operation, member: &str) -> Option<String> {
+    let (fields, _) = struct_fields(graph, op.input.shape).ok()?;
+    let field = fields.iter().find(|field| field.edge.name == member)?;
+    // Enums have an `as_str` too.
+    if !matches!(graph.shape(field.edge.target)?, Shape::String { .. }) {
+        return None;
+    }
+    Some(if field.is_optional() {
+        format!("input.{}.as_ref().map(|value| value.as_str())", field.name)
+    } else {
+        format!("Some(input.{}.as_str())", field.name)
+    })
+}
+
+// For operations whose endpoints are discovered: discovering the endpoint for the input's
+// identifiers, sending the request there, and forgetting it if it sends the request elsewhere
+// with a 421, for the next call to discover another. Empty for other operations.
+fn endpoint_discovery(graph: &ModelGraph<'_>, op: &Operation) -> (String, String, String) {
+    let required = match &op.endpoint_discovery {
+        Some(discovery)
+            if !op.endpoint_operation && graph.model().endpoint_operation().is_some() =>
+        {
+            discovery.required
+        }
+        _ => return Default::default(),
+    };
+    let mut identifiers = String::new();
+    if let Ok((fields, _)) = struct_fields(graph, op.input.shape) {
+        for field in fields {
+            if !field.edge.member.endpoint_discovery_id || field.value_type() != "String" {
+                continue;
+            }
+            identifiers.push_str(&if field.is_optional() {
+                format!(
+                    "if let Some(value) = &input.{} {{\n    \
+                     identifiers.insert({:?}.to_string(), value.clone());\n\
+                     }}\n",
+                    field.name, field.edge.name
+                )
+            } else {
+                format!(
+                    "identifiers.insert({:?}.to_string(), input.{}.clone());\n",
+                    field.edge.name, field.name
+                )
+            });
+        }
+    }
+    let discovery = if identifiers.is_empty() {
+        format!(
+            "let discovered = self\n    \
+             .discovered_endpoint({:?}, Default::default(), {})\n    \
+             .await?;\n",
+            op.name, required
+        )
+    } else {
+        format!(
+            "let mut identifiers = std::collections::BTreeMap::new();\n\
+             {}\
+             let discovered = self\n    \
+             .discovered_endpoint({:?}, identifiers, {})\n    \
+             .await?;\n",
+            identifiers, op.name, required
+        )
+    };
+    let route = "if let Some((_, endpoint)) = &discovered {\n    \
+                 operation = operation.with_endpoint(endpoint.clone());\n\
+                 }\n";
+    let forget = "if let Some((key, _)) = &discovered {\n    \
+                  if response.status().as_u16() == 421 {\n        \
+                  self.endpoints.remove(key);\n    \
+                  }\n\
+                  }\n";
+    (discovery, route.to_string(), forget.to_string())
+}
+
+// The client method discovering the endpoint for an operation's requests with the service's
+// endpoint operation, e.g. `DescribeEndpoints`, which is sent with the operation's name and
+// identifiers if it takes them. The first endpoint it responds with is cached for as long as
+// it says.
+fn discovery_method(graph: &ModelGraph<'_>, endpoint_operation: &Operation) -> Result<String, Err> {
+    let output = endpoint_operation
+        .output
+        .as_ref()
+        .ok_or_else(|| format!("`{}` has no output", endpoint_operation.name))?;
+    let (address, _) = expression(graph, output.shape, "Endpoints[0].Address", "&output")?;
+    let (minutes, _) = expression(
+        graph,
+        output.shape,
+        "Endpoints[0].CachePeriodInMinutes",
+        "&output",
+    )?;
+    let (fields, _) = struct_fields(graph, endpoint_operation.input.shape)?;
+    let mut input = format!(
+        "let input = crate::model::{}::builder()",
+        type_name(endpoint_operation.input.shape.as_str())
+    );
+    for field in &fields {
+        match field.edge.name {
+            "Operation" => input.push_str(&format!("\n    .{}(operation)", field.name)),
+            "Identifiers" => input.push_str(&format!(
+                "\n    .{}(identifiers.into_iter().collect::<std::collections::HashMap<_, _>>())",
+                field.name
+            )),
+            _ => {}
+        }
+    }
+    input.push_str("\n    .build()\n    .map_err(|error| crate::error::SdkError::Construction(error.into()))?;\n");
+
+    Ok(format!(
+        "/// The endpoint discovered for an operation's requests with `identifiers`, and the key\n\
+         /// it's cached under, or `None` if they're sent to the config's endpoint: when the config\n\
+         /// turns discovery off, or discovery fails and isn't `required`.\n\
+         async fn discovered_endpoint<E>(\n    \
+         &self,\n    \
+         operation: &str,\n    \
+         identifiers: std::collections::BTreeMap<String, String>,\n    \
+         required: bool,\n\
+         ) -> Result<Option<(String, String)>, crate::error::SdkError<E>> {{\n    \
+         if !self.config.endpoint_discovery() {{\n        \
+         return Ok(None);\n    \
+         }}\n    \
+         let key = aws_sdk_rust_runtime::endpoint::EndpointCache::key(operation, &identifiers);\n    \
+         if let Some(endpoint) = self.endpoints.get(&key) {{\n        \
+         return Ok(Some((key, endpoint)));\n    \
+         }}\n\
+         {input}    \
+         let discovered = match self.{method}(input).await {{\n        \
+         Ok(output) => {address}\n            \
+         .cloned()\n            \
+         .zip({minutes}.copied())\n            \
+         .ok_or_else(|| crate::error::BoxError::from(\"the response has no endpoints\")),\n        \
+         Err(error) => Err(error.into()),\n    \
+         }};\n    \
+         match discovered {{\n        \
+         Ok((address, minutes)) => {{\n            \
+         let endpoint = if address.contains(\"://\") {{\n                \
+         address\n            \
+         }} else {{\n                \
+         format!(\"https://{{}}\", address)\n            \
+         }};\n            \
+         let ttl = std::time::Duration::from_secs((minutes.max(0) as u64).saturating_mul(60));\n            \
+         self.endpoints.insert(key.clone(), endpoint.clone(), ttl);\n            \
+         Ok(Some((key, endpoint)))\n        \
+         }}\n        \
+         Err(_) if !required => Ok(None),\n        \
+         Err(error) => Err(crate::error::SdkError::Dispatch(\n            \
+         format!(\"couldn't discover an endpoint: {{}}\", error).into(),\n        \
+         )),\n    \
+         }}\n\
+         }}\n",
+        input = indent(&input, 1),
+        method = field_name(&endpoint_operation.name),
+        address = address,
+        minutes = minutes,
+    ))
+}
+
+#[test]
+fn generates_clients() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let def = crate::model::BotocoreModel::from_path("test-data/recursive.json")?;
+    let graph = ModelGraph::new(&def);
+    let client = generate_client(&graph, &DocLinks::new(), &OperationFeatures::default())?;
+    assert!(client.starts_with(
+        "/// A client for Recursive Test Service, which sends requests with a connector of type `C`.\n\
+         pub struct Client<C> {\n"
+    ));
+    assert!(client.contains(
+        r#"    /// Stores an item.
+    pub async fn put_item(
+        &self,
+        input: crate::model::PutItemInput,
+    ) -> Result<crate::model::PutItemOutput, crate::error::SdkError<crate::error::PutItemError>> {
+        let properties = crate::middleware::OperationProperties {
+            name: "PutItem",
+            success_status: 200,
+            errors_in_success: false,
+            bucket_addressed: false,
+            checksum: None,
+            compressed: false,
+            signing: Some(crate::middleware::SigningProperties {
+                signing_name: "recursive",
+                signed_body: true,
+                s3: false,
+                events: false,
+            }),
+        };
+        let operation = crate::middleware::Operation::new(properties, move |endpoint| {
+            crate::protocol::serialize_put_item(
+                http::Request::builder().method("POST"),
+                &endpoint,
+                &input,
+            )
+        });
+        let response = aws_sdk_rust_runtime::timeout::timeout(
+            self.config.operation_timeout(),
+            crate::middleware::send_and_read(self.stack.clone(), operation),
+        )
+        .await
+        .map_err(crate::error::SdkError::Timeout)?
+        .map_err(crate::middleware::sdk_error)?;
+        if response.status().as_u16() == 200 {
+"#
+    ));
+    assert!(client.contains(
+        r#"            let mut output = crate::protocol::deserialize_put_item(&response)
+                .map_err(crate::error::SdkError::Response)?;
+            output._request_id = crate::error::request_id(response.headers());
+            Ok(output)
+"#
+    ));
+    assert!(client.contains("    pub fn with_layer<L>(&self, layer: L) -> Self\n"));
+    assert!(client.contains(
+        "#[cfg(feature = \"hyper\")]\nimpl Client<crate::connector::HyperConnector> {\n"
+    ));
+    assert!(client.contains(
+        "        Client::from_parts(config, crate::middleware::HttpService::new(dispatch), retries)\n"
+    ));
+
+    let def = crate::model::BotocoreModel::from_path("test-data/lambda/2015-03-31/service-2.json")?;
+    let graph = ModelGraph::new(&def);
+    let client = generate_client(&graph, &DocLinks::new(), &OperationFeatures::default())?;
+    assert!(client.contains("        success_status: 204,\n"));
+    assert!(client.contains("        if response.status().as_u16() == 204 {\n"));
+    assert!(!client.contains("presigned_"));
+
+    let features = OperationFeatures::new(&graph, &super::features::OperationGroups::ByPrefix)?;
+    let client = generate_client(&graph, &DocLinks::new(), &features)?;
+    assert!(client.contains("    #[cfg(feature = \"get\")]\n    pub async fn get_function(\n"));
+    assert!(!client.contains("paginate_"));
+
+    let mut def =
+        crate::model::BotocoreModel::from_path("test-data/lambda/2015-03-31/service-2.json")?;
+    def.load_paginators("test-data/lambda/2015-03-31/paginators-1.json")?;
+    let graph = ModelGraph::new(&def);
+    let client = generate_client(&graph, &DocLinks::new(), &features)?;
+    assert!(
+        client.contains("    #[cfg(feature = \"list\")]\n    pub fn paginate_list_functions(\n")
+    );
+    assert!(!client.contains("wait_until_"));
+
+    def.load_waiters("test-data/lambda/2015-03-31/waiters-2.json")?;
+    let graph = ModelGraph::new(&def);
+    let client = generate_client(&graph, &DocLinks::new(), &features)?;
+    assert!(client
+        .contains("    #[cfg(feature = \"get\")]\n    pub async fn wait_until_function_active(\n"));
+    assert!(
+        client.find("wait_until_function_active(") < client.find("wait_until_function_exists(")
+    );
+
+    let def = crate::model::BotocoreModel::from_path("test-data/queues.json")?;
+    let graph = ModelGraph::new(&def);
+    let client = generate_client(&graph, &DocLinks::new(), &OperationFeatures::default())?;
+    assert!(client.contains("    pub async fn presigned_list_queues(\n"));
+    assert!(client.contains(
+        "        let mut input = input;\n        \
+         input.message_deduplication_id.get_or_insert_with(aws_sdk_rust_runtime::uuid::new_v4);\n        \
+         let properties = crate::middleware::OperationProperties {\n"
+    ));
+    // Only `SendMessage` has one, which its presigned requests get too.
+    assert_eq!(client.matches("let mut input = input;\n").count(), 2);
+    // Only `SendMessage`'s bodies are compressed, too.
+    assert!(client.contains(
+        "            name: \"SendMessage\",\n            \
+         success_status: 200,\n            \
+         errors_in_success: false,\n            \
+         bucket_addressed: false,\n            \
+         checksum: None,\n            \
+         compressed: true,\n"
+    ));
+    assert_eq!(client.matches("compressed: true,\n").count(), 1);
+
+    let def = crate::model::BotocoreModel::from_path("test-data/transcripts.json")?;
+    let graph = ModelGraph::new(&def);
+    let client = generate_client(&graph, &DocLinks::new(), &OperationFeatures::default())?;
+    assert!(client.contains("                events: true,\n"));
+
+    let def = crate::model::BotocoreModel::from_path("test-data/tables.json")?;
+    let graph = ModelGraph::new(&def);
+    let client = generate_client(&graph, &DocLinks::new(), &OperationFeatures::default())?;
+    assert!(client.contains("    async fn discovered_endpoint<E>(\n"));
+    assert!(client.contains(
+        "        let mut identifiers = std::collections::BTreeMap::new();\n        \
+         identifiers.insert(\"TableName\".to_string(), input.table_name.clone());\n        \
+         let discovered = self\n            \
+         .discovered_endpoint(\"GetItem\", identifiers, false)\n            \
+         .await?;\n"
+    ));
+    assert!(client.contains(".discovered_endpoint(\"ListTables\", Default::default(), true)"));
+    assert!(!client.contains(".discovered_endpoint(\"DescribeEndpoints\""));
+    // A cache period too long to count in seconds is cached for as long as possible.
+    assert!(client.contains("from_secs((minutes.max(0) as u64).saturating_mul(60));"));
+    assert!(client.contains("            operation = operation.with_endpoint(endpoint.clone());\n"));
+
+    let def = crate::model::BotocoreModel::from_path("test-data/buckets.json")?;
+    let graph = ModelGraph::new(&def);
+    let client = generate_client(&graph, &DocLinks::new(), &OperationFeatures::default())?;
+    assert!(client.contains(
+        "                signing_name: \"buckets\",\n                signed_body: true,\n                s3: true,\n"
+    ));
+    assert!(client.contains(
+        "                signing_name: \"buckets\",\n                signed_body: false,\n                s3: true,\n"
+    ));
+    assert!(client.contains(
+        "    pub async fn presigned_put_bucket_tagging(\n        \
+         &self,\n        \
+         input: crate::model::PutBucketTaggingRequest,\n        \
+         expires_in: std::time::Duration,\n    \
+         ) -> Result<\n        \
+         http::Request<aws_sdk_rust_runtime::body::Body>,\n"
+    ));
+    assert!(client.contains(
+        "        crate::signing::presign_v4(\n            \
+         request,\n            \
+         &self.config,\n            \
+         \"buckets\",\n            \
+         expires_in,\n            \
+         true,\n        \
+         )\n        \
+         .await\n"
+    ));
+    assert!(client.contains(
+        "        .map_err(crate::error::SdkError::Construction)?;\n        \
+         let request = crate::endpoint::address_bucket(request, &self.config)\n            \
+         .map_err(crate::error::SdkError::Construction)?;\n"
+    ));
+    // Flexible checksums, picked by the input, the config, or because they're required.
+    assert!(client.contains(
+        "        let checksum = aws_sdk_rust_runtime::checksum::request_algorithm(\n            \
+         input.checksum_algorithm.as_ref().map(|value| value.as_str()),\n            \
+         self.config.checksum_algorithm(),\n            \
+         false,\n        \
+         )\n"
+    ));
+    assert!(client.contains(
+        "            None,\n            self.config.checksum_algorithm(),\n            true,\n"
+    ));
+    assert_eq!(client.matches("            checksum,\n").count(), 2);
+    assert!(client.contains(
+        "        let validate_checksum = input.checksum_mode.as_ref().map(|value| value.as_str()) == Some(\"ENABLED\")\n            \
+         || self.config.response_checksum_validation();\n"
+    ));
+    assert!(client.contains(
+        "                    &[\n                        \
+         aws_sdk_rust_runtime::checksum::ChecksumAlgorithm::Crc32c,\n"
+    ));
+    // Objects are returned while their bodies are still coming in, and checked as they do.
+    assert!(client.contains(
+        "let (parts, stream) = response.into_parts();\n            \
+         let response = http::Response::from_parts(parts, Vec::new());\n            \
+         let stream = if validate_checksum {\n                \
+         aws_sdk_rust_runtime::checksum::validate_body(\n"
+    ));
+    assert!(client.contains("crate::protocol::deserialize_get_object(&response, stream)\n"));
+    assert!(client.contains(
+        "            let response = aws_sdk_rust_runtime::body::read_response(response)\n"
+    ));
+    // S3's quirks, from its customizations.
+    assert_eq!(client.matches("errors_in_success: true,").count(), 1);
+    assert!(client.contains(
+        "        if response.status().as_u16() == 200\n            \
+         && !aws_sdk_rust_runtime::xml::is_error(response.body()) {\n"
+    ));
+    assert!(client.contains(
+        "        let mut input = input;\n        \
+         let decode_keys = input.encoding_type.is_none();\n"
+    ));
+    assert!(client.contains(
+        "            output.encoding_type = None;\n            \
+         }\n            \
+         Ok(output)\n"
+    ));
+    // `ChangeTags`' bucket isn't at the start of its path.
+    assert_eq!(client.matches("bucket_addressed: true,").count(), 9);
+    assert_eq!(
+        client.matches("crate::endpoint::address_bucket(").count(),
+        9
+    );
+
+    // Only S3's requests address buckets in their host, however they're signed.
+    let def = crate::model::BotocoreModel::from_path("test-data/selects.json")?;
+    let graph = ModelGraph::new(&def);
+    let selects = generate_client(&graph, &DocLinks::new(), &OperationFeatures::default())?;
+    assert!(selects.contains("s3: true,"));
+    assert!(!selects.contains("bucket_addressed: true,"));
+    // Event streams are decoded as their responses' bodies come in.
+    assert!(selects.contains("crate::middleware::send(self.stack.clone(), operation)"));
+
+    let mut def = crate::model::BotocoreModel::from_path("test-data/buckets.json")?;
+    def.metadata.signing_name = Some("objects".to_string());
+    let graph = ModelGraph::new(&def);
+    let client = generate_client(&graph, &DocLinks::new(), &OperationFeatures::default())?;
+    assert!(client.contains("            \"objects\",\n"));
+
+    def.metadata.signature_version = crate::model::Signature::None;
+    let graph = ModelGraph::new(&def);
+    let client = generate_client(&graph, &DocLinks::new(), &OperationFeatures::default())?;
+    assert!(!client.contains("presign_v4"));
+    assert!(client.contains("        signing: None,\n"));
+
+    Ok(())
+}
@@ -0,0 +1,279 @@
+use super::structs::struct_fields;
+use crate::{
+    graph::ModelGraph,
+    model::{Metadata, Operation, Shape, ShapeId},
+    naming::type_name,
+};
+
+/// What's generated differently for services that behave unlike the rest, looked up by their
+/// `service_id`, so protocol code doesn't have to know which services those are.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ServiceCustomizations {
+    // Requests whose path starts with their bucket move it into their host, where its name can
+    // be in one, unless the config has them path-style.
+    pub bucket_addressing: bool,
+    // The crate gets a `customizations` module with an `upload` helper, which uploads large
+    // objects in parts, if the model has the operations it's built on.
+    pub upload: bool,
+    // The crate gets a `customizations` module converting items to and from types that
+    // implement serde's traits, behind a `serde` feature, if the model has the `AttributeValue`
+    // they're built on.
+    pub items: bool,
+    // Operations whose responses are errors, even with the success status, if their body is an
+    // `Error` element, as S3's are when they fail after they've started sending them.
+    pub errors_in_success: &'static [&'static str],
+    // Headers whose values are left unset when they can't be parsed, rather than failing the
+    // response, like S3's `Expires`, which is whatever an object was stored with.
+    pub lenient_headers: &'static [&'static str],
+    // Operations whose response's root element is their output's only member, rather than
+    // holding it, like S3's `GetBucketLocation`'s `LocationConstraint`.
+    pub bare_outputs: &'static [&'static str],
+    // Operations whose keys are listed URL-encoded, so XML can hold them whatever characters
+    // they have, unless the caller asks for an encoding, with the paths of the output's
+    // members that are decoded.
+    pub encoded_keys: &'static [(&'static str, &'static [&'static str])],
+}
+
+// By `service_id`.
+const CUSTOMIZATIONS: &[(&str, ServiceCustomizations)] = &[
+    (
+        "DynamoDB",
+        ServiceCustomizations {
+            bucket_addressing: false,
+            upload: false,
+            items: true,
+            errors_in_success: &[],
+            lenient_headers: &[],
+            bare_outputs: &[],
+            encoded_keys: &[],
+        },
+    ),
+    (
+        "S3",
+        ServiceCustomizations {
+            bucket_addressing: true,
+            upload: true,
+            items: false,
+            errors_in_success: &["CompleteMultipartUpload", "CopyObject", "UploadPartCopy"],
+            lenient_headers: &["Expires"],
+            bare_outputs: &["GetBucketLocation"],
+            encoded_keys: &[
+                (
+                    "ListMultipartUploads",
+                    &[
+                        "Uploads[].Key",
+                        "CommonPrefixes[].Prefix",
+                        "Delimiter",
+                        "KeyMarker",
+                        "NextKeyMarker",
+                        "Prefix",
+                    ],
+                ),
+                (
+                    "ListObjectVersions",
+                    &[
+                        "Versions[].Key",
+                        "DeleteMarkers[].Key",
+                        "CommonPrefixes[].Prefix",
+                        "Delimiter",
+                        "KeyMarker",
+                        "NextKeyMarker",
+                        "Prefix",
+                    ],
+                ),
+                (
+                    "ListObjects",
+                    &[
+                        "Contents[].Key",
+                        "CommonPrefixes[].Prefix",
+                        "Delimiter",
+                        "Marker",
+                        "NextMarker",
+                        "Prefix",
+                    ],
+                ),
+                (
+                    "ListObjectsV2",
+                    &[
+                        "Contents[].Key",
+                        "CommonPrefixes[].Prefix",
+                        "Delimiter",
+                        "Prefix",
+                        "StartAfter",
+                    ],
+                ),
+            ],
+        },
+    ),
+];
+
+impl ServiceCustomizations {
+    pub fn for_service(metadata: &Metadata) -> Self {
+        CUSTOMIZATIONS
+            .iter()
+            .find(|(service_id, _)| *service_id == metadata.service_id)
+            .map(|(_, customizations)| *customizations)
+            .unwrap_or_default()
+    }
+
+    pub fn has_errors_in_success(&self, operation: &str) -> bool {
+        self.errors_in_success.contains(&operation)
+    }
+
+    pub fn is_lenient_header(&self, header: &str) -> bool {
+        self.lenient_headers
+            .iter()
+            .any(|lenient| lenient.eq_ignore_ascii_case(header))
+    }
+
+    pub fn has_bare_output(&self, operation: &str) -> bool {
+        self.bare_outputs.contains(&operation)
+    }
+}
+
+/// For operations whose keys are listed URL-encoded: code asking for them that way if the
+/// caller didn't ask for an encoding, setting `decode_keys` if it didn't, and code decoding the
+/// keys in `output`, once the response says they're encoded, that fails with an
+/// `SdkError::Response` if they can't be. Members of the paths the output doesn't have are
+/// left out. `None` for other operations, or if the input has no `EncodingType`.
+pub fn decode_keys(graph: &ModelGraph<'_>, op: &Operation) -> Option<(String, String)> {
+    let model = graph.model();
+    let (_, paths) = ServiceCustomizations::for_service(&model.metadata)
+        .encoded_keys
+        .iter()
+        .find(|(operation, _)| *operation == op.name)?;
+    let output = op.output.as_ref()?.shape;
+    let (fields, _) = struct_fields(graph, op.input.shape).ok()?;
+    let encoding = fields
+        .iter()
+        .find(|field| field.edge.name == "EncodingType" && field.is_optional())?;
+    let request = format!(
+        "let mut input = input;\n\
+         let decode_keys = input.{field}.is_none();\n\
+         if decode_keys {{\n    \
+         input.{field} = Some(crate::model::{ty}::from(\"url\"));\n\
+         }}\n",
+        field = encoding.name,
+        ty = type_name(encoding.edge.target.as_str())
+    );
+
+    let mut decode = String::new();
+    for path in *paths {
+        if let Some(values) = values_mut(graph, output, path) {
+            decode.push_str(&format!(
+                "for value in {} {{\n    \
+                 *value = aws_sdk_rust_runtime::encoding::url_decode(value)\n        \
+                 .map_err(crate::error::SdkError::Response)?;\n\
+                 }}\n",
+                values
+            ));
+        }
+    }
+    // Services that don't encode keys say so by leaving out the encoding type, if their output
+    // has one, which is cleared once they're decoded so they aren't decoded again.
+    let (fields, _) = struct_fields(graph, output).ok()?;
+    let condition = match fields
+        .iter()
+        .find(|field| field.edge.name == "EncodingType" && field.is_optional())
+    {
+        Some(field) => {
+            decode.push_str(&format!("output.{} = None;\n", field.name));
+            format!(
+                "decode_keys\n    && output.{}.as_ref().map(|encoding| encoding.as_str()) == Some(\"url\")",
+                field.name
+            )
+        }
+        None => "decode_keys".to_string(),
+    };
+    let response = format!("if {} {{\n{}}}\n", condition, super::indent(&decode, 1));
+    Some((request, response))
+}
+
+// Compiles a path of members, any of them lists whose items are projected
+// (`Contents[].Key`), into an iterator over mutable references to the strings it refers to in
+// `output`, an output of the shape `root`. `None` if one of them isn't a member, or what the
+// path refers to isn't a string.
+fn values_mut(graph: &ModelGraph<'_>, root: ShapeId, path: &str) -> Option<String> {
+    let mut code = "std::iter::once(&mut output)".to_string();
+    let mut id = root;
+    for segment in path.split('.') {
+        let (member, projected) = match segment.strip_suffix("[]") {
+            Some(member) => (member, true),
+            None => (segment, false),
+        };
+        let (fields, _) = struct_fields(graph, id).ok()?;
+        let field = fields.iter().find(|field| field.edge.name == member)?;
+        if field.is_optional() {
+            code.push_str(&format!(".filter_map(|v| v.{}.as_mut())", field.name));
+        } else {
+            code.push_str(&format!(".map(|v| &mut v.{})", field.name));
+        }
+        id = field.edge.target;
+        if projected {
+            id = match graph.shape(id) {
+                Some(Shape::List { member, .. }) => member.shape.shape,
+                _ => return None,
+            };
+            code.push_str(".flat_map(|v| v.iter_mut())");
+        }
+    }
+    match graph.shape(id) {
+        Some(Shape::String {
+            enum_values: None, ..
+        }) => Some(code),
+        _ => None,
+    }
+}
+
+#[test]
+fn customizes_services_by_id() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let def = crate::model::BotocoreModel::from_path("test-data/buckets.json")?;
+    let s3 = ServiceCustomizations::for_service(&def.metadata);
+    assert!(s3.bucket_addressing && s3.upload);
+    assert!(s3.has_errors_in_success("CompleteMultipartUpload"));
+    assert!(!s3.has_errors_in_success("PutObject"));
+    assert!(s3.is_lenient_header("expires"));
+    assert!(s3.has_bare_output("GetBucketLocation"));
+    assert!(!s3.items);
+    let def = crate::model::BotocoreModel::from_path("test-data/tables.json")?;
+    let dynamodb = ServiceCustomizations::for_service(&def.metadata);
+    assert!(dynamodb.items && !dynamodb.bucket_addressing);
+
+    // Signing like S3 doesn't make a service S3.
+    let def = crate::model::BotocoreModel::from_path("test-data/selects.json")?;
+    assert_eq!(
+        ServiceCustomizations::for_service(&def.metadata),
+        ServiceCustomizations::default()
+    );
+
+    Ok(())
+}
+
+#[test]
+fn decodes_listed_keys() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let def = crate::model::BotocoreModel::from_path("test-data/buckets.json")?;
+    let graph = ModelGraph::new(&def);
+    let (request, response) = decode_keys(&graph, &def.operations["ListObjects"])
+        .ok_or("`ListObjects`' keys are decoded")?;
+    assert_eq!(
+        request,
+        "let mut input = input;\n\
+         let decode_keys = input.encoding_type.is_none();\n\
+         if decode_keys {\n    \
+         input.encoding_type = Some(crate::model::EncodingType::from(\"url\"));\n\
+         }\n"
+    );
+    assert!(response.starts_with(
+        "if decode_keys\n    \
+         && output.encoding_type.as_ref().map(|encoding| encoding.as_str()) == Some(\"url\") {\n    \
+         for value in std::iter::once(&mut output).filter_map(|v| v.contents.as_mut())\
+         .flat_map(|v| v.iter_mut()).map(|v| &mut v.key) {\n"
+    ));
+    assert!(response.contains(
+        "    for value in std::iter::once(&mut output).filter_map(|v| v.prefix.as_mut()) {\n"
+    ));
+    assert!(response.ends_with("    output.encoding_type = None;\n}\n"));
+    assert_eq!(decode_keys(&graph, &def.operations["GetObject"]), None);
+
+    Ok(())
+}
@@ -0,0 +1,64 @@
+use crate::model::{Shape, ShapeMember};
+
+/// What sensitive values print as, matching the official SDKs.
+pub const REDACTED: &str = "*** Sensitive Data Redacted ***";
+
+/// Whether a member's value must be kept out of `Debug` output, because either the member
+/// or the shape it targets is marked sensitive.
+pub fn is_sensitive(member: &ShapeMember, target: &Shape) -> bool {
+    member.traits.sensitive || target.traits().sensitive
+}
+
+/// A `Debug` impl for a generated struct that prints `REDACTED` in place of its sensitive
+/// fields, for structs that can't simply derive `Debug`.
+///
+/// `fields` are the Rust field names, in declaration order, each with whether it's sensitive.
+pub fn debug_impl(type_name: &str, fields: &[(&str, bool)]) -> String {
+    let mut out = format!(
+        "impl std::fmt::Debug for {} {{\n    \
+         fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {{\n        \
+         f.debug_struct({:?})\n",
+        type_name, type_name
+    );
+    for (field, sensitive) in fields {
+        let label = field.trim_start_matches("r#");
+        if *sensitive {
+            out.push_str(&format!(
+                "            .field({:?}, &{:?})\n",
+                label, REDACTED
+            ));
+        } else {
+            out.push_str(&format!(
+                "            .field({:?}, &self.{})\n",
+                label, field
+            ));
+        }
+    }
+    out.push_str("            .finish()\n    }\n}\n");
+    out
+}
+
+#[test]
+fn redacts_sensitive_fields() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let def = crate::model::BotocoreModel::from_path("test-data/lambda/2015-03-31/service-2.json")?;
+    let member = match &def.shapes[&crate::model::ShapeId::from("Environment")] {
+        Shape::Structure { members, .. } => &members["Variables"],
+        other => panic!("expected a structure shape, got {:?}", other),
+    };
+    assert!(is_sensitive(member, &def.shapes[&member.shape.shape]));
+
+    assert_eq!(
+        debug_impl("Environment", &[("r#type", false), ("variables", true)]),
+        r#"impl std::fmt::Debug for Environment {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Environment")
+            .field("type", &self.r#type)
+            .field("variables", &"*** Sensitive Data Redacted ***")
+            .finish()
+    }
+}
+"#
+    );
+
+    Ok(())
+}
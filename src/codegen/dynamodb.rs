@@ -0,0 +1,745 @@
+use super::{structs::field_type, unions::variant_names};
+use crate::{
+    graph::ModelGraph,
+    model::{Shape, ShapeId},
+};
+
+// The variants `to_item` and `from_item` convert values to and from, with what they hold.
+const ATTRIBUTE_VALUE_VARIANTS: &[(&str, &str)] = &[
+    ("B", "Vec<u8>"),
+    ("Bool", "bool"),
+    ("Bs", "Vec<Vec<u8>>"),
+    ("L", "Vec<AttributeValue>"),
+    ("M", "std::collections::HashMap<String, AttributeValue>"),
+    ("N", "String"),
+    ("Ns", "Vec<String>"),
+    ("Null", "bool"),
+    ("S", "String"),
+    ("Ss", "Vec<String>"),
+];
+
+/// Whether a model has DynamoDB's `AttributeValue` union, with the variants its serde
+/// conversions are built on, so a crate whose customizations have them can get its
+/// `customizations` module.
+pub fn has_attribute_values(graph: &ModelGraph<'_>) -> bool {
+    let id = ShapeId::from("AttributeValue");
+    match graph.shape(id) {
+        Some(Shape::Union { .. }) => {}
+        _ => return false,
+    }
+    let variants = match variant_names(graph, id) {
+        Ok(variants) => variants,
+        Err(_) => return false,
+    };
+    let mut types = graph
+        .neighbors(id)
+        .iter()
+        .filter_map(|edge| {
+            let target = graph.shape(edge.target)?;
+            Some((
+                variants[edge.name].as_str(),
+                field_type(graph, edge, target, true),
+            ))
+        })
+        .collect::<Vec<_>>();
+    types.sort();
+    types
+        .iter()
+        .map(|(variant, ty)| (*variant, ty.as_str()))
+        .eq(ATTRIBUTE_VALUE_VARIANTS.iter().copied())
+}
+
+/// DynamoDB's conversions between items and types that implement serde's traits, emitted into
+/// its crate's `customizations` module, behind its `serde` feature.
+pub const DYNAMODB_ITEMS: &str = r#"use crate::model::AttributeValue;
+use serde::{
+    de::{self, DeserializeOwned, IntoDeserializer},
+    ser, Serialize,
+};
+use std::{collections::HashMap, error::Error, fmt};
+
+/// An item, as operations like `GetItem` and `PutItem` have them: its attributes, by name.
+pub type Item = HashMap<String, AttributeValue>;
+
+/// Converts `value` to an item, e.g. for a `PutItem`, with an attribute for each of its fields,
+/// or entries, since an item's a map.
+///
+/// Strings are `S`, numbers `N`, booleans `BOOL`, bytes `B`, sequences `L`, and maps and
+/// structs `M`. `None`, and other values without any data, are `NULL`. Enum variants are
+/// their names, or maps from their names to their data, if they have any.
+pub fn to_item<T: Serialize + ?Sized>(value: &T) -> Result<Item, ItemError> {
+    match to_attribute_value(value)? {
+        AttributeValue::M(item) => Ok(item),
+        _ => Err(ItemError::new("only maps and structs can be items")),
+    }
+}
+
+/// Converts an item, e.g. from a `GetItem`, back to the type it was converted from by
+/// `to_item`. Sets are read as sequences, and binary values as bytes, or sequences of them.
+pub fn from_item<T: DeserializeOwned>(item: Item) -> Result<T, ItemError> {
+    from_attribute_value(AttributeValue::M(item))
+}
+
+/// Converts `value` to an attribute value, as `to_item` converts its fields.
+pub fn to_attribute_value<T: Serialize + ?Sized>(value: &T) -> Result<AttributeValue, ItemError> {
+    value.serialize(Serializer)
+}
+
+/// Converts an attribute value back to the type it was converted from by `to_attribute_value`.
+pub fn from_attribute_value<T: DeserializeOwned>(value: AttributeValue) -> Result<T, ItemError> {
+    T::deserialize(Deserializer(value))
+}
+
+/// Why a value couldn't be converted to or from an item.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ItemError {
+    message: String,
+}
+
+impl ItemError {
+    fn new(message: impl Into<String>) -> Self {
+        ItemError {
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for ItemError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl Error for ItemError {}
+
+impl ser::Error for ItemError {
+    fn custom<T: fmt::Display>(message: T) -> Self {
+        ItemError::new(message.to_string())
+    }
+}
+
+impl de::Error for ItemError {
+    fn custom<T: fmt::Display>(message: T) -> Self {
+        ItemError::new(message.to_string())
+    }
+}
+
+// Wraps the data of an enum's variant in a map from its name, as serde's data formats do by
+// default, if it's a variant's.
+fn tagged(variant: Option<&'static str>, value: AttributeValue) -> AttributeValue {
+    match variant {
+        Some(variant) => {
+            let mut item = HashMap::new();
+            item.insert(variant.to_string(), value);
+            AttributeValue::M(item)
+        }
+        None => value,
+    }
+}
+
+fn number<T: fmt::Display>(value: T) -> AttributeValue {
+    AttributeValue::N(value.to_string())
+}
+
+struct Serializer;
+
+impl ser::Serializer for Serializer {
+    type Ok = AttributeValue;
+    type Error = ItemError;
+    type SerializeSeq = SerializeList;
+    type SerializeTuple = SerializeList;
+    type SerializeTupleStruct = SerializeList;
+    type SerializeTupleVariant = SerializeList;
+    type SerializeMap = SerializeMap;
+    type SerializeStruct = SerializeMap;
+    type SerializeStructVariant = SerializeMap;
+
+    fn serialize_bool(self, value: bool) -> Result<AttributeValue, ItemError> {
+        Ok(AttributeValue::Bool(value))
+    }
+
+    fn serialize_i8(self, value: i8) -> Result<AttributeValue, ItemError> {
+        Ok(number(value))
+    }
+
+    fn serialize_i16(self, value: i16) -> Result<AttributeValue, ItemError> {
+        Ok(number(value))
+    }
+
+    fn serialize_i32(self, value: i32) -> Result<AttributeValue, ItemError> {
+        Ok(number(value))
+    }
+
+    fn serialize_i64(self, value: i64) -> Result<AttributeValue, ItemError> {
+        Ok(number(value))
+    }
+
+    fn serialize_i128(self, value: i128) -> Result<AttributeValue, ItemError> {
+        Ok(number(value))
+    }
+
+    fn serialize_u8(self, value: u8) -> Result<AttributeValue, ItemError> {
+        Ok(number(value))
+    }
+
+    fn serialize_u16(self, value: u16) -> Result<AttributeValue, ItemError> {
+        Ok(number(value))
+    }
+
+    fn serialize_u32(self, value: u32) -> Result<AttributeValue, ItemError> {
+        Ok(number(value))
+    }
+
+    fn serialize_u64(self, value: u64) -> Result<AttributeValue, ItemError> {
+        Ok(number(value))
+    }
+
+    fn serialize_u128(self, value: u128) -> Result<AttributeValue, ItemError> {
+        Ok(number(value))
+    }
+
+    // Written as themselves rather than as `f64`s, which would add digits they don't have.
+    fn serialize_f32(self, value: f32) -> Result<AttributeValue, ItemError> {
+        if value.is_finite() {
+            Ok(number(value))
+        } else {
+            self.serialize_f64(f64::from(value))
+        }
+    }
+
+    fn serialize_f64(self, value: f64) -> Result<AttributeValue, ItemError> {
+        // DynamoDB's numbers are decimals, which can't be infinite, or not a number.
+        if value.is_finite() {
+            Ok(number(value))
+        } else {
+            Err(ItemError::new(format!("{} isn't a DynamoDB number", value)))
+        }
+    }
+
+    fn serialize_char(self, value: char) -> Result<AttributeValue, ItemError> {
+        Ok(AttributeValue::S(value.to_string()))
+    }
+
+    fn serialize_str(self, value: &str) -> Result<AttributeValue, ItemError> {
+        Ok(AttributeValue::S(value.to_string()))
+    }
+
+    fn serialize_bytes(self, value: &[u8]) -> Result<AttributeValue, ItemError> {
+        Ok(AttributeValue::B(value.to_vec()))
+    }
+
+    fn serialize_none(self) -> Result<AttributeValue, ItemError> {
+        Ok(AttributeValue::Null(true))
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<AttributeValue, ItemError> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<AttributeValue, ItemError> {
+        Ok(AttributeValue::Null(true))
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<AttributeValue, ItemError> {
+        Ok(AttributeValue::Null(true))
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+    ) -> Result<AttributeValue, ItemError> {
+        Ok(AttributeValue::S(variant.to_string()))
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<AttributeValue, ItemError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<AttributeValue, ItemError> {
+        Ok(tagged(Some(variant), value.serialize(self)?))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<SerializeList, ItemError> {
+        Ok(SerializeList {
+            items: Vec::with_capacity(len.unwrap_or_default()),
+            variant: None,
+        })
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<SerializeList, ItemError> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<SerializeList, ItemError> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<SerializeList, ItemError> {
+        Ok(SerializeList {
+            items: Vec::with_capacity(len),
+            variant: Some(variant),
+        })
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<SerializeMap, ItemError> {
+        Ok(SerializeMap {
+            item: HashMap::new(),
+            key: None,
+            variant: None,
+        })
+    }
+
+    fn serialize_struct(self, _name: &'static str, len: usize) -> Result<SerializeMap, ItemError> {
+        self.serialize_map(Some(len))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<SerializeMap, ItemError> {
+        Ok(SerializeMap {
+            item: HashMap::new(),
+            key: None,
+            variant: Some(variant),
+        })
+    }
+}
+
+struct SerializeList {
+    items: Vec<AttributeValue>,
+    variant: Option<&'static str>,
+}
+
+impl ser::SerializeSeq for SerializeList {
+    type Ok = AttributeValue;
+    type Error = ItemError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), ItemError> {
+        self.items.push(to_attribute_value(value)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<AttributeValue, ItemError> {
+        Ok(tagged(self.variant, AttributeValue::L(self.items)))
+    }
+}
+
+impl ser::SerializeTuple for SerializeList {
+    type Ok = AttributeValue;
+    type Error = ItemError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), ItemError> {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<AttributeValue, ItemError> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+impl ser::SerializeTupleStruct for SerializeList {
+    type Ok = AttributeValue;
+    type Error = ItemError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), ItemError> {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<AttributeValue, ItemError> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+impl ser::SerializeTupleVariant for SerializeList {
+    type Ok = AttributeValue;
+    type Error = ItemError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), ItemError> {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<AttributeValue, ItemError> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+struct SerializeMap {
+    item: Item,
+    key: Option<String>,
+    variant: Option<&'static str>,
+}
+
+impl ser::SerializeMap for SerializeMap {
+    type Ok = AttributeValue;
+    type Error = ItemError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), ItemError> {
+        // Attribute names are strings, so numbers are written out, as JSON's keys are.
+        match to_attribute_value(key)? {
+            AttributeValue::S(key) | AttributeValue::N(key) => {
+                self.key = Some(key);
+                Ok(())
+            }
+            _ => Err(ItemError::new("map keys have to be strings or numbers")),
+        }
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), ItemError> {
+        let key = self
+            .key
+            .take()
+            .ok_or_else(|| ItemError::new("a map value was serialized without a key"))?;
+        self.item.insert(key, to_attribute_value(value)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<AttributeValue, ItemError> {
+        Ok(tagged(self.variant, AttributeValue::M(self.item)))
+    }
+}
+
+impl ser::SerializeStruct for SerializeMap {
+    type Ok = AttributeValue;
+    type Error = ItemError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), ItemError> {
+        self.item.insert(key.to_string(), to_attribute_value(value)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<AttributeValue, ItemError> {
+        ser::SerializeMap::end(self)
+    }
+}
+
+impl ser::SerializeStructVariant for SerializeMap {
+    type Ok = AttributeValue;
+    type Error = ItemError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), ItemError> {
+        ser::SerializeStruct::serialize_field(self, key, value)
+    }
+
+    fn end(self) -> Result<AttributeValue, ItemError> {
+        ser::SerializeMap::end(self)
+    }
+}
+
+struct Deserializer(AttributeValue);
+
+impl<'de> de::Deserializer<'de> for Deserializer {
+    type Error = ItemError;
+
+    fn deserialize_any<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, ItemError> {
+        match self.0 {
+            AttributeValue::S(value) => visitor.visit_string(value),
+            // Numbers are read as the first of these they fit, which visitors for other
+            // number types convert from, if the number fits them too.
+            AttributeValue::N(value) => {
+                if let Ok(number) = value.parse::<u64>() {
+                    visitor.visit_u64(number)
+                } else if let Ok(number) = value.parse::<i64>() {
+                    visitor.visit_i64(number)
+                } else if let Ok(number) = value.parse::<f64>() {
+                    visitor.visit_f64(number)
+                } else {
+                    Err(ItemError::new(format!("`{}` isn't a number", value)))
+                }
+            }
+            AttributeValue::B(value) => visitor.visit_byte_buf(value),
+            AttributeValue::Bool(value) => visitor.visit_bool(value),
+            AttributeValue::Null(_) => visitor.visit_unit(),
+            AttributeValue::L(values) => visitor.visit_seq(SeqDeserializer(values.into_iter())),
+            AttributeValue::Ss(values) => visitor.visit_seq(SeqDeserializer(
+                values
+                    .into_iter()
+                    .map(AttributeValue::S)
+                    .collect::<Vec<_>>()
+                    .into_iter(),
+            )),
+            AttributeValue::Ns(values) => visitor.visit_seq(SeqDeserializer(
+                values
+                    .into_iter()
+                    .map(AttributeValue::N)
+                    .collect::<Vec<_>>()
+                    .into_iter(),
+            )),
+            AttributeValue::Bs(values) => visitor.visit_seq(SeqDeserializer(
+                values
+                    .into_iter()
+                    .map(AttributeValue::B)
+                    .collect::<Vec<_>>()
+                    .into_iter(),
+            )),
+            AttributeValue::M(item) => visitor.visit_map(MapDeserializer {
+                entries: item.into_iter(),
+                value: None,
+            }),
+            AttributeValue::Unknown => Err(ItemError::new(
+                "the attribute value is of a type this crate doesn't know",
+            )),
+        }
+    }
+
+    fn deserialize_option<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, ItemError> {
+        match self.0 {
+            AttributeValue::Null(_) => visitor.visit_none(),
+            value => visitor.visit_some(Deserializer(value)),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: de::Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, ItemError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    // Binary values are read as sequences of bytes too, e.g. for a `Vec<u8>`.
+    fn deserialize_seq<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, ItemError> {
+        match self.0 {
+            AttributeValue::B(value) => visitor.visit_seq(SeqDeserializer(
+                value
+                    .into_iter()
+                    .map(number)
+                    .collect::<Vec<_>>()
+                    .into_iter(),
+            )),
+            value => Deserializer(value).deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_enum<V: de::Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, ItemError> {
+        match self.0 {
+            AttributeValue::S(variant) => visitor.visit_enum(variant.into_deserializer()),
+            AttributeValue::M(item) if item.len() == 1 => {
+                let (variant, value) = item.into_iter().next().expect("the item has an entry");
+                visitor.visit_enum(EnumDeserializer { variant, value })
+            }
+            _ => Err(ItemError::new(
+                "enums are strings, or maps with a single entry",
+            )),
+        }
+    }
+
+    fn deserialize_ignored_any<V: de::Visitor<'de>>(
+        self,
+        visitor: V,
+    ) -> Result<V::Value, ItemError> {
+        visitor.visit_unit()
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf
+        unit unit_struct tuple tuple_struct map struct identifier
+    }
+}
+
+struct SeqDeserializer(std::vec::IntoIter<AttributeValue>);
+
+impl<'de> de::SeqAccess<'de> for SeqDeserializer {
+    type Error = ItemError;
+
+    fn next_element_seed<T: de::DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, ItemError> {
+        self.0
+            .next()
+            .map(|value| seed.deserialize(Deserializer(value)))
+            .transpose()
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.0.len())
+    }
+}
+
+struct MapDeserializer {
+    entries: std::collections::hash_map::IntoIter<String, AttributeValue>,
+    value: Option<AttributeValue>,
+}
+
+impl<'de> de::MapAccess<'de> for MapDeserializer {
+    type Error = ItemError;
+
+    fn next_key_seed<K: de::DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, ItemError> {
+        match self.entries.next() {
+            Some((key, value)) => {
+                self.value = Some(value);
+                seed.deserialize(KeyDeserializer(key)).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: de::DeserializeSeed<'de>>(
+        &mut self,
+        seed: V,
+    ) -> Result<V::Value, ItemError> {
+        let value = self
+            .value
+            .take()
+            .ok_or_else(|| ItemError::new("a map value was deserialized without a key"))?;
+        seed.deserialize(Deserializer(value))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.entries.len())
+    }
+}
+
+// Attribute names are strings, but maps with numbers for keys are written with them as
+// numbers, so they're read back as numbers too.
+struct KeyDeserializer(String);
+
+macro_rules! deserialize_number {
+    ($($method:ident)*) => {
+        $(
+            fn $method<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, ItemError> {
+                de::Deserializer::deserialize_any(Deserializer(AttributeValue::N(self.0)), visitor)
+            }
+        )*
+    };
+}
+
+impl<'de> de::Deserializer<'de> for KeyDeserializer {
+    type Error = ItemError;
+
+    fn deserialize_any<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, ItemError> {
+        visitor.visit_string(self.0)
+    }
+
+    fn deserialize_newtype_struct<V: de::Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, ItemError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: de::Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, ItemError> {
+        visitor.visit_enum(self.0.into_deserializer())
+    }
+
+    deserialize_number! {
+        deserialize_i8 deserialize_i16 deserialize_i32 deserialize_i64 deserialize_i128
+        deserialize_u8 deserialize_u16 deserialize_u32 deserialize_u64 deserialize_u128
+        deserialize_f32 deserialize_f64
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool char str string bytes byte_buf option unit unit_struct seq tuple tuple_struct map
+        struct identifier ignored_any
+    }
+}
+
+struct EnumDeserializer {
+    variant: String,
+    value: AttributeValue,
+}
+
+impl<'de> de::EnumAccess<'de> for EnumDeserializer {
+    type Error = ItemError;
+    type Variant = Deserializer;
+
+    fn variant_seed<V: de::DeserializeSeed<'de>>(
+        self,
+        seed: V,
+    ) -> Result<(V::Value, Deserializer), ItemError> {
+        let variant = seed.deserialize(self.variant.into_deserializer())?;
+        Ok((variant, Deserializer(self.value)))
+    }
+}
+
+impl<'de> de::VariantAccess<'de> for Deserializer {
+    type Error = ItemError;
+
+    fn unit_variant(self) -> Result<(), ItemError> {
+        de::Deserialize::deserialize(self)
+    }
+
+    fn newtype_variant_seed<T: de::DeserializeSeed<'de>>(
+        self,
+        seed: T,
+    ) -> Result<T::Value, ItemError> {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V: de::Visitor<'de>>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, ItemError> {
+        de::Deserializer::deserialize_seq(self, visitor)
+    }
+
+    fn struct_variant<V: de::Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, ItemError> {
+        de::Deserializer::deserialize_map(self, visitor)
+    }
+}
+"#;
+
+#[test]
+fn recognizes_attribute_values() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let def = crate::model::BotocoreModel::from_path("test-data/tables.json")?;
+    assert!(has_attribute_values(&ModelGraph::new(&def)));
+    // `recursive.json`'s is only part of DynamoDB's.
+    let def = crate::model::BotocoreModel::from_path("test-data/recursive.json")?;
+    assert!(!has_attribute_values(&ModelGraph::new(&def)));
+    let def = crate::model::BotocoreModel::from_path("test-data/buckets.json")?;
+    assert!(!has_attribute_values(&ModelGraph::new(&def)));
+
+    Ok(())
+}
@@ -0,0 +1,121 @@
+use crate::{
+    docs::{doc_comment, DocLinks},
+    model::Markdown,
+    Err,
+};
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::quote;
+
+/// An identifier, raw if it's spelled like one, e.g. `r#type`.
+pub fn ident(name: &str) -> Ident {
+    match name.strip_prefix("r#") {
+        Some(name) => Ident::new_raw(name, Span::call_site()),
+        None => Ident::new(name, Span::call_site()),
+    }
+}
+
+/// Parses generated source into tokens, so code built up as text can be combined with
+/// code built as tokens. Fails if the source doesn't tokenize, such as when its brackets
+/// don't balance.
+pub fn tokens(code: &str) -> Result<TokenStream, Err> {
+    code.parse::<TokenStream>()
+        .map_err(|error| format!("generated code doesn't tokenize: {}", error).into())
+}
+
+/// `#[doc]` attributes for documentation, one per line, rendered like `doc_comment`.
+pub fn doc_attributes(markdown: &Markdown, links: &DocLinks) -> TokenStream {
+    doc_lines(&doc_comment(markdown, links))
+}
+
+/// `#[doc]` attributes for lines of plain text, e.g. a summary written by the generator.
+pub fn doc_text(text: &str) -> TokenStream {
+    let comment = text
+        .lines()
+        .map(|line| format!("/// {}\n", line))
+        .collect::<String>();
+    doc_lines(&comment)
+}
+
+/// `#![doc]` attributes for lines of plain text, documenting the module or crate they're in.
+pub fn inner_doc_text(text: &str) -> TokenStream {
+    let lines = text.lines().map(|line| {
+        let line = format!(" {}", line);
+        quote!(#![doc = #line])
+    });
+    quote!(#(#lines)*)
+}
+
+fn doc_lines(comment: &str) -> TokenStream {
+    let lines = comment.lines().map(|line| {
+        let line = line.strip_prefix("///").unwrap_or(line);
+        let line = if line.trim().is_empty() { "" } else { line };
+        quote!(#[doc = #line])
+    });
+    quote!(#(#lines)*)
+}
+
+/// `#[derive]` for the named traits.
+pub fn derive(traits: &[&str]) -> TokenStream {
+    let traits = traits
+        .iter()
+        .map(|path| path.split("::").map(ident).collect::<Vec<_>>())
+        .map(|segments| quote!(#(#segments)::*));
+    quote!(#[derive(#(#traits),*)])
+}
+
+/// A public module holding `items`, with `docs` as its inner documentation.
+pub fn module(name: &str, docs: Option<&str>, items: TokenStream) -> TokenStream {
+    let name = ident(name);
+    let docs = docs.map(inner_doc_text);
+    quote! {
+        pub mod #name {
+            #docs
+            #items
+        }
+    }
+}
+
+/// Nests `items` in a module for each segment of `path`, outermost first, e.g. `a::b` for
+/// `pub mod a { pub mod b { ... } }`.
+pub fn nested_module(path: &str, items: TokenStream) -> TokenStream {
+    path.rsplit("::")
+        .fold(items, |items, name| module(name, None, items))
+}
+
+#[test]
+fn emits_tokens() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    assert_eq!(ident("r#type").to_string(), "r#type");
+    assert_eq!(ident("function_name").to_string(), "function_name");
+    assert!(tokens("pub struct Broken {").is_err());
+
+    let item = tokens("pub struct Function { pub r#type: String }")?;
+    let docs = doc_attributes(
+        &Markdown::from("<p>A function.</p><p>See <a>GetFunction</a>.</p>".to_string()),
+        &DocLinks::new(),
+    );
+    let derive = derive(&["Debug", "std::hash::Hash"]);
+    assert_eq!(
+        nested_module("model::lambda", quote!(#docs #derive #item)).to_string(),
+        quote! {
+            pub mod model {
+                pub mod lambda {
+                    #[doc = " A function."]
+                    #[doc = ""]
+                    #[doc = " See `GetFunction`."]
+                    #[derive(Debug, std::hash::Hash)]
+                    pub struct Function {
+                        pub r#type: String
+                    }
+                }
+            }
+        }
+        .to_string()
+    );
+
+    assert_eq!(
+        module("error", Some("Errors."), doc_text("Failed.")).to_string(),
+        quote!(pub mod error { #![doc = " Errors."] #[doc = " Failed."] }).to_string()
+    );
+
+    Ok(())
+}
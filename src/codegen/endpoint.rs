@@ -0,0 +1,177 @@
+use super::indent;
+use crate::{
+    endpoints::{EndpointProperties, Endpoints, Partition},
+    model::Metadata,
+    Err,
+};
+
+/// How S3 clients address buckets, emitted into each generated crate's `endpoint` module.
+pub const ADDRESS_BUCKET: &str = r#"
+/// Moves an S3 request's bucket from its path into its host, unless the config has it
+/// path-style or the bucket's name can't be in a host.
+pub(crate) fn address_bucket(
+    request: http::Request<aws_sdk_rust_runtime::body::Body>,
+    config: &crate::config::Config,
+) -> Result<http::Request<aws_sdk_rust_runtime::body::Body>, crate::error::BoxError> {
+    if config.path_style() {
+        return Ok(request);
+    }
+    match aws_sdk_rust_runtime::endpoint::virtual_hosted_uri(&request.uri().to_string()) {
+        Some(uri) => {
+            let (mut parts, body) = request.into_parts();
+            parts.uri = uri.parse()?;
+            Ok(http::Request::from_parts(parts, body))
+        }
+        None => Ok(request),
+    }
+}
+"#;
+
+/// Generates a service's `endpoint` module: the resolver its config finds the endpoint for a
+/// region with, holding the service's endpoints in each partition of `endpoints`, and
+/// `ADDRESS_BUCKET`. Without them, every region's endpoint follows the `aws` partition's usual
+/// pattern.
+pub fn generate_endpoint_resolver(
+    metadata: &Metadata,
+    endpoints: Option<&Endpoints>,
+) -> Result<String, Err> {
+    let mut partitions = String::new();
+    for partition in endpoints.iter().flat_map(|endpoints| &endpoints.partitions) {
+        partitions.push_str(&generate_partition(partition, &metadata.endpoint_prefix));
+    }
+    let signing_name = metadata
+        .signing_name
+        .as_deref()
+        .unwrap_or(&metadata.endpoint_prefix);
+    Ok(format!(
+        "#[allow(unused_imports)]\n\
+         use aws_sdk_rust_runtime::endpoint::{{EndpointProperties, EndpointResolver, Partition}};\n\n\
+         /// Resolves the service's endpoints for regions, from the partitions in\n\
+         /// `endpoints.json` as they were when the crate was generated.\n\
+         pub const RESOLVER: EndpointResolver = EndpointResolver::new(\n    \
+         {service:?},\n    \
+         {signing_name:?},\n    \
+         &[\n{partitions}    ],\n\
+         );\n\
+         {address_bucket}",
+        service = metadata.endpoint_prefix,
+        signing_name = signing_name,
+        partitions = indent(&partitions, 2),
+        address_bucket = ADDRESS_BUCKET,
+    ))
+}
+
+// A partition, with the service's defaults in it over the partition's own.
+fn generate_partition(partition: &Partition, service: &str) -> String {
+    let mut regions = partition.regions.keys().collect::<Vec<_>>();
+    regions.sort();
+    let service = partition.services.get(service);
+    let mut defaults = partition.defaults.clone();
+    if let Some(overrides) = service.and_then(|service| service.defaults.as_ref()) {
+        defaults = inherit(overrides, &defaults);
+    }
+
+    let mut endpoints = service
+        .map(|service| service.endpoints.iter().collect::<Vec<_>>())
+        .unwrap_or_default();
+    endpoints.sort_by_key(|(name, _)| name.as_str());
+    let endpoints = endpoints
+        .into_iter()
+        .map(|(name, properties)| {
+            format!(
+                "(\n    {:?},\n{}),\n",
+                name,
+                indent(&properties_literal(properties), 1)
+            )
+        })
+        .collect::<String>();
+    // Only services that aren't regionalized send every region's requests to their
+    // partition endpoint.
+    let partition_endpoint = service
+        .filter(|service| service.is_regionalized == Some(false))
+        .and_then(|service| service.partition_endpoint.as_deref());
+
+    format!(
+        "Partition {{\n    \
+         name: {name:?},\n    \
+         dns_suffix: {dns_suffix:?},\n    \
+         region_regex: {region_regex:?},\n    \
+         regions: &{regions:?},\n    \
+         defaults: {defaults}    \
+         endpoints: &[\n{endpoints}    ],\n    \
+         partition_endpoint: {partition_endpoint:?},\n\
+         }},\n",
+        name = partition.partition,
+        dns_suffix = partition.dns_suffix,
+        region_regex = partition.region_regex,
+        regions = regions,
+        defaults = indent(&properties_literal(&defaults), 1).trim_start(),
+        endpoints = indent(&endpoints, 2),
+        partition_endpoint = partition_endpoint,
+    )
+}
+
+// Properties with anything `properties` leaves unset taken from `defaults`.
+fn inherit(properties: &EndpointProperties, defaults: &EndpointProperties) -> EndpointProperties {
+    let scope = |properties: &EndpointProperties| properties.credential_scope.clone();
+    EndpointProperties {
+        hostname: properties
+            .hostname
+            .clone()
+            .or_else(|| defaults.hostname.clone()),
+        protocols: properties
+            .protocols
+            .clone()
+            .or_else(|| defaults.protocols.clone()),
+        credential_scope: match (scope(properties), scope(defaults)) {
+            (Some(mut scope), Some(defaults)) => {
+                scope.region = scope.region.or(defaults.region);
+                scope.service = scope.service.or(defaults.service);
+                Some(scope)
+            }
+            (scope, defaults) => scope.or(defaults),
+        },
+        signature_versions: properties
+            .signature_versions
+            .clone()
+            .or_else(|| defaults.signature_versions.clone()),
+    }
+}
+
+fn properties_literal(properties: &EndpointProperties) -> String {
+    let scope = properties.credential_scope.as_ref();
+    format!(
+        "EndpointProperties {{\n    \
+         hostname: {:?},\n    \
+         signing_region: {:?},\n    \
+         signing_name: {:?},\n\
+         }},\n",
+        properties.hostname,
+        scope.and_then(|scope| scope.region.as_deref()),
+        scope.and_then(|scope| scope.service.as_deref()),
+    )
+}
+
+#[test]
+fn generates_endpoint_resolvers() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let endpoints = Endpoints::from_path("test-data/endpoints.json")?;
+    let mut def = crate::model::BotocoreModel::from_path("test-data/buckets.json")?;
+    def.metadata.endpoint_prefix = "s3".to_string();
+    let code = generate_endpoint_resolver(&def.metadata, Some(&endpoints))?;
+    assert!(code.contains("EndpointResolver::new(\n    \"s3\",\n"));
+    assert!(code.contains("        name: \"aws-cn\",\n"));
+    assert!(code.contains("        regions: &[\"eu-west-1\", \"us-east-1\", \"us-west-2\"],\n"));
+    assert!(code.contains("hostname: Some(\"s3.amazonaws.com\"),\n"));
+    // S3 is regionalized, so its partition endpoint isn't used.
+    assert!(!code.contains("Some(\"aws-global\")"));
+
+    def.metadata.endpoint_prefix = "iam".to_string();
+    let code = generate_endpoint_resolver(&def.metadata, Some(&endpoints))?;
+    assert!(code.contains("partition_endpoint: Some(\"aws-global\"),\n"));
+    assert!(code.contains("signing_region: Some(\"us-east-1\"),\n"));
+
+    let code = generate_endpoint_resolver(&def.metadata, None)?;
+    assert!(code.contains("    &[\n    ],\n"));
+
+    Ok(())
+}
@@ -0,0 +1,166 @@
+use super::{
+    allow_deprecated, deprecated_traits, emit,
+    format::{format_tokens, Formatting},
+};
+use crate::{
+    graph::ModelGraph,
+    model::{Shape, ShapeId},
+    naming::{enum_variants, type_name},
+    Err,
+};
+use proc_macro2::TokenStream;
+use quote::quote;
+use std::collections::HashMap;
+
+/// Generates an enum for every string shape with a list of values, in name order.
+pub fn generate_enums(graph: &ModelGraph<'_>) -> Result<String, Err> {
+    let mut out = String::new();
+    for (id, shape) in graph.shapes() {
+        if let Shape::String {
+            enum_values: Some(_),
+            ..
+        } = shape
+        {
+            if !out.is_empty() {
+                out.push('\n');
+            }
+            out.push_str(&format_tokens(
+                generate_enum(graph, id, &HashMap::new())?,
+                Formatting::Strict,
+            )?);
+        }
+    }
+    Ok(out)
+}
+
+/// Generates the enum for a string shape with a list of values, with `overrides` naming the
+/// variants for particular values. String shapes aren't documented, so neither is the enum.
+///
+/// Services add values over time, so the enum ends in an `Unknown` variant holding any value
+/// it doesn't list, and converting from a string can't fail.
+pub fn generate_enum(
+    graph: &ModelGraph<'_>,
+    id: ShapeId,
+    overrides: &HashMap<String, String>,
+) -> Result<TokenStream, Err> {
+    let (values, traits) = match graph.shape(id) {
+        Some(Shape::String {
+            enum_values: Some(values),
+            traits,
+            ..
+        }) => (values, traits),
+        _ => return Err(format!("`{}` isn't an enum string shape", id).into()),
+    };
+    let variants = enum_variants(values.iter().map(String::as_str), overrides)?;
+    let variants = values
+        .iter()
+        .map(|value| emit::ident(&variants[value.as_str()]))
+        .collect::<Vec<_>>();
+    let name = emit::ident(&type_name(id.as_str()));
+    let derive = emit::derive(&[
+        "Debug",
+        "Clone",
+        "PartialEq",
+        "Eq",
+        "Hash",
+        "PartialOrd",
+        "Ord",
+    ]);
+    let deprecated = deprecated_traits(traits)
+        .as_deref()
+        .map(emit::tokens)
+        .transpose()?;
+    let allow = emit::tokens(allow_deprecated(traits.deprecated))?;
+
+    Ok(quote! {
+        #derive
+        #deprecated
+        pub enum #name {
+            #(#variants,)*
+            /// A value this enum doesn't list, such as one added since it was generated.
+            Unknown(String),
+        }
+
+        #allow
+        impl #name {
+            /// The value as the service sends and receives it.
+            pub fn as_str(&self) -> &str {
+                match self {
+                    #(#name::#variants => #values,)*
+                    #name::Unknown(value) => value.as_str(),
+                }
+            }
+        }
+
+        #allow
+        impl std::str::FromStr for #name {
+            type Err = std::convert::Infallible;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                Ok(#name::from(s))
+            }
+        }
+
+        #allow
+        impl From<&str> for #name {
+            fn from(s: &str) -> Self {
+                match s {
+                    #(#values => #name::#variants,)*
+                    other => #name::Unknown(other.to_string()),
+                }
+            }
+        }
+    })
+}
+
+#[test]
+fn generates_enums() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let def = crate::model::BotocoreModel::from_path("test-data/lambda/2015-03-31/service-2.json")?;
+    let graph = ModelGraph::new(&def);
+    assert_eq!(
+        format_tokens(
+            generate_enum(&graph, ShapeId::from("LogType"), &HashMap::new())?,
+            Formatting::Strict
+        )?,
+        r#"#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum LogType {
+    None,
+    Tail,
+    /// A value this enum doesn't list, such as one added since it was generated.
+    Unknown(String),
+}
+impl LogType {
+    /// The value as the service sends and receives it.
+    pub fn as_str(&self) -> &str {
+        match self {
+            LogType::None => "None",
+            LogType::Tail => "Tail",
+            LogType::Unknown(value) => value.as_str(),
+        }
+    }
+}
+impl std::str::FromStr for LogType {
+    type Err = std::convert::Infallible;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(LogType::from(s))
+    }
+}
+impl From<&str> for LogType {
+    fn from(s: &str) -> Self {
+        match s {
+            "None" => LogType::None,
+            "Tail" => LogType::Tail,
+            other => LogType::Unknown(other.to_string()),
+        }
+    }
+}
+"#
+    );
+
+    let all = generate_enums(&graph)?;
+    assert!(all.contains("    TrimHorizon,\n"));
+    assert!(all.contains("            \"RequestResponse\" => InvocationType::RequestResponse,\n"));
+    assert!(generate_enum(&graph, ShapeId::from("FunctionName"), &HashMap::new()).is_err());
+
+    Ok(())
+}
@@ -0,0 +1,345 @@
+use super::{features::OperationFeatures, indent, structs::struct_fields};
+use crate::{
+    docs::{doc_comment, DocLinks},
+    graph::ModelGraph,
+    model::{Markdown, Operation, Shape, ShapeId},
+    naming::type_name,
+    Err,
+};
+
+/// What every operation's error type is built on, emitted once into each generated crate's
+/// `error` module.
+pub const UNHANDLED_ERROR: &str = r#"/// What every error response says about itself, whichever error it is: its status, code,
+/// message, and the ID the service gave the request, and S3's extended one, as far as the
+/// response has them.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ErrorMetadata {
+    code: Option<String>,
+    message: Option<String>,
+    request_id: Option<String>,
+    extended_request_id: Option<String>,
+    status: Option<u16>,
+}
+
+impl ErrorMetadata {
+    pub fn new(code: Option<String>, message: Option<String>, request_id: Option<String>) -> Self {
+        ErrorMetadata {
+            code,
+            message,
+            request_id,
+            extended_request_id: None,
+            status: None,
+        }
+    }
+
+    pub fn with_extended_request_id(mut self, extended_request_id: Option<String>) -> Self {
+        self.extended_request_id = extended_request_id;
+        self
+    }
+
+    pub fn with_status(mut self, status: u16) -> Self {
+        self.status = Some(status);
+        self
+    }
+
+    pub fn code(&self) -> Option<&str> {
+        self.code.as_deref()
+    }
+
+    pub fn message(&self) -> Option<&str> {
+        self.message.as_deref()
+    }
+
+    pub fn request_id(&self) -> Option<&str> {
+        self.request_id.as_deref()
+    }
+
+    /// The ID S3 gave the host that handled the request.
+    pub fn extended_request_id(&self) -> Option<&str> {
+        self.extended_request_id.as_deref()
+    }
+
+    /// The response's HTTP status code.
+    pub fn status(&self) -> Option<u16> {
+        self.status
+    }
+}
+
+/// The ID the service gave a request, from its response's `x-amzn-RequestId` or
+/// `x-amz-request-id` header.
+pub fn request_id(headers: &http::HeaderMap) -> Option<String> {
+    ["x-amzn-RequestId", "x-amz-request-id"]
+        .iter()
+        .find_map(|name| headers.get(*name)?.to_str().ok())
+        .map(str::to_string)
+}
+
+/// The ID S3 gave the host that handled a request, from its response's `x-amz-id-2` header.
+pub fn extended_request_id(headers: &http::HeaderMap) -> Option<String> {
+    headers.get("x-amz-id-2")?.to_str().ok().map(str::to_string)
+}
+
+/// An error response that isn't one the operation's model lists.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct UnhandledError {
+    code: Option<String>,
+    message: Option<String>,
+}
+
+impl UnhandledError {
+    pub fn new(code: Option<String>, message: Option<String>) -> Self {
+        UnhandledError { code, message }
+    }
+
+    pub fn code(&self) -> Option<&str> {
+        self.code.as_deref()
+    }
+
+    pub fn message(&self) -> Option<&str> {
+        self.message.as_deref()
+    }
+}
+
+/// An error code without the namespace or detail some protocols add to it: both
+/// `aws.lambda#ResourceNotFoundException` and `ResourceNotFoundException:http://internal`
+/// become `ResourceNotFoundException`.
+pub fn sanitize_error_code(code: &str) -> &str {
+    let code = code.split(':').next().unwrap_or(code);
+    code.rsplit('#').next().unwrap_or(code)
+}
+"#;
+
+/// The code an error shape is identified by in responses: the one its `error` trait gives,
+/// or else its name.
+pub fn error_code(id: ShapeId, shape: &Shape) -> &str {
+    match shape {
+        Shape::Structure {
+            error: Some(error), ..
+        } => error.code.as_deref().unwrap_or_else(|| id.as_str()),
+        _ => id.as_str(),
+    }
+}
+
+/// Generates an error enum for every operation in the model, in name order. Enums for
+/// feature-gated operations are only compiled with their feature.
+pub fn generate_errors(
+    graph: &ModelGraph<'_>,
+    links: &DocLinks,
+    features: &OperationFeatures,
+) -> Result<String, Err> {
+    let mut out = String::new();
+    for (_, op) in graph.operations() {
+        if !out.is_empty() {
+            out.push('\n');
+        }
+        out.push_str(&generate_error(graph, op, links, features)?);
+    }
+    Ok(out)
+}
+
+/// Generates the enum of the errors an operation can return, e.g. `GetFunctionError`.
+///
+/// There's a variant holding the struct for each error shape the operation lists, and an
+/// `Unhandled` variant for any other error, since services return errors their models
+/// don't mention. `modeled_error` matches the code a response carries to the shape to
+/// parse it as.
+pub fn generate_error(
+    graph: &ModelGraph<'_>,
+    op: &Operation,
+    links: &DocLinks,
+    features: &OperationFeatures,
+) -> Result<String, Err> {
+    let name = format!("{}Error", type_name(&op.name));
+    let cfg = features
+        .cfg_attribute(&op.name)
+        .map(|attribute| attribute + "\n")
+        .unwrap_or_default();
+
+    let mut variants = String::new();
+    let mut modeled = String::new();
+    let mut codes = String::new();
+    let mut messages = String::new();
+    for reference in &op.errors {
+        let id = reference.shape;
+        let shape = graph.shape(id).ok_or_else(|| {
+            format!(
+                "operation `{}` returns `{}`, which the model doesn't define",
+                op.name, id
+            )
+        })?;
+        let variant = type_name(id.as_str());
+        let code = error_code(id, shape);
+
+        if let Shape::Structure {
+            documentation: Some(documentation),
+            ..
+        } = shape
+        {
+            variants.push_str(&doc_comment(&Markdown::from(documentation.clone()), links));
+        }
+        variants.push_str(&format!("{}({}),\n", variant, variant));
+        modeled.push_str(&format!("{:?} => Some({:?}),\n", code, id.as_str()));
+        codes.push_str(&format!("{}::{}(_) => Some({:?}),\n", name, variant, code));
+
+        let (fields, _) = struct_fields(graph, id)?;
+        let message = fields
+            .iter()
+            .find(|field| field.name == "message")
+            .map(|field| field.ty.as_str());
+        messages.push_str(&match message {
+            Some("String") => format!("{}::{}(error) => Some(&error.message),\n", name, variant),
+            Some("Option<String>") => format!(
+                "{}::{}(error) => error.message.as_deref(),\n",
+                name, variant
+            ),
+            _ => format!("{}::{}(_) => None,\n", name, variant),
+        });
+    }
+    variants.push_str("/// An error the model doesn't list for this operation.\n");
+    variants.push_str("Unhandled(crate::error::UnhandledError),\n");
+    modeled.push_str("_ => None,\n");
+    codes.push_str(&format!("{}::Unhandled(error) => error.code(),\n", name));
+    messages.push_str(&format!("{}::Unhandled(error) => error.message(),\n", name));
+
+    let mut out = format!("/// The errors [`{}`] can return.\n", op.name);
+    out.push_str(&cfg);
+    out.push_str("#[derive(Debug)]\n");
+    out.push_str(&format!(
+        "pub enum {} {{\n{}}}\n\n",
+        name,
+        indent(&variants, 1)
+    ));
+    out.push_str(&format!(
+        "{cfg}impl {name} {{\n    \
+         /// The name of the error shape an error code refers to, if it's one this operation\n    \
+         /// lists.\n    \
+         pub fn modeled_error(code: &str) -> Option<&'static str> {{\n        \
+         match crate::error::sanitize_error_code(code) {{\n{modeled}        \
+         }}\n    \
+         }}\n\n    \
+         /// The error code the service returned.\n    \
+         pub fn code(&self) -> Option<&str> {{\n        \
+         match self {{\n{codes}        \
+         }}\n    \
+         }}\n\n    \
+         /// The error message the service returned, if there was one.\n    \
+         pub fn message(&self) -> Option<&str> {{\n        \
+         match self {{\n{messages}        \
+         }}\n    \
+         }}\n\
+         }}\n\n",
+        cfg = cfg,
+        name = name,
+        modeled = indent(&modeled, 3),
+        codes = indent(&codes, 3),
+        messages = indent(&messages, 3),
+    ));
+    out.push_str(&format!(
+        "{cfg}impl std::fmt::Display for {name} {{\n    \
+         fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {{\n        \
+         match (self.code(), self.message()) {{\n            \
+         (Some(code), Some(message)) => write!(f, \"{{}}: {{}}\", code, message),\n            \
+         (Some(code), None) => f.write_str(code),\n            \
+         (None, Some(message)) => f.write_str(message),\n            \
+         (None, None) => f.write_str(\"unhandled error\"),\n        \
+         }}\n    \
+         }}\n\
+         }}\n\n\
+         {cfg}impl std::error::Error for {name} {{}}\n",
+        cfg = cfg,
+        name = name,
+    ));
+    Ok(out)
+}
+
+#[test]
+fn generates_operation_errors() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let def = crate::model::BotocoreModel::from_path("test-data/recursive.json")?;
+    let graph = ModelGraph::new(&def);
+    assert_eq!(
+        generate_error(
+            &graph,
+            &def.operations["PutItem"],
+            &DocLinks::new(),
+            &OperationFeatures::default()
+        )?,
+        r#"/// The errors [`PutItem`] can return.
+#[derive(Debug)]
+pub enum PutItemError {
+    ValidationException(ValidationException),
+    /// An error the model doesn't list for this operation.
+    Unhandled(crate::error::UnhandledError),
+}
+
+impl PutItemError {
+    /// The name of the error shape an error code refers to, if it's one this operation
+    /// lists.
+    pub fn modeled_error(code: &str) -> Option<&'static str> {
+        match crate::error::sanitize_error_code(code) {
+            "ValidationError" => Some("ValidationException"),
+            _ => None,
+        }
+    }
+
+    /// The error code the service returned.
+    pub fn code(&self) -> Option<&str> {
+        match self {
+            PutItemError::ValidationException(_) => Some("ValidationError"),
+            PutItemError::Unhandled(error) => error.code(),
+        }
+    }
+
+    /// The error message the service returned, if there was one.
+    pub fn message(&self) -> Option<&str> {
+        match self {
+            PutItemError::ValidationException(error) => error.message.as_deref(),
+            PutItemError::Unhandled(error) => error.message(),
+        }
+    }
+}
+
+impl std::fmt::Display for PutItemError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (self.code(), self.message()) {
+            (Some(code), Some(message)) => write!(f, "{}: {}", code, message),
+            (Some(code), None) => f.write_str(code),
+            (None, Some(message)) => f.write_str(message),
+            (None, None) => f.write_str("unhandled error"),
+        }
+    }
+}
+
+impl std::error::Error for PutItemError {}
+"#
+    );
+
+    let def = crate::model::BotocoreModel::from_path("test-data/lambda/2015-03-31/service-2.json")?;
+    let graph = ModelGraph::new(&def);
+    let code = generate_error(
+        &graph,
+        &def.operations["GetFunction"],
+        &DocLinks::new(),
+        &OperationFeatures::default(),
+    )?;
+    assert!(code.contains("    ResourceNotFoundException(ResourceNotFoundException),\n"));
+    assert!(code.contains(
+        "            \"TooManyRequestsException\" => Some(\"TooManyRequestsException\"),\n"
+    ));
+    assert!(code.contains(
+        "            GetFunctionError::ServiceException(error) => error.message.as_deref(),\n"
+    ));
+
+    let features = OperationFeatures::new(&graph, &super::features::OperationGroups::ByPrefix)?;
+    let code = generate_error(
+        &graph,
+        &def.operations["GetFunction"],
+        &DocLinks::new(),
+        &features,
+    )?;
+    assert_eq!(code.matches("#[cfg(feature = \"get\")]\n").count(), 4);
+    assert!(code.starts_with(
+        "/// The errors [`GetFunction`] can return.\n#[cfg(feature = \"get\")]\n#[derive(Debug)]\n"
+    ));
+
+    Ok(())
+}
@@ -0,0 +1,132 @@
+use crate::{graph::ModelGraph, naming::words, Err};
+use std::collections::BTreeMap;
+
+/// How operations are grouped behind cargo features, so crates for services with hundreds
+/// of operations can be built with only the ones that are needed.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum OperationGroups {
+    // A feature for each first word of operation names: `describe` for `DescribeInstances`
+    // and `DescribeVolumes`, `create` for `CreateVolume`, and so on.
+    ByPrefix,
+    // Features named by the keys, each for the operations listed. Operations not listed
+    // aren't behind any feature.
+    Explicit(BTreeMap<String, Vec<String>>),
+}
+
+/// The feature each of a service's feature-gated operations is behind.
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct OperationFeatures {
+    // By operation name.
+    features: BTreeMap<String, String>,
+}
+
+impl OperationFeatures {
+    /// Groups a service's operations. Explicit groups must only list operations the model
+    /// defines, and each operation at most once.
+    pub fn new(graph: &ModelGraph<'_>, groups: &OperationGroups) -> Result<Self, Err> {
+        let mut features = BTreeMap::new();
+        match groups {
+            OperationGroups::ByPrefix => {
+                for (name, _) in graph.operations() {
+                    if let Some(word) = words(name).first() {
+                        features.insert(name.to_string(), word.to_ascii_lowercase());
+                    }
+                }
+            }
+            OperationGroups::Explicit(groups) => {
+                for (feature, operations) in groups {
+                    for name in operations {
+                        if !graph.model().operations.contains_key(name) {
+                            return Err(format!(
+                                "feature `{}` lists `{}`, which isn't an operation",
+                                feature, name
+                            )
+                            .into());
+                        }
+                        if let Some(other) = features.insert(name.clone(), feature.clone()) {
+                            return Err(format!(
+                                "`{}` is in both feature `{}` and feature `{}`",
+                                name, other, feature
+                            )
+                            .into());
+                        }
+                    }
+                }
+            }
+        }
+        // Operations whose endpoints are discovered need the endpoint operation, whichever
+        // features are on.
+        if let Some((name, _)) = graph.model().endpoint_operation() {
+            features.remove(name);
+        }
+        Ok(OperationFeatures { features })
+    }
+
+    pub fn feature(&self, operation: &str) -> Option<&str> {
+        self.features.get(operation).map(String::as_str)
+    }
+
+    /// The `#[cfg]` attribute for items generated for an operation, if it's feature-gated.
+    pub fn cfg_attribute(&self, operation: &str) -> Option<String> {
+        self.feature(operation)
+            .map(|feature| format!("#[cfg(feature = {:?})]", feature))
+    }
+
+    /// The features for the crate's manifest: one for each group, all of them on by
+    /// default.
+    pub fn manifest_features(&self) -> BTreeMap<String, Vec<String>> {
+        let mut features = BTreeMap::new();
+        for feature in self.features.values() {
+            features.insert(feature.clone(), vec![]);
+        }
+        if !features.is_empty() {
+            let default = features.keys().cloned().collect();
+            features.insert("default".to_string(), default);
+        }
+        features
+    }
+}
+
+#[test]
+fn groups_operations_into_features() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let def = crate::model::BotocoreModel::from_path("test-data/lambda/2015-03-31/service-2.json")?;
+    let graph = ModelGraph::new(&def);
+
+    let features = OperationFeatures::new(&graph, &OperationGroups::ByPrefix)?;
+    assert_eq!(features.feature("GetFunction"), Some("get"));
+    assert_eq!(features.feature("ListFunctions"), Some("list"));
+    assert_eq!(
+        features.cfg_attribute("CreateFunction").as_deref(),
+        Some("#[cfg(feature = \"create\")]")
+    );
+    let manifest = features.manifest_features();
+    assert!(manifest["default"].contains(&"get".to_string()));
+    assert_eq!(manifest["get"], Vec::<String>::new());
+
+    let mut groups = BTreeMap::new();
+    groups.insert(
+        "functions".to_string(),
+        vec!["GetFunction".to_string(), "ListFunctions".to_string()],
+    );
+    let features = OperationFeatures::new(&graph, &OperationGroups::Explicit(groups.clone()))?;
+    assert_eq!(features.feature("ListFunctions"), Some("functions"));
+    assert_eq!(features.feature("CreateFunction"), None);
+    assert_eq!(features.manifest_features().len(), 2);
+
+    groups.insert("reads".to_string(), vec!["GetFunction".to_string()]);
+    assert!(OperationFeatures::new(&graph, &OperationGroups::Explicit(groups.clone())).is_err());
+    groups.remove("reads");
+    groups.insert("missing".to_string(), vec!["DeleteEverything".to_string()]);
+    assert!(OperationFeatures::new(&graph, &OperationGroups::Explicit(groups)).is_err());
+    assert!(OperationFeatures::default().manifest_features().is_empty());
+
+    // The endpoint operation is never gated, since the operations it discovers endpoints for
+    // send it.
+    let def = crate::model::BotocoreModel::from_path("test-data/tables.json")?;
+    let graph = ModelGraph::new(&def);
+    let features = OperationFeatures::new(&graph, &OperationGroups::ByPrefix)?;
+    assert_eq!(features.feature("DescribeEndpoints"), None);
+    assert_eq!(features.feature("GetItem"), Some("get"));
+
+    Ok(())
+}
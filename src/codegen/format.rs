@@ -0,0 +1,77 @@
+use crate::Err;
+use proc_macro2::TokenStream;
+
+/// What to do with generated code that can't be formatted.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Formatting {
+    // Fail, since code that can't be formatted doesn't parse and won't compile.
+    Strict,
+    // Keep the code as it was generated, so it can be written out and looked at.
+    FallBackToRaw,
+}
+
+/// Formats a generated source file the way rustfmt would lay it out.
+pub fn format_code(code: &str, formatting: Formatting) -> Result<String, Err> {
+    match syn::parse_file(code) {
+        Ok(file) => Ok(prettyplease::unparse(&file)),
+        Err(_) if formatting == Formatting::FallBackToRaw => Ok(code.to_string()),
+        Err(error) => Err(format!("generated code doesn't parse: {}", error).into()),
+    }
+}
+
+/// `format_code` for a file built as tokens.
+pub fn format_tokens(tokens: TokenStream, formatting: Formatting) -> Result<String, Err> {
+    let raw = tokens.to_string();
+    match syn::parse2(tokens) {
+        Ok(file) => Ok(prettyplease::unparse(&file)),
+        Err(_) if formatting == Formatting::FallBackToRaw => Ok(raw),
+        Err(error) => Err(format!("generated code doesn't parse: {}", error).into()),
+    }
+}
+
+#[test]
+fn formats_generated_code() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    assert_eq!(
+        format_code(
+            "/// A function.\n#[derive(Debug)] pub struct Function { pub name : String , }",
+            Formatting::Strict
+        )?,
+        "/// A function.\n#[derive(Debug)]\npub struct Function {\n    pub name: String,\n}\n"
+    );
+    let tokens = crate::codegen::emit::module(
+        "model",
+        None,
+        quote::quote!(
+            #[doc = " A layer."]
+            pub struct Layer;
+        ),
+    );
+    assert_eq!(
+        format_tokens(tokens, Formatting::Strict)?,
+        "pub mod model {\n    /// A layer.\n    pub struct Layer;\n}\n"
+    );
+
+    let broken = "pub struct Function { pub name: }";
+    assert!(format_code(broken, Formatting::Strict).is_err());
+    assert_eq!(format_code(broken, Formatting::FallBackToRaw)?, broken);
+
+    // Everything generated for a service should parse.
+    let mut def =
+        crate::model::BotocoreModel::from_path("test-data/lambda/2015-03-31/service-2.json")?;
+    // It refers to a shape the fixture doesn't define.
+    def.shapes.remove(&crate::model::ShapeId::from(
+        "DeleteFunctionConcurrencyRequest",
+    ));
+    let graph = crate::graph::ModelGraph::new(&def);
+    let links = crate::docs::DocLinks::new();
+    for code in &[
+        crate::codegen::structs::generate_structs(&graph, &links)?,
+        crate::codegen::enums::generate_enums(&graph)?,
+        crate::codegen::errors::generate_errors(&graph, &links, &Default::default())?,
+        crate::codegen::client::generate_client(&graph, &links, &Default::default())?,
+    ] {
+        format_code(code, Formatting::Strict)?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,172 @@
+use crate::{model::Metadata, naming::ServiceNaming, Err};
+use std::collections::BTreeMap;
+
+/// The crate generated service crates share their runtime code through.
+pub const RUNTIME_CRATE: &str = "aws-sdk-rust-runtime";
+
+// The versions of the dependencies a generated crate only has when one of its features enables
+// them, like `serde`, which DynamoDB's conversions between items and serde's traits need.
+const OPTIONAL_DEPENDENCIES: &[(&str, &str)] = &[("serde", "1")];
+
+/// Where a generated crate depends on the runtime crate from.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum RuntimeSource {
+    // A published version, e.g. `0.1`.
+    Version(String),
+    // A checkout, absolute or relative to the generated crate's directory.
+    Path(String),
+}
+
+/// The version of a service's generated crate: its API version's date as the minor version,
+/// so `2015-03-31` is `0.20150331.0`, and crates for newer API versions sort after older ones.
+pub fn crate_version(api_version: &str) -> Result<String, Err> {
+    let date = api_version.split('-').collect::<Vec<_>>();
+    match date.as_slice() {
+        [year, month, day]
+            if year.len() == 4
+                && month.len() == 2
+                && day.len() == 2
+                && date
+                    .iter()
+                    .all(|part| part.chars().all(|c| c.is_ascii_digit())) =>
+        {
+            Ok(format!("0.{}{}{}.0", year, month, day))
+        }
+        _ => Err(format!(
+            "API version `{}` isn't a date like `2015-03-31`",
+            api_version
+        )
+        .into()),
+    }
+}
+
+/// The `Cargo.toml` for a service's generated crate.
+///
+/// `features` maps each of the crate's features to the features it enables, and is written
+/// as is, apart from a `default` feature that's added as empty if it's missing. Optional
+/// dependencies they enable, as `dep:serde`, are declared too.
+pub fn generate_manifest(
+    metadata: &Metadata,
+    runtime: &RuntimeSource,
+    features: &BTreeMap<String, Vec<String>>,
+) -> Result<String, Err> {
+    let naming = ServiceNaming::new(metadata);
+    let runtime = match runtime {
+        RuntimeSource::Version(version) => format!("{:?}", version),
+        RuntimeSource::Path(path) => format!("{{ path = {:?} }}", path),
+    };
+
+    let optional = OPTIONAL_DEPENDENCIES
+        .iter()
+        .filter(|(name, _)| {
+            let enable = format!("dep:{}", name);
+            features.values().any(|enables| enables.contains(&enable))
+        })
+        .map(|(name, version)| {
+            format!(
+                "{} = {{ version = {:?}, optional = true }}\n",
+                name, version
+            )
+        })
+        .collect::<String>();
+    let mut out = format!(
+        "[package]\n\
+         name = {name:?}\n\
+         version = {version:?}\n\
+         description = {description:?}\n\
+         edition = \"2018\"\n\
+         license = \"Apache-2.0\"\n\
+         \n\
+         [dependencies]\n\
+         {runtime_crate} = {runtime}\n\
+         http = \"1\"\n\
+         {optional}\
+         serde_json = \"1\"\n\
+         tower = {{ version = \"0.5\", features = [\"util\"] }}\n\
+         \n\
+         [features]\n",
+        name = naming.crate_name,
+        version = crate_version(&metadata.api_version)?,
+        description = format!(
+            "A client for {}, API version {}.",
+            metadata.service_full_name, metadata.api_version
+        ),
+        runtime_crate = RUNTIME_CRATE,
+        runtime = runtime,
+        optional = optional,
+    );
+    if !features.contains_key("default") {
+        out.push_str("default = []\n");
+    }
+    for (feature, enables) in features {
+        let enables = enables
+            .iter()
+            .map(|feature| format!("{:?}", feature))
+            .collect::<Vec<_>>();
+        out.push_str(&format!("{} = [{}]\n", feature, enables.join(", ")));
+    }
+    Ok(out)
+}
+
+#[test]
+fn generates_manifests() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    assert_eq!(crate_version("2015-03-31")?, "0.20150331.0");
+    assert!(crate_version("latest").is_err());
+    assert!(crate_version("2015-3-31").is_err());
+
+    let def = crate::model::BotocoreModel::from_path("test-data/lambda/2015-03-31/service-2.json")?;
+    let mut features = BTreeMap::new();
+    features.insert("functions".to_string(), vec![]);
+    features.insert(
+        "layers".to_string(),
+        vec![
+            "functions".to_string(),
+            "aws-sdk-rust-runtime/layers".to_string(),
+        ],
+    );
+    assert_eq!(
+        generate_manifest(
+            &def.metadata,
+            &RuntimeSource::Path("../runtime".to_string()),
+            &features
+        )?,
+        r#"[package]
+name = "aws-sdk-lambda"
+version = "0.20150331.0"
+description = "A client for AWS Lambda, API version 2015-03-31."
+edition = "2018"
+license = "Apache-2.0"
+
+[dependencies]
+aws-sdk-rust-runtime = { path = "../runtime" }
+http = "1"
+serde_json = "1"
+tower = { version = "0.5", features = ["util"] }
+
+[features]
+default = []
+functions = []
+layers = ["functions", "aws-sdk-rust-runtime/layers"]
+"#
+    );
+    assert!(generate_manifest(
+        &def.metadata,
+        &RuntimeSource::Version("0.1".to_string()),
+        &BTreeMap::new()
+    )?
+    .contains("\naws-sdk-rust-runtime = \"0.1\"\n"));
+    let mut features = BTreeMap::new();
+    features.insert("serde".to_string(), vec!["dep:serde".to_string()]);
+    assert!(generate_manifest(
+        &def.metadata,
+        &RuntimeSource::Version("0.1".to_string()),
+        &features
+    )?
+    .contains(
+        "http = \"1\"\n\
+         serde = { version = \"1\", optional = true }\n\
+         serde_json = \"1\"\n"
+    ));
+
+    Ok(())
+}
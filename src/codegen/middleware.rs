@@ -0,0 +1,425 @@
+/// The services a client's requests pass through, emitted into each generated crate's
+/// `middleware` module.
+pub const MIDDLEWARE: &str = r#"use crate::{config::Config, connector::HttpConnector, error::BoxError};
+use aws_sdk_rust_runtime::{
+    body::{read_response, try_clone_request, Body},
+    retry::{sleep, RetryHandler, RetryKind},
+    timeout::{timeout, TimedOut},
+};
+use std::{
+    fmt,
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+use tower::{Service, ServiceBuilder, ServiceExt};
+
+pub use crate::connector::{HttpRequest, HttpResponse};
+
+/// What sends each attempt at a request: the client's connector, under any layers added with
+/// `Client::with_layer`.
+pub type HttpService = tower::util::BoxCloneSyncService<HttpRequest, HttpResponse, BoxError>;
+
+/// A client's whole stack, from an operation's request to the response to it.
+pub type Stack = ResolveEndpoint<Serialize<Sign<Retry<HttpService>>>>;
+
+type BoxFuture<T> = Pin<Box<dyn Future<Output = Result<T, BoxError>> + Send>>;
+
+/// The stack a client with `config` sends requests through, with `retries` and `dispatch`.
+pub fn stack(config: &Config, retries: RetryHandler, dispatch: HttpService) -> Stack {
+    ServiceBuilder::new()
+        .layer_fn(|inner| ResolveEndpoint {
+            config: config.clone(),
+            inner,
+        })
+        .layer_fn(|inner| Serialize {
+            config: config.clone(),
+            inner,
+        })
+        .layer_fn(|inner| Sign {
+            config: config.clone(),
+            inner,
+        })
+        .layer_fn(|inner| Retry {
+            config: config.clone(),
+            retries: retries.clone(),
+            inner,
+        })
+        .service(dispatch)
+}
+
+// Sends an operation through a client's stack. The future is boxed so that client methods'
+// futures are `Send`, which rustc can't prove of the unboxed one.
+pub(crate) fn send(stack: Stack, operation: Operation) -> BoxFuture<HttpResponse> {
+    Box::pin(stack.oneshot(operation))
+}
+
+// Sends an operation like `send` does, and reads the whole of its response's body, for
+// operations whose outputs aren't streams, which some clients have none of.
+#[allow(dead_code)]
+pub(crate) fn send_and_read(
+    stack: Stack,
+    operation: Operation,
+) -> BoxFuture<http::Response<Vec<u8>>> {
+    Box::pin(async move { read_response(send(stack, operation).await?).await })
+}
+
+/// What the layers of a client's stack need to know about the operation a request is for.
+/// Serialization puts them in the request's extensions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OperationProperties {
+    pub name: &'static str,
+    // The status code of successful responses, which aren't retried.
+    pub success_status: u16,
+    // Whether responses with the success status are still errors if their body is an `Error`
+    // element, as S3's copies' can be.
+    pub errors_in_success: bool,
+    // Whether the request is S3's and its path starts with its bucket, which can be addressed
+    // in its host instead.
+    pub bucket_addressed: bool,
+    // The algorithm the request's body is checksummed with, if it's sent with a checksum.
+    pub checksum: Option<aws_sdk_rust_runtime::checksum::ChecksumAlgorithm>,
+    // Whether the request's body can be gzipped, which it is if it's large enough.
+    pub compressed: bool,
+    pub signing: Option<SigningProperties>,
+}
+
+/// How a request is signed with SigV4, as `signing::sign_v4` takes it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SigningProperties {
+    pub signing_name: &'static str,
+    pub signed_body: bool,
+    pub s3: bool,
+    // Whether the body is an event stream, whose events are signed too.
+    pub events: bool,
+}
+
+/// An operation's request before it's serialized: how to build it for an endpoint, once the
+/// endpoint's been resolved.
+pub struct Operation {
+    properties: OperationProperties,
+    endpoint: Option<String>,
+    serialize: Box<dyn FnOnce(String) -> Result<HttpRequest, BoxError> + Send>,
+}
+
+impl Operation {
+    pub fn new(
+        properties: OperationProperties,
+        serialize: impl FnOnce(String) -> Result<HttpRequest, BoxError> + Send + 'static,
+    ) -> Self {
+        Operation {
+            properties,
+            endpoint: None,
+            serialize: Box::new(serialize),
+        }
+    }
+
+    /// The operation with its request sent to `endpoint`, e.g. one discovered for it, rather
+    /// than the config's.
+    pub fn with_endpoint(mut self, endpoint: String) -> Self {
+        self.endpoint = Some(endpoint);
+        self
+    }
+
+    pub fn properties(&self) -> &OperationProperties {
+        &self.properties
+    }
+
+    pub fn endpoint(&self) -> Option<&str> {
+        self.endpoint.as_deref()
+    }
+}
+
+impl fmt::Debug for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Operation")
+            .field("properties", &self.properties)
+            .field("endpoint", &self.endpoint)
+            .finish()
+    }
+}
+
+/// A request that couldn't be built, as opposed to one that couldn't be sent.
+#[derive(Debug)]
+pub struct ConstructionError(pub BoxError);
+
+impl fmt::Display for ConstructionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl std::error::Error for ConstructionError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(self.0.as_ref())
+    }
+}
+
+// The `SdkError` for an error out of the stack, by the layer it came from.
+pub(crate) fn sdk_error<E>(error: BoxError) -> crate::error::SdkError<E> {
+    let error = match error.downcast::<ConstructionError>() {
+        Ok(error) => return crate::error::SdkError::Construction(error.0),
+        Err(error) => error,
+    };
+    match error.downcast::<TimedOut>() {
+        Ok(timed_out) => crate::error::SdkError::Timeout(*timed_out),
+        Err(error) => crate::error::SdkError::Dispatch(error),
+    }
+}
+
+/// Resolves the endpoint an operation's request goes to: the config's, as
+/// `Config::for_region` resolved it or `Config::with_endpoint_url` overrode it, unless the
+/// operation was given one `with_endpoint`.
+#[derive(Debug, Clone)]
+pub struct ResolveEndpoint<S> {
+    config: Config,
+    inner: S,
+}
+
+impl<S> Service<Operation> for ResolveEndpoint<S>
+where
+    S: Service<Operation, Response = HttpResponse, Error = BoxError> + Clone + Send + 'static,
+    S::Future: Send,
+{
+    type Response = HttpResponse;
+    type Error = BoxError;
+    type Future = BoxFuture<HttpResponse>;
+
+    fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), BoxError>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, mut operation: Operation) -> Self::Future {
+        if operation.endpoint.is_none() {
+            operation.endpoint = Some(self.config.endpoint().to_string());
+        }
+        Box::pin(self.inner.clone().oneshot(operation))
+    }
+}
+
+/// Builds an operation's HTTP request for its endpoint, with the operation's properties and
+/// the config's connector timeouts in its extensions, its body gzipped if it can be and it's
+/// large enough, its body's checksum if it has one, and S3's bucket in its host where the
+/// config has it there.
+#[derive(Debug, Clone)]
+pub struct Serialize<S> {
+    config: Config,
+    inner: S,
+}
+
+impl<S> Service<Operation> for Serialize<S>
+where
+    S: Service<HttpRequest, Response = HttpResponse, Error = BoxError> + Clone + Send + 'static,
+    S::Future: Send,
+{
+    type Response = HttpResponse;
+    type Error = BoxError;
+    type Future = BoxFuture<HttpResponse>;
+
+    fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), BoxError>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, operation: Operation) -> Self::Future {
+        let properties = operation.properties;
+        let endpoint = operation
+            .endpoint
+            .unwrap_or_else(|| self.config.endpoint().to_string());
+        let request = (operation.serialize)(endpoint).and_then(|mut request| {
+            request.extensions_mut().insert(properties);
+            request
+                .extensions_mut()
+                .insert(self.config.connector_timeouts());
+            // Before the checksum, which is of the body that's sent.
+            if properties.compressed && self.config.request_compression() {
+                aws_sdk_rust_runtime::compression::compress_request(
+                    &mut request,
+                    self.config.min_compression_size(),
+                );
+            }
+            if let Some(algorithm) = properties.checksum {
+                aws_sdk_rust_runtime::checksum::add_checksum(&mut request, algorithm);
+            }
+            if properties.bucket_addressed {
+                crate::endpoint::address_bucket(request, &self.config)
+            } else {
+                Ok(request)
+            }
+        });
+        match request {
+            Ok(request) => Box::pin(self.inner.clone().oneshot(request)),
+            Err(error) => Box::pin(async move { Err(ConstructionError(error).into()) }),
+        }
+    }
+}
+
+/// Signs requests as their operation's properties say, with the config's credentials.
+#[derive(Debug, Clone)]
+pub struct Sign<S> {
+    config: Config,
+    inner: S,
+}
+
+impl<S> Service<HttpRequest> for Sign<S>
+where
+    S: Service<HttpRequest, Response = HttpResponse, Error = BoxError> + Clone + Send + 'static,
+    S::Future: Send,
+{
+    type Response = HttpResponse;
+    type Error = BoxError;
+    type Future = BoxFuture<HttpResponse>;
+
+    fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), BoxError>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: HttpRequest) -> Self::Future {
+        let config = self.config.clone();
+        let inner = self.inner.clone();
+        Box::pin(async move {
+            let signing = request
+                .extensions()
+                .get::<OperationProperties>()
+                .and_then(|properties| properties.signing);
+            let request = match signing {
+                Some(signing) => crate::signing::sign_v4(
+                    request,
+                    &config,
+                    signing.signing_name,
+                    signing.signed_body,
+                    signing.s3,
+                    signing.events,
+                )
+                .await
+                .map_err(ConstructionError)?,
+                None => request,
+            };
+            inner.oneshot(request).await
+        })
+    }
+}
+
+/// Sends requests again when they can't be sent or their responses are errors
+/// `retry::classify_response` finds retryable, as the client's retry handler allows, giving
+/// each attempt as long as the config's connector timeouts do. The last attempt's error
+/// response is returned as is, with its body read into memory. Requests whose bodies can only
+/// be read once, e.g. streams, are only sent once.
+#[derive(Debug, Clone)]
+pub struct Retry<S> {
+    config: Config,
+    retries: RetryHandler,
+    inner: S,
+}
+
+impl<S> Service<HttpRequest> for Retry<S>
+where
+    S: Service<HttpRequest, Response = HttpResponse, Error = BoxError> + Clone + Send + 'static,
+    S::Future: Send,
+{
+    type Response = HttpResponse;
+    type Error = BoxError;
+    type Future = BoxFuture<HttpResponse>;
+
+    fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), BoxError>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: HttpRequest) -> Self::Future {
+        let attempt_timeout = self.config.connector_timeouts().attempt();
+        let retries = self.retries.clone();
+        let inner = self.inner.clone();
+        Box::pin(async move {
+            let (success_status, errors_in_success) = request
+                .extensions()
+                .get::<OperationProperties>()
+                .map_or((200, false), |properties| {
+                    (properties.success_status, properties.errors_in_success)
+                });
+            let mut request = Some(request);
+            let mut attempts = 0;
+            loop {
+                attempts += 1;
+                if let Some(delay) = retries.send_delay() {
+                    sleep(delay).await;
+                }
+                let attempt = match request.as_ref().and_then(try_clone_request) {
+                    Some(attempt) => attempt,
+                    None => request.take().ok_or("the request can't be sent again")?,
+                };
+                let call = timeout(attempt_timeout, inner.clone().oneshot(attempt));
+                let (kind, result) = match call.await {
+                    Ok(Ok(response))
+                        if response.status().as_u16() == success_status && !errors_in_success =>
+                    {
+                        retries.succeeded(attempts);
+                        return Ok(response);
+                    }
+                    // Bodies of error responses are read to classify them, as are those of
+                    // successful ones that can be errors.
+                    Ok(Ok(response)) => match read_response(response).await {
+                        Ok(response)
+                            if response.status().as_u16() == success_status
+                                && !aws_sdk_rust_runtime::xml::is_error(response.body()) =>
+                        {
+                            retries.succeeded(attempts);
+                            return Ok(response.map(Body::from));
+                        }
+                        Ok(response) => (
+                            crate::retry::classify_response(&response),
+                            Ok(response.map(Body::from)),
+                        ),
+                        Err(error) => (RetryKind::Transient, Err(error)),
+                    },
+                    Ok(Err(error)) => (RetryKind::Transient, Err(error)),
+                    Err(timed_out) => (RetryKind::Transient, Err(timed_out.into())),
+                };
+                if request.is_none() {
+                    return result;
+                }
+                match retries.retry_after(attempts, kind) {
+                    Some(delay) => sleep(delay).await,
+                    None => return result,
+                }
+            }
+        })
+    }
+}
+
+/// Sends requests with a connector, at the bottom of every client's stack.
+#[derive(Debug)]
+pub struct Dispatch<C> {
+    connector: Arc<C>,
+}
+
+impl<C> Dispatch<C> {
+    pub fn new(connector: C) -> Self {
+        Dispatch {
+            connector: Arc::new(connector),
+        }
+    }
+}
+
+impl<C> Clone for Dispatch<C> {
+    fn clone(&self) -> Self {
+        Dispatch {
+            connector: self.connector.clone(),
+        }
+    }
+}
+
+impl<C: HttpConnector + Send + Sync + 'static> Service<HttpRequest> for Dispatch<C> {
+    type Response = HttpResponse;
+    type Error = BoxError;
+    type Future = BoxFuture<HttpResponse>;
+
+    fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), BoxError>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: HttpRequest) -> Self::Future {
+        let connector = self.connector.clone();
+        Box::pin(async move { connector.call(request).await })
+    }
+}
+"#;
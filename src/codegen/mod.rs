@@ -0,0 +1,191 @@
+use crate::{
+    graph::ModelGraph,
+    model::{Operation, Shape, ShapeId, Traits},
+    naming::type_name,
+};
+
+pub mod blocking;
+pub mod builders;
+pub mod client;
+pub mod customizations;
+pub mod debug;
+pub mod dynamodb;
+pub mod emit;
+pub mod endpoint;
+pub mod enums;
+pub mod errors;
+pub mod features;
+pub mod format;
+pub mod manifest;
+pub mod middleware;
+pub mod paginators;
+pub mod protocol;
+pub mod retry;
+pub mod s3;
+pub mod service;
+pub mod sso;
+pub mod structs;
+pub mod sts;
+pub mod unions;
+pub mod waiters;
+pub mod workspace;
+
+/// The Rust type values of a shape are held in: a primitive where there's one, a collection
+/// of the member types for lists and maps, the runtime's `ByteStream` for streaming blobs,
+/// an `EventSender` or `EventReceiver` of the shape's enum for event streams requests send or
+/// responses receive, and otherwise the type generated for the shape.
+pub fn rust_type(graph: &ModelGraph<'_>, id: ShapeId) -> String {
+    let shape = match graph.shape(id) {
+        Some(shape) => shape,
+        None => return type_name(id.as_str()),
+    };
+    if let Some(primitive) = shape.rust_primitive() {
+        return primitive.to_string();
+    }
+    match shape {
+        Shape::String {
+            enum_values: None, ..
+        } => "String".to_string(),
+        Shape::Blob {
+            streaming: true, ..
+        } => "aws_sdk_rust_runtime::byte_stream::ByteStream".to_string(),
+        Shape::Blob { .. } => "Vec<u8>".to_string(),
+        Shape::EventStream { .. } => format!(
+            "aws_sdk_rust_runtime::event_stream::{}<{}>",
+            if is_input_event_stream(graph, id) {
+                "EventSender"
+            } else {
+                "EventReceiver"
+            },
+            type_name(id.as_str())
+        ),
+        Shape::Timestamp { .. } => "std::time::SystemTime".to_string(),
+        Shape::List { member, .. } => format!("Vec<{}>", rust_type(graph, member.shape.shape)),
+        Shape::Map { key, value, .. } => format!(
+            "std::collections::HashMap<{}, {}>",
+            rust_type(graph, key.shape.shape),
+            rust_type(graph, value.shape.shape)
+        ),
+        _ => type_name(id.as_str()),
+    }
+}
+
+/// Whether an event stream is one an operation's input sends, rather than one its output
+/// receives.
+pub fn is_input_event_stream(graph: &ModelGraph<'_>, id: ShapeId) -> bool {
+    graph.operations().any(|(_, op)| {
+        graph
+            .neighbors(op.input.shape)
+            .iter()
+            .any(|edge| edge.target == id)
+    })
+}
+
+// Indents every non-empty line of `code` by `levels` of four spaces.
+fn indent(code: &str, levels: usize) -> String {
+    let prefix = "    ".repeat(levels);
+    code.lines()
+        .map(|line| {
+            if line.is_empty() {
+                "\n".to_string()
+            } else {
+                format!("{}{}\n", prefix, line)
+            }
+        })
+        .collect()
+}
+
+/// The `#[deprecated]` attribute for an item generated from something the model marks as
+/// deprecated, with the model's message as its note, or `None` if it isn't deprecated.
+pub fn deprecated_attribute(deprecated: bool, message: Option<&str>) -> Option<String> {
+    match (deprecated, message) {
+        (false, _) => None,
+        // `Debug` formatting escapes the message into a valid string literal.
+        (true, Some(message)) => Some(format!("#[deprecated(note = {:?})]", message)),
+        (true, None) => Some("#[deprecated]".to_string()),
+    }
+}
+
+/// `deprecated_attribute` for a shape or member.
+pub fn deprecated_traits(traits: &Traits) -> Option<String> {
+    deprecated_attribute(traits.deprecated, traits.deprecated_message.as_deref())
+}
+
+/// `deprecated_attribute` for an operation's client method and types.
+pub fn deprecated_operation(op: &Operation) -> Option<String> {
+    deprecated_attribute(op.deprecated, op.deprecated_message.as_deref())
+}
+
+/// Whether the code generated for a shape names something the model marks as deprecated: the
+/// shape, one of its members, or a shape they hold, directly or in lists and maps. Those uses
+/// warn in the generated crate unless they're allowed.
+pub fn uses_deprecated(graph: &ModelGraph<'_>, id: ShapeId) -> bool {
+    let deprecated = |id| {
+        graph
+            .shape(id)
+            .is_some_and(|shape| shape.traits().deprecated)
+    };
+    deprecated(id)
+        || graph.neighbors(id).iter().any(|edge| {
+            edge.member.traits.deprecated
+                || deprecated(edge.target)
+                || matches!(
+                    graph.shape(edge.target),
+                    Some(Shape::List { .. }) | Some(Shape::Map { .. })
+                ) && uses_deprecated(graph, edge.target)
+        })
+}
+
+/// `uses_deprecated` for an operation's serializers and client methods, which name its input,
+/// output, and errors.
+pub fn operation_uses_deprecated(graph: &ModelGraph<'_>, op: &Operation) -> bool {
+    op.deprecated
+        || std::iter::once(&op.input)
+            .chain(&op.output)
+            .chain(&op.errors)
+            .any(|reference| uses_deprecated(graph, reference.shape))
+}
+
+/// `#[allow(deprecated)]`, for an item that `uses_deprecated`, or nothing.
+pub fn allow_deprecated(uses: bool) -> &'static str {
+    if uses {
+        "#[allow(deprecated)]\n"
+    } else {
+        ""
+    }
+}
+
+#[test]
+fn generates_deprecated_attributes() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let def = crate::model::BotocoreModel::from_path("test-data/lambda/2015-03-31/service-2.json")?;
+    assert_eq!(
+        deprecated_operation(&def.operations["InvokeAsync"]).as_deref(),
+        Some("#[deprecated]")
+    );
+    assert_eq!(deprecated_operation(&def.operations["Invoke"]), None);
+    let graph = ModelGraph::new(&def);
+    assert!(uses_deprecated(
+        &graph,
+        ShapeId::from("InvokeAsyncResponse")
+    ));
+    assert!(!uses_deprecated(&graph, ShapeId::from("InvocationRequest")));
+    assert!(operation_uses_deprecated(
+        &graph,
+        &def.operations["InvokeAsync"]
+    ));
+    assert!(!operation_uses_deprecated(
+        &graph,
+        &def.operations["Invoke"]
+    ));
+    assert_eq!(allow_deprecated(true), "#[allow(deprecated)]\n");
+
+    let traits = serde_json::from_str::<Traits>(
+        r#"{"deprecated": true, "deprecatedMessage": "Use \"Name\" instead"}"#,
+    )?;
+    assert_eq!(
+        deprecated_traits(&traits).as_deref(),
+        Some(r#"#[deprecated(note = "Use \"Name\" instead")]"#)
+    );
+
+    Ok(())
+}
@@ -0,0 +1,331 @@
+use super::{indent, rust_type, structs::struct_fields};
+use crate::{
+    graph::ModelGraph,
+    model::{paginators::Paginator, Operation, Shape, ShapeId},
+    naming::{field_name, type_name},
+    Err,
+};
+
+/// The client method paging through an operation's results, e.g. `paginate_list_functions`,
+/// or `None` if the operation has no output to page through.
+///
+/// It returns the runtime's `Paginator`, which sends each request after the first with the
+/// paginator's input tokens set from the last output's output tokens, until the output has
+/// none, has the tokens it was sent with, or its `more_results` is false. Its items are the
+/// list the first result key refers to, or none if that isn't a list.
+pub fn paginate_method(
+    graph: &ModelGraph<'_>,
+    op: &Operation,
+    paginator: &Paginator,
+    attributes: &str,
+) -> Result<Option<String>, Err> {
+    let output = match &op.output {
+        Some(output) => output.shape,
+        None => return Ok(None),
+    };
+    if paginator.input_token.len() != paginator.output_token.len() {
+        return Err(format!(
+            "`{}`'s paginator has {} input tokens but {} output tokens",
+            op.name,
+            paginator.input_token.len(),
+            paginator.output_token.len()
+        )
+        .into());
+    }
+    let input = op.input.shape;
+    let (input_fields, _) = struct_fields(graph, input)?;
+
+    let mut next_input = String::new();
+    if let Some(more_results) = &paginator.more_results {
+        let (more, target) = expression(graph, output, more_results, "output")?;
+        if !matches!(graph.shape(target), Some(Shape::Boolean { .. })) {
+            return Err(format!("`{}`'s `more_results` isn't a boolean", op.name).into());
+        }
+        next_input.push_str(&format!(
+            "if !{}.copied().unwrap_or(false) {{\n    return None;\n}}\n",
+            more
+        ));
+    }
+    let mut tokens = Vec::new();
+    for (input_token, output_token) in paginator.input_token.iter().zip(&paginator.output_token) {
+        let field = input_fields
+            .iter()
+            .find(|field| field.edge.name == input_token)
+            .filter(|field| field.is_optional())
+            .ok_or_else(|| {
+                format!(
+                    "`{}`'s input token `{}` isn't an optional member of `{}`",
+                    op.name, input_token, input
+                )
+            })?;
+        let (value, target) = expression(graph, output, output_token, "output")?;
+        if rust_type(graph, target) != field.value_type() {
+            return Err(format!(
+                "`{}`'s output token `{}` isn't a `{}.{}`",
+                op.name, output_token, input, input_token
+            )
+            .into());
+        }
+        let name = field.name.trim_start_matches("r#");
+        next_input.push_str(&format!("let {} = {};\n", name, value));
+        tokens.push((field.name.as_str(), name));
+    }
+    let ended = tokens
+        .iter()
+        .map(|(_, token)| format!("{}.is_none()", token))
+        .collect::<Vec<_>>()
+        .join(" && ");
+    let unchanged = tokens
+        .iter()
+        .map(|(field, token)| format!("input.{}.as_ref() == {}", field, token))
+        .collect::<Vec<_>>()
+        .join(" && ");
+    // A service that sends back the token it was sent would otherwise be paged through
+    // forever.
+    next_input.push_str(&format!(
+        "if {} || {} {{\n    return None;\n}}\n",
+        ended, unchanged
+    ));
+    for (field, token) in &tokens {
+        next_input.push_str(&format!("input.{} = {}.cloned();\n", field, token));
+    }
+    next_input.push_str("Some(input)\n");
+
+    let list = paginator
+        .result_key
+        .first()
+        .and_then(|key| expression(graph, output, key, "&output").ok())
+        .and_then(|(value, target)| match graph.shape(target) {
+            Some(Shape::List { member, .. }) => {
+                Some((value, item_type(graph, member.shape.shape)?))
+            }
+            _ => None,
+        });
+    let (item, items) = match &list {
+        Some((value, item)) => (
+            item.clone(),
+            format!("|output| {}.cloned().unwrap_or_default()", value),
+        ),
+        None => (
+            "std::convert::Infallible".to_string(),
+            "|_| Vec::new()".to_string(),
+        ),
+    };
+
+    let method = field_name(&op.name);
+    let items_doc = match paginator.result_key.first() {
+        Some(key) if list.is_some() => format!("\n/// Its `items()` are each page's `{}`.", key),
+        _ => String::new(),
+    };
+    let token_doc = paginator
+        .input_token
+        .iter()
+        .zip(&paginator.output_token)
+        .map(|(input, output)| format!("`{}` set to the last response's `{}`", input, output))
+        .collect::<Vec<_>>()
+        .join(" and ");
+    let model_type = |id: ShapeId| format!("crate::model::{}", type_name(id.as_str()));
+    Ok(Some(format!(
+        "/// Pages through `{name}`'s results, sending each request after the first with its\n\
+         /// {token_doc}.{items_doc}\n\
+         {attributes}\
+         pub fn paginate_{method}(\n    \
+         &self,\n    \
+         input: {input},\n\
+         ) -> aws_sdk_rust_runtime::paginator::Paginator<\n    \
+         {input},\n    \
+         {output},\n    \
+         crate::error::SdkError<crate::error::{error}Error>,\n    \
+         {item},\n\
+         >\n\
+         where\n    \
+         C: 'static,\n\
+         {{\n    \
+         let client = self.clone();\n    \
+         aws_sdk_rust_runtime::paginator::Paginator::new(\n        \
+         input,\n        \
+         move |input| {{\n            \
+         let client = client.clone();\n            \
+         async move {{ client.{method}(input).await }}\n        \
+         }},\n        \
+         |mut input, output| {{\n{next_input}        \
+         }},\n        \
+         {items},\n    \
+         )\n\
+         }}\n",
+        name = op.name,
+        token_doc = token_doc,
+        items_doc = items_doc,
+        attributes = attributes,
+        method = method,
+        input = model_type(input),
+        output = model_type(output),
+        error = type_name(&op.name),
+        item = item,
+        next_input = indent(&next_input, 3),
+        items = items,
+    )))
+}
+
+// The type of a paginator's items, as the client module names it, for items that aren't
+// lists or maps themselves.
+fn item_type(graph: &ModelGraph<'_>, id: ShapeId) -> Option<String> {
+    match graph.shape(id)? {
+        Shape::List { .. } | Shape::Map { .. } => None,
+        Shape::Structure { .. }
+        | Shape::Union { .. }
+        | Shape::String {
+            enum_values: Some(_),
+            ..
+        } => Some(format!("crate::model::{}", rust_type(graph, id))),
+        _ => Some(rust_type(graph, id)),
+    }
+}
+
+// Compiles a paginator's JMESPath `expression` over a value of the shape `root` into an
+// expression of an `Option` of a reference to what it refers to, and that's shape. `value` is
+// an expression of a reference to the root value. Only the expressions paginators use are
+// supported: members and their members (`Paging.NextToken`), indexes into lists
+// (`Contents[-1].Key`), and alternatives (`NextMarker || Contents[-1].Key`).
+pub(crate) fn expression(
+    graph: &ModelGraph<'_>,
+    root: ShapeId,
+    expression: &str,
+    value: &str,
+) -> Result<(String, ShapeId), Err> {
+    let mut alternatives = expression.split("||").map(str::trim);
+    let first = alternatives.next().unwrap_or_default();
+    let (mut code, target) = path(graph, root, first, value)?;
+    for alternative in alternatives {
+        let (other, other_target) = path(graph, root, alternative, value)?;
+        if rust_type(graph, other_target) != rust_type(graph, target) {
+            return Err(format!("`{}`'s alternatives have different types", expression).into());
+        }
+        code = format!("{}.or({})", code, other);
+    }
+    Ok((code, target))
+}
+
+// Compiles one of an expression's alternatives: a `.`-separated path of members, any of
+// them indexed.
+fn path(
+    graph: &ModelGraph<'_>,
+    root: ShapeId,
+    path: &str,
+    value: &str,
+) -> Result<(String, ShapeId), Err> {
+    let unsupported = || format!("unsupported paginator expression `{}`", path);
+    let mut code = format!("Some({})", value);
+    let mut id = root;
+    for segment in path.split('.') {
+        let (member, index) = match segment.find('[') {
+            Some(start) => {
+                let index = segment[start..]
+                    .strip_prefix('[')
+                    .and_then(|index| index.strip_suffix(']'))
+                    .and_then(|index| index.parse::<i64>().ok())
+                    .ok_or_else(unsupported)?;
+                (&segment[..start], Some(index))
+            }
+            None => (segment, None),
+        };
+        let (fields, _) = struct_fields(graph, id).map_err(|_| unsupported())?;
+        let field = fields
+            .iter()
+            .find(|field| field.edge.name == member)
+            .ok_or_else(|| format!("`{}` has no member `{}`", id, member))?;
+        if field.is_optional() {
+            code.push_str(&format!(".and_then(|v| v.{}.as_ref())", field.name));
+        } else {
+            code.push_str(&format!(".map(|v| &v.{})", field.name));
+        }
+        if field.value_type().starts_with("Box<") {
+            code.push_str(".map(|v| &**v)");
+        }
+        id = field.edge.target;
+        if let Some(index) = index {
+            id = match graph.shape(id) {
+                Some(Shape::List { member, .. }) => member.shape.shape,
+                _ => return Err(unsupported().into()),
+            };
+            code.push_str(&match index {
+                -1 => ".and_then(|v| v.last())".to_string(),
+                index if index >= 0 => format!(".and_then(|v| v.get({}))", index),
+                _ => return Err(unsupported().into()),
+            });
+        }
+    }
+    Ok((code, id))
+}
+
+#[test]
+fn generates_paginate_methods() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    use crate::codegen::format::{format_code, Formatting};
+
+    let mut def =
+        crate::model::BotocoreModel::from_path("test-data/lambda/2015-03-31/service-2.json")?;
+    def.load_paginators("test-data/lambda/2015-03-31/paginators-1.json")?;
+    let graph = ModelGraph::new(&def);
+    let op = &def.operations["ListFunctions"];
+    let method = paginate_method(&graph, op, &def.paginators["ListFunctions"], "")?
+        .ok_or("`ListFunctions` has an output")?;
+    let code = format_code(
+        &format!("impl<C> Client<C> {{\n{}}}\n", method),
+        Formatting::Strict,
+    )?;
+    assert!(code.contains(
+        r#"    /// Pages through `ListFunctions`'s results, sending each request after the first with its
+    /// `Marker` set to the last response's `NextMarker`.
+    /// Its `items()` are each page's `Functions`.
+    pub fn paginate_list_functions(
+        &self,
+        input: crate::model::ListFunctionsRequest,
+    ) -> aws_sdk_rust_runtime::paginator::Paginator<
+        crate::model::ListFunctionsRequest,
+        crate::model::ListFunctionsResponse,
+        crate::error::SdkError<crate::error::ListFunctionsError>,
+        crate::model::FunctionConfiguration,
+    >
+    where
+        C: 'static,
+    {
+"#
+    ));
+    assert!(code.contains(
+        r#"            |mut input, output| {
+                let marker = Some(output).and_then(|v| v.next_marker.as_ref());
+                if marker.is_none() || input.marker.as_ref() == marker {
+                    return None;
+                }
+                input.marker = marker.cloned();
+                Some(input)
+            },
+            |output| {
+                Some(&output)
+                    .and_then(|v| v.functions.as_ref())
+                    .cloned()
+                    .unwrap_or_default()
+            },
+"#
+    ));
+
+    // Tokens can be taken from the last item on the page, if there's no token of its own.
+    let mut paginator = def.paginators["ListFunctions"].clone();
+    paginator.output_token = vec!["NextMarker || Functions[-1].FunctionName".to_string()];
+    paginator.result_key = vec!["NextMarker".to_string()];
+    let method = paginate_method(&graph, op, &paginator, "")?.ok_or("no method")?;
+    assert!(method.contains(
+        "let marker = Some(output).and_then(|v| v.next_marker.as_ref())\
+         .or(Some(output).and_then(|v| v.functions.as_ref()).and_then(|v| v.last())\
+         .and_then(|v| v.function_name.as_ref()));\n"
+    ));
+    assert!(method.contains("    std::convert::Infallible,\n"));
+    assert!(!method.contains("items()"));
+
+    paginator.output_token = vec!["Functions".to_string()];
+    assert!(paginate_method(&graph, op, &paginator, "").is_err());
+    paginator.output_token = vec!["Functions[].FunctionName".to_string()];
+    assert!(paginate_method(&graph, op, &paginator, "").is_err());
+
+    Ok(())
+}
@@ -0,0 +1,230 @@
+use super::{
+    error_deserializer,
+    json::{json_deserializers, json_serializers, JSON_ERROR_METADATA},
+    operation_serializer, response_deserializer, shape_deserializer, shape_serializer,
+};
+use crate::{
+    codegen::{allow_deprecated, features::OperationFeatures, operation_uses_deprecated},
+    graph::ModelGraph,
+    model::Operation,
+    naming::type_name,
+    Err,
+};
+
+/// Generates the awsJson protocol's request serializers and response deserializers: a
+/// `serialize_`, a `deserialize_`, and a `deserialize_..._error` function for each operation,
+/// and the `json_ser_` and `json_de_` functions they use, including the ones for the inputs,
+/// outputs, and errors.
+pub fn generate(graph: &ModelGraph<'_>, features: &OperationFeatures) -> Result<String, Err> {
+    let metadata = &graph.model().metadata;
+    let target_prefix = metadata
+        .target_prefix
+        .as_deref()
+        .ok_or("awsJson services need a `targetPrefix`")?;
+    let content_type = format!(
+        "application/x-amz-json-{}",
+        metadata.json_version.as_deref().unwrap_or("1.0")
+    );
+
+    let mut out = String::new();
+    for (_, op) in graph.operations() {
+        out.push_str(&serialize_operation(
+            graph,
+            op,
+            target_prefix,
+            &content_type,
+            features,
+        ));
+        out.push('\n');
+        out.push_str(&deserialize_operation(graph, op, features));
+        out.push('\n');
+        out.push_str(&error_deserializer(
+            graph,
+            op,
+            features,
+            "json",
+            "json_error_metadata",
+        )?);
+        out.push('\n');
+    }
+    out.push_str(JSON_ERROR_METADATA);
+    out.push('\n');
+    out.push_str(&json_serializers(
+        graph,
+        graph.operations().map(|(_, op)| op.input.shape),
+    )?);
+    out.push('\n');
+    out.push_str(&json_deserializers(
+        graph,
+        graph
+            .operations()
+            .flat_map(|(_, op)| op.output.iter().chain(&op.errors))
+            .map(|reference| reference.shape),
+    )?);
+    Ok(out)
+}
+
+// The function that turns a request builder, the endpoint, and the operation's input into a
+// request.
+//
+// Every awsJson request is a `POST` to `/`, whatever the model's `http` says, naming the
+// operation in its `X-Amz-Target` header, with the whole input as its JSON body.
+fn serialize_operation(
+    graph: &ModelGraph<'_>,
+    op: &Operation,
+    target_prefix: &str,
+    content_type: &str,
+    features: &OperationFeatures,
+) -> String {
+    let mut out = String::new();
+    if let Some(cfg) = features.cfg_attribute(&op.name) {
+        out.push_str(&cfg);
+        out.push('\n');
+    }
+    out.push_str(allow_deprecated(operation_uses_deprecated(graph, op)));
+    out.push_str(&format!(
+        "pub(crate) fn {}(\n\
+         request: http::request::Builder,\n\
+         endpoint: &str,\n\
+         input: &{},\n\
+         ) -> Result<http::Request<aws_sdk_rust_runtime::body::Body>, crate::error::BoxError> {{\n\
+         let body = serde_json::to_vec(&{}(input))?;\n\
+         Ok(request\n\
+         .method(\"POST\")\n\
+         .uri(format!(\"{{}}/\", endpoint))\n\
+         .header(\"X-Amz-Target\", {:?})\n\
+         .header(\"Content-Type\", {:?})\n\
+         .body(body.into())?)\n\
+         }}\n",
+        operation_serializer(&op.name),
+        type_name(op.input.shape.as_str()),
+        shape_serializer("json", op.input.shape),
+        format!("{}.{}", target_prefix, op.name),
+        content_type
+    ));
+    out
+}
+
+// The operation's response deserializer; see `response_deserializer`. The whole body is the
+// output as a JSON object, and an empty one is read as if it were `{}`.
+fn deserialize_operation(
+    graph: &ModelGraph<'_>,
+    op: &Operation,
+    features: &OperationFeatures,
+) -> String {
+    let code = match &op.output {
+        Some(output) => format!(
+            "let body = response.body();\n\
+             let parsed = if body.is_empty() {{\n\
+             serde_json::Value::Object(Default::default())\n\
+             }} else {{\n\
+             serde_json::from_slice::<serde_json::Value>(body)?\n\
+             }};\n\
+             {}(&parsed)\n",
+            shape_deserializer("json", output.shape)
+        ),
+        None => "Ok(())\n".to_string(),
+    };
+    response_deserializer(graph, op, features, false, &code)
+}
+
+#[test]
+fn generates_aws_json_serializers() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    use crate::codegen::format::{format_code, Formatting};
+
+    let mut def = crate::model::BotocoreModel::from_path("test-data/recursive.json")?;
+    let graph = ModelGraph::new(&def);
+    let code = generate(&graph, &OperationFeatures::default())?;
+    let code = format_code(&code, Formatting::Strict)?;
+    assert!(code.starts_with(
+        r#"pub(crate) fn serialize_put_item(
+    request: http::request::Builder,
+    endpoint: &str,
+    input: &PutItemInput,
+) -> Result<http::Request<aws_sdk_rust_runtime::body::Body>, crate::error::BoxError> {
+    let body = serde_json::to_vec(&json_ser_put_item_input(input))?;
+    Ok(
+        request
+            .method("POST")
+            .uri(format!("{}/", endpoint))
+            .header("X-Amz-Target", "Recursive_20200101.PutItem")
+            .header("Content-Type", "application/x-amz-json-1.0")
+            .body(body.into())?,
+    )
+}
+"#
+    ));
+    assert!(code.contains("pub(crate) fn json_ser_put_item_input(input: &PutItemInput)"));
+    assert!(code.contains(
+        r#"pub(crate) fn deserialize_put_item(
+    response: &http::Response<Vec<u8>>,
+) -> Result<PutItemOutput, crate::error::BoxError> {
+    let body = response.body();
+    let parsed = if body.is_empty() {
+        serde_json::Value::Object(Default::default())
+    } else {
+        serde_json::from_slice::<serde_json::Value>(body)?
+    };
+    json_de_put_item_output(&parsed)
+}
+"#
+    ));
+    assert!(code.contains("pub(crate) fn json_de_attribute_value(\n"));
+
+    def.metadata.json_version = Some("1.1".to_string());
+    let graph = ModelGraph::new(&def);
+    let code = generate(&graph, &OperationFeatures::default())?;
+    assert!(code.contains(".header(\"Content-Type\", \"application/x-amz-json-1.1\")\n"));
+
+    def.metadata.target_prefix = None;
+    let graph = ModelGraph::new(&def);
+    assert!(generate(&graph, &OperationFeatures::default()).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn generates_aws_json_error_deserializers() -> Result<(), Box<dyn std::error::Error + Send + Sync>>
+{
+    use crate::codegen::format::{format_code, Formatting};
+
+    let def = crate::model::BotocoreModel::from_path("test-data/recursive.json")?;
+    let graph = ModelGraph::new(&def);
+    let code = format_code(
+        &generate(&graph, &OperationFeatures::default())?,
+        Formatting::Strict,
+    )?;
+    assert!(code.contains(
+        r#"pub(crate) fn deserialize_put_item_error(
+    response: &http::Response<Vec<u8>>,
+) -> crate::error::SdkError<crate::error::PutItemError> {
+    let (meta, parsed) = match json_error_metadata(response) {
+        Ok(metadata) => metadata,
+        Err(error) => return crate::error::SdkError::Response(error),
+    };
+    let error = match meta.code().and_then(crate::error::PutItemError::modeled_error) {
+        Some("ValidationException") => {
+            json_de_validation_exception(&parsed)
+                .map(crate::error::PutItemError::ValidationException)
+        }
+        _ => {
+            Ok(
+                crate::error::PutItemError::Unhandled(
+                    crate::error::UnhandledError::new(
+                        meta.code().map(str::to_string),
+                        meta.message().map(str::to_string),
+                    ),
+                ),
+            )
+        }
+    };
+"#
+    ));
+    assert_eq!(
+        code.matches("pub(crate) fn json_error_metadata(\n").count(),
+        1
+    );
+    assert!(code.contains("pub(crate) fn json_de_validation_exception(\n"));
+
+    Ok(())
+}
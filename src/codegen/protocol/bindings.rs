@@ -0,0 +1,679 @@
+use super::{
+    event_stream::event_deserializer_name, field_value, parse_wire_string, response_deserializer,
+    shape_deserializer, wire_string, with_field,
+};
+use crate::{
+    codegen::{
+        customizations::ServiceCustomizations, features::OperationFeatures, structs::struct_fields,
+    },
+    graph::ModelGraph,
+    model::{Location, Operation, Shape, ShapeId, ShapeMember, TimestampFormat, UriSegment},
+    Err,
+};
+
+/// Code declaring `uri`, a mutable `String`, holding the operation's request URI with its
+/// labels filled in from the members of `input` bound to them. Labels are percent-encoded as
+/// single path segments, except for greedy ones, which keep their `/`s.
+pub fn expand_uri(graph: &ModelGraph<'_>, op: &Operation) -> Result<String, Err> {
+    let input = op.input.shape;
+    let template = &op.http.request_uri;
+    let (fields, _) = struct_fields(graph, input)?;
+    let mut out = String::new();
+    for segment in &template.path {
+        let code = match segment {
+            UriSegment::Literal(text) => format!("uri.push_str({:?});\n", text),
+            UriSegment::Label { name, greedy } => {
+                let field = fields
+                    .iter()
+                    .find(|field| {
+                        field.edge.member.location(field.edge.name) == Location::Uri(name.clone())
+                    })
+                    .ok_or_else(|| {
+                        format!(
+                            "`{}`'s request URI `{}` has a label no member of `{}` is bound to",
+                            op.name, template, input
+                        )
+                    })?;
+                let code = format!(
+                    "uri.push_str(&aws_sdk_rust_runtime::encoding::{}(&{}));\n",
+                    if *greedy {
+                        "percent_encode_greedy"
+                    } else {
+                        "percent_encode"
+                    },
+                    wire_string(
+                        graph,
+                        field.edge.member,
+                        field.edge.target,
+                        "value",
+                        TimestampFormat::Iso8601
+                    )?
+                );
+                let missing = format!(
+                    "return Err({:?}.into());\n",
+                    format!("`{}` is required to build the request URI", field.name)
+                );
+                with_field(field, &code, Some(&missing))
+            }
+        };
+        out.push_str(&code);
+    }
+    if let Some(query) = &template.query {
+        out.push_str(&format!("uri.push_str({:?});\n", format!("?{}", query)));
+    }
+    Ok(format!("let mut uri = String::new();\n{}", out))
+}
+
+/// Code binding the members of `input` that REST protocols send in the query string and
+/// headers; see `expand_uri` for the ones in the request URI's path.
+///
+/// The code expects a mutable `uri`, a `String` holding the request URI, and a mutable
+/// `request`, an `http::request::Builder`. Query parameters are appended to `uri`; headers
+/// are set on `request`, failing if their values have control characters in them. Timestamps go in query strings as ISO 8601 and in headers as HTTP
+/// dates, unless the model says otherwise.
+///
+/// List members repeat their parameter for each item. Map members add a parameter for each
+/// entry, named for its key, after the rest, and leave out any already bound to a member.
+/// Lists in headers are joined with commas, and `jsonvalue` headers are base64-encoded.
+pub fn serialize_bindings(graph: &ModelGraph<'_>, input: ShapeId) -> Result<String, Err> {
+    let payload = graph.shape(input).and_then(Shape::payload);
+    let (fields, _) = struct_fields(graph, input)?;
+    let bound = fields
+        .iter()
+        .filter_map(|field| match field.edge.member.location(field.edge.name) {
+            Location::QueryString(parameter) => match graph.shape(field.edge.target) {
+                Some(Shape::Map { .. }) => None,
+                _ => Some(parameter),
+            },
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    let mut out = String::new();
+    let mut maps = String::new();
+    for field in &fields {
+        let (name, member, target) = (field.edge.name, field.edge.member, field.edge.target);
+        if Some(name) == payload {
+            continue;
+        }
+        let scalar = |expr: &str, format| wire_string(graph, member, target, expr, format);
+        let code = match member.location(name) {
+            Location::QueryString(parameter) => {
+                let item = |member: &ShapeMember, expr: &str| {
+                    wire_string(
+                        graph,
+                        member,
+                        member.shape.shape,
+                        expr,
+                        TimestampFormat::Iso8601,
+                    )
+                };
+                let code = match graph.shape(target) {
+                    Some(Shape::List { member, .. }) => format!(
+                        "for value in value {{\n\
+                         aws_sdk_rust_runtime::encoding::push_query(&mut uri, {:?}, &{});\n\
+                         }}\n",
+                        parameter,
+                        item(member, "value")?
+                    ),
+                    Some(Shape::Map { key, value, .. }) => {
+                        let push = match graph.shape(value.shape.shape) {
+                            Some(Shape::List { member, .. }) => format!(
+                                "for value in value {{\n\
+                                 aws_sdk_rust_runtime::encoding::push_query(&mut uri, &key, &{});\n\
+                                 }}\n",
+                                item(member, "value")?
+                            ),
+                            _ => format!(
+                                "aws_sdk_rust_runtime::encoding::push_query(&mut uri, &key, &{});\n",
+                                item(value, "value")?
+                            ),
+                        };
+                        let push = if bound.is_empty() {
+                            push
+                        } else {
+                            format!("if !{:?}.contains(&key.as_str()) {{\n{}}}\n", bound, push)
+                        };
+                        let code = format!(
+                            "for (key, value) in value {{\n\
+                             let key = {};\n\
+                             {}\
+                             }}\n",
+                            item(key, "key")?,
+                            push
+                        );
+                        maps.push_str(&with_field(field, &code, None));
+                        continue;
+                    }
+                    _ => format!(
+                        "aws_sdk_rust_runtime::encoding::push_query(&mut uri, {:?}, &{});\n",
+                        parameter,
+                        scalar("value", TimestampFormat::Iso8601)?
+                    ),
+                };
+                with_field(field, &code, None)
+            }
+            Location::Header(header) => {
+                let value = match graph.shape(target) {
+                    Some(Shape::List { member, .. }) => {
+                        let item = wire_string(
+                            graph,
+                            member,
+                            member.shape.shape,
+                            "value",
+                            TimestampFormat::Rfc822,
+                        )?;
+                        // HTTP dates have commas in them, but they're never quoted.
+                        match graph.shape(member.shape.shape) {
+                            Some(Shape::Timestamp { .. }) => format!(
+                                "value.iter().map(|value| {}).collect::<Vec<_>>().join(\", \")",
+                                item
+                            ),
+                            _ => format!(
+                                "aws_sdk_rust_runtime::encoding::header_list(\
+                                 value.iter().map(|value| {}))",
+                                item
+                            ),
+                        }
+                    }
+                    _ => scalar("value", TimestampFormat::Rfc822)?,
+                };
+                let code = format!(
+                    "request = request.header({:?}, \
+                     aws_sdk_rust_runtime::encoding::header_value({:?}, {})?);\n",
+                    header, header, value
+                );
+                with_field(field, &code, None)
+            }
+            Location::JsonHeader(header) => {
+                let code = format!(
+                    "request = request.header({:?}, \
+                     aws_sdk_rust_runtime::encoding::base64_encode(value.as_bytes()));\n",
+                    header
+                );
+                with_field(field, &code, None)
+            }
+            Location::Headers { prefix } => {
+                let value = match graph.shape(target) {
+                    Some(Shape::Map { value, .. }) => wire_string(
+                        graph,
+                        value,
+                        value.shape.shape,
+                        "value",
+                        TimestampFormat::Rfc822,
+                    )?,
+                    _ => return Err(format!("`{}.{}` isn't a map", input, name).into()),
+                };
+                let code = format!(
+                    "for (key, value) in value {{\n\
+                     let name = format!(\"{}{{}}\", key);\n\
+                     let value = aws_sdk_rust_runtime::encoding::header_value(&name, {})?;\n\
+                     request = request.header(name, value);\n\
+                     }}\n",
+                    prefix, value
+                );
+                with_field(field, &code, None)
+            }
+            Location::Uri(_) | Location::StatusCode | Location::Payload | Location::Body(_) => {
+                continue
+            }
+        };
+        out.push_str(&code);
+    }
+    out.push_str(&maps);
+    Ok(out)
+}
+
+/// Code setting the members of `output`, a mutable output structure, that REST protocols
+/// receive in the status code and headers of `response`, an `&http::Response<Vec<u8>>`.
+///
+/// This undoes `serialize_bindings`. Prefix headers are matched case-insensitively, as header
+/// names are, and required members missing from the response are an error.
+pub fn deserialize_bindings(graph: &ModelGraph<'_>, output: ShapeId) -> Result<String, Err> {
+    let customizations = ServiceCustomizations::for_service(&graph.model().metadata);
+    let payload = graph.shape(output).and_then(Shape::payload);
+    let (fields, _) = struct_fields(graph, output)?;
+    let mut out = String::new();
+    for field in &fields {
+        let (name, member, target) = (field.edge.name, field.edge.member, field.edge.target);
+        if Some(name) == payload {
+            continue;
+        }
+        let missing = if field.is_optional() {
+            String::new()
+        } else {
+            format!(
+                " else {{\nreturn Err({:?}.into());\n}}",
+                format!("`{}.{}` is missing", output, name)
+            )
+        };
+        let header = |header: &str, value: String| {
+            format!(
+                "if let Some(value) = response.headers().get({:?}) {{\n\
+                 let value = value.to_str()?;\n\
+                 output.{} = {};\n\
+                 }}{}\n",
+                header,
+                field.name,
+                field_value(field, &value),
+                missing
+            )
+        };
+        let code = match member.location(name) {
+            Location::StatusCode => format!(
+                "output.{} = {};\n",
+                field.name,
+                field_value(field, "response.status().as_u16() as i32")
+            ),
+            Location::Header(name) => {
+                let value = match graph.shape(target) {
+                    Some(Shape::List { member, .. }) => {
+                        let split = match graph.shape(member.shape.shape) {
+                            Some(Shape::Timestamp { .. }) => {
+                                "aws_sdk_rust_runtime::encoding::split_http_dates(value)"
+                            }
+                            _ => "aws_sdk_rust_runtime::encoding::split_header_list(value)?",
+                        };
+                        format!(
+                            "{{\n\
+                             let mut items = Vec::new();\n\
+                             for value in {} {{\n\
+                             let value = value.as_str();\n\
+                             items.push({});\n\
+                             }}\n\
+                             items\n\
+                             }}",
+                            split,
+                            parse_wire_string(
+                                graph,
+                                member,
+                                member.shape.shape,
+                                "value",
+                                TimestampFormat::Rfc822
+                            )?
+                        )
+                    }
+                    _ => {
+                        parse_wire_string(graph, member, target, "value", TimestampFormat::Rfc822)?
+                    }
+                };
+                if field.is_optional() && customizations.is_lenient_header(&name) {
+                    format!(
+                        "if let Some(value) = response.headers().get({:?}) {{\n\
+                         output.{} = (|| -> Result<_, Box<dyn std::error::Error + Send + Sync>> {{\n\
+                         let value = value.to_str()?;\n\
+                         Ok({})\n\
+                         }})()\n\
+                         .ok();\n\
+                         }}\n",
+                        name, field.name, value
+                    )
+                } else {
+                    header(&name, value)
+                }
+            }
+            Location::JsonHeader(name) => header(
+                &name,
+                "String::from_utf8(aws_sdk_rust_runtime::encoding::base64_decode(value)?)?"
+                    .to_string(),
+            ),
+            Location::Headers { prefix } => {
+                let (key, value) = match graph.shape(target) {
+                    Some(Shape::Map { key, value, .. }) => (key, value),
+                    _ => return Err(format!("`{}.{}` isn't a map", output, name).into()),
+                };
+                format!(
+                    "{{\n\
+                     let mut headers = std::collections::HashMap::new();\n\
+                     for (name, value) in response.headers() {{\n\
+                     if let Some(key) = name.as_str().strip_prefix({:?}) {{\n\
+                     let value = value.to_str()?;\n\
+                     headers.insert({}, {});\n\
+                     }}\n\
+                     }}\n\
+                     {}\
+                     }}\n",
+                    prefix.to_ascii_lowercase(),
+                    parse_wire_string(graph, key, key.shape.shape, "key", TimestampFormat::Rfc822)?,
+                    parse_wire_string(
+                        graph,
+                        value,
+                        value.shape.shape,
+                        "value",
+                        TimestampFormat::Rfc822
+                    )?,
+                    if field.is_optional() {
+                        format!(
+                            "if !headers.is_empty() {{\noutput.{} = Some(headers);\n}}\n",
+                            field.name
+                        )
+                    } else {
+                        format!("output.{} = headers;\n", field.name)
+                    }
+                )
+            }
+            Location::Uri(_) | Location::QueryString(_) | Location::Payload | Location::Body(_) => {
+                continue
+            }
+        };
+        out.push_str(&code);
+    }
+    Ok(out)
+}
+
+/// Code setting the payload member of `output`, a mutable output structure, from `body`, the
+/// response's body as a `&Vec<u8>`, or `None` if the output has no payload. Blobs are the body
+/// as it is, or if they're streaming, a `ByteStream` of `stream`, the body as it comes in (see
+/// `streams_payload`). Strings are the body as UTF-8, documents the body as JSON, and event
+/// streams an `EventReceiver` of the messages in `stream`. Structures and
+/// unions are read by `read`, which wraps an expression for the body's bytes, and are left
+/// unset if the body is empty.
+pub fn deserialize_payload(
+    graph: &ModelGraph<'_>,
+    output: ShapeId,
+    read: impl Fn(ShapeId, &str) -> String,
+) -> Result<Option<String>, Err> {
+    let payload = match graph.shape(output).and_then(Shape::payload) {
+        Some(payload) => payload,
+        None => return Ok(None),
+    };
+    let (fields, _) = struct_fields(graph, output)?;
+    let field = fields
+        .iter()
+        .find(|field| field.edge.name == payload)
+        .ok_or_else(|| format!("`{}` has no payload member `{}`", output, payload))?;
+    let assign = |value: &str| format!("output.{} = {};\n", field.name, field_value(field, value));
+    Ok(Some(match graph.shape(field.edge.target) {
+        Some(Shape::Blob {
+            streaming: true, ..
+        }) => assign("aws_sdk_rust_runtime::byte_stream::ByteStream::from(stream)"),
+        Some(Shape::Blob { .. }) => assign("body.clone()"),
+        Some(Shape::EventStream { .. }) => assign(&format!(
+            "aws_sdk_rust_runtime::event_stream::EventReceiver::new(stream, {})",
+            event_deserializer_name(field.edge.target)
+        )),
+        Some(Shape::String { .. }) => assign("String::from_utf8(body.clone())?"),
+        Some(Shape::Document { .. }) => format!(
+            "if !body.is_empty() {{\n{}}}\n",
+            assign("serde_json::from_slice(body)?")
+        ),
+        Some(Shape::Structure { .. }) | Some(Shape::Union { .. }) => format!(
+            "if !body.is_empty() {{\n{}}}\n",
+            assign(&read(field.edge.target, "body"))
+        ),
+        _ => {
+            return Err(format!(
+                "`{}`'s payload `{}` can't be read from a body",
+                output, payload
+            )
+            .into())
+        }
+    }))
+}
+
+/// Whether `output`'s payload is a streaming blob or an event stream, which are read from their
+/// response's body as it comes in rather than once all of it has.
+pub fn streams_payload(graph: &ModelGraph<'_>, output: ShapeId) -> bool {
+    graph
+        .shape(output)
+        .and_then(Shape::payload)
+        .and_then(|payload| {
+            graph
+                .neighbors(output)
+                .iter()
+                .find(|edge| edge.name == payload)
+        })
+        .is_some_and(|edge| {
+            matches!(
+                graph.shape(edge.target),
+                Some(Shape::Blob {
+                    streaming: true,
+                    ..
+                }) | Some(Shape::EventStream { .. })
+            )
+        })
+}
+
+/// The response deserializer for an operation of a REST protocol; see
+/// `response_deserializer`. Its output is read from the body by the `{format}_de_` function
+/// for it, with `empty` standing in for an empty body, or for the whole body if the output
+/// has a payload member. `parse` wraps an expression for the body's bytes to parse them into
+/// what the `{format}_de_` functions read. Members bound to the status code and headers are
+/// read from there.
+pub fn rest_response_deserializer(
+    graph: &ModelGraph<'_>,
+    op: &Operation,
+    features: &OperationFeatures,
+    format: &str,
+    empty: &str,
+    parse: impl Fn(&str) -> String,
+) -> Result<String, Err> {
+    let output = match &op.output {
+        Some(output) => output.shape,
+        None => {
+            return Ok(response_deserializer(
+                graph, op, features, false, "Ok(())\n",
+            ))
+        }
+    };
+    let deserializer = shape_deserializer(format, output);
+    let payload = deserialize_payload(graph, output, |id, body| {
+        format!("{}(&{})?", shape_deserializer(format, id), parse(body))
+    })?;
+    let body = match payload {
+        Some(payload) => format!(
+            "let mut output = {}(&{})?;\n{}",
+            deserializer, empty, payload
+        ),
+        None => format!(
+            "let parsed = if body.is_empty() {{\n{}\n}} else {{\n{}\n}};\n\
+             let mut output = {}(&parsed)?;\n",
+            empty,
+            parse("body"),
+            deserializer
+        ),
+    };
+    let stream = streams_payload(graph, output);
+    Ok(response_deserializer(
+        graph,
+        op,
+        features,
+        stream,
+        &format!(
+            "{}{}{}Ok(output)\n",
+            if stream {
+                ""
+            } else {
+                "let body = response.body();\n"
+            },
+            body,
+            deserialize_bindings(graph, output)?
+        ),
+    ))
+}
+
+#[test]
+fn serializes_http_bindings() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let def = crate::model::BotocoreModel::from_path("test-data/lambda/2015-03-31/service-2.json")?;
+    let graph = ModelGraph::new(&def);
+    let code = expand_uri(&graph, &def.operations["GetFunction"])?;
+    assert_eq!(
+        code,
+        "let mut uri = String::new();\n\
+         uri.push_str(\"/2015-03-31/functions/\");\n\
+         {\n\
+         let value = &input.function_name;\n\
+         uri.push_str(&aws_sdk_rust_runtime::encoding::percent_encode(&value.to_string()));\n\
+         }\n"
+    );
+    let code = serialize_bindings(&graph, ShapeId::from("GetFunctionRequest"))?;
+    assert_eq!(
+        code,
+        "if let Some(value) = &input.qualifier {\n\
+         aws_sdk_rust_runtime::encoding::push_query(&mut uri, \"Qualifier\", &value.to_string());\n\
+         }\n"
+    );
+
+    let code = serialize_bindings(&graph, ShapeId::from("InvocationRequest"))?;
+    assert!(code.contains(
+        "request = request.header(\"X-Amz-Invocation-Type\", \
+         aws_sdk_rust_runtime::encoding::header_value(\"X-Amz-Invocation-Type\", \
+         value.as_str().to_string())?);\n"
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn serializes_query_string_bindings() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    use crate::codegen::format::{format_code, Formatting};
+
+    let def = crate::model::BotocoreModel::from_path("test-data/buckets.json")?;
+    let graph = ModelGraph::new(&def);
+    let code = format_code(
+        &format!(
+            "fn f() {{\n{}}}\n",
+            serialize_bindings(&graph, ShapeId::from("ListObjectsRequest"))?
+        ),
+        Formatting::Strict,
+    )?;
+    assert!(code.contains("&aws_sdk_rust_runtime::time::format_iso8601(*value),\n"));
+    assert!(code.contains(
+        r#"        for value in value {
+            aws_sdk_rust_runtime::encoding::push_query(
+                &mut uri,
+                "storage-class",
+                &value.as_str().to_string(),
+            );
+        }
+"#
+    ));
+    // Map entries come last, and don't override parameters bound to members.
+    assert!(code.ends_with(
+        r#"    if let Some(value) = &input.filters {
+        for (key, value) in value {
+            let key = key.to_string();
+            if ![
+                "encoding-type",
+                "fetch-owner",
+                "max-keys",
+                "modified-since",
+                "prefix",
+                "storage-class",
+            ]
+                .contains(&key.as_str())
+            {
+                for value in value {
+                    aws_sdk_rust_runtime::encoding::push_query(
+                        &mut uri,
+                        &key,
+                        &value.to_string(),
+                    );
+                }
+            }
+        }
+    }
+}
+"#
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn serializes_header_bindings() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    use crate::codegen::format::{format_code, Formatting};
+
+    let def = crate::model::BotocoreModel::from_path("test-data/buckets.json")?;
+    let graph = ModelGraph::new(&def);
+    let code = format_code(
+        &format!(
+            "fn f() {{\n{}}}\n",
+            serialize_bindings(&graph, ShapeId::from("GetObjectRequest"))?
+        ),
+        Formatting::Strict,
+    )?;
+    assert!(code.contains(
+        r#"                aws_sdk_rust_runtime::encoding::header_value(
+                    "If-Modified-Since",
+                    aws_sdk_rust_runtime::time::format_http_date(*value),
+                )?,
+"#
+    ));
+    assert!(code.contains(
+        r#"                    aws_sdk_rust_runtime::encoding::header_list(
+                        value.iter().map(|value| value.to_string()),
+                    ),
+"#
+    ));
+    assert!(code.contains(".join(\", \"),\n"));
+    assert!(code.contains("aws_sdk_rust_runtime::encoding::base64_encode(value.as_bytes()),\n"));
+    assert!(code.contains(
+        r#"        for (key, value) in value {
+            let name = format!("x-amz-meta-{}", key);
+            let value = aws_sdk_rust_runtime::encoding::header_value(
+                &name,
+                value.to_string(),
+            )?;
+            request = request.header(name, value);
+        }
+"#
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn deserializes_http_bindings() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    use crate::codegen::format::{format_code, Formatting};
+
+    let def = crate::model::BotocoreModel::from_path("test-data/buckets.json")?;
+    let graph = ModelGraph::new(&def);
+    let code = format_code(
+        &rest_response_deserializer(
+            &graph,
+            &def.operations["GetObject"],
+            &OperationFeatures::default(),
+            "xml",
+            "aws_sdk_rust_runtime::xml::Element::default()",
+            |body| format!("aws_sdk_rust_runtime::xml::parse({})?", body),
+        )?,
+        Formatting::Strict,
+    )?;
+    assert!(code.contains(
+        r#"    response: &http::Response<Vec<u8>>,
+    stream: aws_sdk_rust_runtime::body::Body,
+) -> Result<GetObjectOutput, crate::error::BoxError> {
+    let mut output = xml_de_get_object_output(
+        &aws_sdk_rust_runtime::xml::Element::default(),
+    )?;
+    output.body = Some(aws_sdk_rust_runtime::byte_stream::ByteStream::from(stream));
+    if let Some(value) = response.headers().get("Content-Length") {
+        let value = value.to_str()?;
+        output.content_length = Some(value.parse::<i64>()?);
+    }
+"#
+    ));
+    assert!(code.contains(
+        r#"        for (name, value) in response.headers() {
+            if let Some(key) = name.as_str().strip_prefix("x-amz-meta-") {
+                let value = value.to_str()?;
+                headers.insert(key.to_string(), value.to_string());
+            }
+        }
+        if !headers.is_empty() {
+            output.metadata = Some(headers);
+        }
+"#
+    ));
+    assert!(
+        code.contains("for value in aws_sdk_rust_runtime::encoding::split_header_list(value)? {\n")
+    );
+    // S3's `Expires` is whatever the object was stored with.
+    assert!(code.contains(
+        "        output.expires = (|| -> Result<_, Box<dyn std::error::Error + Send + Sync>> {\n"
+    ));
+    assert!(code.contains("        .ok();\n"));
+    assert!(code.ends_with("    Ok(output)\n}\n"));
+
+    Ok(())
+}
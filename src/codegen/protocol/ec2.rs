@@ -0,0 +1,46 @@
+use super::query::{generate_dialect, Dialect};
+use crate::{codegen::features::OperationFeatures, graph::ModelGraph, Err};
+
+/// Generates the EC2 protocol's request serializers, a `serialize_` function for each
+/// operation, and the `ec2_ser_` functions they use for the structures and unions in their
+/// inputs. It's the query protocol but for its parameter names: see `query::Dialect::Ec2`.
+/// Responses are XML, so the `xml_de_` functions for outputs and errors are generated too.
+pub fn generate(graph: &ModelGraph<'_>, features: &OperationFeatures) -> Result<String, Err> {
+    generate_dialect(graph, Dialect::Ec2, features)
+}
+
+#[test]
+fn generates_ec2_serializers() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    use crate::codegen::format::{format_code, Formatting};
+
+    let def = crate::model::BotocoreModel::from_path("test-data/instances.json")?;
+    let graph = ModelGraph::new(&def);
+    let code = format_code(
+        &generate(&graph, &OperationFeatures::default())?,
+        Formatting::Strict,
+    )?;
+    assert!(code.contains(
+        r#"    aws_sdk_rust_runtime::encoding::push_form(&mut form, "Action", "DescribeInstances");
+    aws_sdk_rust_runtime::encoding::push_form(&mut form, "Version", "2016-11-15");
+    ec2_ser_describe_instances_request(&mut form, "", input);
+"#
+    ));
+    assert!(code.contains(
+        r#"    if let Some(value) = &input.filters {
+        let name = format!("{}Filter", prefix);
+        for (i, value) in value.iter().enumerate() {
+            let name = format!("{}.{}", name, i + 1);
+            ec2_ser_filter(form, &format!("{}.", name), value);
+        }
+    }
+"#
+    ));
+    assert!(code.contains(r#"let name = format!("{}DryRun", prefix);"#));
+    assert!(code.contains(r#"let name = format!("{}Token", prefix);"#));
+    assert!(code.contains(r#"let name = format!("{}Value", prefix);"#));
+    assert!(!code.contains(".member."));
+    assert!(!code.contains("if value.is_empty() {"));
+    assert!(code.contains("pub(crate) fn xml_de_describe_instances_result("));
+
+    Ok(())
+}
@@ -0,0 +1,510 @@
+use super::{field_value, shape_deserializer, shape_serializer, with_field};
+use crate::{
+    codegen::{
+        allow_deprecated,
+        structs::{field_type, struct_fields},
+        unions::variant_names,
+        uses_deprecated,
+    },
+    graph::ModelGraph,
+    model::{Shape, ShapeId},
+    naming::type_name,
+    visit::{walk_shape, ShapeVisitor},
+    Err,
+};
+use std::collections::BTreeSet;
+
+/// Generates the `event_de_` function for every event stream `roots` reference, in name
+/// order. Each decodes a message of the stream into the variant of its enum the message's
+/// event type names, or `Unknown` if it's an event the enum doesn't list. Unlisted exceptions
+/// are an error, as are error messages.
+///
+/// An event's `eventpayload` member is the message's payload, as an operation's payload
+/// member is its response's body, and its `eventheader` members are read from the message's
+/// headers. Events without a payload member are read from the payload by the `{format}_de_`
+/// function for them, with `empty` standing in for an empty payload and `parse` wrapping an
+/// expression for its bytes to parse them, as for response bodies.
+pub fn event_deserializers(
+    graph: &ModelGraph<'_>,
+    roots: impl IntoIterator<Item = ShapeId>,
+    format: &str,
+    empty: &str,
+    parse: impl Fn(&str) -> String,
+) -> Result<String, Err> {
+    let mut out = String::new();
+    for id in event_streams(graph, roots) {
+        if !out.is_empty() {
+            out.push('\n');
+        }
+        out.push_str(&event_deserializer(graph, id, format, empty, &parse)?);
+    }
+    Ok(out)
+}
+
+/// Generates the `event_ser_` function for every event stream `roots` reference, in name
+/// order. Each encodes an event of the stream as the message `event_deserializers` decodes,
+/// with a `:content-type` header saying what its payload is. Payloads that aren't blobs or
+/// strings are `content_type`, serialized as `serialize` says: it wraps a shape to serialize
+/// and an expression for a reference to it into an expression for its bytes.
+pub fn event_serializers(
+    graph: &ModelGraph<'_>,
+    roots: impl IntoIterator<Item = ShapeId>,
+    content_type: &str,
+    serialize: impl Fn(ShapeId, &str) -> String,
+) -> Result<String, Err> {
+    let mut out = String::new();
+    for id in event_streams(graph, roots) {
+        if !out.is_empty() {
+            out.push('\n');
+        }
+        out.push_str(&event_serializer(graph, id, content_type, &serialize)?);
+    }
+    Ok(out)
+}
+
+// The event streams `roots` reference, including themselves.
+fn event_streams(
+    graph: &ModelGraph<'_>,
+    roots: impl IntoIterator<Item = ShapeId>,
+) -> BTreeSet<ShapeId> {
+    #[derive(Default)]
+    struct EventStreams(BTreeSet<ShapeId>);
+
+    impl ShapeVisitor<'_> for EventStreams {
+        fn visit_shape(&mut self, id: ShapeId, shape: &Shape) -> bool {
+            if let Shape::EventStream { .. } = shape {
+                self.0.insert(id);
+            }
+            true
+        }
+    }
+
+    let mut streams = EventStreams::default();
+    for root in roots {
+        walk_shape(graph, root, &mut streams);
+    }
+    streams.0
+}
+
+/// The name of the generated function decoding a message of an event stream, e.g.
+/// `event_de_select_object_content_event_stream`, which `EventReceiver`s of it are made with.
+pub fn event_deserializer_name(id: ShapeId) -> String {
+    shape_deserializer("event", id)
+}
+
+fn event_deserializer(
+    graph: &ModelGraph<'_>,
+    id: ShapeId,
+    format: &str,
+    empty: &str,
+    parse: &dyn Fn(&str) -> String,
+) -> Result<String, Err> {
+    let name = type_name(id.as_str());
+    let variants = variant_names(graph, id)?;
+    let mut arms = String::new();
+    for edge in graph.neighbors(id) {
+        let target = graph.shape(edge.target).ok_or_else(|| {
+            format!(
+                "`{}.{}` targets undefined shape `{}`",
+                id, edge.name, edge.target
+            )
+        })?;
+        let event = read_event(graph, edge.target, format, empty, parse)?;
+        arms.push_str(&format!(
+            "{:?} => {}::{}({}),\n",
+            edge.name,
+            name,
+            variants[edge.name],
+            if field_type(graph, edge, target, true).starts_with("Box<") {
+                format!("Box::new({})", event)
+            } else {
+                event
+            }
+        ));
+    }
+    Ok(format!(
+        "{}pub(crate) fn {}(\n\
+         message: &aws_sdk_rust_runtime::event_stream::Message,\n\
+         ) -> Result<{}, crate::error::BoxError> {{\n\
+         let payload = &message.payload;\n\
+         Ok(match message.event_type()? {{\n\
+         {}\
+         other if message.is_exception() => {{\n\
+         return Err(format!(\"the stream failed with exception `{{}}`\", other).into())\n\
+         }}\n\
+         _ => {}::Unknown,\n\
+         }})\n\
+         }}\n",
+        allow_deprecated(uses_deprecated(graph, id)),
+        event_deserializer_name(id),
+        name,
+        arms,
+        name
+    ))
+}
+
+// A block evaluating to an event, a structure, read from `message` and `payload`.
+fn read_event(
+    graph: &ModelGraph<'_>,
+    id: ShapeId,
+    format: &str,
+    empty: &str,
+    parse: &dyn Fn(&str) -> String,
+) -> Result<String, Err> {
+    let shape = graph
+        .shape(id)
+        .ok_or_else(|| format!("`{}` isn't defined", id))?;
+    let payload = shape.payload();
+    let (fields, _) = struct_fields(graph, id)?;
+    let mut out = format!(
+        "{{\nlet mut event = {}(&{})?;\n",
+        shape_deserializer(format, id),
+        if payload.is_some() {
+            empty.to_string()
+        } else {
+            format!(
+                "if payload.is_empty() {{\n{}\n}} else {{\n{}\n}}",
+                empty,
+                parse("payload")
+            )
+        }
+    );
+    for field in &fields {
+        let (member, target) = (field.edge.member, field.edge.target);
+        let assign =
+            |value: &str| format!("event.{} = {};\n", field.name, field_value(field, value));
+        if Some(field.edge.name) == payload {
+            out.push_str(&match graph.shape(target) {
+                Some(Shape::Blob {
+                    streaming: true, ..
+                }) => {
+                    assign("aws_sdk_rust_runtime::byte_stream::ByteStream::from(payload.clone())")
+                }
+                Some(Shape::Blob { .. }) => assign("payload.clone()"),
+                Some(Shape::String { .. }) => assign("String::from_utf8(payload.clone())?"),
+                Some(Shape::Structure { .. }) | Some(Shape::Union { .. }) => format!(
+                    "if !payload.is_empty() {{\n{}}}\n",
+                    assign(&format!(
+                        "{}(&{})?",
+                        shape_deserializer(format, target),
+                        parse("payload")
+                    ))
+                ),
+                _ => {
+                    return Err(format!(
+                        "`{}`'s payload `{}` can't be read from a message",
+                        id, field.edge.name
+                    )
+                    .into())
+                }
+            });
+        } else if member.event_header {
+            let (method, kind) = match graph.shape(target) {
+                Some(Shape::String { .. }) => ("as_str", "string"),
+                Some(Shape::Boolean { .. }) => ("as_bool", "boolean"),
+                Some(Shape::Integer { .. }) => ("as_i32", "32-bit integer"),
+                Some(Shape::Long { .. }) => ("as_i64", "integer"),
+                Some(Shape::Blob { .. }) => ("as_bytes", "byte array"),
+                Some(Shape::Timestamp { .. }) => ("as_timestamp", "timestamp"),
+                _ => {
+                    return Err(format!(
+                        "`{}.{}` can't be read from a message header",
+                        id, field.edge.name
+                    )
+                    .into())
+                }
+            };
+            let value = format!(
+                "value.{}().ok_or({:?})?",
+                method,
+                format!("`{}.{}` isn't a {}", id, field.edge.name, kind)
+            );
+            let value = match graph.shape(target) {
+                Some(Shape::String {
+                    enum_values: Some(_),
+                    ..
+                }) => format!("{}::from({})", type_name(target.as_str()), value),
+                Some(Shape::String { .. }) => format!("{}.to_string()", value),
+                Some(Shape::Blob { .. }) => format!("{}.to_vec()", value),
+                _ => value,
+            };
+            out.push_str(&format!(
+                "if let Some(value) = message.header({:?}) {{\n{}}}\n",
+                member.location_name.as_deref().unwrap_or(field.edge.name),
+                assign(&value)
+            ));
+        }
+    }
+    out.push_str("event\n}");
+    Ok(out)
+}
+
+fn event_serializer(
+    graph: &ModelGraph<'_>,
+    id: ShapeId,
+    content_type: &str,
+    serialize: &dyn Fn(ShapeId, &str) -> String,
+) -> Result<String, Err> {
+    let name = type_name(id.as_str());
+    let variants = variant_names(graph, id)?;
+    let mut arms = String::new();
+    for edge in graph.neighbors(id) {
+        let event = edge.target;
+        let shape = graph
+            .shape(event)
+            .ok_or_else(|| format!("`{}` isn't defined", event))?;
+        let payload = shape.payload();
+        let (fields, _) = struct_fields(graph, event)?;
+        let payload_field = fields.iter().find(|field| Some(field.edge.name) == payload);
+        let (payload_type, mut body) = match payload_field {
+            Some(field) => {
+                let (payload_type, value) = match graph.shape(field.edge.target) {
+                    Some(Shape::Blob {
+                        streaming: true, ..
+                    }) => (
+                        "application/octet-stream",
+                        format!(
+                            "value.bytes().ok_or({:?})?.to_vec()",
+                            format!(
+                                "`{}.{}` has to be in memory to be sent",
+                                event, field.edge.name
+                            )
+                        ),
+                    ),
+                    Some(Shape::Blob { .. }) => {
+                        ("application/octet-stream", "value.clone()".to_string())
+                    }
+                    Some(Shape::String { .. }) => {
+                        ("text/plain", "value.clone().into_bytes()".to_string())
+                    }
+                    Some(Shape::Structure { .. }) | Some(Shape::Union { .. }) => {
+                        (content_type, serialize(field.edge.target, "value"))
+                    }
+                    _ => {
+                        return Err(format!(
+                            "`{}`'s payload `{}` can't be sent in a message",
+                            event, field.edge.name
+                        )
+                        .into())
+                    }
+                };
+                (
+                    payload_type,
+                    format!(
+                        "let mut payload = Vec::new();\n{}",
+                        with_field(field, &format!("payload = {};\n", value), None)
+                    ),
+                )
+            }
+            None => (
+                content_type,
+                format!("let payload = {};\n", serialize(event, "input")),
+            ),
+        };
+        body.push_str(&format!(
+            "let mut message = Message::new(payload)\n\
+             .with_header(\":message-type\", HeaderValue::String(\"event\".to_string()))\n\
+             .with_header(\":event-type\", HeaderValue::String({:?}.to_string()))\n\
+             .with_header(\":content-type\", HeaderValue::String({:?}.to_string()));\n",
+            edge.name, payload_type
+        ));
+        for field in fields.iter().filter(|field| field.edge.member.event_header) {
+            let value = match graph.shape(field.edge.target) {
+                Some(Shape::String {
+                    enum_values: Some(_),
+                    ..
+                }) => "HeaderValue::String(value.as_str().to_string())",
+                Some(Shape::String { .. }) => "HeaderValue::String(value.clone())",
+                Some(Shape::Boolean { .. }) => "HeaderValue::Bool(*value)",
+                Some(Shape::Integer { .. }) => "HeaderValue::Int32(*value)",
+                Some(Shape::Long { .. }) => "HeaderValue::Int64(*value)",
+                Some(Shape::Blob { .. }) => "HeaderValue::Bytes(value.clone())",
+                Some(Shape::Timestamp { .. }) => "HeaderValue::Timestamp(*value)",
+                _ => {
+                    return Err(format!(
+                        "`{}.{}` can't be sent in a message header",
+                        event, field.edge.name
+                    )
+                    .into())
+                }
+            };
+            body.push_str(&with_field(
+                field,
+                &format!(
+                    "message = message.with_header({:?}, {});\n",
+                    field
+                        .edge
+                        .member
+                        .location_name
+                        .as_deref()
+                        .unwrap_or(field.edge.name),
+                    value
+                ),
+                None,
+            ));
+        }
+        body.push_str("message\n");
+        arms.push_str(&format!(
+            "{}::{}(input) => {{\n{}}}\n",
+            name, variants[edge.name], body
+        ));
+    }
+    Ok(format!(
+        "{}pub(crate) fn {}(\n\
+         event: &{},\n\
+         ) -> Result<aws_sdk_rust_runtime::event_stream::Message, crate::error::BoxError> {{\n\
+         use aws_sdk_rust_runtime::event_stream::{{HeaderValue, Message}};\n\
+         Ok(match event {{\n\
+         {}\
+         {}::Unknown => return Err({:?}.into()),\n\
+         }})\n\
+         }}\n",
+        allow_deprecated(uses_deprecated(graph, id)),
+        shape_serializer("event", id),
+        name,
+        arms,
+        name,
+        format!("`{}::Unknown` events can't be sent", name)
+    ))
+}
+
+#[test]
+fn generates_event_deserializers() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    use crate::codegen::format::{format_code, Formatting};
+
+    let def = crate::model::BotocoreModel::from_path("test-data/selects.json")?;
+    let graph = ModelGraph::new(&def);
+    let code = format_code(
+        &event_deserializers(
+            &graph,
+            vec![ShapeId::from("SelectObjectContentOutput")],
+            "xml",
+            "aws_sdk_rust_runtime::xml::Element::default()",
+            |payload| format!("aws_sdk_rust_runtime::xml::parse({})?", payload),
+        )?,
+        Formatting::Strict,
+    )?;
+    assert!(code.starts_with(
+        r#"pub(crate) fn event_de_select_object_content_event_stream(
+    message: &aws_sdk_rust_runtime::event_stream::Message,
+) -> Result<SelectObjectContentEventStream, crate::error::BoxError> {
+    let payload = &message.payload;
+    Ok(
+        match message.event_type()? {
+            "Cont" => {
+"#
+    ));
+    assert!(code.contains(
+        r#"                SelectObjectContentEventStream::Records({
+                    let mut event = xml_de_records_event(
+                        &aws_sdk_rust_runtime::xml::Element::default(),
+                    )?;
+                    if let Some(value) = message.header("ContentType") {
+                        event.content_type = Some(
+                            value
+                                .as_str()
+                                .ok_or("`RecordsEvent.ContentType` isn't a string")?
+                                .to_string(),
+                        );
+                    }
+                    event.payload = Some(payload.clone());
+                    event
+                })
+"#
+    ));
+    assert!(code.contains(
+        r#"                    if !payload.is_empty() {
+                        event.details = Some(
+                            xml_de_stats(&aws_sdk_rust_runtime::xml::parse(payload)?)?,
+                        );
+                    }
+"#
+    ));
+    // Events without a payload member are the whole payload.
+    assert!(code.contains(
+        r#"                    let mut event = xml_de_progress_event(
+                        &if payload.is_empty() {
+                            aws_sdk_rust_runtime::xml::Element::default()
+                        } else {
+                            aws_sdk_rust_runtime::xml::parse(payload)?
+                        },
+                    )?;
+"#
+    ));
+    assert!(code.contains("            other if message.is_exception() => {\n"));
+    assert!(code.contains("            _ => SelectObjectContentEventStream::Unknown,\n"));
+
+    let code = event_deserializers(
+        &graph,
+        vec![ShapeId::from("SelectObjectContentRequest")],
+        "xml",
+        "",
+        |payload| payload.to_string(),
+    )?;
+    assert_eq!(code, "");
+
+    Ok(())
+}
+
+#[test]
+fn generates_event_serializers() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    use crate::codegen::format::{format_code, Formatting};
+
+    let def = crate::model::BotocoreModel::from_path("test-data/transcripts.json")?;
+    let graph = ModelGraph::new(&def);
+    let code = format_code(
+        &event_serializers(
+            &graph,
+            vec![ShapeId::from("StartStreamTranscriptionRequest")],
+            "application/json",
+            |id, value| {
+                format!(
+                    "serde_json::to_vec(&{}({}))?",
+                    shape_serializer("json", id),
+                    value
+                )
+            },
+        )?,
+        Formatting::Strict,
+    )?;
+    assert!(code.starts_with(
+        r#"pub(crate) fn event_ser_audio_stream(
+    event: &AudioStream,
+) -> Result<aws_sdk_rust_runtime::event_stream::Message, crate::error::BoxError> {
+    use aws_sdk_rust_runtime::event_stream::{HeaderValue, Message};
+    Ok(
+        match event {
+            AudioStream::AudioEvent(input) => {
+                let mut payload = Vec::new();
+                if let Some(value) = &input.audio_chunk {
+                    payload = value.clone();
+                }
+                let mut message = Message::new(payload)
+"#
+    ));
+    assert!(code.contains(
+        r#"                        HeaderValue::String("application/octet-stream".to_string()),
+"#
+    ));
+    // Events without a payload member are the whole payload, in the protocol's format.
+    assert!(code.contains(
+        r#"                let payload = serde_json::to_vec(&json_ser_configuration_event(input))?;
+"#
+    ));
+    assert!(code.contains(
+        r#"                if let Some(value) = &input.channel_count {
+                    message = message
+                        .with_header("ChannelCount", HeaderValue::Int32(*value));
+                }
+                message
+"#
+    ));
+    assert!(code.contains(
+        r#"            AudioStream::Unknown => {
+                return Err("`AudioStream::Unknown` events can't be sent".into());
+            }
+"#
+    ));
+
+    Ok(())
+}
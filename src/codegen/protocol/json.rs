@@ -0,0 +1,610 @@
+use super::{
+    aggregates, field_value, format_timestamp, in_body, parse_timestamp, shape_deserializer,
+    shape_serializer, timestamp_format, with_field,
+};
+use crate::{
+    codegen::{
+        allow_deprecated,
+        structs::{field_type, request_id_fields, struct_fields},
+        unions::variant_names,
+        uses_deprecated,
+    },
+    graph::ModelGraph,
+    model::{Shape, ShapeId, ShapeMember, TimestampFormat},
+    naming::type_name,
+    Err,
+};
+
+/// What the awsJson and rest-json error deserializers read error responses with, emitted once
+/// into the `protocol` module. The code comes from the `X-Amzn-Errortype` header, or else the
+/// body's `__type` or `code` field, without the namespace or detail services add to it.
+pub const JSON_ERROR_METADATA: &str = r#"pub(crate) fn json_error_metadata(
+    response: &http::Response<Vec<u8>>,
+) -> Result<(crate::error::ErrorMetadata, serde_json::Value), crate::error::BoxError> {
+    let body = response.body();
+    let parsed = if body.is_empty() {
+        serde_json::Value::Object(Default::default())
+    } else {
+        serde_json::from_slice::<serde_json::Value>(body)?
+    };
+    let header = |name: &str| {
+        response
+            .headers()
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string)
+    };
+    let field = |name: &str| parsed.get(name).and_then(|value| value.as_str()).map(str::to_string);
+    let code = header("X-Amzn-Errortype")
+        .or_else(|| field("__type"))
+        .or_else(|| field("code"))
+        .map(|code| crate::error::sanitize_error_code(&code).to_string());
+    let message = field("message")
+        .or_else(|| field("Message"))
+        .or_else(|| field("errorMessage"));
+    let request_id = crate::error::request_id(response.headers());
+    let meta = crate::error::ErrorMetadata::new(code, message, request_id)
+        .with_extended_request_id(crate::error::extended_request_id(response.headers()))
+        .with_status(response.status().as_u16());
+    Ok((meta, parsed))
+}
+"#;
+
+/// An expression converting `expr`, a reference to a member's value, into the
+/// `serde_json::Value` it's sent as.
+///
+/// Blobs are base64-encoded and timestamps are sent as seconds since the epoch unless the
+/// model says otherwise. Structures and unions are converted by their `json_ser_` function.
+pub fn json_value(
+    graph: &ModelGraph<'_>,
+    member: &ShapeMember,
+    target: ShapeId,
+    expr: &str,
+) -> Result<String, Err> {
+    let shape = graph
+        .shape(target)
+        .ok_or_else(|| format!("`{}` isn't defined", target))?;
+    Ok(match shape {
+        Shape::String {
+            enum_values: Some(_),
+            ..
+        } => format!("serde_json::Value::String({}.as_str().to_string())", expr),
+        Shape::String { .. } => format!("serde_json::Value::String({}.clone())", expr),
+        Shape::Boolean { .. } | Shape::Integer { .. } | Shape::Long { .. } => {
+            format!("serde_json::Value::from(*{})", expr)
+        }
+        // JSON numbers can't be NaN or infinite, so those are sent as the strings `json_read`
+        // parses, rather than as `null`.
+        Shape::Float { .. } | Shape::Double { .. } => format!(
+            "if {expr}.is_finite() {{\n\
+             serde_json::Value::from(*{expr})\n\
+             }} else {{\n\
+             serde_json::Value::String(aws_sdk_rust_runtime::encoding::format_float(*{expr}))\n\
+             }}",
+            expr = expr
+        ),
+        Shape::Blob { .. } => format!(
+            "serde_json::Value::String(aws_sdk_rust_runtime::encoding::base64_encode({}))",
+            expr
+        ),
+        Shape::Timestamp { .. } => match timestamp_format(member, shape) {
+            None | Some(TimestampFormat::UnixTimestamp) => format!(
+                "serde_json::Value::from(aws_sdk_rust_runtime::time::epoch_seconds(*{}))",
+                expr
+            ),
+            Some(format) => format!(
+                "serde_json::Value::String({})",
+                format_timestamp(format, expr)
+            ),
+        },
+        Shape::Document { .. } => format!("{}.clone()", expr),
+        Shape::List { member, .. } => format!(
+            "serde_json::Value::Array({}.iter().map(|value| {}).collect())",
+            expr,
+            json_value(graph, member, member.shape.shape, "value")?
+        ),
+        Shape::Map { key, value, .. } => format!(
+            "serde_json::Value::Object({}.iter().map(|(key, value)| ({}, {})).collect())",
+            expr,
+            match graph.shape(key.shape.shape) {
+                Some(Shape::String {
+                    enum_values: Some(_),
+                    ..
+                }) => "key.as_str().to_string()",
+                _ => "key.clone()",
+            },
+            json_value(graph, value, value.shape.shape, "value")?
+        ),
+        Shape::Structure { .. } | Shape::Union { .. } => {
+            format!("{}({})", shape_serializer("json", target), expr)
+        }
+        Shape::EventStream { .. } => {
+            return Err(format!("event stream `{}` can't be sent as JSON", target).into())
+        }
+    })
+}
+
+/// Code inserting the set members of `input`, a structure, into `object`, a
+/// `serde_json::Map`, under their `locationName`s. `include` picks which members go in.
+/// Streaming blobs and event streams never do, since they can only be payloads, and nor do
+/// events' header and payload members, which are sent outside of them.
+pub fn json_members(
+    graph: &ModelGraph<'_>,
+    id: ShapeId,
+    include: impl Fn(&str, &ShapeMember) -> bool,
+) -> Result<String, Err> {
+    let (fields, _) = struct_fields(graph, id)?;
+    let mut out = String::new();
+    for field in fields.iter().filter(|field| {
+        let member = field.edge.member;
+        let streaming = graph.shape(field.edge.target).is_some_and(|target| {
+            member.is_streaming(target) || matches!(target, Shape::EventStream { .. })
+        });
+        !streaming
+            && !member.event_header
+            && !member.event_payload
+            && include(field.edge.name, member)
+    }) {
+        let wire_name = field
+            .edge
+            .member
+            .location_name
+            .as_deref()
+            .unwrap_or(field.edge.name);
+        let insert = format!(
+            "object.insert({:?}.to_string(), {});\n",
+            wire_name,
+            json_value(graph, field.edge.member, field.edge.target, "value")?
+        );
+        out.push_str(&with_field(field, &insert, None));
+    }
+    Ok(out)
+}
+
+/// Generates the `json_ser_` function for every structure and union `roots` reference,
+/// including themselves, in name order.
+pub fn json_serializers(
+    graph: &ModelGraph<'_>,
+    roots: impl IntoIterator<Item = ShapeId>,
+) -> Result<String, Err> {
+    let mut out = String::new();
+    for id in aggregates(graph, roots) {
+        if !out.is_empty() {
+            out.push('\n');
+        }
+        out.push_str(&json_serializer(graph, id)?);
+    }
+    Ok(out)
+}
+
+// The `json_ser_` function for a structure or union.
+fn json_serializer(graph: &ModelGraph<'_>, id: ShapeId) -> Result<String, Err> {
+    let name = type_name(id.as_str());
+    let body = match graph.shape(id) {
+        Some(Shape::Union { .. }) => {
+            let variants = variant_names(graph, id)?;
+            let mut arms = String::new();
+            for edge in graph.neighbors(id) {
+                let wire_name = edge.member.location_name.as_deref().unwrap_or(edge.name);
+                arms.push_str(&format!(
+                    "{}::{}(value) => {{\nobject.insert({:?}.to_string(), {});\n}}\n",
+                    name,
+                    variants[edge.name],
+                    wire_name,
+                    json_value(graph, edge.member, edge.target, "value")?
+                ));
+            }
+            format!("match input {{\n{}{}::Unknown => {{}}\n}}\n", arms, name)
+        }
+        _ => json_members(graph, id, |_, _| true)?,
+    };
+    Ok(format!(
+        "{}pub(crate) fn {}(input: &{}) -> serde_json::Value {{\n\
+         let mut object = serde_json::Map::new();\n\
+         {}\
+         serde_json::Value::Object(object)\n\
+         }}\n",
+        allow_deprecated(uses_deprecated(graph, id)),
+        shape_serializer("json", id),
+        name,
+        body
+    ))
+}
+
+/// An expression reading a member's value from `expr`, a `&serde_json::Value`, undoing
+/// `json_value`. `what` describes the value in errors, e.g. "`Function.Name`". It uses `?`, so
+/// it has to be in a function returning a `Result` with a boxed error.
+///
+/// Floats may also be strings, for `NaN` and the infinities, and nulls in lists and maps are
+/// skipped.
+pub fn json_read(
+    graph: &ModelGraph<'_>,
+    member: &ShapeMember,
+    target: ShapeId,
+    expr: &str,
+    what: &str,
+) -> Result<String, Err> {
+    let shape = graph
+        .shape(target)
+        .ok_or_else(|| format!("`{}` isn't defined", target))?;
+    let expect = |method: &str, kind: &str| {
+        format!(
+            "{}.{}().ok_or({:?})?",
+            expr,
+            method,
+            format!("{} isn't {}", what, kind)
+        )
+    };
+    let float = |ty: &str, cast: &str| {
+        format!(
+            "match {}.as_str() {{\n\
+             Some(text) => text.parse::<{}>()?,\n\
+             None => {}{},\n\
+             }}",
+            expr,
+            ty,
+            expect("as_f64", "a number"),
+            cast
+        )
+    };
+    Ok(match shape {
+        Shape::String {
+            enum_values: Some(_),
+            ..
+        } => format!(
+            "{}::from({})",
+            type_name(target.as_str()),
+            expect("as_str", "a string")
+        ),
+        Shape::String { .. } => format!("{}.to_string()", expect("as_str", "a string")),
+        Shape::Boolean { .. } => expect("as_bool", "a boolean"),
+        Shape::Integer { .. } => format!("{} as i32", expect("as_i64", "an integer")),
+        Shape::Long { .. } => expect("as_i64", "an integer"),
+        Shape::Float { .. } => float("f32", " as f32"),
+        Shape::Double { .. } => float("f64", ""),
+        Shape::Blob { .. } => format!(
+            "aws_sdk_rust_runtime::encoding::base64_decode({})?",
+            expect("as_str", "a string")
+        ),
+        Shape::Timestamp { .. } => match timestamp_format(member, shape) {
+            None | Some(TimestampFormat::UnixTimestamp) => format!(
+                "aws_sdk_rust_runtime::time::from_epoch_seconds({})?",
+                expect("as_f64", "a number")
+            ),
+            Some(format) => parse_timestamp(format, &expect("as_str", "a string")),
+        },
+        Shape::Document { .. } => format!("{}.clone()", expr),
+        Shape::List { member, .. } => format!(
+            "{{\n\
+             let mut items = Vec::new();\n\
+             for value in {} {{\n\
+             if value.is_null() {{\n\
+             continue;\n\
+             }}\n\
+             items.push({});\n\
+             }}\n\
+             items\n\
+             }}",
+            expect("as_array", "an array"),
+            json_read(
+                graph,
+                member,
+                member.shape.shape,
+                "value",
+                &format!("an item of {}", what)
+            )?
+        ),
+        Shape::Map { key, value, .. } => format!(
+            "{{\n\
+             let mut entries = std::collections::HashMap::new();\n\
+             for (key, value) in {} {{\n\
+             if value.is_null() {{\n\
+             continue;\n\
+             }}\n\
+             entries.insert({}, {});\n\
+             }}\n\
+             entries\n\
+             }}",
+            expect("as_object", "an object"),
+            match graph.shape(key.shape.shape) {
+                Some(Shape::String {
+                    enum_values: Some(_),
+                    ..
+                }) => format!(
+                    "{}::from(key.as_str())",
+                    type_name(key.shape.shape.as_str())
+                ),
+                _ => "key.clone()".to_string(),
+            },
+            json_read(
+                graph,
+                value,
+                value.shape.shape,
+                "value",
+                &format!("a value of {}", what)
+            )?
+        ),
+        Shape::Structure { .. } | Shape::Union { .. } => {
+            format!("{}({})?", shape_deserializer("json", target), expr)
+        }
+        Shape::EventStream { .. } => {
+            return Err(format!("event stream `{}` can't be read from JSON", target).into())
+        }
+    })
+}
+
+/// Generates the `json_de_` function for every structure and union `roots` reference,
+/// including themselves, in name order. Each reads its value from a `serde_json::Value`.
+pub fn json_deserializers(
+    graph: &ModelGraph<'_>,
+    roots: impl IntoIterator<Item = ShapeId>,
+) -> Result<String, Err> {
+    let mut out = String::new();
+    for id in aggregates(graph, roots) {
+        if !out.is_empty() {
+            out.push('\n');
+        }
+        out.push_str(&json_deserializer(graph, id)?);
+    }
+    Ok(out)
+}
+
+// The `json_de_` function for a structure or union.
+//
+// Like the `xml_de_` functions, members bound outside of the body are left unset, or at their
+// defaults if they're required, and other required members are an error when they're missing
+// or null. A union is whichever of its members is set first, or `Unknown` if none of them are.
+fn json_deserializer(graph: &ModelGraph<'_>, id: ShapeId) -> Result<String, Err> {
+    let name = type_name(id.as_str());
+    let body = match graph.shape(id) {
+        Some(Shape::Union { .. }) => {
+            let variants = variant_names(graph, id)?;
+            let mut out = String::new();
+            for edge in graph.neighbors(id) {
+                let target = graph
+                    .shape(edge.target)
+                    .ok_or_else(|| format!("`{}` isn't defined", edge.target))?;
+                let value = json_read(
+                    graph,
+                    edge.member,
+                    edge.target,
+                    "value",
+                    &format!("`{}.{}`", id, edge.name),
+                )?;
+                out.push_str(&format!(
+                    "if let Some(value) = object.get({:?}).filter(|value| !value.is_null()) {{\n\
+                     return Ok({}::{}({}));\n\
+                     }}\n",
+                    edge.member.location_name.as_deref().unwrap_or(edge.name),
+                    name,
+                    variants[edge.name],
+                    if field_type(graph, edge, target, true).starts_with("Box<") {
+                        format!("Box::new({})", value)
+                    } else {
+                        value
+                    }
+                ));
+            }
+            format!("{}Ok({}::Unknown)\n", out, name)
+        }
+        Some(shape) => {
+            let (fields, _) = struct_fields(graph, id)?;
+            let mut members = String::new();
+            for field in &fields {
+                let optional = field.is_optional();
+                let value = if !in_body(shape, field) {
+                    if optional {
+                        "None"
+                    } else {
+                        "Default::default()"
+                    }
+                    .to_string()
+                } else {
+                    let value = json_read(
+                        graph,
+                        field.edge.member,
+                        field.edge.target,
+                        "value",
+                        &format!("`{}.{}`", id, field.edge.name),
+                    )?;
+                    format!(
+                        "match object.get({:?}) {{\n\
+                         Some(value) if !value.is_null() => {},\n\
+                         _ => {},\n\
+                         }}",
+                        field
+                            .edge
+                            .member
+                            .location_name
+                            .as_deref()
+                            .unwrap_or(field.edge.name),
+                        field_value(field, &value),
+                        if optional {
+                            "None".to_string()
+                        } else {
+                            format!(
+                                "return Err({:?}.into())",
+                                format!("`{}.{}` is missing", id, field.edge.name)
+                            )
+                        }
+                    )
+                };
+                members.push_str(&format!("{}: {},\n", field.name, value));
+            }
+            for name in request_id_fields(graph, id) {
+                members.push_str(&format!("_{}: None,\n", name));
+            }
+            format!("Ok({} {{\n{}}})\n", name, members)
+        }
+        None => return Err(format!("`{}` isn't defined", id).into()),
+    };
+    Ok(format!(
+        "{}pub(crate) fn {}(value: &serde_json::Value) -> Result<{}, crate::error::BoxError> {{\n\
+         let object = value.as_object().ok_or({:?})?;\n\
+         {}\
+         }}\n",
+        allow_deprecated(uses_deprecated(graph, id)),
+        shape_deserializer("json", id),
+        name,
+        format!("`{}` isn't an object", id),
+        body
+    ))
+}
+
+#[test]
+fn generates_json_serializers() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    use crate::codegen::format::{format_code, Formatting};
+
+    let def = crate::model::BotocoreModel::from_path("test-data/recursive.json")?;
+    let graph = ModelGraph::new(&def);
+    let code = json_serializers(&graph, vec![ShapeId::from("PutItemInput")])?;
+    assert_eq!(
+        format_code(&code, Formatting::Strict)?,
+        r#"pub(crate) fn json_ser_attribute_value(input: &AttributeValue) -> serde_json::Value {
+    let mut object = serde_json::Map::new();
+    match input {
+        AttributeValue::L(value) => {
+            object
+                .insert(
+                    "L".to_string(),
+                    serde_json::Value::Array(
+                        value
+                            .iter()
+                            .map(|value| json_ser_attribute_value(value))
+                            .collect(),
+                    ),
+                );
+        }
+        AttributeValue::M(value) => {
+            object
+                .insert(
+                    "M".to_string(),
+                    serde_json::Value::Object(
+                        value
+                            .iter()
+                            .map(|(key, value)| (
+                                key.clone(),
+                                json_ser_attribute_value(value),
+                            ))
+                            .collect(),
+                    ),
+                );
+        }
+        AttributeValue::S(value) => {
+            object.insert("S".to_string(), serde_json::Value::String(value.clone()));
+        }
+        AttributeValue::Unknown => {}
+    }
+    serde_json::Value::Object(object)
+}
+pub(crate) fn json_ser_put_item_input(input: &PutItemInput) -> serde_json::Value {
+    let mut object = serde_json::Map::new();
+    {
+        let value = &input.item;
+        object
+            .insert(
+                "Item".to_string(),
+                serde_json::Value::Object(
+                    value
+                        .iter()
+                        .map(|(key, value)| (
+                            key.clone(),
+                            json_ser_attribute_value(value),
+                        ))
+                        .collect(),
+                ),
+            );
+    }
+    serde_json::Value::Object(object)
+}
+"#
+    );
+
+    let def = crate::model::BotocoreModel::from_path("test-data/lambda/2015-03-31/service-2.json")?;
+    let graph = ModelGraph::new(&def);
+    let code = json_serializers(&graph, vec![ShapeId::from("CreateFunctionRequest")])?;
+    assert!(code.contains("pub(crate) fn json_ser_function_code(input: &FunctionCode)"));
+    assert!(code.contains(
+        "object.insert(\"ZipFile\".to_string(), \
+         serde_json::Value::String(aws_sdk_rust_runtime::encoding::base64_encode(value)));\n"
+    ));
+    assert!(code.contains(
+        "object.insert(\"Runtime\".to_string(), \
+         serde_json::Value::String(value.as_str().to_string()));\n"
+    ));
+    let weight = serde_json::from_str::<ShapeMember>(r#"{"shape": "Weight"}"#)?;
+    assert_eq!(
+        json_value(&graph, &weight, ShapeId::from("Weight"), "value")?,
+        "if value.is_finite() {\n\
+         serde_json::Value::from(*value)\n\
+         } else {\n\
+         serde_json::Value::String(aws_sdk_rust_runtime::encoding::format_float(*value))\n\
+         }"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn generates_json_deserializers() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    use crate::codegen::format::{format_code, Formatting};
+
+    let def = crate::model::BotocoreModel::from_path("test-data/recursive.json")?;
+    let graph = ModelGraph::new(&def);
+    let code = format_code(
+        &json_deserializers(&graph, vec![ShapeId::from("PutItemOutput")])?,
+        Formatting::Strict,
+    )?;
+    assert!(code.contains(
+        r#"    if let Some(value) = object.get("S").filter(|value| !value.is_null()) {
+        return Ok(
+            AttributeValue::S(
+                value.as_str().ok_or("`AttributeValue.S` isn't a string")?.to_string(),
+            ),
+        );
+    }
+    Ok(AttributeValue::Unknown)
+}
+"#
+    ));
+    assert!(code.contains(
+        r#"pub(crate) fn json_de_put_item_output(
+    value: &serde_json::Value,
+) -> Result<PutItemOutput, crate::error::BoxError> {
+    let object = value.as_object().ok_or("`PutItemOutput` isn't an object")?;
+    Ok(PutItemOutput {
+        attributes: match object.get("Attributes") {
+            Some(value) if !value.is_null() => {
+                Some({
+                    let mut entries = std::collections::HashMap::new();
+                    for (key, value) in value
+                        .as_object()
+                        .ok_or("`PutItemOutput.Attributes` isn't an object")?
+                    {
+                        if value.is_null() {
+                            continue;
+                        }
+                        entries.insert(key.clone(), json_de_attribute_value(value)?);
+                    }
+                    entries
+                })
+            }
+            _ => None,
+        },
+        _request_id: None,
+    })
+}
+"#
+    ));
+
+    let def = crate::model::BotocoreModel::from_path("test-data/lambda/2015-03-31/service-2.json")?;
+    let graph = ModelGraph::new(&def);
+    let code = json_deserializers(&graph, vec![ShapeId::from("FunctionConfiguration")])?;
+    assert!(code.contains(
+        "Some(value) if !value.is_null() => Some(json_de_environment_response(value)?),\n"
+    ));
+    assert!(code
+        .contains("value.as_i64().ok_or(\"`FunctionConfiguration.CodeSize` isn't an integer\")?"));
+
+    Ok(())
+}
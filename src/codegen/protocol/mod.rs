@@ -0,0 +1,398 @@
+use super::{
+    allow_deprecated, features::OperationFeatures, operation_uses_deprecated, structs::Field,
+};
+use crate::{
+    graph::ModelGraph,
+    model::{Location, Operation, Protocol, Shape, ShapeId, ShapeMember, TimestampFormat},
+    naming::{to_snake_case, type_name},
+    visit::{walk_shape, ShapeVisitor},
+    Err,
+};
+use std::collections::BTreeSet;
+
+pub mod aws_json;
+pub mod bindings;
+pub mod ec2;
+pub mod event_stream;
+pub mod json;
+pub mod query;
+pub mod rest_json;
+pub mod rest_xml;
+pub mod xml;
+
+/// Generates the `protocol` module of a service's crate, with the functions its client calls
+/// to serialize each operation's requests and deserialize its responses and errors.
+pub fn generate_protocol(
+    graph: &ModelGraph<'_>,
+    features: &OperationFeatures,
+) -> Result<String, Err> {
+    let code = match graph.model().metadata.protocol {
+        Protocol::RestJson => rest_json::generate(graph, features)?,
+        Protocol::Json => aws_json::generate(graph, features)?,
+        Protocol::RestXml => rest_xml::generate(graph, features)?,
+        Protocol::Query => query::generate(graph, features)?,
+        Protocol::Ec2 => ec2::generate(graph, features)?,
+    };
+    Ok(format!(
+        "// Not every function is used when operations are feature-gated, and generated bodies\n\
+         // don't special-case inputs with nothing to bind.\n\
+         #![allow(dead_code, unused_assignments, unused_mut, unused_variables)]\n\n\
+         #[allow(unused_imports)]\n\
+         use crate::model::*;\n\n\
+         {}",
+        code
+    ))
+}
+
+// Whether a list or map member leaves out the wrapper around its items or entries, whether the
+// member or its target says so.
+pub(crate) fn is_flattened(member: &ShapeMember, target: Option<&Shape>) -> bool {
+    member.flattened
+        || match target {
+            Some(Shape::List { flattened, .. }) | Some(Shape::Map { flattened, .. }) => *flattened,
+            _ => false,
+        }
+}
+
+// How a timestamp member is formatted: as the member says, or else as its shape says.
+pub(crate) fn timestamp_format(member: &ShapeMember, target: &Shape) -> Option<TimestampFormat> {
+    match target {
+        Shape::Timestamp {
+            timestamp_format, ..
+        } => member.timestamp_format.or(*timestamp_format),
+        _ => member.timestamp_format,
+    }
+}
+
+// An expression formatting `expr`, a `&SystemTime`, as a `String`.
+pub(crate) fn format_timestamp(format: TimestampFormat, expr: &str) -> String {
+    match format {
+        TimestampFormat::Iso8601 => {
+            format!("aws_sdk_rust_runtime::time::format_iso8601(*{})", expr)
+        }
+        TimestampFormat::Rfc822 => {
+            format!("aws_sdk_rust_runtime::time::format_http_date(*{})", expr)
+        }
+        TimestampFormat::UnixTimestamp => format!(
+            "aws_sdk_rust_runtime::time::epoch_seconds(*{}).to_string()",
+            expr
+        ),
+    }
+}
+
+// An expression parsing `expr`, a `&str`, as a timestamp in the given format.
+pub(crate) fn parse_timestamp(format: TimestampFormat, expr: &str) -> String {
+    match format {
+        TimestampFormat::Iso8601 => format!("aws_sdk_rust_runtime::time::parse_iso8601({})?", expr),
+        TimestampFormat::Rfc822 => {
+            format!("aws_sdk_rust_runtime::time::parse_http_date({})?", expr)
+        }
+        TimestampFormat::UnixTimestamp => format!(
+            "aws_sdk_rust_runtime::time::from_epoch_seconds({}.parse::<f64>()?)?",
+            expr
+        ),
+    }
+}
+
+// An expression converting `expr`, a reference to the value of a member targeting a scalar
+// shape, into the `String` it's sent as in a URI, query string, or header. Timestamps are
+// formatted as `default_format` unless the model says otherwise.
+pub(crate) fn wire_string(
+    graph: &ModelGraph<'_>,
+    member: &ShapeMember,
+    target: ShapeId,
+    expr: &str,
+    default_format: TimestampFormat,
+) -> Result<String, Err> {
+    let shape = graph
+        .shape(target)
+        .ok_or_else(|| format!("`{}` isn't defined", target))?;
+    Ok(match shape {
+        Shape::String {
+            enum_values: Some(_),
+            ..
+        } => format!("{}.as_str().to_string()", expr),
+        Shape::String { .. }
+        | Shape::Boolean { .. }
+        | Shape::Integer { .. }
+        | Shape::Long { .. } => {
+            format!("{}.to_string()", expr)
+        }
+        Shape::Float { .. } | Shape::Double { .. } => {
+            format!("aws_sdk_rust_runtime::encoding::format_float(*{})", expr)
+        }
+        Shape::Blob { .. } => format!("aws_sdk_rust_runtime::encoding::base64_encode({})", expr),
+        Shape::Timestamp { .. } => format_timestamp(
+            timestamp_format(member, shape).unwrap_or(default_format),
+            expr,
+        ),
+        _ => {
+            return Err(format!(
+                "`{}` can't be sent in a URI, query string, or header",
+                target
+            )
+            .into())
+        }
+    })
+}
+
+// The inverse of `wire_string`: an expression parsing `expr`, a `&str` from a URI, header, or
+// XML element, into the value of a member targeting a scalar shape. It uses `?`, so it has to
+// be in a function returning a `Result` with a boxed error.
+pub(crate) fn parse_wire_string(
+    graph: &ModelGraph<'_>,
+    member: &ShapeMember,
+    target: ShapeId,
+    expr: &str,
+    default_format: TimestampFormat,
+) -> Result<String, Err> {
+    let shape = graph
+        .shape(target)
+        .ok_or_else(|| format!("`{}` isn't defined", target))?;
+    Ok(match shape {
+        Shape::String {
+            enum_values: Some(_),
+            ..
+        } => format!("{}::from({})", type_name(target.as_str()), expr),
+        Shape::String { .. } => format!("{}.to_string()", expr),
+        Shape::Boolean { .. }
+        | Shape::Integer { .. }
+        | Shape::Long { .. }
+        | Shape::Float { .. }
+        | Shape::Double { .. } => format!(
+            "{}.parse::<{}>()?",
+            expr,
+            shape.rust_primitive().unwrap_or_default()
+        ),
+        Shape::Blob { .. } => format!("aws_sdk_rust_runtime::encoding::base64_decode({})?", expr),
+        Shape::Timestamp { .. } => parse_timestamp(
+            timestamp_format(member, shape).unwrap_or(default_format),
+            expr,
+        ),
+        _ => return Err(format!("`{}` can't be parsed from a string", target).into()),
+    })
+}
+
+// Code that runs `code` with `value` bound to a reference to a field's value, if it's set.
+// `otherwise` runs if it isn't.
+pub(crate) fn with_field(field: &Field<'_>, code: &str, otherwise: Option<&str>) -> String {
+    if field.is_optional() {
+        let mut out = format!("if let Some(value) = &input.{} {{\n{}}}", field.name, code);
+        if let Some(otherwise) = otherwise {
+            out.push_str(&format!(" else {{\n{}}}", otherwise));
+        }
+        out.push('\n');
+        out
+    } else {
+        format!("{{\nlet value = &input.{};\n{}}}\n", field.name, code)
+    }
+}
+
+// The name of the generated function serializing a shape into the given format, e.g.
+// `json_ser_function_code`.
+pub(crate) fn shape_serializer(format: &str, id: ShapeId) -> String {
+    format!("{}_ser_{}", format, to_snake_case(id.as_str()))
+}
+
+// The name of the generated function deserializing a shape from the given format, e.g.
+// `xml_de_function_code`.
+pub(crate) fn shape_deserializer(format: &str, id: ShapeId) -> String {
+    format!("{}_de_{}", format, to_snake_case(id.as_str()))
+}
+
+// The function that turns a request builder with the operation's method set, the endpoint,
+// and the operation's input into a request. The request URI is expanded first, and then
+// `code` builds the request's `body` from `input`, adjusting `uri` and `request` as it goes.
+pub(crate) fn request_serializer(
+    graph: &ModelGraph<'_>,
+    op: &Operation,
+    features: &OperationFeatures,
+    code: &str,
+) -> Result<String, Err> {
+    let mut out = String::new();
+    if let Some(cfg) = features.cfg_attribute(&op.name) {
+        out.push_str(&cfg);
+        out.push('\n');
+    }
+    out.push_str(allow_deprecated(operation_uses_deprecated(graph, op)));
+    out.push_str(&format!(
+        "pub(crate) fn {}(\n\
+         request: http::request::Builder,\n\
+         endpoint: &str,\n\
+         input: &{},\n\
+         ) -> Result<http::Request<aws_sdk_rust_runtime::body::Body>, crate::error::BoxError> {{\n\
+         {}\
+         let mut request = request;\n\
+         {}\
+         Ok(request.uri(format!(\"{{}}{{}}\", endpoint, uri)).body(body.into())?)\n\
+         }}\n",
+        operation_serializer(&op.name),
+        type_name(op.input.shape.as_str()),
+        bindings::expand_uri(graph, op)?,
+        code
+    ));
+    Ok(out)
+}
+
+// The name of the generated function serializing an operation's requests.
+pub(crate) fn operation_serializer(operation: &str) -> String {
+    format!("serialize_{}", to_snake_case(operation))
+}
+
+// The name of the generated function deserializing an operation's successful responses. Its
+// error responses are deserialized by the function of the same name with an `_error` suffix.
+pub(crate) fn operation_deserializer(operation: &str) -> String {
+    format!("deserialize_{}", to_snake_case(operation))
+}
+
+// The function that turns a successful response into the operation's output, or `()` if it
+// has none. `code` is its body, with the response in `response`, and if the output's payload
+// is a stream, the response's body in `stream`, while it's still coming in.
+pub(crate) fn response_deserializer(
+    graph: &ModelGraph<'_>,
+    op: &Operation,
+    features: &OperationFeatures,
+    stream: bool,
+    code: &str,
+) -> String {
+    let mut out = String::new();
+    if let Some(cfg) = features.cfg_attribute(&op.name) {
+        out.push_str(&cfg);
+        out.push('\n');
+    }
+    out.push_str(allow_deprecated(operation_uses_deprecated(graph, op)));
+    out.push_str(&format!(
+        "pub(crate) fn {}(\n\
+         response: &http::Response<Vec<u8>>,\n\
+         {}\
+         ) -> Result<{}, crate::error::BoxError> {{\n\
+         {}\
+         }}\n",
+        operation_deserializer(&op.name),
+        if stream {
+            "stream: aws_sdk_rust_runtime::body::Body,\n"
+        } else {
+            ""
+        },
+        op.output
+            .as_ref()
+            .map(|output| type_name(output.shape.as_str()))
+            .unwrap_or_else(|| "()".to_string()),
+        code
+    ));
+    out
+}
+
+// The function that turns an error response into the operation's error type. `metadata`
+// names the function reading the response's `ErrorMetadata` and the part of it holding the
+// error's members, which the deserializer for the error shape its code names, in the given
+// format, reads. Errors with codes the operation doesn't list are `Unhandled`.
+pub(crate) fn error_deserializer(
+    graph: &ModelGraph<'_>,
+    op: &Operation,
+    features: &OperationFeatures,
+    format: &str,
+    metadata: &str,
+) -> Result<String, Err> {
+    let error = format!("crate::error::{}Error", type_name(&op.name));
+    let mut arms = String::new();
+    for reference in &op.errors {
+        let id = reference.shape;
+        if graph.shape(id).is_none() {
+            return Err(format!(
+                "operation `{}` returns `{}`, which the model doesn't define",
+                op.name, id
+            )
+            .into());
+        }
+        arms.push_str(&format!(
+            "Some({:?}) => {}(&parsed).map({}::{}),\n",
+            id.as_str(),
+            shape_deserializer(format, id),
+            error,
+            type_name(id.as_str())
+        ));
+    }
+
+    let mut out = String::new();
+    if let Some(cfg) = features.cfg_attribute(&op.name) {
+        out.push_str(&cfg);
+        out.push('\n');
+    }
+    out.push_str(allow_deprecated(operation_uses_deprecated(graph, op)));
+    out.push_str(&format!(
+        "pub(crate) fn {deserializer}_error(\n\
+         response: &http::Response<Vec<u8>>,\n\
+         ) -> crate::error::SdkError<{error}> {{\n\
+         let (meta, parsed) = match {metadata}(response) {{\n\
+         Ok(metadata) => metadata,\n\
+         Err(error) => return crate::error::SdkError::Response(error),\n\
+         }};\n\
+         let error = match meta.code().and_then({error}::modeled_error) {{\n\
+         {arms}\
+         _ => Ok({error}::Unhandled(crate::error::UnhandledError::new(\n\
+         meta.code().map(str::to_string),\n\
+         meta.message().map(str::to_string),\n\
+         ))),\n\
+         }};\n\
+         match error {{\n\
+         Ok(error) => crate::error::SdkError::Service {{ error, meta }},\n\
+         Err(error) => crate::error::SdkError::Response(error),\n\
+         }}\n\
+         }}\n",
+        deserializer = operation_deserializer(&op.name),
+        error = error,
+        metadata = metadata,
+        arms = arms,
+    ));
+    Ok(out)
+}
+
+// Whether a member of a structure is in its body, rather than bound to the URI, query string,
+// a header, an event's header, or the status code, or being the whole payload.
+pub(crate) fn in_body(shape: &Shape, field: &Field<'_>) -> bool {
+    let name = field.edge.name;
+    Some(name) != shape.payload()
+        && !field.edge.member.event_header
+        && matches!(field.edge.member.location(name), Location::Body(_))
+}
+
+// An expression for a field's value from `value`, an expression of the type the field holds:
+// boxed if the field is, and in `Some` if it's optional.
+pub(crate) fn field_value(field: &Field<'_>, value: &str) -> String {
+    let value = if field.value_type().starts_with("Box<") {
+        format!("Box::new({})", value)
+    } else {
+        value.to_string()
+    };
+    if field.is_optional() {
+        format!("Some({})", value)
+    } else {
+        value
+    }
+}
+
+// The structures and unions `roots` reference, including themselves, which are what get
+// their own serializer and deserializer functions.
+pub(crate) fn aggregates(
+    graph: &ModelGraph<'_>,
+    roots: impl IntoIterator<Item = ShapeId>,
+) -> BTreeSet<ShapeId> {
+    #[derive(Default)]
+    struct Aggregates(BTreeSet<ShapeId>);
+
+    impl ShapeVisitor<'_> for Aggregates {
+        fn visit_shape(&mut self, id: ShapeId, shape: &Shape) -> bool {
+            if let Shape::Structure { .. } | Shape::Union { .. } = shape {
+                self.0.insert(id);
+            }
+            true
+        }
+    }
+
+    let mut aggregates = Aggregates::default();
+    for root in roots {
+        walk_shape(graph, root, &mut aggregates);
+    }
+    aggregates.0
+}
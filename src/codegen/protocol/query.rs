@@ -0,0 +1,413 @@
+use super::{
+    aggregates, error_deserializer, is_flattened, request_serializer, response_deserializer,
+    shape_deserializer, shape_serializer, wire_string, with_field,
+    xml::{xml_deserializers, XML_ERROR_METADATA},
+};
+use crate::{
+    codegen::{
+        allow_deprecated, features::OperationFeatures, structs::struct_fields,
+        unions::variant_names, uses_deprecated,
+    },
+    graph::ModelGraph,
+    model::{Operation, Shape, ShapeId, ShapeMember, TimestampFormat},
+    naming::type_name,
+    Err,
+};
+
+/// The flavors of the query protocol, which differ in how they name parameters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dialect {
+    Query,
+    // EC2's, which names parameters for their `queryName`, or else their `locationName` with its
+    // first letter capitalized, and numbers list items right after the list's name.
+    Ec2,
+}
+
+impl Dialect {
+    // The format in the names of the dialect's serializer functions, e.g. `query_ser_tag`.
+    fn format(self) -> &'static str {
+        match self {
+            Dialect::Query => "query",
+            Dialect::Ec2 => "ec2",
+        }
+    }
+}
+
+/// Generates the query protocol's request serializers and response deserializers: a
+/// `serialize_`, a `deserialize_`, and a `deserialize_..._error` function for each operation,
+/// and the `query_ser_` functions they use for the structures and unions in their inputs.
+/// Responses are XML, read by the `xml_de_` functions for the structures and unions in
+/// outputs and errors.
+pub fn generate(graph: &ModelGraph<'_>, features: &OperationFeatures) -> Result<String, Err> {
+    generate_dialect(graph, Dialect::Query, features)
+}
+
+/// Generates the request serializers for a dialect of the query protocol; see `generate`.
+pub fn generate_dialect(
+    graph: &ModelGraph<'_>,
+    dialect: Dialect,
+    features: &OperationFeatures,
+) -> Result<String, Err> {
+    let mut out = String::new();
+    for (_, op) in graph.operations() {
+        out.push_str(&serialize_operation(graph, dialect, op, features)?);
+        out.push('\n');
+        out.push_str(&deserialize_operation(graph, dialect, op, features));
+        out.push('\n');
+        out.push_str(&error_deserializer(
+            graph,
+            op,
+            features,
+            "xml",
+            "xml_error_metadata",
+        )?);
+        out.push('\n');
+    }
+    out.push_str(XML_ERROR_METADATA);
+    out.push('\n');
+    out.push_str(&query_serializers(
+        graph,
+        dialect,
+        graph.operations().map(|(_, op)| op.input.shape),
+    )?);
+    out.push('\n');
+    out.push_str(&xml_deserializers(
+        graph,
+        graph
+            .operations()
+            .flat_map(|(_, op)| op.output.iter().chain(&op.errors))
+            .map(|reference| reference.shape),
+    )?);
+    Ok(out)
+}
+
+// The operation's request serializer; see `request_serializer`. The body is a form naming the
+// operation and the API version, followed by the input's members.
+fn serialize_operation(
+    graph: &ModelGraph<'_>,
+    dialect: Dialect,
+    op: &Operation,
+    features: &OperationFeatures,
+) -> Result<String, Err> {
+    let code = format!(
+        "let mut form = String::new();\n\
+         aws_sdk_rust_runtime::encoding::push_form(&mut form, \"Action\", {:?});\n\
+         aws_sdk_rust_runtime::encoding::push_form(&mut form, \"Version\", {:?});\n\
+         {}(&mut form, \"\", input);\n\
+         let body = form.into_bytes();\n\
+         request = request.header(\n\
+         \"Content-Type\",\n\
+         \"application/x-www-form-urlencoded; charset=utf-8\",\n\
+         );\n",
+        op.name,
+        graph.model().metadata.api_version,
+        shape_serializer(dialect.format(), op.input.shape)
+    );
+    request_serializer(graph, op, features, &code)
+}
+
+// The operation's response deserializer; see `response_deserializer`. The output is the
+// response's root element, e.g. `<ListQueuesResponse>`, or the child of it the output's
+// `resultWrapper` names, e.g. `<ListQueuesResult>`. EC2 doesn't wrap outputs.
+fn deserialize_operation(
+    graph: &ModelGraph<'_>,
+    dialect: Dialect,
+    op: &Operation,
+    features: &OperationFeatures,
+) -> String {
+    let output = match &op.output {
+        Some(output) => output,
+        None => return response_deserializer(graph, op, features, false, "Ok(())\n"),
+    };
+    let unwrap = match (&output.result_wrapper, dialect) {
+        (Some(wrapper), Dialect::Query) => format!(
+            "let element = element.child({:?}).ok_or({:?})?;\n",
+            wrapper,
+            format!("the response is missing its `{}` element", wrapper)
+        ),
+        _ => String::new(),
+    };
+    response_deserializer(
+        graph,
+        op,
+        features,
+        false,
+        &format!(
+            "let element = aws_sdk_rust_runtime::xml::parse(response.body())?;\n\
+             {}\
+             {}(&element)\n",
+            unwrap,
+            shape_deserializer("xml", output.shape)
+        ),
+    )
+}
+
+/// Code adding `expr`, a reference to a member's value, to `form`, a `&mut String`, under the
+/// parameter name in `name`, a `String`.
+///
+/// Structures add their members under `name` followed by a `.`. List items are numbered from
+/// 1 under `name.member`, and map entries under `name.entry`, each with a `key` and a
+/// `value`, unless the model names them otherwise. Flattened lists and maps leave out the
+/// `member` or `entry`. An empty list is sent as an empty parameter, so it can be told apart
+/// from an unset one.
+///
+/// EC2 lists are always numbered right after `name`, and empty ones aren't sent.
+pub fn query_value(
+    graph: &ModelGraph<'_>,
+    dialect: Dialect,
+    member: &ShapeMember,
+    target: ShapeId,
+    expr: &str,
+) -> Result<String, Err> {
+    let shape = graph.shape(target);
+    let flattened = is_flattened(member, shape);
+    Ok(match shape {
+        Some(Shape::Structure { .. }) | Some(Shape::Union { .. }) => format!(
+            "{}(form, &format!(\"{{}}.\", name), {});\n",
+            shape_serializer(dialect.format(), target),
+            expr
+        ),
+        Some(Shape::List { member: item, .. }) => {
+            let (segment, empty) = match dialect {
+                Dialect::Ec2 => (String::new(), ""),
+                Dialect::Query => (
+                    if flattened {
+                        String::new()
+                    } else {
+                        format!("{}.", item.location_name.as_deref().unwrap_or("member"))
+                    },
+                    "aws_sdk_rust_runtime::encoding::push_form(form, &name, \"\");\n",
+                ),
+            };
+            let mut out = String::new();
+            if !empty.is_empty() {
+                out.push_str(&format!("if {}.is_empty() {{\n{}}}\n", expr, empty));
+            }
+            out.push_str(&format!(
+                "for (i, value) in {}.iter().enumerate() {{\n\
+                 let name = format!(\"{{}}.{}{{}}\", name, i + 1);\n\
+                 {}\
+                 }}\n",
+                expr,
+                segment,
+                query_value(graph, dialect, item, item.shape.shape, "value")?
+            ));
+            out
+        }
+        Some(Shape::Map { key, value, .. }) => {
+            let segment = if flattened { "" } else { "entry." };
+            let part = |member: &ShapeMember, default: &str, expr: &str| -> Result<String, Err> {
+                Ok(format!(
+                    "{{\n\
+                     let name = format!(\"{{}}.{}\", name);\n\
+                     {}\
+                     }}\n",
+                    member.location_name.as_deref().unwrap_or(default),
+                    query_value(graph, dialect, member, member.shape.shape, expr)?
+                ))
+            };
+            format!(
+                "for (i, (key, value)) in {}.iter().enumerate() {{\n\
+                 let name = format!(\"{{}}.{}{{}}\", name, i + 1);\n\
+                 {}{}\
+                 }}\n",
+                expr,
+                segment,
+                part(key, "key", "key")?,
+                part(value, "value", "value")?
+            )
+        }
+        _ => format!(
+            "aws_sdk_rust_runtime::encoding::push_form(form, &name, &{});\n",
+            wire_string(graph, member, target, expr, TimestampFormat::Iso8601)?
+        ),
+    })
+}
+
+// The parameter name of a structure member. A flattened list's items are named for the list's
+// member when it has a `locationName`, rather than for the structure's member. EC2 names it
+// for its `queryName`, or else its capitalized `locationName`.
+fn parameter_name(
+    graph: &ModelGraph<'_>,
+    dialect: Dialect,
+    name: &str,
+    member: &ShapeMember,
+) -> String {
+    if dialect == Dialect::Ec2 {
+        if let Some(query_name) = &member.query_name {
+            return query_name.clone();
+        }
+        return match &member.location_name {
+            Some(location_name) => {
+                let mut chars = location_name.chars();
+                chars
+                    .next()
+                    .map(|first| first.to_uppercase().chain(chars).collect())
+                    .unwrap_or_default()
+            }
+            None => name.to_string(),
+        };
+    }
+    if let Some(Shape::List { member: item, .. }) = graph.shape(member.shape.shape) {
+        if is_flattened(member, graph.shape(member.shape.shape)) {
+            if let Some(name) = &item.location_name {
+                return name.clone();
+            }
+        }
+    }
+    member.location_name.as_deref().unwrap_or(name).to_string()
+}
+
+/// Generates the `query_ser_` function for every structure and union `roots` reference,
+/// including themselves, in name order. Each adds its members to a form, with their names
+/// following a prefix.
+pub fn query_serializers(
+    graph: &ModelGraph<'_>,
+    dialect: Dialect,
+    roots: impl IntoIterator<Item = ShapeId>,
+) -> Result<String, Err> {
+    let mut out = String::new();
+    for id in aggregates(graph, roots) {
+        if !out.is_empty() {
+            out.push('\n');
+        }
+        out.push_str(&query_serializer(graph, dialect, id)?);
+    }
+    Ok(out)
+}
+
+// The `query_ser_` function for a structure or union.
+fn query_serializer(graph: &ModelGraph<'_>, dialect: Dialect, id: ShapeId) -> Result<String, Err> {
+    let name = type_name(id.as_str());
+    let parameter = |name: &str, member: &ShapeMember, expr: &str| -> Result<String, Err> {
+        Ok(format!(
+            "let name = format!(\"{{}}{}\", prefix);\n{}",
+            name,
+            query_value(graph, dialect, member, member.shape.shape, expr)?
+        ))
+    };
+    let body = match graph.shape(id) {
+        Some(Shape::Union { .. }) => {
+            let variants = variant_names(graph, id)?;
+            let mut arms = String::new();
+            for edge in graph.neighbors(id) {
+                arms.push_str(&format!(
+                    "{}::{}(value) => {{\n{}}}\n",
+                    name,
+                    variants[edge.name],
+                    parameter(
+                        &parameter_name(graph, dialect, edge.name, edge.member),
+                        edge.member,
+                        "value"
+                    )?
+                ));
+            }
+            format!("match input {{\n{}{}::Unknown => {{}}\n}}\n", arms, name)
+        }
+        _ => {
+            let (fields, _) = struct_fields(graph, id)?;
+            let mut out = String::new();
+            for field in &fields {
+                let code = parameter(
+                    &parameter_name(graph, dialect, field.edge.name, field.edge.member),
+                    field.edge.member,
+                    "value",
+                )?;
+                out.push_str(&with_field(field, &code, None));
+            }
+            out
+        }
+    };
+    Ok(format!(
+        "{}pub(crate) fn {}(form: &mut String, prefix: &str, input: &{}) {{\n{}}}\n",
+        allow_deprecated(uses_deprecated(graph, id)),
+        shape_serializer(dialect.format(), id),
+        name,
+        body
+    ))
+}
+
+#[test]
+fn generates_query_serializers() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    use crate::codegen::format::{format_code, Formatting};
+
+    let def = crate::model::BotocoreModel::from_path("test-data/queues.json")?;
+    let graph = ModelGraph::new(&def);
+    let features = OperationFeatures::default();
+    let code = serialize_operation(
+        &graph,
+        Dialect::Query,
+        &def.operations["SendMessage"],
+        &features,
+    )?;
+    assert!(format_code(&code, Formatting::Strict)?.contains(
+        r#"    let mut form = String::new();
+    aws_sdk_rust_runtime::encoding::push_form(&mut form, "Action", "SendMessage");
+    aws_sdk_rust_runtime::encoding::push_form(&mut form, "Version", "2012-11-05");
+    query_ser_send_message_request(&mut form, "", input);
+    let body = form.into_bytes();
+"#
+    ));
+
+    let code = format_code(
+        &query_serializers(
+            &graph,
+            Dialect::Query,
+            vec![ShapeId::from("SendMessageRequest")],
+        )?,
+        Formatting::Strict,
+    )?;
+    assert!(code.contains(
+        r#"    if let Some(value) = &input.message_attributes {
+        let name = format!("{}MessageAttribute", prefix);
+        for (i, (key, value)) in value.iter().enumerate() {
+            let name = format!("{}.{}", name, i + 1);
+            {
+                let name = format!("{}.Name", name);
+                aws_sdk_rust_runtime::encoding::push_form(form, &name, &key.to_string());
+            }
+            {
+                let name = format!("{}.Value", name);
+                query_ser_message_attribute_value(form, &format!("{}.", name), value);
+            }
+        }
+    }
+"#
+    ));
+    assert!(code.contains(
+        r#"    if let Some(value) = &input.tags {
+        let name = format!("{}Tags", prefix);
+        if value.is_empty() {
+            aws_sdk_rust_runtime::encoding::push_form(form, &name, "");
+        }
+        for (i, value) in value.iter().enumerate() {
+            let name = format!("{}.member.{}", name, i + 1);
+            query_ser_tag(form, &format!("{}.", name), value);
+        }
+    }
+"#
+    ));
+    assert!(code.contains(
+        "aws_sdk_rust_runtime::encoding::push_form(\n\
+         \x20           form,\n\
+         \x20           &name,\n\
+         \x20           &aws_sdk_rust_runtime::encoding::base64_encode(value),\n\
+         \x20       );\n"
+    ));
+
+    let code = generate(&graph, &features)?;
+    format_code(&code, Formatting::Strict)?;
+    assert!(code.contains("pub(crate) fn xml_de_list_queues_result("));
+    assert!(code.contains("let (meta, parsed) = match xml_error_metadata(response) {\n"));
+    assert!(code.contains("pub(crate) fn xml_error_metadata(\n"));
+    assert!(code
+        .contains("Some(\"InvalidMessageContents\") => xml_de_invalid_message_contents(&parsed)"));
+    assert!(code.contains(
+        "let element = aws_sdk_rust_runtime::xml::parse(response.body())?;\n\
+         let element = element.child(\"ListQueuesResult\")\
+         .ok_or(\"the response is missing its `ListQueuesResult` element\")?;\n\
+         xml_de_list_queues_result(&element)\n"
+    ));
+
+    Ok(())
+}
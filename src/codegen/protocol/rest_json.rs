@@ -0,0 +1,294 @@
+use super::{
+    bindings::{rest_response_deserializer, serialize_bindings},
+    error_deserializer,
+    event_stream::{event_deserializers, event_serializers},
+    json::{json_deserializers, json_members, json_serializers, json_value, JSON_ERROR_METADATA},
+    request_serializer, shape_serializer, with_field,
+};
+use crate::{
+    codegen::{features::OperationFeatures, structs::struct_fields},
+    graph::ModelGraph,
+    model::{Location, Operation, Shape},
+    Err,
+};
+
+/// Generates the rest-json protocol's request serializers and response deserializers: a
+/// `serialize_`, a `deserialize_`, and a `deserialize_..._error` function for each operation,
+/// and the `json_ser_` and `json_de_` functions they use for the structures and unions in
+/// their bodies and in errors, and the `event_ser_` and `event_de_` functions for event streams.
+pub fn generate(graph: &ModelGraph<'_>, features: &OperationFeatures) -> Result<String, Err> {
+    let mut out = String::new();
+    for (_, op) in graph.operations() {
+        out.push_str(&serialize_operation(graph, op, features)?);
+        out.push('\n');
+        out.push_str(&deserialize_operation(graph, op, features)?);
+        out.push('\n');
+        out.push_str(&error_deserializer(
+            graph,
+            op,
+            features,
+            "json",
+            "json_error_metadata",
+        )?);
+        out.push('\n');
+    }
+    out.push_str(JSON_ERROR_METADATA);
+    out.push('\n');
+    out.push_str(&json_serializers(
+        graph,
+        graph.operations().map(|(_, op)| op.input.shape),
+    )?);
+    out.push('\n');
+    out.push_str(&json_deserializers(
+        graph,
+        graph
+            .operations()
+            .flat_map(|(_, op)| op.output.iter().chain(&op.errors))
+            .map(|reference| reference.shape),
+    )?);
+    out.push('\n');
+    out.push_str(&event_serializers(
+        graph,
+        graph.operations().map(|(_, op)| op.input.shape),
+        "application/json",
+        |id, value| {
+            format!(
+                "serde_json::to_vec(&{}({}))?",
+                shape_serializer("json", id),
+                value
+            )
+        },
+    )?);
+    out.push('\n');
+    out.push_str(&event_deserializers(
+        graph,
+        graph
+            .operations()
+            .flat_map(|(_, op)| &op.output)
+            .map(|reference| reference.shape),
+        "json",
+        "serde_json::Value::Object(Default::default())",
+        |payload| format!("serde_json::from_slice::<serde_json::Value>({})?", payload),
+    )?);
+    Ok(out)
+}
+
+// The operation's response deserializer; see `rest_response_deserializer`. Bodies are JSON
+// objects, and empty ones are read as if they were `{}`.
+fn deserialize_operation(
+    graph: &ModelGraph<'_>,
+    op: &Operation,
+    features: &OperationFeatures,
+) -> Result<String, Err> {
+    rest_response_deserializer(
+        graph,
+        op,
+        features,
+        "json",
+        "serde_json::Value::Object(Default::default())",
+        |body| format!("serde_json::from_slice::<serde_json::Value>({})?", body),
+    )
+}
+
+// The operation's request serializer; see `request_serializer`.
+//
+// Members bound to the URI, query string, and headers go there. If the input has a payload
+// member, it's the body: blobs and strings as they are, streaming blobs as streams,
+// and structures as JSON. Event streams are their events' messages, one after
+// another, which signing wraps in signed ones. Otherwise the rest of the members make up a JSON object, and
+// operations without any have an empty body.
+fn serialize_operation(
+    graph: &ModelGraph<'_>,
+    op: &Operation,
+    features: &OperationFeatures,
+) -> Result<String, Err> {
+    let input = op.input.shape;
+    let shape = graph
+        .shape(input)
+        .ok_or_else(|| format!("`{}`'s input `{}` isn't defined", op.name, input))?;
+
+    let body = match shape.payload() {
+        Some(payload) => {
+            let (fields, _) = struct_fields(graph, input)?;
+            let field = fields
+                .iter()
+                .find(|field| field.edge.name == payload)
+                .ok_or_else(|| format!("`{}` has no payload member `{}`", input, payload))?;
+            let code = match graph.shape(field.edge.target) {
+                Some(Shape::Structure { .. }) | Some(Shape::Union { .. }) => format!(
+                    "body = serde_json::to_vec(&{})?;\n\
+                     request = request.header(\"Content-Type\", \"application/json\");\n",
+                    json_value(graph, field.edge.member, field.edge.target, "value")?
+                ),
+                Some(Shape::EventStream { .. }) => format!(
+                    "body = value.clone().into_body({}).into();\n\
+                     request = request.header(\"Content-Type\", \"application/vnd.amazon.eventstream\");\n",
+                    shape_serializer("event", field.edge.target)
+                ),
+                Some(Shape::Blob {
+                    streaming: true, ..
+                }) => "body = value.clone();\n".to_string(),
+                _ => "body = value.clone().into();\n".to_string(),
+            };
+            // Streaming blobs and event streams are streamed to the connection rather than read
+            // into memory.
+            let empty = match graph.shape(field.edge.target) {
+                Some(Shape::Blob {
+                    streaming: true, ..
+                })
+                | Some(Shape::EventStream { .. }) => {
+                    "aws_sdk_rust_runtime::byte_stream::ByteStream::default()"
+                }
+                _ => "Vec::new()",
+            };
+            format!(
+                "let mut body = {};\n{}",
+                empty,
+                with_field(field, &code, None)
+            )
+        }
+        None => {
+            let in_body = |name: &str, member: &crate::model::ShapeMember| {
+                matches!(member.location(name), Location::Body(_))
+            };
+            let members = json_members(graph, input, in_body)?;
+            if members.is_empty() {
+                "let body = Vec::new();\n".to_string()
+            } else {
+                format!(
+                    "let mut object = serde_json::Map::new();\n\
+                     {}\
+                     let body = serde_json::to_vec(&serde_json::Value::Object(object))?;\n\
+                     request = request.header(\"Content-Type\", \"application/json\");\n",
+                    members
+                )
+            }
+        }
+    };
+
+    request_serializer(
+        graph,
+        op,
+        features,
+        &format!("{}{}", serialize_bindings(graph, input)?, body),
+    )
+}
+
+#[test]
+fn generates_rest_json_serializers() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    use crate::codegen::format::{format_code, Formatting};
+
+    let mut def =
+        crate::model::BotocoreModel::from_path("test-data/lambda/2015-03-31/service-2.json")?;
+    // It refers to a shape the fixture doesn't define.
+    def.operations.remove("DeleteFunctionConcurrency");
+    def.shapes.remove(&crate::model::ShapeId::from(
+        "DeleteFunctionConcurrencyRequest",
+    ));
+    let graph = ModelGraph::new(&def);
+    let features = OperationFeatures::default();
+
+    let code = serialize_operation(&graph, &def.operations["PutFunctionConcurrency"], &features)?;
+    assert_eq!(
+        format_code(&code, Formatting::Strict)?,
+        r#"pub(crate) fn serialize_put_function_concurrency(
+    request: http::request::Builder,
+    endpoint: &str,
+    input: &PutFunctionConcurrencyRequest,
+) -> Result<http::Request<aws_sdk_rust_runtime::body::Body>, crate::error::BoxError> {
+    let mut uri = String::new();
+    uri.push_str("/2017-10-31/functions/");
+    {
+        let value = &input.function_name;
+        uri.push_str(
+            &aws_sdk_rust_runtime::encoding::percent_encode(&value.to_string()),
+        );
+    }
+    uri.push_str("/concurrency");
+    let mut request = request;
+    let mut object = serde_json::Map::new();
+    {
+        let value = &input.reserved_concurrent_executions;
+        object
+            .insert(
+                "ReservedConcurrentExecutions".to_string(),
+                serde_json::Value::from(*value),
+            );
+    }
+    let body = serde_json::to_vec(&serde_json::Value::Object(object))?;
+    request = request.header("Content-Type", "application/json");
+    Ok(request.uri(format!("{}{}", endpoint, uri)).body(body.into())?)
+}
+"#
+    );
+
+    let code = serialize_operation(&graph, &def.operations["Invoke"], &features)?;
+    assert!(code.contains(
+        "let mut body = Vec::new();\nif let Some(value) = &input.payload {\nbody = value.clone().into();\n}\n"
+    ));
+    let code = serialize_operation(&graph, &def.operations["GetFunction"], &features)?;
+    assert!(code.contains("let body = Vec::new();\n"));
+    let code = serialize_operation(&graph, &def.operations["InvokeAsync"], &features)?;
+    assert!(code.contains(
+        "let mut body = aws_sdk_rust_runtime::byte_stream::ByteStream::default();\n\
+         {\n\
+         let value = &input.invoke_args;\n\
+         body = value.clone();\n"
+    ));
+
+    let code = generate(&graph, &features)?;
+    format_code(&code, Formatting::Strict)?;
+    assert!(code.contains("pub(crate) fn json_ser_vpc_config(input: &VpcConfig)"));
+
+    // Event streams are encoded as they're sent.
+    let def = crate::model::BotocoreModel::from_path("test-data/transcripts.json")?;
+    let graph = ModelGraph::new(&def);
+    let code = serialize_operation(
+        &graph,
+        &def.operations["StartStreamTranscription"],
+        &features,
+    )?;
+    assert!(code.contains(
+        "let mut body = aws_sdk_rust_runtime::byte_stream::ByteStream::default();
+"
+    ));
+    assert!(code.contains(
+        "body = value.clone().into_body(event_ser_audio_stream).into();
+"
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn generates_rest_json_deserializers() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    use crate::codegen::format::{format_code, Formatting};
+
+    let def = crate::model::BotocoreModel::from_path("test-data/lambda/2015-03-31/service-2.json")?;
+    let graph = ModelGraph::new(&def);
+    let features = OperationFeatures::default();
+
+    let code = deserialize_operation(&graph, &def.operations["Invoke"], &features)?;
+    assert!(format_code(&code, Formatting::Strict)?.contains(
+        r#"    let body = response.body();
+    let mut output = json_de_invocation_response(
+        &serde_json::Value::Object(Default::default()),
+    )?;
+    output.payload = Some(body.clone());
+    if let Some(value) = response.headers().get("X-Amz-Executed-Version") {
+        let value = value.to_str()?;
+        output.executed_version = Some(value.to_string());
+    }
+"#
+    ));
+    assert!(code.contains("output.status_code = Some(response.status().as_u16() as i32);\n"));
+    let code = deserialize_operation(&graph, &def.operations["GetFunction"], &features)?;
+    assert!(code.contains(
+        "serde_json::from_slice::<serde_json::Value>(body)?\n};\n\
+         let mut output = json_de_get_function_response(&parsed)?;\n"
+    ));
+    let code = deserialize_operation(&graph, &def.operations["DeleteFunction"], &features)?;
+    assert!(code.ends_with("-> Result<(), crate::error::BoxError> {\nOk(())\n}\n"));
+
+    Ok(())
+}
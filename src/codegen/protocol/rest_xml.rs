@@ -0,0 +1,383 @@
+use super::{
+    bindings::{rest_response_deserializer, serialize_bindings},
+    error_deserializer,
+    event_stream::{event_deserializers, event_serializers},
+    request_serializer, shape_serializer, with_field,
+    xml::{
+        member_namespace, namespace_attribute, xml_deserializers, xml_serializers,
+        XML_ERROR_METADATA,
+    },
+};
+use crate::{
+    codegen::{
+        customizations::ServiceCustomizations, features::OperationFeatures, structs::struct_fields,
+    },
+    graph::ModelGraph,
+    model::{Location, Operation, Shape},
+    Err,
+};
+
+/// Generates the rest-xml protocol's request serializers and response deserializers: a
+/// `serialize_`, a `deserialize_`, and a `deserialize_..._error` function for each operation,
+/// and the `xml_ser_` and `xml_de_` functions they use for the structures and unions in their
+/// bodies and in errors, and the `event_ser_` and `event_de_` functions for event streams.
+pub fn generate(graph: &ModelGraph<'_>, features: &OperationFeatures) -> Result<String, Err> {
+    let mut out = String::new();
+    for (_, op) in graph.operations() {
+        out.push_str(&serialize_operation(graph, op, features)?);
+        out.push('\n');
+        out.push_str(&deserialize_operation(graph, op, features)?);
+        out.push('\n');
+        out.push_str(&error_deserializer(
+            graph,
+            op,
+            features,
+            "xml",
+            "xml_error_metadata",
+        )?);
+        out.push('\n');
+    }
+    out.push_str(XML_ERROR_METADATA);
+    out.push('\n');
+    out.push_str(&xml_serializers(
+        graph,
+        graph.operations().map(|(_, op)| op.input.shape),
+    )?);
+    out.push('\n');
+    out.push_str(&xml_deserializers(
+        graph,
+        graph
+            .operations()
+            .flat_map(|(_, op)| op.output.iter().chain(&op.errors))
+            .map(|reference| reference.shape),
+    )?);
+    out.push('\n');
+    out.push_str(&event_serializers(
+        graph,
+        graph.operations().map(|(_, op)| op.input.shape),
+        "application/xml",
+        |id, value| {
+            format!(
+                "{{\nlet mut out = String::new();\n{}(&mut out, {:?}, \"\", {});\nout.into_bytes()\n}}",
+                shape_serializer("xml", id),
+                id.as_str(),
+                value
+            )
+        },
+    )?);
+    out.push('\n');
+    out.push_str(&event_deserializers(
+        graph,
+        graph
+            .operations()
+            .flat_map(|(_, op)| &op.output)
+            .map(|reference| reference.shape),
+        "xml",
+        "aws_sdk_rust_runtime::xml::Element::default()",
+        |payload| format!("aws_sdk_rust_runtime::xml::parse({})?", payload),
+    )?);
+    Ok(out)
+}
+
+// The operation's request serializer; see `request_serializer`.
+//
+// Members bound to the URI, query string, and headers go there. If the input has a payload
+// member, it's the body: blobs and strings as they are, streaming blobs as streams,
+// and structures as XML elements named for the member. Event streams are their events' messages,
+// one after another, which signing wraps in signed ones. Otherwise the rest of the members are
+// the children of an element named for the input, and operations without any have an empty
+// body.
+fn serialize_operation(
+    graph: &ModelGraph<'_>,
+    op: &Operation,
+    features: &OperationFeatures,
+) -> Result<String, Err> {
+    let input = op.input.shape;
+    let shape = graph
+        .shape(input)
+        .ok_or_else(|| format!("`{}`'s input `{}` isn't defined", op.name, input))?;
+    let from_xml = |code: String| {
+        format!(
+            "let mut out = String::new();\n\
+             {}\
+             body = out.into_bytes();\n\
+             request = request.header(\"Content-Type\", \"application/xml\");\n",
+            code
+        )
+    };
+
+    let body = match shape.payload() {
+        Some(payload) => {
+            let (fields, _) = struct_fields(graph, input)?;
+            let field = fields
+                .iter()
+                .find(|field| field.edge.name == payload)
+                .ok_or_else(|| format!("`{}` has no payload member `{}`", input, payload))?;
+            let code = match graph.shape(field.edge.target) {
+                Some(Shape::Structure { .. }) | Some(Shape::Union { .. }) => {
+                    let member = field.edge.member;
+                    from_xml(format!(
+                        "{}(&mut out, {:?}, {:?}, value);\n",
+                        shape_serializer("xml", field.edge.target),
+                        member.location_name.as_deref().unwrap_or(payload),
+                        member_namespace(member, graph.shape(field.edge.target))
+                    ))
+                }
+                Some(Shape::EventStream { .. }) => format!(
+                    "body = value.clone().into_body({}).into();\n\
+                     request = request.header(\"Content-Type\", \"application/vnd.amazon.eventstream\");\n",
+                    shape_serializer("event", field.edge.target)
+                ),
+                Some(Shape::Blob {
+                    streaming: true, ..
+                }) => "body = value.clone();\n".to_string(),
+                _ => "body = value.clone().into();\n".to_string(),
+            };
+            // Streaming blobs and event streams are streamed to the connection rather than read
+            // into memory.
+            let empty = match graph.shape(field.edge.target) {
+                Some(Shape::Blob {
+                    streaming: true, ..
+                })
+                | Some(Shape::EventStream { .. }) => {
+                    "aws_sdk_rust_runtime::byte_stream::ByteStream::default()"
+                }
+                _ => "Vec::new()",
+            };
+            format!(
+                "let mut body = {};\n{}",
+                empty,
+                with_field(field, &code, None)
+            )
+        }
+        None => {
+            let in_body = shape
+                .member_locations()
+                .iter()
+                .any(|(_, location)| matches!(location, Location::Body(_)));
+            if in_body {
+                // Botocore names the element for the input's `locationName` on the operation,
+                // which this model doesn't keep, but it's the input's name in practice.
+                let namespace = match shape {
+                    Shape::Structure {
+                        xml_namespace: Some(namespace),
+                        ..
+                    } => namespace_attribute(namespace),
+                    _ => String::new(),
+                };
+                format!(
+                    "let mut body = Vec::new();\n{}",
+                    from_xml(format!(
+                        "{}(&mut out, {:?}, {:?}, input);\n",
+                        shape_serializer("xml", input),
+                        input.as_str(),
+                        namespace
+                    ))
+                )
+            } else {
+                "let body = Vec::new();\n".to_string()
+            }
+        }
+    };
+
+    request_serializer(
+        graph,
+        op,
+        features,
+        &format!("{}{}", serialize_bindings(graph, input)?, body),
+    )
+}
+
+// The operation's response deserializer; see `rest_response_deserializer`. Bodies are read
+// from their root element, and empty ones as if they were an element with nothing in it.
+// Bodies whose root element is the output's only member are read from an element holding it.
+fn deserialize_operation(
+    graph: &ModelGraph<'_>,
+    op: &Operation,
+    features: &OperationFeatures,
+) -> Result<String, Err> {
+    let bare =
+        ServiceCustomizations::for_service(&graph.model().metadata).has_bare_output(&op.name);
+    rest_response_deserializer(
+        graph,
+        op,
+        features,
+        "xml",
+        "aws_sdk_rust_runtime::xml::Element::default()",
+        |body| {
+            let parse = format!("aws_sdk_rust_runtime::xml::parse({})?", body);
+            if bare {
+                format!("aws_sdk_rust_runtime::xml::enclose({})", parse)
+            } else {
+                parse
+            }
+        },
+    )
+}
+
+#[test]
+fn generates_rest_xml_serializers() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    use crate::codegen::format::{format_code, Formatting};
+
+    let def = crate::model::BotocoreModel::from_path("test-data/buckets.json")?;
+    let graph = ModelGraph::new(&def);
+    let features = OperationFeatures::default();
+
+    let code = serialize_operation(&graph, &def.operations["PutBucketTagging"], &features)?;
+    assert!(format_code(&code, Formatting::Strict)?.contains(
+        r#"    let mut body = Vec::new();
+    {
+        let value = &input.tagging;
+        let mut out = String::new();
+        xml_ser_tagging(
+            &mut out,
+            "Tagging",
+            " xmlns=\"https://buckets.amazonaws.com/doc/2020-06-01/\"",
+            value,
+        );
+        body = out.into_bytes();
+        request = request.header("Content-Type", "application/xml");
+    }
+"#
+    ));
+    let code = deserialize_operation(&graph, &def.operations["GetBucketLocation"], &features)?;
+    assert!(code.contains("aws_sdk_rust_runtime::xml::enclose(aws_sdk_rust_runtime::xml::parse("));
+    let code = deserialize_operation(&graph, &def.operations["ListObjects"], &features)?;
+    assert!(!code.contains("enclose("));
+    let code = serialize_operation(&graph, &def.operations["ChangeTags"], &features)?;
+    assert!(code.contains("xml_ser_change_tags_request(&mut out, \"ChangeTagsRequest\", "));
+    let code = serialize_operation(&graph, &def.operations["ListObjects"], &features)?;
+    assert!(code.contains("let body = Vec::new();\n"));
+    let code = serialize_operation(&graph, &def.operations["PutBucketTagging"], &features)?;
+    assert!(code.contains("uri.push_str(\"?tagging\");\n"));
+    let code = format_code(
+        &serialize_operation(&graph, &def.operations["GetObject"], &features)?,
+        Formatting::Strict,
+    )?;
+    assert!(code.contains(
+        r#"    let mut uri = String::new();
+    uri.push_str("/");
+    {
+        let value = &input.bucket;
+        uri.push_str(
+            &aws_sdk_rust_runtime::encoding::percent_encode(&value.to_string()),
+        );
+    }
+    uri.push_str("/");
+    {
+        let value = &input.key;
+        uri.push_str(
+            &aws_sdk_rust_runtime::encoding::percent_encode_greedy(&value.to_string()),
+        );
+    }
+"#
+    ));
+
+    let code = format_code(&generate(&graph, &features)?, Formatting::Strict)?;
+    assert!(code.contains(
+        r#"pub(crate) fn xml_ser_tagging(
+    out: &mut String,
+    tag: &str,
+    namespace: &str,
+    input: &Tagging,
+) {
+    out.push('<');
+    out.push_str(tag);
+    out.push_str(namespace);
+    out.push('>');
+    {
+        let value = &input.tag_set;
+        out.push_str("<TagSet>");
+        for value in value {
+            xml_ser_tag(out, "Tag", "", value);
+        }
+        out.push_str("</TagSet>");
+    }
+    out.push_str("</");
+    out.push_str(tag);
+    out.push('>');
+}
+"#
+    ));
+    // The bucket is in the URI, not the body.
+    assert!(!code.contains("\"Bucket\", &value"));
+    assert!(code.contains(
+        r#"        for (key, value) in value {
+            out.push_str("<entry>");
+            aws_sdk_rust_runtime::xml::write_element(out, "Name", &key.to_string());
+            aws_sdk_rust_runtime::xml::write_element(out, "value", &value.to_string());
+            out.push_str("</entry>");
+        }
+"#
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn generates_xml_deserializers() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    use crate::codegen::format::{format_code, Formatting};
+
+    let def = crate::model::BotocoreModel::from_path("test-data/buckets.json")?;
+    let graph = ModelGraph::new(&def);
+    let code = format_code(
+        &xml_deserializers(
+            &graph,
+            vec![crate::model::ShapeId::from("ListObjectsOutput")],
+        )?,
+        Formatting::Strict,
+    )?;
+    assert!(code.contains(
+        r#"pub(crate) fn xml_de_list_objects_output(
+    element: &aws_sdk_rust_runtime::xml::Element,
+) -> Result<ListObjectsOutput, crate::error::BoxError> {
+    Ok(ListObjectsOutput {
+        contents: Some({
+                let mut items = Vec::new();
+                for element in element.children_named("Contents") {
+                    items.push(xml_de_object(element)?);
+                }
+                items
+            })
+            .filter(|items| !items.is_empty()),
+        encoding_type: match element.child("EncodingType") {
+            Some(element) => Some(EncodingType::from(element.text.as_str())),
+            None => None,
+        },
+        is_truncated: match element.child("IsTruncated") {
+            Some(element) => Some(element.text.as_str().parse::<bool>()?),
+            None => None,
+        },
+        name: match element.child("Name") {
+            Some(element) => Some(element.text.as_str().to_string()),
+            None => None,
+        },
+        next_token: None,
+        prefix: match element.child("Prefix") {
+            Some(element) => Some(element.text.as_str().to_string()),
+            None => None,
+        },
+        _request_id: None,
+        _extended_request_id: None,
+    })
+}
+"#
+    ));
+    assert!(code.contains(
+        r#"        key: match element.child("Key") {
+            Some(element) => element.text.as_str().to_string(),
+            None => return Err("`Object.Key` is missing".into()),
+        },
+"#
+    ));
+    assert!(code.contains("aws_sdk_rust_runtime::time::parse_iso8601(element.text.as_str())?"));
+    assert!(code.contains(
+        r#"        r#type: match element.attribute("type") {
+            Some(text) => Some(OwnerType::from(text)),
+            None => None,
+        },
+"#
+    ));
+
+    Ok(())
+}
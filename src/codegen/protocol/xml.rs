@@ -0,0 +1,472 @@
+use super::{
+    aggregates, field_value, in_body, is_flattened, parse_wire_string, shape_deserializer,
+    shape_serializer, wire_string, with_field,
+};
+use crate::{
+    codegen::{
+        allow_deprecated,
+        structs::{request_id_fields, struct_fields, Field},
+        unions::variant_names,
+        uses_deprecated,
+    },
+    graph::ModelGraph,
+    model::{Shape, ShapeId, ShapeMember, TimestampFormat, XmlNamespace},
+    naming::type_name,
+    Err,
+};
+
+/// What the rest-xml, query, and EC2 error deserializers read error responses with, emitted
+/// once into the `protocol` module. The code and message are in an `<Error>` element, which
+/// is the root of some rest-xml responses, in `<ErrorResponse>` in query and other rest-xml
+/// ones, and in `<Response><Errors>` in EC2's. The request ID is in a header or beside it.
+pub const XML_ERROR_METADATA: &str = r#"pub(crate) fn xml_error_metadata(
+    response: &http::Response<Vec<u8>>,
+) -> Result<(crate::error::ErrorMetadata, aws_sdk_rust_runtime::xml::Element), crate::error::BoxError> {
+    let body = response.body();
+    let root = if body.is_empty() {
+        aws_sdk_rust_runtime::xml::Element::default()
+    } else {
+        aws_sdk_rust_runtime::xml::parse(body)?
+    };
+    let error = if root.name == "Error" {
+        root.clone()
+    } else {
+        root.child("Error")
+            .or_else(|| root.child("Errors").and_then(|errors| errors.child("Error")))
+            .cloned()
+            .unwrap_or_default()
+    };
+    let text = |element: &aws_sdk_rust_runtime::xml::Element, name: &str| {
+        element.child(name).map(|child| child.text.clone())
+    };
+    let code = text(&error, "Code");
+    let message = text(&error, "Message");
+    let request_id = crate::error::request_id(response.headers())
+        .or_else(|| text(&root, "RequestId"))
+        .or_else(|| text(&root, "RequestID"))
+        .or_else(|| text(&error, "RequestId"));
+    let meta = crate::error::ErrorMetadata::new(code, message, request_id)
+        .with_extended_request_id(crate::error::extended_request_id(response.headers()))
+        .with_status(response.status().as_u16());
+    Ok((meta, error))
+}
+"#;
+
+/// The attribute declaring an XML namespace, with the space that goes before it, e.g.
+/// ` xmlns="https://route53.amazonaws.com/doc/2013-04-01/"`.
+pub fn namespace_attribute(namespace: &XmlNamespace) -> String {
+    match &namespace.prefix {
+        Some(prefix) => format!(" xmlns:{}=\"{}\"", prefix, namespace.uri),
+        None => format!(" xmlns=\"{}\"", namespace.uri),
+    }
+}
+
+// The namespace attribute for the element of a member: the member's namespace, or else its
+// target's, or nothing.
+pub(crate) fn member_namespace(member: &ShapeMember, target: Option<&Shape>) -> String {
+    let namespace = member.xml_namespace.as_ref().or(match target {
+        Some(Shape::Structure { xml_namespace, .. }) => xml_namespace.as_ref(),
+        _ => None,
+    });
+    namespace.map(namespace_attribute).unwrap_or_default()
+}
+
+fn wire_name<'a>(field: &Field<'a>) -> &'a str {
+    field
+        .edge
+        .member
+        .location_name
+        .as_deref()
+        .unwrap_or(field.edge.name)
+}
+
+/// Code writing `expr`, a reference to a member's value, to `out`, a `&mut String`, as the
+/// element `tag`.
+///
+/// Lists wrap their items in `member` elements and maps their entries in `entry` elements,
+/// unless the model names them otherwise. Flattened lists and maps leave out the wrapping
+/// element, so each item or entry is a `tag` element of its own. Timestamps are ISO 8601
+/// unless the model says otherwise.
+pub fn xml_write(
+    graph: &ModelGraph<'_>,
+    member: &ShapeMember,
+    target: ShapeId,
+    tag: &str,
+    expr: &str,
+) -> Result<String, Err> {
+    let shape = graph.shape(target);
+    let flattened = is_flattened(member, shape);
+    let wrap = |code: String| {
+        if flattened {
+            code
+        } else {
+            format!(
+                "out.push_str({:?});\n{}out.push_str({:?});\n",
+                format!("<{}{}>", tag, member_namespace(member, shape)),
+                code,
+                format!("</{}>", tag)
+            )
+        }
+    };
+    Ok(match shape {
+        Some(Shape::Structure { .. }) | Some(Shape::Union { .. }) => format!(
+            "{}(out, {:?}, {:?}, {});\n",
+            shape_serializer("xml", target),
+            tag,
+            member_namespace(member, shape),
+            expr
+        ),
+        Some(Shape::List { member: item, .. }) => {
+            let item_tag = match &item.location_name {
+                _ if flattened => tag,
+                Some(name) => name,
+                None => "member",
+            };
+            wrap(format!(
+                "for value in {} {{\n{}}}\n",
+                expr,
+                xml_write(graph, item, item.shape.shape, item_tag, "value")?
+            ))
+        }
+        Some(Shape::Map { key, value, .. }) => {
+            let entry_tag = if flattened { tag } else { "entry" };
+            let key_tag = key.location_name.as_deref().unwrap_or("key");
+            let value_tag = value.location_name.as_deref().unwrap_or("value");
+            wrap(format!(
+                "for (key, value) in {} {{\n\
+                 out.push_str({:?});\n\
+                 {}{}\
+                 out.push_str({:?});\n\
+                 }}\n",
+                expr,
+                format!("<{}>", entry_tag),
+                xml_write(graph, key, key.shape.shape, key_tag, "key")?,
+                xml_write(graph, value, value.shape.shape, value_tag, "value")?,
+                format!("</{}>", entry_tag)
+            ))
+        }
+        _ => format!(
+            "aws_sdk_rust_runtime::xml::write_element(out, {:?}, &{});\n",
+            tag,
+            wire_string(graph, member, target, expr, TimestampFormat::Iso8601)?
+        ),
+    })
+}
+
+/// An expression reading a member's value from `element`, an
+/// `&aws_sdk_rust_runtime::xml::Element` for the member. It uses `?`, so it has to be in a
+/// function returning a `Result` with a boxed error.
+///
+/// This undoes `xml_write`, except that flattened lists and maps are read from their parent
+/// element by `xml_read_field`.
+pub fn xml_read(
+    graph: &ModelGraph<'_>,
+    member: &ShapeMember,
+    target: ShapeId,
+    element: &str,
+) -> Result<String, Err> {
+    Ok(match graph.shape(target) {
+        Some(Shape::Structure { .. }) | Some(Shape::Union { .. }) => {
+            format!("{}({})?", shape_deserializer("xml", target), element)
+        }
+        Some(Shape::List { member: item, .. }) => read_items(
+            graph,
+            item,
+            element,
+            item.location_name.as_deref().unwrap_or("member"),
+        )?,
+        Some(Shape::Map { .. }) => read_entries(graph, target, element, "entry")?,
+        _ => parse_wire_string(
+            graph,
+            member,
+            target,
+            &format!("{}.text.as_str()", element),
+            TimestampFormat::Iso8601,
+        )?,
+    })
+}
+
+// A block reading the `tag` children of `parent` into a `Vec` of list items.
+fn read_items(
+    graph: &ModelGraph<'_>,
+    item: &ShapeMember,
+    parent: &str,
+    tag: &str,
+) -> Result<String, Err> {
+    Ok(format!(
+        "{{\n\
+         let mut items = Vec::new();\n\
+         for element in {}.children_named({:?}) {{\n\
+         items.push({});\n\
+         }}\n\
+         items\n\
+         }}",
+        parent,
+        tag,
+        xml_read(graph, item, item.shape.shape, "element")?
+    ))
+}
+
+// A block reading the `tag` children of `parent`, each holding a key element and a value
+// element, into a `HashMap`.
+fn read_entries(
+    graph: &ModelGraph<'_>,
+    map: ShapeId,
+    parent: &str,
+    tag: &str,
+) -> Result<String, Err> {
+    let (key, value) = match graph.shape(map) {
+        Some(Shape::Map { key, value, .. }) => (key, value),
+        _ => return Err(format!("`{}` isn't a map", map).into()),
+    };
+    let read = |member: &ShapeMember, default: &str| -> Result<String, Err> {
+        let tag = member.location_name.as_deref().unwrap_or(default);
+        Ok(format!(
+            "{{\n\
+             let element = entry.child({:?}).ok_or({:?})?;\n\
+             {}\n\
+             }}",
+            tag,
+            format!("a map entry is missing its `{}`", tag),
+            xml_read(graph, member, member.shape.shape, "element")?
+        ))
+    };
+    Ok(format!(
+        "{{\n\
+         let mut entries = std::collections::HashMap::new();\n\
+         for entry in {}.children_named({:?}) {{\n\
+         let key = {};\n\
+         let value = {};\n\
+         entries.insert(key, value);\n\
+         }}\n\
+         entries\n\
+         }}",
+        parent,
+        tag,
+        read(key, "key")?,
+        read(value, "value")?
+    ))
+}
+
+// An expression reading a structure field's value from `element`, the structure's element.
+// Flattened lists and maps with no items are unset, and other required members are an error
+// when they're missing.
+fn xml_read_field(graph: &ModelGraph<'_>, id: ShapeId, field: &Field<'_>) -> Result<String, Err> {
+    let (member, target) = (field.edge.member, field.edge.target);
+    let shape = graph.shape(target);
+    let tag = wire_name(field);
+    let optional = field.is_optional();
+    let wrap = |value: String| field_value(field, &value);
+    let missing = if optional {
+        "None".to_string()
+    } else {
+        format!(
+            "return Err({:?}.into())",
+            format!("`{}.{}` is missing", id, field.edge.name)
+        )
+    };
+
+    if member.xml_attribute {
+        return Ok(format!(
+            "match element.attribute({:?}) {{\n\
+             Some(text) => {},\n\
+             None => {},\n\
+             }}",
+            tag.rsplit(':').next().unwrap_or(tag),
+            wrap(parse_wire_string(
+                graph,
+                member,
+                target,
+                "text",
+                TimestampFormat::Iso8601
+            )?),
+            missing
+        ));
+    }
+    if is_flattened(member, shape) {
+        let read = match shape {
+            Some(Shape::List { member: item, .. }) => read_items(graph, item, "element", tag)?,
+            _ => read_entries(graph, target, "element", tag)?,
+        };
+        return Ok(if optional {
+            format!("Some({}).filter(|items| !items.is_empty())", read)
+        } else {
+            read
+        });
+    }
+    Ok(format!(
+        "match element.child({:?}) {{\n\
+         Some(element) => {},\n\
+         None => {},\n\
+         }}",
+        tag,
+        wrap(xml_read(graph, member, target, "element")?),
+        missing
+    ))
+}
+
+/// Generates the `xml_ser_` function for every structure and union `roots` reference,
+/// including themselves, in name order. Each writes its value as an element with the tag and
+/// namespace attribute it's given.
+pub fn xml_serializers(
+    graph: &ModelGraph<'_>,
+    roots: impl IntoIterator<Item = ShapeId>,
+) -> Result<String, Err> {
+    let mut out = String::new();
+    for id in aggregates(graph, roots) {
+        if !out.is_empty() {
+            out.push('\n');
+        }
+        out.push_str(&xml_serializer(graph, id)?);
+    }
+    Ok(out)
+}
+
+// The `xml_ser_` function for a structure or union. Members bound outside of the body are
+// left out, so an operation's input can be serialized as its body.
+fn xml_serializer(graph: &ModelGraph<'_>, id: ShapeId) -> Result<String, Err> {
+    let name = type_name(id.as_str());
+    let (attributes, children) = match graph.shape(id) {
+        Some(Shape::Union { .. }) => {
+            let variants = variant_names(graph, id)?;
+            let mut arms = String::new();
+            for edge in graph.neighbors(id) {
+                let tag = edge.member.location_name.as_deref().unwrap_or(edge.name);
+                arms.push_str(&format!(
+                    "{}::{}(value) => {{\n{}}}\n",
+                    name,
+                    variants[edge.name],
+                    xml_write(graph, edge.member, edge.target, tag, "value")?
+                ));
+            }
+            (
+                String::new(),
+                format!("match input {{\n{}{}::Unknown => {{}}\n}}\n", arms, name),
+            )
+        }
+        Some(shape) => {
+            let (fields, _) = struct_fields(graph, id)?;
+            let (mut attributes, mut children) = (String::new(), String::new());
+            for field in fields.iter().filter(|field| in_body(shape, field)) {
+                let (member, target) = (field.edge.member, field.edge.target);
+                if member.xml_attribute {
+                    let code = format!(
+                        "aws_sdk_rust_runtime::xml::write_attribute(out, {:?}, &{});\n",
+                        wire_name(field),
+                        wire_string(graph, member, target, "value", TimestampFormat::Iso8601)?
+                    );
+                    attributes.push_str(&with_field(field, &code, None));
+                } else {
+                    let code = xml_write(graph, member, target, wire_name(field), "value")?;
+                    children.push_str(&with_field(field, &code, None));
+                }
+            }
+            (attributes, children)
+        }
+        None => return Err(format!("`{}` isn't defined", id).into()),
+    };
+    Ok(format!(
+        "{}pub(crate) fn {}(out: &mut String, tag: &str, namespace: &str, input: &{}) {{\n\
+         out.push('<');\n\
+         out.push_str(tag);\n\
+         out.push_str(namespace);\n\
+         {}\
+         out.push('>');\n\
+         {}\
+         out.push_str(\"</\");\n\
+         out.push_str(tag);\n\
+         out.push('>');\n\
+         }}\n",
+        allow_deprecated(uses_deprecated(graph, id)),
+        shape_serializer("xml", id),
+        name,
+        attributes,
+        children
+    ))
+}
+
+/// Generates the `xml_de_` function for every structure and union `roots` reference,
+/// including themselves, in name order. Each reads its value from an element.
+pub fn xml_deserializers(
+    graph: &ModelGraph<'_>,
+    roots: impl IntoIterator<Item = ShapeId>,
+) -> Result<String, Err> {
+    let mut out = String::new();
+    for id in aggregates(graph, roots) {
+        if !out.is_empty() {
+            out.push('\n');
+        }
+        out.push_str(&xml_deserializer(graph, id)?);
+    }
+    Ok(out)
+}
+
+// The `xml_de_` function for a structure or union.
+//
+// Members bound outside of the body are left unset, or at their defaults if they're required,
+// for an operation's response deserializer to fill in. Required members missing from the
+// element are an error. A union is whichever of its members comes first, or `Unknown` if none
+// of them do.
+fn xml_deserializer(graph: &ModelGraph<'_>, id: ShapeId) -> Result<String, Err> {
+    let name = type_name(id.as_str());
+    let body = match graph.shape(id) {
+        Some(Shape::Union { .. }) => {
+            let variants = variant_names(graph, id)?;
+            let mut out = String::new();
+            for edge in graph.neighbors(id) {
+                let target = graph
+                    .shape(edge.target)
+                    .ok_or_else(|| format!("`{}` isn't defined", edge.target))?;
+                let boxed = crate::codegen::structs::field_type(graph, edge, target, true)
+                    .starts_with("Box<");
+                let value = xml_read(graph, edge.member, edge.target, "element")?;
+                out.push_str(&format!(
+                    "if let Some(element) = element.child({:?}) {{\n\
+                     return Ok({}::{}({}));\n\
+                     }}\n",
+                    edge.member.location_name.as_deref().unwrap_or(edge.name),
+                    name,
+                    variants[edge.name],
+                    if boxed {
+                        format!("Box::new({})", value)
+                    } else {
+                        value
+                    }
+                ));
+            }
+            format!("{}Ok({}::Unknown)\n", out, name)
+        }
+        Some(shape) => {
+            let (fields, _) = struct_fields(graph, id)?;
+            let mut members = String::new();
+            for field in &fields {
+                let value = if !in_body(shape, field) {
+                    if field.is_optional() {
+                        "None".to_string()
+                    } else {
+                        "Default::default()".to_string()
+                    }
+                } else {
+                    xml_read_field(graph, id, field)?
+                };
+                members.push_str(&format!("{}: {},\n", field.name, value));
+            }
+            for name in request_id_fields(graph, id) {
+                members.push_str(&format!("_{}: None,\n", name));
+            }
+            format!("Ok({} {{\n{}}})\n", name, members)
+        }
+        None => return Err(format!("`{}` isn't defined", id).into()),
+    };
+    Ok(format!(
+        "{}pub(crate) fn {}(\n\
+         element: &aws_sdk_rust_runtime::xml::Element,\n\
+         ) -> Result<{}, crate::error::BoxError> {{\n\
+         {}\
+         }}\n",
+        allow_deprecated(uses_deprecated(graph, id)),
+        shape_deserializer("xml", id),
+        name,
+        body
+    ))
+}
@@ -0,0 +1,68 @@
+use super::errors::error_code;
+use crate::{graph::ModelGraph, model::Protocol};
+use std::collections::BTreeMap;
+
+/// Generates a service's `retry` module, whose `classify` tells how retryable an error
+/// response is: as its error shape's `retryable` trait says, if its code is one of the
+/// model's retryable errors, and by its status code otherwise. `classify_response` reads the
+/// code from the response as the protocol's error deserializers do.
+pub fn generate_retry_classifier(graph: &ModelGraph<'_>) -> String {
+    let mut kinds = BTreeMap::new();
+    for (id, shape) in graph.shapes() {
+        if let Some(retryable) = shape.retryable() {
+            let kind = if retryable.throttling {
+                "Throttling"
+            } else {
+                "Transient"
+            };
+            kinds.insert(error_code(id, shape), kind);
+        }
+    }
+    let arms = kinds
+        .iter()
+        .map(|(code, kind)| format!("        Some({:?}) => RetryKind::{},\n", code, kind))
+        .collect::<String>();
+    let metadata = match graph.model().metadata.protocol {
+        Protocol::RestJson | Protocol::Json => "json_error_metadata",
+        Protocol::RestXml | Protocol::Query | Protocol::Ec2 => "xml_error_metadata",
+    };
+    format!(
+        "use aws_sdk_rust_runtime::retry::RetryKind;\n\n\
+         /// How retryable an error response with `status` and, if it has one, error `code` is:\n\
+         /// the errors the model marks retryable are, whatever their status, and otherwise\n\
+         /// 429s and 5xxs are.\n\
+         pub fn classify(status: u16, code: Option<&str>) -> RetryKind {{\n    \
+         match code.map(crate::error::sanitize_error_code) {{\n\
+         {arms}        \
+         _ => RetryKind::from_status(status),\n    \
+         }}\n\
+         }}\n\n\
+         /// How retryable an error response is, by its status and the error code in it.\n\
+         pub fn classify_response(response: &http::Response<Vec<u8>>) -> RetryKind {{\n    \
+         let meta = crate::protocol::{metadata}(response).ok().map(|(meta, _)| meta);\n    \
+         let code = meta.as_ref().and_then(|meta| meta.code());\n    \
+         classify(response.status().as_u16(), code)\n\
+         }}\n",
+        arms = arms,
+        metadata = metadata,
+    )
+}
+
+#[test]
+fn generates_retry_classifiers() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let def = crate::model::BotocoreModel::from_path("test-data/queues.json")?;
+    let code = generate_retry_classifier(&ModelGraph::new(&def));
+    assert!(code.contains("        Some(\"RequestThrottled\") => RetryKind::Throttling,\n"));
+    assert!(code.contains(
+        "        Some(\"AWS.SimpleQueueService.QueueUnavailable\") => RetryKind::Transient,\n"
+    ));
+    assert!(!code.contains("InvalidMessageContents"));
+    assert!(code.contains("crate::protocol::xml_error_metadata(response)"));
+
+    let def = crate::model::BotocoreModel::from_path("test-data/recursive.json")?;
+    let code = generate_retry_classifier(&ModelGraph::new(&def));
+    assert!(code.contains("    match code.map(crate::error::sanitize_error_code) {\n        _ =>"));
+    assert!(code.contains("crate::protocol::json_error_metadata(response)"));
+
+    Ok(())
+}
@@ -0,0 +1,246 @@
+use crate::model::BotocoreModel;
+
+// The operations `Client::upload` is built on.
+const UPLOAD_OPERATIONS: &[&str] = &[
+    "AbortMultipartUpload",
+    "CompleteMultipartUpload",
+    "CreateMultipartUpload",
+    "PutObject",
+    "UploadPart",
+];
+
+/// Whether a model has the operations S3's `upload` helper is built on, so a crate whose
+/// customizations have it can get its `customizations` module.
+pub fn has_upload_operations(model: &BotocoreModel) -> bool {
+    UPLOAD_OPERATIONS
+        .iter()
+        .all(|op| model.operations.contains_key(*op))
+}
+
+/// The operations' names, e.g. for the features the `customizations` module needs.
+pub fn upload_operations() -> &'static [&'static str] {
+    UPLOAD_OPERATIONS
+}
+
+/// S3's `upload` helper, emitted into its crate's `customizations` module: a `PutObject` for
+/// small bodies, and a multipart upload, with parts uploaded concurrently and aborted if any
+/// of them fail, for large ones.
+pub const S3_UPLOAD: &str = r#"use crate::{
+    client::Client,
+    connector::HttpConnector,
+    error::{
+        BuildError, CompleteMultipartUploadError, CreateMultipartUploadError, PutObjectError,
+        SdkError, UploadPartError,
+    },
+    model::{
+        AbortMultipartUploadRequest, CompleteMultipartUploadOutput,
+        CompleteMultipartUploadRequest, CompletedMultipartUpload, CompletedPart,
+        CreateMultipartUploadRequest, PutObjectOutput, PutObjectRequest, UploadPartRequest,
+    },
+};
+use aws_sdk_rust_runtime::upload::try_join;
+use std::{error::Error, fmt, io};
+
+pub use aws_sdk_rust_runtime::upload::UploadConfig;
+
+/// How `Client::upload` wrote an object.
+#[derive(Debug, Clone, PartialEq)]
+pub enum UploadOutput {
+    /// With a `PutObject`, since its body was smaller than the config's threshold.
+    PutObject(PutObjectOutput),
+    /// With a multipart upload.
+    Multipart(CompleteMultipartUploadOutput),
+}
+
+impl UploadOutput {
+    pub fn e_tag(&self) -> Option<&str> {
+        match self {
+            UploadOutput::PutObject(output) => output.e_tag.as_deref(),
+            UploadOutput::Multipart(output) => output.e_tag.as_deref(),
+        }
+    }
+
+    pub fn version_id(&self) -> Option<&str> {
+        match self {
+            UploadOutput::PutObject(output) => output.version_id.as_deref(),
+            UploadOutput::Multipart(output) => output.version_id.as_deref(),
+        }
+    }
+}
+
+/// Why `Client::upload` couldn't write an object. Multipart uploads that fail once they've
+/// started are aborted, so their parts aren't kept, and billed for, until a lifecycle rule
+/// deletes them, unless aborting them fails too.
+#[derive(Debug)]
+pub enum UploadError {
+    PutObject(SdkError<PutObjectError>),
+    CreateMultipartUpload(SdkError<CreateMultipartUploadError>),
+    /// The body couldn't be split into parts, e.g. because it's a stream, or the range of a
+    /// part is past its end.
+    Body(io::Error),
+    UploadPart {
+        part_number: i32,
+        error: SdkError<UploadPartError>,
+    },
+    CompleteMultipartUpload(SdkError<CompleteMultipartUploadError>),
+}
+
+impl fmt::Display for UploadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UploadError::PutObject(error) => write!(f, "{}", error),
+            UploadError::CreateMultipartUpload(error) => {
+                write!(f, "the multipart upload couldn't be started: {}", error)
+            }
+            UploadError::Body(error) => write!(f, "the body couldn't be split: {}", error),
+            UploadError::UploadPart { part_number, error } => {
+                write!(f, "part {} couldn't be uploaded: {}", part_number, error)
+            }
+            UploadError::CompleteMultipartUpload(error) => {
+                write!(f, "the multipart upload couldn't be completed: {}", error)
+            }
+        }
+    }
+}
+
+impl Error for UploadError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            UploadError::PutObject(error) => Some(error),
+            UploadError::CreateMultipartUpload(error) => Some(error),
+            UploadError::Body(error) => Some(error),
+            UploadError::UploadPart { error, .. } => Some(error),
+            UploadError::CompleteMultipartUpload(error) => Some(error),
+        }
+    }
+}
+
+impl<C: HttpConnector + Send + Sync + 'static> Client<C> {
+    /// Writes an object, like `put_object`, but in the parts of a multipart upload if its
+    /// body is large, so objects larger than a `PutObject` allows can be written, and faster,
+    /// with parts uploaded concurrently. Bodies are split as `UploadConfig::default()` has it.
+    pub async fn upload(&self, input: PutObjectRequest) -> Result<UploadOutput, UploadError> {
+        self.upload_with_config(input, UploadConfig::default()).await
+    }
+
+    /// `upload`, splitting bodies as `config` says instead. A multipart upload only gets the
+    /// input's bucket, key, body, content type, metadata, and storage class. Bodies of unknown
+    /// length are put whole, and streams that can only be read in order, e.g. another
+    /// response's, can't be split.
+    pub async fn upload_with_config(
+        &self,
+        input: PutObjectRequest,
+        config: UploadConfig,
+    ) -> Result<UploadOutput, UploadError> {
+        let len = input
+            .body
+            .as_ref()
+            .and_then(|body| body.content_length())
+            .unwrap_or(0);
+        if !config.is_multipart(len) {
+            return self
+                .put_object(input)
+                .await
+                .map(UploadOutput::PutObject)
+                .map_err(UploadError::PutObject);
+        }
+
+        let create = CreateMultipartUploadRequest::builder()
+            .bucket(input.bucket.as_str())
+            .key(input.key.as_str())
+            .set_content_type(input.content_type.clone())
+            .set_metadata(input.metadata.clone())
+            .set_storage_class(input.storage_class.clone())
+            .build()
+            .map_err(|error| UploadError::CreateMultipartUpload(construction(error)))?;
+        let upload_id = self
+            .create_multipart_upload(create)
+            .await
+            .map_err(UploadError::CreateMultipartUpload)?
+            .upload_id
+            .ok_or_else(|| {
+                UploadError::CreateMultipartUpload(SdkError::Response(
+                    "the multipart upload has no ID".into(),
+                ))
+            })?;
+        let result = self.upload_parts(&input, &upload_id, config).await;
+        if result.is_err() {
+            let abort = AbortMultipartUploadRequest::builder()
+                .bucket(input.bucket.as_str())
+                .key(input.key.as_str())
+                .upload_id(upload_id)
+                .build();
+            if let Ok(abort) = abort {
+                // The upload's error says more than the abort's would, if it failed.
+                let _ = self.abort_multipart_upload(abort).await;
+            }
+        }
+        result.map(UploadOutput::Multipart)
+    }
+
+    async fn upload_parts(
+        &self,
+        input: &PutObjectRequest,
+        upload_id: &str,
+        config: UploadConfig,
+    ) -> Result<CompleteMultipartUploadOutput, UploadError> {
+        let body = input.body.clone().unwrap_or_default();
+        let parts = config
+            .parts(body.content_length().unwrap_or(0))
+            .into_iter()
+            .zip(1..)
+            .map(|(range, part_number)| {
+                let body = &body;
+                async move {
+                    let part_error = |error| UploadError::UploadPart { part_number, error };
+                    let request = UploadPartRequest::builder()
+                        .bucket(input.bucket.as_str())
+                        .key(input.key.as_str())
+                        .upload_id(upload_id)
+                        .part_number(part_number)
+                        .body(body.slice(range).map_err(UploadError::Body)?)
+                        .build()
+                        .map_err(|error| part_error(construction(error)))?;
+                    let output = self.upload_part(request).await.map_err(part_error)?;
+                    CompletedPart::builder()
+                        .set_e_tag(output.e_tag)
+                        .part_number(part_number)
+                        .build()
+                        .map_err(|error| part_error(construction(error)))
+                }
+            });
+        let parts = try_join(parts, config.concurrency()).await?;
+
+        let parts = CompletedMultipartUpload::builder()
+            .parts(parts)
+            .build()
+            .map_err(|error| UploadError::CompleteMultipartUpload(construction(error)))?;
+        let complete = CompleteMultipartUploadRequest::builder()
+            .bucket(input.bucket.as_str())
+            .key(input.key.as_str())
+            .upload_id(upload_id)
+            .multipart_upload(parts)
+            .build()
+            .map_err(|error| UploadError::CompleteMultipartUpload(construction(error)))?;
+        self.complete_multipart_upload(complete)
+            .await
+            .map_err(UploadError::CompleteMultipartUpload)
+    }
+}
+
+fn construction<E>(error: BuildError) -> SdkError<E> {
+    SdkError::Construction(error.into())
+}
+"#;
+
+#[test]
+fn recognizes_upload_operations() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let mut def = crate::model::BotocoreModel::from_path("test-data/buckets.json")?;
+    assert!(has_upload_operations(&def));
+    def.operations.remove("AbortMultipartUpload");
+    assert!(!has_upload_operations(&def));
+    let def = crate::model::BotocoreModel::from_path("test-data/selects.json")?;
+    assert!(!has_upload_operations(&def));
+
+    Ok(())
+}
@@ -0,0 +1,433 @@
+use super::{
+    blocking::generate_blocking_client,
+    builders::BUILD_ERROR,
+    client::{generate_client, CONFIG, CONNECTOR, SDK_ERROR, SIGNING},
+    customizations::ServiceCustomizations,
+    dynamodb::{has_attribute_values, DYNAMODB_ITEMS},
+    emit,
+    endpoint::generate_endpoint_resolver,
+    enums::generate_enums,
+    errors::{generate_errors, UNHANDLED_ERROR},
+    features::{OperationFeatures, OperationGroups},
+    format::{format_code, format_tokens, Formatting},
+    manifest::{generate_manifest, RuntimeSource, RUNTIME_CRATE},
+    middleware::MIDDLEWARE,
+    protocol::generate_protocol,
+    retry::generate_retry_classifier,
+    s3::{has_upload_operations, upload_operations, S3_UPLOAD},
+    sso::{is_sso, SSO_CREDENTIALS},
+    structs::generate_structs,
+    sts::{is_sts, STS_CREDENTIALS},
+    unions::generate_unions,
+};
+use crate::{
+    docs::DocLinks,
+    endpoints::Endpoints,
+    graph::ModelGraph,
+    model::{BotocoreModel, Shape},
+    naming::{field_name, type_name},
+    Err,
+};
+use quote::quote;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::{Path, PathBuf},
+};
+
+/// How a service's crate is generated.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct CrateOptions {
+    pub runtime: RuntimeSource,
+    // The crate's features, as `generate_manifest` takes them. Features for operation
+    // groups and the runtime's connectors are added to them, with `hyper` the default unless
+    // they have a `default`.
+    pub features: BTreeMap<String, Vec<String>>,
+    // How operations are feature-gated, if they are.
+    pub operation_groups: Option<OperationGroups>,
+    // Whether the crate also has a blocking client, in its `blocking` module, for programs
+    // without an async runtime.
+    pub blocking: bool,
+    pub formatting: Formatting,
+    // The partitions' endpoints, which the crate's endpoint resolver is generated from.
+    pub endpoints: Option<Endpoints>,
+}
+
+/// The files of a service's generated crate, keyed by their path in the crate's directory.
+pub fn generate_crate(
+    model: &BotocoreModel,
+    options: &CrateOptions,
+) -> Result<BTreeMap<PathBuf, String>, Err> {
+    let graph = ModelGraph::new(model);
+    graph.validate()?;
+    let links = doc_links(&graph);
+    let operation_features = match &options.operation_groups {
+        Some(groups) => OperationFeatures::new(&graph, groups)?,
+        None => OperationFeatures::default(),
+    };
+    let mut features = options.features.clone();
+    // The runtime's connectors are behind features of its own, which the crate's enable.
+    // Clients send requests with hyper, on tokio, unless the crate's default features are
+    // given.
+    for connector in &["hyper", "reqwest"] {
+        features
+            .entry(connector.to_string())
+            .or_insert_with(|| vec![format!("{}/{}", RUNTIME_CRATE, connector)]);
+    }
+    if !options.features.contains_key("default") {
+        features.insert("default".to_string(), vec!["hyper".to_string()]);
+    }
+    for (feature, enables) in operation_features.manifest_features() {
+        let existing = features.entry(feature).or_default();
+        for enable in enables {
+            if !existing.contains(&enable) {
+                existing.push(enable);
+            }
+        }
+    }
+
+    let protocol = generate_protocol(&graph, &operation_features)?;
+    // STS's and SSO's crates also have a module with the runtime's providers for them.
+    let credentials_rs = if is_sts(model) {
+        Some(STS_CREDENTIALS)
+    } else if is_sso(model) {
+        Some(SSO_CREDENTIALS)
+    } else {
+        None
+    };
+    // S3's also has helpers layered over its client, and DynamoDB's conversions for its items.
+    let customizations = ServiceCustomizations::for_service(&model.metadata);
+    let customizations_rs = if customizations.upload && has_upload_operations(model) {
+        Some(S3_UPLOAD)
+    } else if customizations.items && has_attribute_values(&graph) {
+        // Only crates that convert items need serde, so it's optional.
+        features
+            .entry("serde".to_string())
+            .or_insert_with(|| vec!["dep:serde".to_string()]);
+        Some(DYNAMODB_ITEMS)
+    } else {
+        None
+    };
+    let lib = {
+        let blocking = if options.blocking {
+            quote! { pub mod blocking; }
+        } else {
+            quote! {}
+        };
+        let credentials = if credentials_rs.is_some() {
+            quote! { pub mod credentials; }
+        } else {
+            quote! {}
+        };
+        let customizations = if customizations_rs == Some(DYNAMODB_ITEMS) {
+            quote! {
+                #[cfg(feature = "serde")]
+                pub mod customizations;
+            }
+        } else if customizations_rs.is_some() {
+            // The helpers need all the operations they're built on, whichever features those
+            // are behind.
+            let features = upload_operations()
+                .iter()
+                .filter_map(|op| operation_features.feature(op))
+                .collect::<BTreeSet<_>>();
+            if features.is_empty() {
+                quote! { pub mod customizations; }
+            } else {
+                let features = features.iter();
+                quote! {
+                    #[cfg(all(#(feature = #features),*))]
+                    pub mod customizations;
+                }
+            }
+        } else {
+            quote! {}
+        };
+        let docs = emit::inner_doc_text(&format!(
+            "A client for {}, generated from its model.",
+            model.metadata.service_full_name
+        ));
+        quote! {
+            #docs
+            #blocking
+            pub mod client;
+            pub mod config;
+            pub mod connector;
+            #credentials
+            #customizations
+            pub mod endpoint;
+            pub mod error;
+            pub mod middleware;
+            pub mod model;
+            pub mod retry;
+            mod protocol;
+            mod signing;
+
+            pub use client::Client;
+            pub use config::Config;
+        }
+    };
+    let model_rs = format!(
+        "{}\n{}\n{}",
+        generate_structs(&graph, &links)?,
+        generate_unions(&graph, &links)?,
+        generate_enums(&graph)?
+    );
+    let error_rs = format!(
+        "#[allow(unused_imports)]\nuse crate::model::*;\n\n{}\n{}\n{}\n{}",
+        BUILD_ERROR,
+        UNHANDLED_ERROR,
+        SDK_ERROR,
+        generate_errors(&graph, &links, &operation_features)?
+    );
+
+    let mut files = BTreeMap::new();
+    files.insert(
+        PathBuf::from("Cargo.toml"),
+        generate_manifest(&model.metadata, &options.runtime, &features)?,
+    );
+    files.insert(
+        PathBuf::from("src/lib.rs"),
+        format_tokens(lib, options.formatting)?,
+    );
+    let mut sources = vec![
+        (
+            "src/client.rs",
+            generate_client(&graph, &links, &operation_features)?,
+        ),
+        ("src/config.rs", CONFIG.to_string()),
+        ("src/connector.rs", CONNECTOR.to_string()),
+        (
+            "src/endpoint.rs",
+            generate_endpoint_resolver(&model.metadata, options.endpoints.as_ref())?,
+        ),
+        ("src/error.rs", error_rs),
+        ("src/middleware.rs", MIDDLEWARE.to_string()),
+        ("src/model.rs", model_rs),
+        ("src/protocol.rs", protocol),
+        ("src/retry.rs", generate_retry_classifier(&graph)),
+        ("src/signing.rs", SIGNING.to_string()),
+    ];
+    if options.blocking {
+        sources.push((
+            "src/blocking.rs",
+            generate_blocking_client(&graph, &links, &operation_features)?,
+        ));
+    }
+    if let Some(credentials_rs) = credentials_rs {
+        sources.push(("src/credentials.rs", credentials_rs.to_string()));
+    }
+    if let Some(customizations_rs) = customizations_rs {
+        sources.push(("src/customizations.rs", customizations_rs.to_string()));
+    }
+    for (path, code) in &sources {
+        let code = format_code(code, options.formatting)
+            .map_err(|error| format!("can't generate `{}`: {}", path, error))?;
+        files.insert(PathBuf::from(path), code);
+    }
+    Ok(files)
+}
+
+/// Writes generated files to their paths under `dir`.
+pub fn write_files(dir: impl AsRef<Path>, files: &BTreeMap<PathBuf, String>) -> Result<(), Err> {
+    for (path, contents) in files {
+        let path = dir.as_ref().join(path);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, contents)?;
+    }
+    Ok(())
+}
+
+// Links from documentation to the types generated for shapes and the client methods
+// generated for operations.
+fn doc_links(graph: &ModelGraph<'_>) -> DocLinks {
+    let mut links = DocLinks::new();
+    for (id, shape) in graph.shapes() {
+        let generated = match shape {
+            Shape::Structure { .. } => true,
+            Shape::String { enum_values, .. } => enum_values.is_some(),
+            _ => false,
+        };
+        if generated {
+            links.insert(
+                id.as_str(),
+                format!("crate::model::{}", type_name(id.as_str())),
+            );
+        }
+    }
+    for (name, _) in graph.operations() {
+        links.insert(name, format!("crate::Client::{}", field_name(name)));
+    }
+    links
+}
+
+#[test]
+fn generates_service_crates() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let def = crate::model::BotocoreModel::from_path("test-data/recursive.json")?;
+    let mut options = CrateOptions {
+        runtime: RuntimeSource::Path("../runtime".to_string()),
+        features: BTreeMap::new(),
+        operation_groups: None,
+        blocking: false,
+        formatting: Formatting::Strict,
+        endpoints: Some(crate::endpoints::Endpoints::from_path(
+            "test-data/endpoints.json",
+        )?),
+    };
+    let files = generate_crate(&def, &options)?;
+    assert_eq!(
+        files.keys().map(|path| path.to_str()).collect::<Vec<_>>(),
+        vec![
+            Some("Cargo.toml"),
+            Some("src/client.rs"),
+            Some("src/config.rs"),
+            Some("src/connector.rs"),
+            Some("src/endpoint.rs"),
+            Some("src/error.rs"),
+            Some("src/lib.rs"),
+            Some("src/middleware.rs"),
+            Some("src/model.rs"),
+            Some("src/protocol.rs"),
+            Some("src/retry.rs"),
+            Some("src/signing.rs"),
+        ]
+    );
+    assert_eq!(
+        files[Path::new("src/lib.rs")],
+        "//! A client for Recursive Test Service, generated from its model.\n\
+         pub mod client;\n\
+         pub mod config;\n\
+         pub mod connector;\n\
+         pub mod endpoint;\n\
+         pub mod error;\n\
+         pub mod middleware;\n\
+         pub mod model;\n\
+         pub mod retry;\n\
+         mod protocol;\n\
+         mod signing;\n\
+         pub use client::Client;\n\
+         pub use config::Config;\n"
+    );
+    assert!(files[Path::new("src/config.rs")].contains("pub fn with_region_set("));
+    assert!(files[Path::new("src/config.rs")].contains("pub fn with_credentials_provider("));
+    assert!(files[Path::new("src/config.rs")].contains("pub fn for_region("));
+    assert!(files[Path::new("src/config.rs")].contains("pub fn with_endpoint_url("));
+    assert!(files[Path::new("src/config.rs")].contains("pub fn with_retry_policy("));
+    assert!(files[Path::new("src/config.rs")].contains("pub fn with_operation_timeout("));
+    assert!(files[Path::new("src/error.rs")]
+        .contains("Timeout(aws_sdk_rust_runtime::timeout::TimedOut),"));
+    assert!(files[Path::new("src/middleware.rs")].contains("pub struct Retry<S> {\n"));
+    assert!(files[Path::new("src/connector.rs")].contains(
+        "#[cfg(feature = \"hyper\")]\npub use aws_sdk_rust_runtime::connector::HyperConnector;\n"
+    ));
+    assert!(files[Path::new("src/retry.rs")]
+        .contains("pub fn classify(status: u16, code: Option<&str>)"));
+    assert!(files[Path::new("src/endpoint.rs")].contains("pub(crate) fn address_bucket(\n"));
+    assert!(files[Path::new("src/endpoint.rs")].contains("name: \"aws-cn\",\n"));
+    assert!(files[Path::new("src/signing.rs")]
+        .contains("Some(region_set) => (Algorithm::V4a, region_set),"));
+    assert!(files[Path::new("src/signing.rs")].contains("pub(crate) async fn presign_v4(\n"));
+    assert!(files[Path::new("src/error.rs")].contains("pub struct BuildError {\n"));
+    assert!(files[Path::new("src/error.rs")].contains("pub struct ErrorMetadata {\n"));
+    assert!(files[Path::new("src/error.rs")].contains("pub enum PutItemError {\n"));
+    assert!(files[Path::new("src/model.rs")].contains("pub struct PutItemInputBuilder {\n"));
+    assert!(files[Path::new("src/protocol.rs")].contains("pub(crate) fn serialize_put_item(\n"));
+
+    let dir = std::env::temp_dir().join("aws-sdk-rust-codegen-generates-service-crates");
+    write_files(&dir, &files)?;
+    assert_eq!(
+        std::fs::read_to_string(dir.join("src/lib.rs"))?,
+        files[Path::new("src/lib.rs")]
+    );
+    std::fs::remove_dir_all(&dir)?;
+
+    options.operation_groups = Some(OperationGroups::ByPrefix);
+    options.blocking = true;
+    let files = generate_crate(&def, &options)?;
+    assert!(files[Path::new("src/lib.rs")].contains("\npub mod blocking;\npub mod client;\n"));
+    assert!(files[Path::new("src/blocking.rs")].contains("    #[cfg(feature = \"put\")]\n"));
+    assert!(files[Path::new("Cargo.toml")].ends_with(
+        "[features]\n\
+         default = [\"hyper\", \"put\"]\n\
+         hyper = [\"aws-sdk-rust-runtime/hyper\"]\n\
+         put = []\n\
+         reqwest = [\"aws-sdk-rust-runtime/reqwest\"]\n"
+    ));
+    assert!(files[Path::new("src/client.rs")].contains("    #[cfg(feature = \"put\")]\n"));
+
+    let def = crate::model::BotocoreModel::from_path("test-data/sts.json")?;
+    options.operation_groups = None;
+    options.blocking = false;
+    let files = generate_crate(&def, &options)?;
+    assert!(files[Path::new("src/lib.rs")].contains("pub mod connector;\npub mod credentials;\n"));
+    assert!(files[Path::new("src/credentials.rs")]
+        .starts_with("pub use aws_sdk_rust_runtime::credentials::{\n"));
+    assert!(files[Path::new("src/credentials.rs")]
+        .contains(" AssumeRoleProvider, WebIdentityProvider,"));
+
+    let def = crate::model::BotocoreModel::from_path("test-data/sso.json")?;
+    let files = generate_crate(&def, &options)?;
+    assert!(files[Path::new("src/credentials.rs")].contains("SsoProfile, SsoProvider};\n"));
+
+    // S3's crate has its `upload` helper, behind the features of the operations it's built
+    // on, if they're feature-gated.
+    let def = crate::model::BotocoreModel::from_path("test-data/buckets.json")?;
+    let files = generate_crate(&def, &options)?;
+    assert!(
+        files[Path::new("src/lib.rs")].contains("pub mod connector;\npub mod customizations;\n")
+    );
+    assert!(files[Path::new("src/customizations.rs")].contains("    pub async fn upload(\n"));
+    options.operation_groups = Some(OperationGroups::ByPrefix);
+    let files = generate_crate(&def, &options)?;
+    assert!(
+        files[Path::new("src/lib.rs")].contains("#[cfg(\n    all(\n        feature = \"abort\",\n")
+    );
+
+    // DynamoDB's converts items with serde, which the crate only depends on for it.
+    let def = crate::model::BotocoreModel::from_path("test-data/tables.json")?;
+    let files = generate_crate(&def, &options)?;
+    assert!(files[Path::new("src/lib.rs")]
+        .contains("#[cfg(feature = \"serde\")]\npub mod customizations;\n"));
+    assert!(files[Path::new("src/customizations.rs")].contains("pub fn to_item<"));
+    let manifest = &files[Path::new("Cargo.toml")];
+    assert!(manifest.contains("\nserde = { version = \"1\", optional = true }\n"));
+    assert!(manifest.contains("\nserde = [\"dep:serde\"]\n"));
+
+    Ok(())
+}
+
+#[test]
+fn builds_crates_without_warnings() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    // Lambda's model deprecates `InvokeAsync` and its shapes, which the crate itself uses.
+    let mut def =
+        crate::model::BotocoreModel::from_path("test-data/lambda/2015-03-31/service-2.json")?;
+    // It refers to a shape the fixture doesn't define.
+    def.operations.remove("DeleteFunctionConcurrency");
+    def.shapes.remove(&crate::model::ShapeId::from(
+        "DeleteFunctionConcurrencyRequest",
+    ));
+    let options = CrateOptions {
+        runtime: RuntimeSource::Path(std::fs::canonicalize("runtime")?.display().to_string()),
+        features: BTreeMap::new(),
+        operation_groups: None,
+        blocking: true,
+        formatting: Formatting::FallBackToRaw,
+        endpoints: Some(Endpoints::from_path("test-data/endpoints.json")?),
+    };
+    let dir = std::env::temp_dir().join(format!("aws-sdk-rust-lambda-{}", std::process::id()));
+    write_files(&dir, &generate_crate(&def, &options)?)?;
+    let status = std::process::Command::new(env!("CARGO"))
+        .args(["check", "--quiet"])
+        .current_dir(&dir)
+        .env("RUSTFLAGS", "-D warnings")
+        .env(
+            "CARGO_TARGET_DIR",
+            std::env::current_dir()?.join("target/generated"),
+        )
+        .status();
+    std::fs::remove_dir_all(&dir)?;
+    assert!(status?.success());
+
+    Ok(())
+}
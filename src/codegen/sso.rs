@@ -0,0 +1,25 @@
+use crate::model::BotocoreModel;
+
+/// Whether a model is SSO's portal's, whose crate also gets a `credentials` module with the
+/// runtime's provider for its roles.
+pub fn is_sso(model: &BotocoreModel) -> bool {
+    model.metadata.endpoint_prefix == "portal.sso"
+        && model.operations.contains_key("GetRoleCredentials")
+}
+
+/// The `credentials` module of SSO's crate, which re-exports the runtime's provider for SSO
+/// roles, so the crate resolves credentials as the runtime does.
+pub const SSO_CREDENTIALS: &str = r#"pub use aws_sdk_rust_runtime::credentials::{default_provider, SsoProfile, SsoProvider};
+"#;
+
+#[test]
+fn recognizes_sso() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let mut def = crate::model::BotocoreModel::from_path("test-data/sso.json")?;
+    assert!(is_sso(&def));
+    def.operations.remove("GetRoleCredentials");
+    assert!(!is_sso(&def));
+    let def = crate::model::BotocoreModel::from_path("test-data/sts.json")?;
+    assert!(!is_sso(&def));
+
+    Ok(())
+}
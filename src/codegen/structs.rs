@@ -0,0 +1,324 @@
+use super::{
+    allow_deprecated, builders, debug, deprecated_traits, emit,
+    format::{format_tokens, Formatting},
+    rust_type, uses_deprecated,
+};
+use crate::{
+    docs::DocLinks,
+    graph::{Edge, ModelGraph},
+    model::{Markdown, Shape, ShapeId, Signature, Traits},
+    naming::{assign_names, field_name, type_name},
+    Err,
+};
+use proc_macro2::TokenStream;
+use quote::quote;
+
+/// Generates a struct and its builder for every structure shape in the model, in
+/// topological order.
+pub fn generate_structs(graph: &ModelGraph<'_>, links: &DocLinks) -> Result<String, Err> {
+    let mut out = String::new();
+    for id in graph.topological_order() {
+        if let Some(Shape::Structure { .. }) = graph.shape(id) {
+            if !out.is_empty() {
+                out.push('\n');
+            }
+            out.push_str(&format_tokens(
+                generate_struct(graph, id, links)?,
+                Formatting::Strict,
+            )?);
+            out.push('\n');
+            out.push_str(&builders::generate_builder(graph, id, links)?);
+        }
+    }
+    Ok(out)
+}
+
+// A field of the struct generated for a structure shape.
+pub(crate) struct Field<'a> {
+    pub name: String,
+    // The whole type, including any `Option`.
+    pub ty: String,
+    pub sensitive: bool,
+    pub documentation: Option<&'a Markdown>,
+    pub deprecated: Option<String>,
+    // The member the field is for.
+    pub edge: Edge<'a>,
+}
+
+impl Field<'_> {
+    /// The field's type without its `Option`, which is what builders take.
+    pub fn value_type(&self) -> &str {
+        self.ty
+            .strip_prefix("Option<")
+            .and_then(|ty| ty.strip_suffix('>'))
+            .unwrap_or(&self.ty)
+    }
+
+    pub fn is_optional(&self) -> bool {
+        self.ty.starts_with("Option<")
+    }
+}
+
+// The fields of the struct for a structure shape, in member order, and the shape's traits.
+pub(crate) fn struct_fields<'a>(
+    graph: &ModelGraph<'a>,
+    id: ShapeId,
+) -> Result<(Vec<Field<'a>>, &'a Traits), Err> {
+    let (required, traits) = match graph.shape(id) {
+        Some(Shape::Structure {
+            required, traits, ..
+        }) => (required.as_deref().unwrap_or_default(), traits),
+        _ => return Err(format!("`{}` isn't a structure", id).into()),
+    };
+    let edges = graph.neighbors(id);
+    let names = assign_names(edges.iter().map(|edge| edge.name), field_name)?;
+
+    let mut fields = vec![];
+    for edge in edges {
+        let target = graph.shape(edge.target).ok_or_else(|| {
+            format!(
+                "`{}.{}` targets undefined shape `{}`",
+                id, edge.name, edge.target
+            )
+        })?;
+        let required = required.iter().any(|member| member == edge.name);
+        fields.push(Field {
+            name: names[edge.name].clone(),
+            ty: field_type(graph, edge, target, required),
+            // A sensitive structure hides all of its fields, not just the sensitive ones.
+            sensitive: traits.sensitive || debug::is_sensitive(edge.member, target),
+            documentation: edge.member.documentation.as_ref(),
+            deprecated: deprecated_traits(&edge.member.traits),
+            edge: *edge,
+        });
+    }
+    Ok((fields, traits))
+}
+
+/// The fields the struct for an operation's output has besides its members' ones: the ID the
+/// service gave the request and, in S3's, the extended ID of the host that handled it. Each
+/// starts out `None` and is set by the client, with the `crate::error` function named after
+/// it, from the response's headers.
+pub(crate) fn request_id_fields(graph: &ModelGraph<'_>, id: ShapeId) -> &'static [&'static str] {
+    let output = graph
+        .operations()
+        .any(|(_, op)| op.output.as_ref().is_some_and(|output| output.shape == id));
+    if !output {
+        &[]
+    } else if graph.model().metadata.signature_version == Signature::S3V4 {
+        &["request_id", "extended_request_id"]
+    } else {
+        &["request_id"]
+    }
+}
+
+/// Generates the struct for a structure shape.
+///
+/// Members in `required` are plain fields and the rest are `Option`s. Members that close a
+/// reference cycle are boxed, so the struct has a finite size. If any member is sensitive,
+/// `Debug` is implemented by hand to redact it. Operations' outputs also have the request ID
+/// fields `request_id_fields` lists, which are private to the crate and read by accessors.
+pub fn generate_struct(
+    graph: &ModelGraph<'_>,
+    id: ShapeId,
+    links: &DocLinks,
+) -> Result<TokenStream, Err> {
+    let (fields, traits) = struct_fields(graph, id)?;
+    let type_name = type_name(id.as_str());
+    let name = emit::ident(&type_name);
+
+    let mut members = vec![];
+    for field in &fields {
+        let docs = field
+            .documentation
+            .map(|documentation| emit::doc_attributes(documentation, links));
+        let deprecated = field.deprecated.as_deref().map(emit::tokens).transpose()?;
+        let field_name = emit::ident(&field.name);
+        let ty = emit::tokens(&field.ty)?;
+        members.push(quote! {
+            #docs
+            #deprecated
+            pub #field_name: #ty
+        });
+    }
+    let mut accessors = vec![];
+    for field in request_id_fields(graph, id) {
+        let docs = emit::doc_text(match *field {
+            "request_id" => "The ID the service gave the request, which AWS Support asks for.",
+            _ => {
+                "The ID S3 gave the host that handled the request, which AWS Support asks for too."
+            }
+        });
+        let accessor = emit::ident(field);
+        let field = emit::ident(&format!("_{}", field));
+        members.push(quote!(pub(crate) #field: Option<String>));
+        accessors.push(quote! {
+            #docs
+            pub fn #accessor(&self) -> Option<&str> {
+                self.#field.as_deref()
+            }
+        });
+    }
+
+    let docs = match graph.shape(id) {
+        Some(Shape::Structure {
+            documentation: Some(documentation),
+            ..
+        }) => Some(emit::doc_attributes(
+            &Markdown::from(documentation.clone()),
+            links,
+        )),
+        _ => None,
+    };
+    let redact = fields.iter().any(|field| field.sensitive);
+    let derive = if redact {
+        emit::derive(&["Clone", "PartialEq"])
+    } else {
+        emit::derive(&["Debug", "Clone", "PartialEq"])
+    };
+    let deprecated = deprecated_traits(traits)
+        .as_deref()
+        .map(emit::tokens)
+        .transpose()?;
+    let allow = emit::tokens(allow_deprecated(uses_deprecated(graph, id)))?;
+    let accessors = if accessors.is_empty() {
+        None
+    } else {
+        Some(quote! {
+            #allow
+            impl #name {
+                #(#accessors)*
+            }
+        })
+    };
+    let debug = if redact {
+        let debug = emit::tokens(&debug::debug_impl(&type_name, &debug_fields(&fields)))?;
+        Some(quote!(#allow #debug))
+    } else {
+        None
+    };
+    Ok(quote! {
+        #docs
+        #derive
+        #deprecated
+        pub struct #name {
+            #(#members,)*
+        }
+        #accessors
+        #debug
+    })
+}
+
+pub(crate) fn debug_fields<'f>(fields: &'f [Field<'_>]) -> Vec<(&'f str, bool)> {
+    fields
+        .iter()
+        .map(|field| (field.name.as_str(), field.sensitive))
+        .collect()
+}
+
+// The type of the field for a member. Lists and maps already hold their values on the heap,
+// so only members targeting structures or unions need boxing to break a cycle.
+pub(crate) fn field_type(
+    graph: &ModelGraph<'_>,
+    edge: &Edge<'_>,
+    target: &Shape,
+    required: bool,
+) -> String {
+    let mut ty = rust_type(graph, edge.target);
+    let aggregate = matches!(target, Shape::Structure { .. } | Shape::Union { .. });
+    if edge.back_edge && aggregate && !edge.member.boxed {
+        ty = format!("Box<{}>", ty);
+    }
+    edge.member.field_type(target, &ty, required)
+}
+
+#[test]
+fn generates_structs() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let def = crate::model::BotocoreModel::from_path("test-data/lambda/2015-03-31/service-2.json")?;
+    let graph = ModelGraph::new(&def);
+    let code = format_tokens(
+        generate_struct(
+            &graph,
+            ShapeId::from("GetFunctionRequest"),
+            &DocLinks::new(),
+        )?,
+        Formatting::Strict,
+    )?;
+    assert!(
+        code.starts_with("#[derive(Debug, Clone, PartialEq)]\npub struct GetFunctionRequest {\n")
+    );
+    assert!(code.contains("    /// The name of the Lambda function, version, or alias.\n"));
+    assert!(code.contains("\n    pub function_name: String,\n"));
+    assert!(code.contains("\n    pub qualifier: Option<String>,\n"));
+
+    let code = format_tokens(
+        generate_struct(&graph, ShapeId::from("Environment"), &DocLinks::new())?,
+        Formatting::Strict,
+    )?;
+    assert!(code.contains("pub variables: Option<std::collections::HashMap<String, String>>,"));
+    assert!(code.contains("impl std::fmt::Debug for Environment {"));
+
+    let mut def = crate::model::BotocoreModel::from_path("test-data/recursive.json")?;
+    def.shapes.insert(
+        ShapeId::from("TreeNode"),
+        serde_json::from_str(
+            r#"{
+                "type": "structure",
+                "required": ["Value"],
+                "members": {
+                    "Children": {"shape": "TreeNodeList"},
+                    "Parent": {"shape": "TreeNode"},
+                    "Value": {"shape": "AttributeValue"}
+                },
+                "documentation": "<p>A node in a tree.</p>"
+            }"#,
+        )?,
+    );
+    def.shapes.insert(
+        ShapeId::from("TreeNodeList"),
+        serde_json::from_str(r#"{"type": "list", "member": {"shape": "TreeNode"}}"#)?,
+    );
+    let graph = ModelGraph::new(&def);
+    assert_eq!(
+        format_tokens(
+            generate_struct(&graph, ShapeId::from("TreeNode"), &DocLinks::new())?,
+            Formatting::Strict
+        )?,
+        "/// A node in a tree.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TreeNode {
+    pub children: Option<Vec<TreeNode>>,
+    pub parent: Option<Box<TreeNode>>,
+    pub value: AttributeValue,
+}
+"
+    );
+    assert!(generate_struct(&graph, ShapeId::from("TreeNodeList"), &DocLinks::new()).is_err());
+    let all = generate_structs(&graph, &DocLinks::new())?;
+    assert!(all.find("pub struct TreeNode ").is_some());
+    assert!(all.find("pub struct PutItemInput ") < all.find("pub struct PutItemOutput "));
+    // Only outputs carry request IDs.
+    assert!(!all.contains("pub struct PutItemInput {\n    pub(crate) _request_id"));
+    assert!(all.contains(
+        "    pub(crate) _request_id: Option<String>,\n\
+         }\n\
+         impl PutItemOutput {\n    \
+         /// The ID the service gave the request, which AWS Support asks for.\n    \
+         pub fn request_id(&self) -> Option<&str> {\n        \
+         self._request_id.as_deref()\n    \
+         }\n\
+         }\n"
+    ));
+    assert!(!all.contains("extended_request_id"));
+
+    let def = crate::model::BotocoreModel::from_path("test-data/buckets.json")?;
+    let graph = ModelGraph::new(&def);
+    let code = format_tokens(
+        generate_struct(&graph, ShapeId::from("ListObjectsOutput"), &DocLinks::new())?,
+        Formatting::Strict,
+    )?;
+    assert!(code.contains("    pub(crate) _extended_request_id: Option<String>,\n"));
+    assert!(code.contains("    pub fn extended_request_id(&self) -> Option<&str> {\n"));
+
+    Ok(())
+}
@@ -0,0 +1,29 @@
+use crate::model::BotocoreModel;
+
+/// Whether a model is STS's, whose crate also gets a `credentials` module with the runtime's
+/// providers for its roles.
+pub fn is_sts(model: &BotocoreModel) -> bool {
+    model.metadata.endpoint_prefix == "sts"
+        && ["AssumeRole", "AssumeRoleWithWebIdentity"]
+            .iter()
+            .all(|op| model.operations.contains_key(*op))
+}
+
+/// The `credentials` module of STS's crate, which re-exports the runtime's providers for the
+/// roles STS assumes, so the crate resolves credentials as the runtime does.
+pub const STS_CREDENTIALS: &str = r#"pub use aws_sdk_rust_runtime::credentials::{
+    default_provider, AssumeRoleProvider, WebIdentityProvider,
+};
+"#;
+
+#[test]
+fn recognizes_sts() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let mut def = crate::model::BotocoreModel::from_path("test-data/sts.json")?;
+    assert!(is_sts(&def));
+    def.operations.remove("AssumeRoleWithWebIdentity");
+    assert!(!is_sts(&def));
+    let def = crate::model::BotocoreModel::from_path("test-data/queues.json")?;
+    assert!(!is_sts(&def));
+
+    Ok(())
+}
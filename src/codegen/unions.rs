@@ -0,0 +1,187 @@
+use super::{
+    allow_deprecated,
+    debug::{is_sensitive, REDACTED},
+    deprecated_traits, indent,
+    structs::field_type,
+    uses_deprecated,
+};
+use crate::{
+    docs::{doc_comment, DocLinks},
+    graph::ModelGraph,
+    model::{Markdown, Shape, ShapeId},
+    naming::{assign_names, type_name},
+    Err,
+};
+use std::collections::BTreeMap;
+
+/// Generates an enum for every union and event stream shape in the model, in name order.
+pub fn generate_unions(graph: &ModelGraph<'_>, links: &DocLinks) -> Result<String, Err> {
+    let mut out = String::new();
+    for (id, shape) in graph.shapes() {
+        if let Shape::Union { .. } | Shape::EventStream { .. } = shape {
+            if !out.is_empty() {
+                out.push('\n');
+            }
+            out.push_str(&generate_union(graph, id, links)?);
+        }
+    }
+    Ok(out)
+}
+
+/// Generates the enum for a union shape, with a variant holding the value of each of its
+/// members, since a union has exactly one set. An event stream's enum is the same, with a
+/// variant for each kind of event; the stream itself is an `EventReceiver` of them.
+///
+/// Services add members over time, so there's also an `Unknown` variant for responses that
+/// set one this enum doesn't list. Sensitive members are redacted by a hand-written `Debug`.
+pub fn generate_union(
+    graph: &ModelGraph<'_>,
+    id: ShapeId,
+    links: &DocLinks,
+) -> Result<String, Err> {
+    let (documentation, traits) = match graph.shape(id) {
+        Some(Shape::Union {
+            documentation,
+            traits,
+            ..
+        })
+        | Some(Shape::EventStream {
+            documentation,
+            traits,
+            ..
+        }) => (documentation, traits),
+        _ => return Err(format!("`{}` isn't a union", id).into()),
+    };
+    let name = type_name(id.as_str());
+    let edges = graph.neighbors(id);
+    let variants = variant_names(graph, id)?;
+
+    let mut body = String::new();
+    let mut debug = String::new();
+    let mut redact = false;
+    for edge in edges {
+        let target = graph.shape(edge.target).ok_or_else(|| {
+            format!(
+                "`{}.{}` targets undefined shape `{}`",
+                id, edge.name, edge.target
+            )
+        })?;
+        let variant = &variants[edge.name];
+        if let Some(documentation) = &edge.member.documentation {
+            body.push_str(&doc_comment(documentation, links));
+        }
+        if let Some(attribute) = deprecated_traits(&edge.member.traits) {
+            body.push_str(&attribute);
+            body.push('\n');
+        }
+        body.push_str(&format!(
+            "{}({}),\n",
+            variant,
+            field_type(graph, edge, target, true)
+        ));
+
+        let sensitive = traits.sensitive || is_sensitive(edge.member, target);
+        redact |= sensitive;
+        debug.push_str(&if sensitive {
+            format!(
+                "{}::{}(_) => f.debug_tuple({:?}).field(&{:?}).finish(),\n",
+                name, variant, variant, REDACTED
+            )
+        } else {
+            format!(
+                "{}::{}(value) => f.debug_tuple({:?}).field(value).finish(),\n",
+                name, variant, variant
+            )
+        });
+    }
+    body.push_str(
+        "/// A member this enum doesn't list, such as one added since it was generated.\n",
+    );
+    body.push_str("Unknown,\n");
+    debug.push_str(&format!("{}::Unknown => f.write_str(\"Unknown\"),\n", name));
+
+    let mut out = String::new();
+    if let Some(documentation) = documentation {
+        out.push_str(&doc_comment(&Markdown::from(documentation.clone()), links));
+    }
+    if redact {
+        out.push_str("#[derive(Clone, PartialEq)]\n");
+    } else {
+        out.push_str("#[derive(Debug, Clone, PartialEq)]\n");
+    }
+    if let Some(attribute) = deprecated_traits(traits) {
+        out.push_str(&attribute);
+        out.push('\n');
+    }
+    out.push_str(&format!("pub enum {} {{\n{}}}\n", name, indent(&body, 1)));
+    if redact {
+        out.push_str(&format!(
+            "\n{}impl std::fmt::Debug for {} {{\n    \
+             fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {{\n        \
+             match self {{\n{}        \
+             }}\n    \
+             }}\n\
+             }}\n",
+            allow_deprecated(uses_deprecated(graph, id)),
+            name,
+            indent(&debug, 3)
+        ));
+    }
+    Ok(out)
+}
+
+// The variant for each of a union's members, by member name.
+pub(crate) fn variant_names<'a>(
+    graph: &ModelGraph<'a>,
+    id: ShapeId,
+) -> Result<BTreeMap<&'a str, String>, Err> {
+    let edges = graph.neighbors(id);
+    Ok(assign_names(edges.iter().map(|edge| edge.name), type_name)?)
+}
+
+#[test]
+fn generates_unions() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let mut def = crate::model::BotocoreModel::from_path("test-data/recursive.json")?;
+    assert_eq!(
+        generate_union(
+            &ModelGraph::new(&def),
+            ShapeId::from("AttributeValue"),
+            &DocLinks::new()
+        )?,
+        r#"#[derive(Debug, Clone, PartialEq)]
+pub enum AttributeValue {
+    L(Vec<AttributeValue>),
+    M(std::collections::HashMap<String, AttributeValue>),
+    S(String),
+    /// A member this enum doesn't list, such as one added since it was generated.
+    Unknown,
+}
+"#
+    );
+
+    if let Some(Shape::String { traits, .. }) =
+        def.shapes.get_mut(&ShapeId::from("StringAttributeValue"))
+    {
+        traits.sensitive = true;
+    }
+    let code = generate_unions(&ModelGraph::new(&def), &DocLinks::new())?;
+    assert!(code.starts_with("#[derive(Clone, PartialEq)]\npub enum AttributeValue {\n"));
+    assert!(code.contains(
+        "            AttributeValue::S(_) => f.debug_tuple(\"S\").field(&\"*** Sensitive Data Redacted ***\").finish(),\n"
+    ));
+    assert!(code.contains(
+        "            AttributeValue::L(value) => f.debug_tuple(\"L\").field(value).finish(),\n"
+    ));
+
+    let def = crate::model::BotocoreModel::from_path("test-data/selects.json")?;
+    let code = generate_unions(&ModelGraph::new(&def), &DocLinks::new())?;
+    assert!(code.starts_with(
+        "/// The events of a select's response.\n\
+         #[derive(Debug, Clone, PartialEq)]\n\
+         pub enum SelectObjectContentEventStream {\n    \
+         Cont(ContinuationEvent),\n    \
+         End(EndEvent),\n"
+    ));
+
+    Ok(())
+}
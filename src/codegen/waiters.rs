@@ -0,0 +1,314 @@
+use super::{indent, structs::struct_fields};
+use crate::{
+    graph::ModelGraph,
+    model::{
+        waiters::{Acceptor, AcceptorState, Matcher, Waiter},
+        Shape, ShapeId,
+    },
+    naming::{field_name, type_name},
+    Err,
+};
+use serde_json::Value;
+
+/// The client methods polling a waiter's operation until its acceptors match, e.g.
+/// `wait_until_function_active`, which polls as the model says, and
+/// `wait_until_function_active_with_config`, which polls as its `WaiterConfig` says. Returns
+/// `None` if any of the acceptors' expressions aren't supported, since the waiter can't be
+/// evaluated without them.
+///
+/// The methods return the runtime's `wait`, with the acceptors compiled, in order, into the
+/// `accept` function it passes each result to. Path matchers compare the values their expression refers
+/// to in an output with what's expected, status matchers its success status or the status an
+/// error response had, and error matchers an error response's code, or whether there was an
+/// error at all.
+pub fn waiter_methods(
+    graph: &ModelGraph<'_>,
+    name: &str,
+    waiter: &Waiter,
+    attributes: &str,
+) -> Result<Option<String>, Err> {
+    let op = &graph.model().operations[&waiter.operation];
+    let output = op.output.as_ref().map(|output| output.shape);
+    let status = op
+        .http
+        .response_code
+        .map(|code| code.as_u16())
+        .unwrap_or(200);
+    let mut accept = String::new();
+    for acceptor in &waiter.acceptors {
+        let condition = match condition(graph, output, status, acceptor) {
+            Some(condition) => condition,
+            None => return Ok(None),
+        };
+        let state = match acceptor.state {
+            AcceptorState::Success => "Success",
+            AcceptorState::Failure => "Failure",
+            AcceptorState::Retry => "Retry",
+        };
+        accept.push_str(&format!(
+            "if {} {{\n    return Some(aws_sdk_rust_runtime::waiter::WaiterState::{});\n}}\n",
+            condition, state
+        ));
+    }
+    accept.push_str("None\n");
+
+    let method = format!("wait_until_{}", field_name(name).trim_start_matches("r#"));
+    let output_type = match output {
+        Some(output) => format!("crate::model::{}", type_name(output.as_str())),
+        None => "()".to_string(),
+    };
+    let error_type = format!(
+        "crate::error::SdkError<crate::error::{}Error>",
+        type_name(&op.name)
+    );
+    let result = format!(
+        "Result<\n    Option<{output}>,\n    aws_sdk_rust_runtime::waiter::WaiterError<{output}, {error}>,\n>",
+        output = output_type,
+        error = error_type
+    );
+    let description = match &waiter.description {
+        Some(description) => format!("/// {}\n", description),
+        None => format!(
+            "/// Waits until `{}`'s results are as the `{}` waiter expects.\n",
+            op.name, name
+        ),
+    };
+    Ok(Some(format!(
+        "{description}\
+         /// It polls `{name}` every {delay} seconds, up to {max_attempts} times.\n\
+         {attributes}\
+         pub async fn {method}(\n    \
+         &self,\n    \
+         input: crate::model::{input},\n\
+         ) -> {result} {{\n    \
+         let config = aws_sdk_rust_runtime::waiter::WaiterConfig::new(\n        \
+         std::time::Duration::from_secs({delay}),\n        \
+         {max_attempts},\n    \
+         );\n    \
+         self.{method}_with_config(input, config).await\n\
+         }}\n\n\
+         /// `{method}`, polling as `config` says instead.\n\
+         {attributes}\
+         pub async fn {method}_with_config(\n    \
+         &self,\n    \
+         input: crate::model::{input},\n    \
+         config: aws_sdk_rust_runtime::waiter::WaiterConfig,\n\
+         ) -> {result} {{\n    \
+         fn accept(\n        \
+         result: &Result<{output}, {error}>,\n    \
+         ) -> Option<aws_sdk_rust_runtime::waiter::WaiterState> {{\n{accept}    \
+         }}\n    \
+         aws_sdk_rust_runtime::waiter::wait(config, || self.{operation}(input.clone()), accept).await\n\
+         }}\n",
+        description = description,
+        name = op.name,
+        delay = waiter.delay,
+        max_attempts = waiter.max_attempts,
+        attributes = attributes,
+        method = method,
+        input = type_name(op.input.shape.as_str()),
+        result = result,
+        operation = field_name(&op.name),
+        output = output_type,
+        error = error_type,
+        accept = indent(&accept, 2),
+    )))
+}
+
+// The condition an acceptor matches `result` on, or `None` if it isn't supported. `status` is
+// the operation's success status.
+fn condition(
+    graph: &ModelGraph<'_>,
+    output: Option<ShapeId>,
+    status: u16,
+    acceptor: &Acceptor,
+) -> Option<String> {
+    match acceptor.matcher {
+        Matcher::Status => {
+            let expected = acceptor.expected.as_u64()?;
+            if expected == u64::from(status) {
+                Some("result.is_ok()".to_string())
+            } else {
+                Some(format!(
+                    "result.as_ref().err().and_then(|error| error.meta()?.status()) == Some({})",
+                    expected
+                ))
+            }
+        }
+        Matcher::Error => match &acceptor.expected {
+            Value::String(code) => Some(format!(
+                "result\n    .as_ref()\n    .err()\n    .and_then(|error| error.meta()?.code())\n    \
+                 .map(crate::error::sanitize_error_code)\n    == Some({:?})",
+                code
+            )),
+            Value::Bool(true) => Some("result.is_err()".to_string()),
+            Value::Bool(false) => Some("result.is_ok()".to_string()),
+            _ => None,
+        },
+        Matcher::Path | Matcher::PathAll | Matcher::PathAny => {
+            let (values, target, projects) =
+                values(graph, output?, acceptor.argument.as_deref()?)?;
+            let equals = equals(graph, target, &acceptor.expected)?;
+            let matched = match acceptor.matcher {
+                Matcher::Path if projects => return None,
+                Matcher::Path | Matcher::PathAny => format!("{}.any(|v| {})", values, equals),
+                // Like botocore, nothing matches all of no values.
+                _ => format!(
+                    "{{\n    let mut values = {}.peekable();\n    \
+                     values.peek().is_some() && values.all(|v| {})\n}}",
+                    values, equals
+                ),
+            };
+            Some(format!(
+                "result.as_ref().is_ok_and(|output| {})",
+                matched
+            ))
+        }
+    }
+}
+
+// Compiles a path matcher's JMESPath expression over an output of the shape `root` into an
+// iterator over references to the values it refers to, their shape, and whether it projects
+// lists into more than one of them. Only paths of members are supported, any of them
+// flattened (`Reservations[].Instances[].State`) or indexed (`Versions[-1].Status`).
+fn values(graph: &ModelGraph<'_>, root: ShapeId, path: &str) -> Option<(String, ShapeId, bool)> {
+    let mut code = "std::iter::once(output)".to_string();
+    let mut id = root;
+    let mut projects = false;
+    for segment in path.split('.') {
+        let (member, index) = match segment.find('[') {
+            Some(start) => {
+                let index = segment[start..]
+                    .strip_prefix('[')
+                    .and_then(|index| index.strip_suffix(']'))?;
+                (&segment[..start], Some(index))
+            }
+            None => (segment, None),
+        };
+        let (fields, _) = struct_fields(graph, id).ok()?;
+        let field = fields.iter().find(|field| field.edge.name == member)?;
+        if field.is_optional() {
+            code.push_str(&format!(".filter_map(|v| v.{}.as_ref())", field.name));
+        } else {
+            code.push_str(&format!(".map(|v| &v.{})", field.name));
+        }
+        if field.value_type().starts_with("Box<") {
+            code.push_str(".map(|v| &**v)");
+        }
+        id = field.edge.target;
+        if let Some(index) = index {
+            id = match graph.shape(id) {
+                Some(Shape::List { member, .. }) => member.shape.shape,
+                _ => return None,
+            };
+            code.push_str(&match index {
+                "" => {
+                    projects = true;
+                    ".flat_map(|v| v.iter())".to_string()
+                }
+                "-1" => ".filter_map(|v| v.last())".to_string(),
+                index => format!(".filter_map(|v| v.get({}))", index.parse::<usize>().ok()?),
+            });
+        }
+    }
+    Some((code, id, projects))
+}
+
+// Whether `v`, a reference to a value of the shape `id`, equals what an acceptor expects.
+fn equals(graph: &ModelGraph<'_>, id: ShapeId, expected: &Value) -> Option<String> {
+    match (graph.shape(id)?, expected) {
+        (Shape::String { .. }, Value::String(expected)) => {
+            Some(format!("v.as_str() == {:?}", expected))
+        }
+        (Shape::Boolean { .. }, Value::Bool(true)) => Some("*v".to_string()),
+        (Shape::Boolean { .. }, Value::Bool(false)) => Some("!*v".to_string()),
+        (Shape::Integer { .. }, Value::Number(expected))
+        | (Shape::Long { .. }, Value::Number(expected)) => {
+            Some(format!("*v == {}", expected.as_i64()?))
+        }
+        _ => None,
+    }
+}
+
+#[test]
+fn generates_waiter_methods() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    use crate::codegen::format::{format_code, Formatting};
+
+    let mut def =
+        crate::model::BotocoreModel::from_path("test-data/lambda/2015-03-31/service-2.json")?;
+    def.load_waiters("test-data/lambda/2015-03-31/waiters-2.json")?;
+    let graph = ModelGraph::new(&def);
+    let methods = waiter_methods(&graph, "FunctionActive", &def.waiters["FunctionActive"], "")?
+        .ok_or("`FunctionActive`'s acceptors are supported")?;
+    let code = format_code(
+        &format!("impl<C> Client<C> {{\n{}}}\n", methods),
+        Formatting::Strict,
+    )?;
+    assert!(code.contains(
+        r#"    /// Waits for the function's State to be Active.
+    /// It polls `GetFunctionConfiguration` every 5 seconds, up to 60 times.
+    pub async fn wait_until_function_active(
+        &self,
+        input: crate::model::GetFunctionConfigurationRequest,
+    ) -> Result<
+        Option<crate::model::FunctionConfiguration>,
+        aws_sdk_rust_runtime::waiter::WaiterError<
+            crate::model::FunctionConfiguration,
+            crate::error::SdkError<crate::error::GetFunctionConfigurationError>,
+        >,
+    > {
+        let config = aws_sdk_rust_runtime::waiter::WaiterConfig::new(
+            std::time::Duration::from_secs(5),
+            60,
+        );
+        self.wait_until_function_active_with_config(input, config).await
+    }
+"#
+    ));
+    assert!(code.contains(
+        r#"        fn accept(
+            result: &Result<
+                crate::model::FunctionConfiguration,
+                crate::error::SdkError<crate::error::GetFunctionConfigurationError>,
+            >,
+        ) -> Option<aws_sdk_rust_runtime::waiter::WaiterState> {
+            if result
+                .as_ref()
+                .is_ok_and(|output| {
+                    std::iter::once(output)
+                        .filter_map(|v| v.state.as_ref())
+                        .any(|v| v.as_str() == "Active")
+                })
+            {
+                return Some(aws_sdk_rust_runtime::waiter::WaiterState::Success);
+            }
+"#
+    ));
+    assert!(code.contains("                || self.get_function_configuration(input.clone()),\n"));
+
+    let methods = waiter_methods(&graph, "FunctionExists", &def.waiters["FunctionExists"], "")?
+        .ok_or("`FunctionExists`'s acceptors are supported")?;
+    assert!(methods.contains("if result.is_ok() {\n"));
+    assert!(methods.contains(
+        ".map(crate::error::sanitize_error_code)\n            == Some(\"ResourceNotFoundException\")"
+    ));
+
+    // Flattened lists are matched all or any of their values, and unsupported expressions
+    // leave the waiter out.
+    let mut waiter = def.waiters["FunctionActive"].clone();
+    waiter.acceptors[0].matcher = Matcher::PathAll;
+    waiter.acceptors[0].argument = Some("Layers[].Arn".to_string());
+    waiter.acceptors[0].expected = "arn".into();
+    let methods = waiter_methods(&graph, "LayersReady", &waiter, "")?.ok_or("no methods")?;
+    assert!(methods.contains(
+        "let mut values = std::iter::once(output).filter_map(|v| v.layers.as_ref())\
+         .flat_map(|v| v.iter()).filter_map(|v| v.arn.as_ref()).peekable();\n"
+    ));
+    waiter.acceptors[0].matcher = Matcher::Path;
+    assert_eq!(waiter_methods(&graph, "LayersReady", &waiter, "")?, None);
+    waiter.acceptors[0].argument = Some("length(Layers) > `0`".to_string());
+    waiter.acceptors[0].expected = true.into();
+    assert_eq!(waiter_methods(&graph, "LayersReady", &waiter, "")?, None);
+
+    Ok(())
+}
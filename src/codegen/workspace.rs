@@ -0,0 +1,152 @@
+use super::{
+    features::OperationGroups,
+    format::Formatting,
+    manifest::RuntimeSource,
+    service::{generate_crate, CrateOptions},
+};
+use crate::{endpoints::Endpoints, model::BotocoreModel, naming::ServiceNaming, Err};
+use std::{
+    collections::BTreeMap,
+    path::{Component, Path, PathBuf},
+};
+
+/// How a workspace of service crates is generated.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct WorkspaceOptions {
+    // Where every crate gets the runtime from. A relative path is from the workspace's
+    // directory, and is made a member of the workspace if it's inside it. Absolute paths are
+    // used as they are, and aren't members.
+    pub runtime: RuntimeSource,
+    // How every crate's operations are feature-gated, if they are.
+    pub operation_groups: Option<OperationGroups>,
+    // Whether every crate also has a blocking client.
+    pub blocking: bool,
+    pub formatting: Formatting,
+    // The partitions' endpoints, which every crate's endpoint resolver is generated from.
+    pub endpoints: Option<Endpoints>,
+}
+
+/// The files of a workspace holding a crate for each service, keyed by their path in the
+/// workspace's directory. Each crate goes in a directory named after it.
+///
+/// `features` gives the features of each service's crate, by crate name.
+pub fn generate_workspace(
+    models: &[BotocoreModel],
+    features: &BTreeMap<String, BTreeMap<String, Vec<String>>>,
+    options: &WorkspaceOptions,
+) -> Result<BTreeMap<PathBuf, String>, Err> {
+    // Crates are one directory down from the workspace, so relative paths to the runtime get
+    // one more `..` for them.
+    let (crate_runtime, runtime_member) = match &options.runtime {
+        RuntimeSource::Version(version) => (RuntimeSource::Version(version.clone()), None),
+        RuntimeSource::Path(path) if Path::new(path).is_absolute() => {
+            (RuntimeSource::Path(path.clone()), None)
+        }
+        RuntimeSource::Path(path) => (
+            RuntimeSource::Path(format!("../{}", path)),
+            Some(path.clone()).filter(|path| {
+                Path::new(path)
+                    .components()
+                    .all(|component| matches!(component, Component::Normal(_) | Component::CurDir))
+            }),
+        ),
+    };
+
+    let mut files = BTreeMap::new();
+    let mut members = BTreeMap::new();
+    for model in models {
+        let naming = ServiceNaming::new(&model.metadata);
+        if let Some(other) = members.insert(naming.crate_name.clone(), &model.metadata) {
+            return Err(format!(
+                "both {} and {} would be generated as `{}`",
+                other.service_full_name, model.metadata.service_full_name, naming.crate_name
+            )
+            .into());
+        }
+        let crate_options = CrateOptions {
+            runtime: crate_runtime.clone(),
+            features: features
+                .get(&naming.crate_name)
+                .cloned()
+                .unwrap_or_default(),
+            operation_groups: options.operation_groups.clone(),
+            blocking: options.blocking,
+            formatting: options.formatting,
+            endpoints: options.endpoints.clone(),
+        };
+        for (path, contents) in generate_crate(model, &crate_options)? {
+            files.insert(PathBuf::from(&naming.crate_name).join(path), contents);
+        }
+    }
+
+    let mut manifest = String::from("[workspace]\nmembers = [\n");
+    for member in runtime_member.iter().chain(members.keys()) {
+        manifest.push_str(&format!("    {:?},\n", member));
+    }
+    manifest.push_str("]\nresolver = \"2\"\n");
+    files.insert(PathBuf::from("Cargo.toml"), manifest);
+    Ok(files)
+}
+
+#[test]
+fn generates_workspaces() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let recursive = crate::model::BotocoreModel::from_path("test-data/recursive.json")?;
+    let mut other = crate::model::BotocoreModel::from_path("test-data/recursive.json")?;
+    other.metadata.service_id = "Other".to_string();
+    let options = WorkspaceOptions {
+        runtime: RuntimeSource::Path("runtime".to_string()),
+        operation_groups: None,
+        blocking: true,
+        formatting: Formatting::Strict,
+        endpoints: None,
+    };
+
+    let files = generate_workspace(&[recursive, other], &BTreeMap::new(), &options)?;
+    assert_eq!(
+        files[&PathBuf::from("Cargo.toml")],
+        "[workspace]\n\
+         members = [\n    \
+         \"runtime\",\n    \
+         \"aws-sdk-other\",\n    \
+         \"aws-sdk-recursive\",\n\
+         ]\n\
+         resolver = \"2\"\n"
+    );
+    assert!(files[&PathBuf::from("aws-sdk-other/Cargo.toml")]
+        .contains("\naws-sdk-rust-runtime = { path = \"../runtime\" }\n"));
+    assert!(files.contains_key(&PathBuf::from("aws-sdk-recursive/src/lib.rs")));
+    assert!(files.contains_key(&PathBuf::from("aws-sdk-recursive/src/blocking.rs")));
+
+    let duplicate = vec![
+        crate::model::BotocoreModel::from_path("test-data/recursive.json")?,
+        crate::model::BotocoreModel::from_path("test-data/recursive.json")?,
+    ];
+    assert!(generate_workspace(&duplicate, &BTreeMap::new(), &options).is_err());
+
+    // Runtimes outside the workspace aren't members of it, and absolute paths to them are
+    // left as they are.
+    for (runtime, dependency) in [
+        ("/opt/aws-sdk-rust/runtime", "/opt/aws-sdk-rust/runtime"),
+        ("../runtime", "../../runtime"),
+        ("vendor/../../runtime", "../vendor/../../runtime"),
+    ] {
+        let options = WorkspaceOptions {
+            runtime: RuntimeSource::Path(runtime.to_string()),
+            ..options.clone()
+        };
+        let recursive = crate::model::BotocoreModel::from_path("test-data/recursive.json")?;
+        let files = generate_workspace(&[recursive], &BTreeMap::new(), &options)?;
+        assert_eq!(
+            files[&PathBuf::from("Cargo.toml")],
+            "[workspace]\nmembers = [\n    \"aws-sdk-recursive\",\n]\nresolver = \"2\"\n"
+        );
+        assert!(
+            files[&PathBuf::from("aws-sdk-recursive/Cargo.toml")].contains(&format!(
+                "\naws-sdk-rust-runtime = {{ path = {:?} }}\n",
+                dependency
+            ))
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,163 @@
+use crate::model::Markdown;
+use std::collections::HashMap;
+
+/// The model names documentation can link to, each with the path of the item generated for
+/// it, e.g. `CreateFunction` to `crate::operation::CreateFunction`, and how links out to
+/// AWS's documentation are rewritten.
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct DocLinks {
+    targets: HashMap<String, String>,
+    // What relative links like `API_Invoke.html` are relative to, e.g.
+    // `https://docs.aws.amazon.com/lambda/latest/api/`. Without it, they're dropped.
+    relative_base: Option<String>,
+    // Links to `#fragment`s point into pages that don't exist once the docs are in rustdoc,
+    // so they're dropped unless this is set.
+    keep_fragment_links: bool,
+}
+
+const DOCS_HOST: &str = "https://docs.aws.amazon.com";
+
+impl DocLinks {
+    pub fn new() -> Self {
+        DocLinks::default()
+    }
+
+    pub fn insert(&mut self, name: impl Into<String>, path: impl Into<String>) {
+        self.targets.insert(name.into(), path.into());
+    }
+
+    pub fn set_relative_base(&mut self, base: impl Into<String>) {
+        self.relative_base = Some(base.into());
+    }
+
+    pub fn set_keep_fragment_links(&mut self, keep: bool) {
+        self.keep_fragment_links = keep;
+    }
+
+    /// Rewrites the links in some Markdown.
+    ///
+    /// Botocore docs refer to other operations and shapes as `<a>CreateFunction</a>`, which
+    /// converts to a link with nothing to go to. Known names link to their generated item,
+    /// and the rest are left as code spans. Links to paths on the docs site, like
+    /// `/lambda/latest/dg/welcome.html`, are made absolute. Dropping a link keeps its text.
+    pub fn rewrite(&self, markdown: &str) -> String {
+        let mut out = String::with_capacity(markdown.len());
+        let mut rest = markdown;
+        while let Some(middle) = rest.find("](") {
+            let (start, end) = match (rest[..middle].rfind('['), rest[middle..].find(')')) {
+                (Some(start), Some(end)) => (start, middle + end),
+                _ => {
+                    out.push_str(&rest[..middle + 2]);
+                    rest = &rest[middle + 2..];
+                    continue;
+                }
+            };
+            out.push_str(&rest[..start]);
+            let text = &rest[start + 1..middle];
+            out.push_str(&self.link(text, &rest[middle + 2..end]));
+            rest = &rest[end + 1..];
+        }
+        out.push_str(rest);
+        out
+    }
+
+    fn link(&self, text: &str, url: &str) -> String {
+        if url.is_empty() {
+            return match self.targets.get(text) {
+                Some(path) => format!("[`{}`]({})", text, path),
+                None => format!("`{}`", text),
+            };
+        }
+        let url = if url.contains("://") || url.starts_with("mailto:") {
+            Some(url.to_string())
+        } else if url.starts_with('#') {
+            Some(url.to_string()).filter(|_| self.keep_fragment_links)
+        } else if url.starts_with('/') {
+            Some(format!("{}{}", DOCS_HOST, url))
+        } else {
+            self.relative_base
+                .as_ref()
+                .map(|base| format!("{}{}", base, url))
+        };
+        match url {
+            Some(url) => format!("[{}]({})", text, url),
+            None => text.to_string(),
+        }
+    }
+}
+
+/// Renders documentation as `///` comments, one per line, with cross-references rewritten
+/// by `links`.
+///
+/// Code blocks are marked as `text`, since rustdoc would otherwise try to compile them as
+/// doctests.
+pub fn doc_comment(markdown: &Markdown, links: &DocLinks) -> String {
+    let mut comment = String::new();
+    let mut in_code_block = false;
+    for line in links.rewrite(markdown.as_str()).lines() {
+        let mut line = line.trim_end().to_string();
+        if line.trim_start().starts_with("```") {
+            if !in_code_block && line.trim_start() == "```" {
+                line.push_str("text");
+            }
+            in_code_block = !in_code_block;
+        }
+        if line.is_empty() {
+            comment.push_str("///\n");
+        } else {
+            comment.push_str("/// ");
+            comment.push_str(&line);
+            comment.push('\n');
+        }
+    }
+    comment
+}
+
+#[test]
+fn renders_doc_comments() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let mut links = DocLinks::new();
+    links.insert("GetFunction", "crate::operation::GetFunction");
+    let markdown = Markdown::from(
+        "<p>Use <a>GetFunction</a> or <a>ListFunctions</a>, and see \
+         <a href=\"https://docs.aws.amazon.com/lambda/latest/dg/tagging.html\">tags</a>.</p>\
+         <pre><code>aws lambda get-function</code></pre><p>Done.</p>"
+            .to_string(),
+    );
+    assert_eq!(
+        doc_comment(&markdown, &links),
+        "/// Use [`GetFunction`](crate::operation::GetFunction) or `ListFunctions`, and see \
+         [tags](https://docs.aws.amazon.com/lambda/latest/dg/tagging.html).\n\
+         ///\n\
+         /// ```text\n\
+         /// aws lambda get-function\n\
+         /// ```\n\
+         ///\n\
+         /// Done.\n"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn rewrites_doc_urls() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let markdown =
+        "See [layers](/lambda/latest/dg/configuration-layers.html), [Invoke](API_Invoke.html), \
+         [below](#limits), and [tags](https://docs.aws.amazon.com/lambda/latest/dg/tagging.html).";
+    let mut links = DocLinks::new();
+    assert_eq!(
+        links.rewrite(markdown),
+        "See [layers](https://docs.aws.amazon.com/lambda/latest/dg/configuration-layers.html), \
+         Invoke, below, and [tags](https://docs.aws.amazon.com/lambda/latest/dg/tagging.html)."
+    );
+
+    links.set_relative_base("https://docs.aws.amazon.com/lambda/latest/api/");
+    links.set_keep_fragment_links(true);
+    assert_eq!(
+        links.rewrite(markdown),
+        "See [layers](https://docs.aws.amazon.com/lambda/latest/dg/configuration-layers.html), \
+         [Invoke](https://docs.aws.amazon.com/lambda/latest/api/API_Invoke.html), \
+         [below](#limits), and [tags](https://docs.aws.amazon.com/lambda/latest/dg/tagging.html)."
+    );
+
+    Ok(())
+}
@@ -0,0 +1,96 @@
+use crate::model::Signature;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// The contents of an `endpoints.json` file.
+#[derive(Debug, PartialEq, Eq, Deserialize, Clone)]
+pub struct Endpoints {
+    pub partitions: Vec<Partition>,
+    pub version: u32,
+}
+
+/// A group of regions that share a DNS suffix and credentials, such as `aws` or `aws-cn`.
+#[derive(Debug, PartialEq, Eq, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Partition {
+    pub partition: String,
+    pub partition_name: String,
+    pub dns_suffix: String,
+    // Matches regions in this partition that aren't listed in `regions` yet.
+    pub region_regex: String,
+    pub defaults: EndpointProperties,
+    pub regions: HashMap<String, Region>,
+    // Keyed by endpoint prefix.
+    pub services: HashMap<String, Service>,
+}
+
+#[derive(Debug, PartialEq, Eq, Deserialize, Clone)]
+pub struct Region {
+    pub description: String,
+}
+
+#[derive(Debug, PartialEq, Eq, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Service {
+    // Overrides the partition's defaults for every endpoint of this service.
+    pub defaults: Option<EndpointProperties>,
+    // Keyed by region, or by a pseudo-region like `aws-global`.
+    pub endpoints: HashMap<String, EndpointProperties>,
+    // Services that aren't regionalized are served from `partition_endpoint` alone.
+    pub is_regionalized: Option<bool>,
+    pub partition_endpoint: Option<String>,
+}
+
+/// Properties of an endpoint. Anything left unset is inherited from the service's
+/// defaults, then the partition's defaults.
+#[derive(Debug, PartialEq, Eq, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct EndpointProperties {
+    // A template such as `{service}.{region}.{dnsSuffix}`.
+    pub hostname: Option<String>,
+    pub protocols: Option<Vec<String>>,
+    pub credential_scope: Option<CredentialScope>,
+    pub signature_versions: Option<Vec<Signature>>,
+}
+
+/// Overrides the region and service name used when signing requests to an endpoint.
+#[derive(Debug, PartialEq, Eq, Deserialize, Clone, Default)]
+pub struct CredentialScope {
+    pub region: Option<String>,
+    pub service: Option<String>,
+}
+
+impl Endpoints {
+    /// Parses an `endpoints.json` file.
+    pub fn from_path(path: impl AsRef<std::path::Path>) -> Result<Self, crate::Err> {
+        crate::read_json(path)
+    }
+}
+
+#[test]
+fn parses_partitions() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let endpoints = Endpoints::from_path("test-data/endpoints.json")?;
+    let aws = &endpoints.partitions[0];
+    assert_eq!(aws.dns_suffix, "amazonaws.com");
+    assert_eq!(
+        aws.defaults.hostname.as_deref(),
+        Some("{service}.{region}.{dnsSuffix}")
+    );
+    let iam = &aws.services["iam"];
+    assert_eq!(iam.is_regionalized, Some(false));
+    let global = &iam.endpoints["aws-global"];
+    assert_eq!(
+        global
+            .credential_scope
+            .as_ref()
+            .and_then(|s| s.region.as_deref()),
+        Some("us-east-1")
+    );
+    assert_eq!(
+        aws.services["s3"].endpoints["us-east-1"].signature_versions,
+        Some(vec![Signature::S3, Signature::S3V4])
+    );
+    assert_eq!(endpoints.partitions[1].partition, "aws-cn");
+
+    Ok(())
+}
@@ -0,0 +1,504 @@
+use crate::{
+    model::{BotocoreModel, Operation, Shape, ShapeId, ShapeMember},
+    visit::{walk_model, walk_operation, ShapeVisitor},
+};
+use std::{
+    cmp::Reverse,
+    collections::{BTreeSet, BinaryHeap, HashMap},
+    fmt,
+};
+
+/// The shapes of a model as a graph: shapes are nodes, and every member reference is an
+/// edge to the shape it targets.
+///
+/// References are resolved once when the graph is built, so codegen passes can walk from
+/// shape to shape without looking names up or cloning shapes.
+#[derive(Debug)]
+pub struct ModelGraph<'a> {
+    model: &'a BotocoreModel,
+    // Sorted by name, so iteration is deterministic. A shape's position here is its node
+    // index in `edges` and `referrers`.
+    nodes: Vec<(ShapeId, &'a Shape)>,
+    index: HashMap<ShapeId, usize>,
+    edges: Vec<Vec<Edge<'a>>>,
+    referrers: Vec<Vec<ShapeId>>,
+}
+
+/// A reference from a shape to the shape one of its members targets.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Edge<'a> {
+    // The member name for structures, unions, and event streams; `member` for lists, and
+    // `key` or `value` for maps.
+    pub name: &'a str,
+    pub member: &'a ShapeMember,
+    pub target: ShapeId,
+    // Set when following this edge leads back to a shape that refers to it, directly or
+    // through other shapes. Breaking the cycle here, e.g. by boxing the member's type, makes
+    // every generated type finite. Lists and maps already put their members behind a pointer.
+    pub back_edge: bool,
+}
+
+/// A reference to a shape the model doesn't define.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct DanglingReference {
+    // Where the reference is, e.g. `operations.Invoke.input` or `shapes.Foo.members.Bar`.
+    pub path: String,
+    pub missing_shape: ShapeId,
+}
+
+/// Every dangling reference found by `ModelGraph::validate`.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct DanglingReferences(pub Vec<DanglingReference>);
+
+impl fmt::Display for DanglingReferences {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} reference(s) to undefined shapes", self.0.len())?;
+        for reference in &self.0 {
+            write!(f, "\n  {}: `{}`", reference.path, reference.missing_shape)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for DanglingReferences {}
+
+impl<'a> ModelGraph<'a> {
+    /// Builds the graph for every shape in `model`.
+    ///
+    /// References to shapes the model doesn't define are kept as edges, but `shape` returns
+    /// `None` for their targets.
+    pub fn new(model: &'a BotocoreModel) -> Self {
+        let mut nodes = model
+            .shapes
+            .iter()
+            .map(|(id, shape)| (*id, shape))
+            .collect::<Vec<_>>();
+        nodes.sort_by_key(|(id, _)| *id);
+        let index = nodes
+            .iter()
+            .enumerate()
+            .map(|(i, (id, _))| (*id, i))
+            .collect::<HashMap<_, _>>();
+
+        let edges = nodes
+            .iter()
+            .map(|(_, shape)| {
+                members(shape)
+                    .into_iter()
+                    .map(|(name, member)| Edge {
+                        name,
+                        member,
+                        target: member.shape.shape,
+                        back_edge: false,
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let mut referrers = vec![Vec::new(); nodes.len()];
+        for (i, edges) in edges.iter().enumerate() {
+            for edge in edges {
+                let referrer = nodes[i].0;
+                if let Some(&target) = index.get(&edge.target) {
+                    if !referrers[target].contains(&referrer) {
+                        referrers[target].push(referrer);
+                    }
+                }
+            }
+        }
+
+        let mut graph = ModelGraph {
+            model,
+            nodes,
+            index,
+            edges,
+            referrers,
+        };
+        let mut cycles = BackEdges::default();
+        walk_model(&graph, &mut cycles);
+        for (from, name) in cycles.0 {
+            let edges = &mut graph.edges[graph.index[&from]];
+            if let Some(edge) = edges.iter_mut().find(|edge| edge.name == name) {
+                edge.back_edge = true;
+            }
+        }
+
+        graph
+    }
+
+    /// The model the graph was built from.
+    pub fn model(&self) -> &'a BotocoreModel {
+        self.model
+    }
+
+    /// The shape with the given id, if the model defines it.
+    pub fn shape(&self, id: ShapeId) -> Option<&'a Shape> {
+        self.index.get(&id).map(|&i| self.nodes[i].1)
+    }
+
+    /// Every shape in the model, sorted by name.
+    pub fn shapes(&self) -> impl Iterator<Item = (ShapeId, &'a Shape)> + '_ {
+        self.nodes.iter().copied()
+    }
+
+    /// Every operation in the model, sorted by name.
+    pub fn operations(&self) -> impl Iterator<Item = (&'a str, &'a Operation)> {
+        let mut operations = self
+            .model
+            .operations
+            .iter()
+            .map(|(name, op)| (name.as_str(), op))
+            .collect::<Vec<_>>();
+        operations.sort_by_key(|(name, _)| *name);
+        operations.into_iter()
+    }
+
+    /// The references from a shape to its members' shapes, in member order. Empty for
+    /// shapes without members, and for shapes the model doesn't define.
+    pub fn neighbors(&self, id: ShapeId) -> &[Edge<'a>] {
+        self.index
+            .get(&id)
+            .map(|&i| self.edges[i].as_slice())
+            .unwrap_or_default()
+    }
+
+    /// The edges that close a reference cycle, each paired with the shape it starts from.
+    ///
+    /// Which edge of a cycle is picked depends on walking the shapes in name order, so it's
+    /// stable for a given model.
+    pub fn back_edges(&self) -> impl Iterator<Item = (ShapeId, &Edge<'a>)> + '_ {
+        self.nodes
+            .iter()
+            .zip(&self.edges)
+            .flat_map(|((id, _), edges)| {
+                edges
+                    .iter()
+                    .filter(|edge| edge.back_edge)
+                    .map(move |edge| (*id, edge))
+            })
+    }
+
+    /// Every shape, ordered so that the shapes a shape's members target come before it.
+    ///
+    /// Back edges are left out, since a cycle has no such order. Among shapes whose members
+    /// are all placed, the name that sorts first goes next, so adding or removing a shape only
+    /// moves the shapes that depend on it.
+    pub fn topological_order(&self) -> Vec<ShapeId> {
+        // How many distinct shapes each shape still waits on, and who waits on each shape.
+        let mut waiting = vec![0; self.nodes.len()];
+        let mut dependents = vec![Vec::new(); self.nodes.len()];
+        for (i, edges) in self.edges.iter().enumerate() {
+            for edge in edges.iter().filter(|edge| !edge.back_edge) {
+                if let Some(&target) = self.index.get(&edge.target) {
+                    if !dependents[target].contains(&i) {
+                        dependents[target].push(i);
+                        waiting[i] += 1;
+                    }
+                }
+            }
+        }
+
+        let mut ready = (0..self.nodes.len())
+            .filter(|&i| waiting[i] == 0)
+            .map(|i| Reverse((self.nodes[i].0, i)))
+            .collect::<BinaryHeap<_>>();
+        let mut order = Vec::with_capacity(self.nodes.len());
+        while let Some(Reverse((id, i))) = ready.pop() {
+            order.push(id);
+            for &dependent in &dependents[i] {
+                waiting[dependent] -= 1;
+                if waiting[dependent] == 0 {
+                    ready.push(Reverse((self.nodes[dependent].0, dependent)));
+                }
+            }
+        }
+
+        order
+    }
+
+    /// The shapes that some operation's input, output, or errors lead to, directly or not.
+    /// Everything else in the model is dead weight for codegen.
+    pub fn reachable_from_operations(&self) -> BTreeSet<ShapeId> {
+        // Returning `false` for shapes an earlier operation reached stops the walk from
+        // going over their members again.
+        struct Reachable(BTreeSet<ShapeId>);
+
+        impl ShapeVisitor<'_> for Reachable {
+            fn visit_shape(&mut self, id: ShapeId, _shape: &Shape) -> bool {
+                self.0.insert(id)
+            }
+        }
+
+        let mut reachable = Reachable(BTreeSet::new());
+        for (_, op) in self.operations() {
+            walk_operation(self, op, &mut reachable);
+        }
+        reachable.0
+    }
+
+    /// Checks that every shape that operations and members refer to is defined, reporting
+    /// each reference that isn't. Operations come first, then shapes, both in name order.
+    pub fn validate(&self) -> Result<(), DanglingReferences> {
+        let mut dangling = vec![];
+        let mut check = |path: String, id: ShapeId| {
+            if !self.index.contains_key(&id) {
+                dangling.push(DanglingReference {
+                    path,
+                    missing_shape: id,
+                });
+            }
+        };
+
+        for (name, op) in self.operations() {
+            check(format!("operations.{}.input", name), op.input.shape);
+            if let Some(output) = &op.output {
+                check(format!("operations.{}.output", name), output.shape);
+            }
+            for error in &op.errors {
+                check(format!("operations.{}.errors", name), error.shape);
+            }
+        }
+        for ((id, shape), edges) in self.nodes.iter().zip(&self.edges) {
+            for edge in edges {
+                let path = match shape {
+                    Shape::List { .. } | Shape::Map { .. } => {
+                        format!("shapes.{}.{}", id, edge.name)
+                    }
+                    _ => format!("shapes.{}.members.{}", id, edge.name),
+                };
+                check(path, edge.target);
+            }
+        }
+
+        if dangling.is_empty() {
+            Ok(())
+        } else {
+            Err(DanglingReferences(dangling))
+        }
+    }
+
+    /// The shapes with a member targeting `id`, each listed once, sorted by name.
+    pub fn referrers(&self, id: ShapeId) -> &[ShapeId] {
+        self.index
+            .get(&id)
+            .map(|&i| self.referrers[i].as_slice())
+            .unwrap_or_default()
+    }
+}
+
+impl BotocoreModel {
+    /// Drops the shapes no operation can reach, returning their ids in name order.
+    pub fn prune(&mut self) -> Vec<ShapeId> {
+        let reachable = ModelGraph::new(self).reachable_from_operations();
+        let mut pruned = self
+            .shapes
+            .keys()
+            .filter(|id| !reachable.contains(id))
+            .copied()
+            .collect::<Vec<_>>();
+        pruned.sort();
+        for id in &pruned {
+            self.shapes.remove(id);
+        }
+        pruned
+    }
+}
+
+// The members that lead back into the walk, as (shape, member name) pairs.
+#[derive(Default)]
+struct BackEdges<'a>(Vec<(ShapeId, &'a str)>);
+
+impl<'a> ShapeVisitor<'a> for BackEdges<'a> {
+    fn visit_cycle(&mut self, from: ShapeId, edge: &Edge<'a>) {
+        self.0.push((from, edge.name));
+    }
+}
+
+// A shape's members, sorted by name for structures; a map's key comes before its value.
+fn members(shape: &Shape) -> Vec<(&str, &ShapeMember)> {
+    match shape {
+        Shape::Structure { members, .. }
+        | Shape::Union { members, .. }
+        | Shape::EventStream { members, .. } => {
+            let mut members = members
+                .iter()
+                .map(|(name, member)| (name.as_str(), member))
+                .collect::<Vec<_>>();
+            members.sort_by_key(|(name, _)| *name);
+            members
+        }
+        Shape::List { member, .. } => vec![("member", member)],
+        Shape::Map { key, value, .. } => vec![("key", key), ("value", value)],
+        _ => vec![],
+    }
+}
+
+#[test]
+fn links_members_to_shapes() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let def = BotocoreModel::from_path("test-data/lambda/2015-03-31/service-2.json")?;
+    let graph = ModelGraph::new(&def);
+    assert_eq!(graph.shapes().count(), def.shapes.len());
+
+    let request = ShapeId::from("InvocationRequest");
+    let function_name = graph
+        .neighbors(request)
+        .iter()
+        .find(|edge| edge.name == "FunctionName")
+        .expect("InvocationRequest has a FunctionName member");
+    assert_eq!(
+        function_name.target,
+        ShapeId::from("NamespacedFunctionName")
+    );
+    assert!(graph.referrers(function_name.target).contains(&request));
+
+    let tags = graph
+        .neighbors(ShapeId::from("Tags"))
+        .iter()
+        .map(|edge| (edge.name, edge.target))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        tags,
+        vec![
+            ("key", ShapeId::from("TagKey")),
+            ("value", ShapeId::from("TagValue"))
+        ]
+    );
+    assert_eq!(
+        graph.referrers(ShapeId::from("Tags")),
+        &[
+            ShapeId::from("CreateFunctionRequest"),
+            ShapeId::from("GetFunctionResponse"),
+            ShapeId::from("ListTagsResponse"),
+            ShapeId::from("TagResourceRequest"),
+        ]
+    );
+    assert!(graph.neighbors(ShapeId::from("NoSuchShape")).is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn records_back_edges() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let def = BotocoreModel::from_path("test-data/recursive.json")?;
+    let graph = ModelGraph::new(&def);
+    let back_edges = graph
+        .back_edges()
+        .map(|(from, edge)| (from.as_str(), edge.name, edge.target.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        back_edges,
+        vec![
+            ("ListAttributeValue", "member", "AttributeValue"),
+            ("MapAttributeValue", "value", "AttributeValue"),
+        ]
+    );
+
+    let def = BotocoreModel::from_path("test-data/lambda/2015-03-31/service-2.json")?;
+    assert_eq!(ModelGraph::new(&def).back_edges().count(), 0);
+
+    Ok(())
+}
+
+#[test]
+fn reports_dangling_references() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let mut def = BotocoreModel::from_path("test-data/recursive.json")?;
+    assert_eq!(ModelGraph::new(&def).validate(), Ok(()));
+
+    def.shapes.remove(&ShapeId::from("AttributeMap"));
+    def.shapes.remove(&ShapeId::from("AttributeName"));
+    def.shapes.remove(&ShapeId::from("ValidationException"));
+    let dangling = ModelGraph::new(&def)
+        .validate()
+        .expect_err("shapes were removed")
+        .0
+        .into_iter()
+        .map(|reference| (reference.path, reference.missing_shape.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        dangling,
+        vec![
+            (
+                "operations.PutItem.errors".to_string(),
+                "ValidationException"
+            ),
+            ("shapes.MapAttributeValue.key".to_string(), "AttributeName"),
+            (
+                "shapes.PutItemInput.members.Item".to_string(),
+                "AttributeMap"
+            ),
+            (
+                "shapes.PutItemOutput.members.Attributes".to_string(),
+                "AttributeMap"
+            ),
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn orders_members_before_containers() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let def = BotocoreModel::from_path("test-data/recursive.json")?;
+    let order = ModelGraph::new(&def).topological_order();
+    assert_eq!(
+        order.iter().map(|id| id.as_str()).collect::<Vec<_>>(),
+        vec![
+            "AttributeName",
+            "ErrorMessage",
+            "ListAttributeValue",
+            "MapAttributeValue",
+            "StringAttributeValue",
+            "AttributeValue",
+            "AttributeMap",
+            "PutItemInput",
+            "PutItemOutput",
+            "ValidationException",
+        ]
+    );
+
+    let def = BotocoreModel::from_path("test-data/lambda/2015-03-31/service-2.json")?;
+    let graph = ModelGraph::new(&def);
+    let order = graph.topological_order();
+    assert_eq!(order.len(), def.shapes.len());
+    let position = order
+        .iter()
+        .enumerate()
+        .map(|(i, id)| (*id, i))
+        .collect::<HashMap<_, _>>();
+    for (id, _) in graph.shapes() {
+        // The fixture has one member targeting an undefined shape, which has no position.
+        for edge in graph.neighbors(id) {
+            if let Some(target) = position.get(&edge.target) {
+                assert!(*target < position[&id]);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn prunes_unreachable_shapes() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let mut def = BotocoreModel::from_path("test-data/recursive.json")?;
+    let reachable = ModelGraph::new(&def).reachable_from_operations();
+    assert_eq!(reachable.len(), def.shapes.len());
+
+    def.shapes.insert(
+        ShapeId::from("UnusedList"),
+        serde_json::from_str(r#"{"type": "list", "member": {"shape": "UnusedString"}}"#)?,
+    );
+    def.shapes.insert(
+        ShapeId::from("UnusedString"),
+        serde_json::from_str(r#"{"type": "string"}"#)?,
+    );
+    assert!(!ModelGraph::new(&def)
+        .reachable_from_operations()
+        .contains(&ShapeId::from("UnusedString")));
+    assert_eq!(
+        def.prune(),
+        vec![ShapeId::from("UnusedList"), ShapeId::from("UnusedString")]
+    );
+    assert_eq!(def.shapes.len(), reachable.len());
+    assert!(def.prune().is_empty());
+
+    Ok(())
+}
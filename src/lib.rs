@@ -0,0 +1,19 @@
+//! Parses botocore service models, as a base for generating Rust SDKs from them.
+
+use std::path::Path;
+
+pub mod codegen;
+pub mod docs;
+pub mod endpoints;
+pub mod graph;
+pub mod model;
+pub mod naming;
+pub mod resolve;
+pub mod visit;
+
+type Err = Box<dyn std::error::Error + Send + Sync + 'static>;
+
+fn read_json<T: serde::de::DeserializeOwned>(path: impl AsRef<Path>) -> Result<T, Err> {
+    let def = std::fs::read_to_string(path)?;
+    Ok(serde_json::from_str(&def)?)
+}
@@ -0,0 +1,168 @@
+use super::BotocoreModel;
+use crate::Err;
+use std::path::{Path, PathBuf};
+
+/// Which of a service's API versions to load.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum VersionSelection {
+    // The version that sorts last. Versions are dates, so that's the newest.
+    Latest,
+    Exact(String),
+    // Start from the newest version, then add whatever older versions define that newer
+    // ones don't, such as operations that were dropped when the API was revised.
+    Merged,
+}
+
+/// A service model, with its paginators, waiters, examples, and smoke tests merged in, for
+/// one API version.
+#[derive(Debug, PartialEq)]
+pub struct ServiceBundle {
+    // For merged bundles, the newest of the versions that went in.
+    pub api_version: String,
+    pub model: BotocoreModel,
+}
+
+impl ServiceBundle {
+    /// The API versions in a botocore service directory, e.g. `botocore/data/lambda`,
+    /// oldest first. Each one is a subdirectory holding a `service-2.json`.
+    pub fn api_versions(service_dir: impl AsRef<Path>) -> Result<Vec<String>, Err> {
+        let mut versions = vec![];
+        for entry in std::fs::read_dir(service_dir)? {
+            let entry = entry?;
+            if entry.path().join("service-2.json").is_file() {
+                versions.push(entry.file_name().to_string_lossy().into_owned());
+            }
+        }
+        versions.sort();
+        Ok(versions)
+    }
+
+    /// Loads the selected API versions of the service in `service_dir`.
+    pub fn load(service_dir: impl AsRef<Path>, selection: VersionSelection) -> Result<Self, Err> {
+        let service_dir = service_dir.as_ref();
+        let versions = Self::api_versions(service_dir)?;
+        let newest = versions
+            .last()
+            .ok_or_else(|| format!("no API versions in `{}`", service_dir.display()))?;
+        match selection {
+            VersionSelection::Latest => Self::load_version(service_dir, newest),
+            VersionSelection::Exact(version) => {
+                if !versions.contains(&version) {
+                    return Err(format!(
+                        "no API version `{}` in `{}`",
+                        version,
+                        service_dir.display()
+                    )
+                    .into());
+                }
+                Self::load_version(service_dir, &version)
+            }
+            VersionSelection::Merged => {
+                let mut bundle = Self::load_version(service_dir, newest)?;
+                for version in versions.iter().rev().skip(1) {
+                    let older = Self::load_version(service_dir, version)?;
+                    merge_older(&mut bundle.model, older.model);
+                }
+                Ok(bundle)
+            }
+        }
+    }
+
+    // Loads `service-2.json` from a version directory, along with whichever of the other
+    // files are there.
+    fn load_version(service_dir: &Path, version: &str) -> Result<Self, Err> {
+        let dir = service_dir.join(version);
+        let file = |name: &str| -> Option<PathBuf> {
+            let path = dir.join(name);
+            if path.is_file() {
+                Some(path)
+            } else {
+                None
+            }
+        };
+
+        let mut model = BotocoreModel::from_path(dir.join("service-2.json"))?;
+        if let Some(path) = file("paginators-1.json") {
+            model.load_paginators(path)?;
+        }
+        if let Some(path) = file("waiters-2.json") {
+            model.load_waiters(path)?;
+        }
+        if let Some(path) = file("examples-1.json") {
+            model.load_examples(path)?;
+        }
+        if let Some(path) = file("smoke.json") {
+            model.load_smoke_tests(path)?;
+        }
+
+        Ok(ServiceBundle {
+            api_version: version.to_string(),
+            model,
+        })
+    }
+}
+
+// Adds the operations, shapes, paginators, waiters, and examples that only `older` defines.
+// Anything both define is kept as `newer` has it.
+fn merge_older(newer: &mut BotocoreModel, older: BotocoreModel) {
+    for (name, op) in older.operations {
+        newer.operations.entry(name).or_insert(op);
+    }
+    for (id, shape) in older.shapes {
+        newer.shapes.entry(id).or_insert(shape);
+    }
+    for (name, paginator) in older.paginators {
+        newer.paginators.entry(name).or_insert(paginator);
+    }
+    for (name, waiter) in older.waiters {
+        newer.waiters.entry(name).or_insert(waiter);
+    }
+    for (name, examples) in older.examples {
+        newer.examples.entry(name).or_insert(examples);
+    }
+    if newer.smoke_tests.is_none() {
+        newer.smoke_tests = older.smoke_tests;
+    }
+}
+
+#[test]
+fn selects_and_merges_versions() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    assert_eq!(
+        ServiceBundle::api_versions("test-data/lambda")?,
+        vec!["2014-11-11", "2015-03-31"]
+    );
+
+    let latest = ServiceBundle::load("test-data/lambda", VersionSelection::Latest)?;
+    assert_eq!(latest.api_version, "2015-03-31");
+    assert!(!latest.model.operations.contains_key("ListEventSources"));
+    assert!(latest.model.paginators.contains_key("ListFunctions"));
+    assert!(latest.model.waiters.contains_key("FunctionExists"));
+    assert!(latest.model.smoke_tests.is_some());
+
+    let old = ServiceBundle::load(
+        "test-data/lambda",
+        VersionSelection::Exact("2014-11-11".to_string()),
+    )?;
+    assert_eq!(old.model.metadata.api_version, "2014-11-11");
+    assert!(old.model.waiters.is_empty());
+    assert!(ServiceBundle::load(
+        "test-data/lambda",
+        VersionSelection::Exact("2020-01-01".to_string())
+    )
+    .is_err());
+
+    let merged = ServiceBundle::load("test-data/lambda", VersionSelection::Merged)?;
+    assert_eq!(merged.api_version, "2015-03-31");
+    assert_eq!(
+        merged.model.operations["GetFunction"],
+        latest.model.operations["GetFunction"]
+    );
+    assert!(merged.model.operations.contains_key("ListEventSources"));
+    assert!(merged.model.paginators.contains_key("ListEventSources"));
+    assert_eq!(
+        merged.model.operations.len(),
+        latest.model.operations.len() + 1
+    );
+
+    Ok(())
+}
@@ -0,0 +1,32 @@
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// The contents of an `examples-1.json` file.
+#[derive(Debug, PartialEq, Deserialize)]
+pub struct Examples {
+    pub version: String,
+    // Keyed by operation name.
+    pub examples: HashMap<String, Vec<Example>>,
+}
+
+/// A worked call to an operation, with the input sent and the output received.
+#[derive(Debug, PartialEq, Deserialize, Clone)]
+pub struct Example {
+    pub id: String,
+    pub title: String,
+    pub description: String,
+    pub input: Option<Value>,
+    pub output: Option<Value>,
+    #[serde(default)]
+    pub comments: Comments,
+}
+
+/// Annotations on individual input and output members, keyed by member name.
+#[derive(Debug, PartialEq, Deserialize, Clone, Default)]
+pub struct Comments {
+    #[serde(default)]
+    pub input: HashMap<String, Value>,
+    #[serde(default)]
+    pub output: HashMap<String, Value>,
+}
@@ -0,0 +1,199 @@
+use super::{BotocoreModel, Operation, Shape, ShapeId, ShapeMember};
+use serde::Deserialize;
+use serde_json::Value;
+use std::{collections::HashMap, fmt, path::Path};
+
+/// The contents of a `service-2.sdk-extras.json` file: additions the AWS SDKs layer on top
+/// of the published model.
+#[derive(Debug, PartialEq, Deserialize)]
+pub struct SdkExtras {
+    pub version: f64,
+    pub merge: Merge,
+}
+
+/// Operations and shapes to layer onto the model. These are kept as raw JSON until they're
+/// merged, since an entry can either define something new or extend an existing structure.
+/// Anything else, such as `metadata`, isn't merged, so it's rejected rather than dropped.
+#[derive(Debug, PartialEq, Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub struct Merge {
+    #[serde(default)]
+    pub operations: HashMap<String, Value>,
+    #[serde(default)]
+    pub shapes: HashMap<ShapeId, Value>,
+}
+
+// An entry that extends a structure already in the model.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct StructureExtras {
+    #[serde(default)]
+    members: HashMap<String, ShapeMember>,
+    #[serde(default)]
+    required: Vec<String>,
+}
+
+/// An extras entry that couldn't be layered onto the model.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Conflict {
+    // Where in the model the conflict is, e.g. `shapes.Foo.members.Bar`.
+    pub path: String,
+    pub reason: String,
+}
+
+/// Every conflict found while merging extras. Entries that didn't conflict are still merged.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct MergeConflicts(pub Vec<Conflict>);
+
+impl fmt::Display for MergeConflicts {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} conflict(s) merging sdk extras", self.0.len())?;
+        for conflict in &self.0 {
+            write!(f, "\n  {}: {}", conflict.path, conflict.reason)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for MergeConflicts {}
+
+impl BotocoreModel {
+    /// Parses a `service-2.sdk-extras.json` file and merges it into the model.
+    pub fn load_extras(&mut self, path: impl AsRef<Path>) -> Result<(), crate::Err> {
+        let extras = crate::read_json::<SdkExtras>(path)?;
+        Ok(self.merge_extras(extras)?)
+    }
+
+    /// Layers extras onto the model.
+    ///
+    /// New operations and shapes are added outright. Existing structures can gain members
+    /// and required members, but nothing that's already defined is overridden: redefining
+    /// an operation, shape, or member differently is reported as a conflict.
+    pub fn merge_extras(&mut self, extras: SdkExtras) -> Result<(), MergeConflicts> {
+        let mut conflicts = vec![];
+        let mut conflict = |path: String, reason: String| conflicts.push(Conflict { path, reason });
+
+        let mut operations = extras.merge.operations.into_iter().collect::<Vec<_>>();
+        operations.sort_by(|a, b| a.0.cmp(&b.0));
+        for (name, value) in operations {
+            let path = format!("operations.{}", name);
+            if self.operations.contains_key(&name) {
+                conflict(path, "operation is already defined".to_string());
+                continue;
+            }
+            match serde_json::from_value::<Operation>(value) {
+                Ok(op) => {
+                    self.operations.insert(name, op);
+                }
+                Err(e) => conflict(path, e.to_string()),
+            }
+        }
+
+        let mut shapes = extras.merge.shapes.into_iter().collect::<Vec<_>>();
+        shapes.sort_by_key(|(name, _)| *name);
+        for (name, value) in shapes {
+            let path = format!("shapes.{}", name);
+            match self.shapes.get_mut(&name) {
+                None => match serde_json::from_value::<Shape>(value) {
+                    Ok(shape) => {
+                        self.shapes.insert(name, shape);
+                    }
+                    Err(e) => conflict(path, e.to_string()),
+                },
+                Some(Shape::Structure {
+                    members, required, ..
+                }) => {
+                    let extras = match serde_json::from_value::<StructureExtras>(value) {
+                        Ok(extras) => extras,
+                        Err(e) => {
+                            conflict(path, e.to_string());
+                            continue;
+                        }
+                    };
+                    let mut added = extras.members.into_iter().collect::<Vec<_>>();
+                    added.sort_by(|a, b| a.0.cmp(&b.0));
+                    for (member_name, member) in added {
+                        match members.get(&member_name) {
+                            Some(existing) if *existing != member => conflict(
+                                format!("{}.members.{}", path, member_name),
+                                "member is already defined differently".to_string(),
+                            ),
+                            _ => {
+                                members.insert(member_name, member);
+                            }
+                        }
+                    }
+                    let required = required.get_or_insert_with(Vec::new);
+                    for member_name in extras.required {
+                        if !members.contains_key(&member_name) {
+                            conflict(
+                                format!("{}.required", path),
+                                format!("`{}` is not a member", member_name),
+                            );
+                        } else if !required.contains(&member_name) {
+                            required.push(member_name);
+                        }
+                    }
+                }
+                Some(existing) => match serde_json::from_value::<Shape>(value) {
+                    Ok(shape) if shape == *existing => {}
+                    Ok(_) => conflict(path, "shape is already defined differently".to_string()),
+                    Err(e) => conflict(path, e.to_string()),
+                },
+            }
+        }
+
+        if conflicts.is_empty() {
+            Ok(())
+        } else {
+            Err(MergeConflicts(conflicts))
+        }
+    }
+}
+
+#[test]
+fn merges_extras() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let mut def = BotocoreModel::from_path("test-data/lambda/2015-03-31/service-2.json")?;
+    let extras = serde_json::from_str::<SdkExtras>(
+        r#"{
+            "version": 1.0,
+            "merge": {
+                "shapes": {
+                    "GetFunctionRequest": {
+                        "members": {
+                            "Endpoint": { "shape": "String" },
+                            "FunctionName": { "shape": "Arn" }
+                        }
+                    },
+                    "Endpoint": { "type": "string" },
+                    "Runtime": { "type": "string" }
+                }
+            }
+        }"#,
+    )?;
+    let conflicts = def.merge_extras(extras).unwrap_err();
+    assert_eq!(
+        conflicts
+            .0
+            .iter()
+            .map(|c| c.path.as_str())
+            .collect::<Vec<_>>(),
+        vec![
+            "shapes.GetFunctionRequest.members.FunctionName",
+            "shapes.Runtime"
+        ]
+    );
+    assert!(def.shapes.contains_key(&ShapeId::from("Endpoint")));
+    match &def.shapes[&ShapeId::from("GetFunctionRequest")] {
+        Shape::Structure { members, .. } => assert!(members.contains_key("Endpoint")),
+        other => panic!("expected a structure shape, got {:?}", other),
+    }
+
+    // Only operations and shapes are merged, so overriding anything else fails to load.
+    assert!(serde_json::from_str::<SdkExtras>(
+        r#"{"version": 1.0, "merge": {"metadata": {"protocols": ["json"]}}}"#
+    )
+    .is_err());
+
+    Ok(())
+}
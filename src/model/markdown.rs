@@ -0,0 +1,98 @@
+use serde::Deserialize;
+
+/// Documentation, converted from the HTML botocore models carry.
+#[derive(Debug, PartialEq, Deserialize, Clone)]
+#[serde(from = "String")]
+pub struct Markdown(String);
+
+impl From<String> for Markdown {
+    fn from(s: String) -> Self {
+        Markdown(tidy(&html2md::parse_html(&rewrite_tags(&s))))
+    }
+}
+
+impl Markdown {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+// Tags AWS docs use that `html2md` drops, and the HTML to replace them with. Callouts become
+// block quotes led by their kind, and definition lists become lists with each term in bold.
+const TAGS: &[(&str, &str)] = &[
+    ("<note>", "<blockquote><p><b>Note:</b></p>"),
+    ("</note>", "</blockquote>"),
+    ("<important>", "<blockquote><p><b>Important:</b></p>"),
+    ("</important>", "</blockquote>"),
+    ("<dl>", "<ul>"),
+    ("</dl>", "</ul>"),
+    ("<dt>", "<li><p><b>"),
+    ("</dt>", "</b></p>"),
+    ("<dd>", ""),
+    ("</dd>", "</li>"),
+];
+
+fn rewrite_tags(html: &str) -> String {
+    TAGS.iter()
+        .fold(html.to_string(), |html, (tag, replacement)| {
+            html.replace(tag, replacement)
+        })
+}
+
+// `html2md` pads block quotes with empty `>` lines and sometimes leaves runs of blank lines.
+// Keeps one empty line at most between paragraphs, quoted or not, and none at either end of
+// a quote.
+fn tidy(markdown: &str) -> String {
+    let quote_content = |line: &str| line.starts_with('>') && line.trim() != ">";
+    let mut lines: Vec<&str> = vec![];
+    for line in markdown.lines() {
+        let line = line.trim_end();
+        let previous = lines.last().copied();
+        let keep = if line == ">" {
+            previous.is_some_and(quote_content)
+        } else if line.is_empty() {
+            previous.is_some_and(|previous| !previous.is_empty())
+        } else {
+            true
+        };
+        if keep {
+            lines.push(line);
+        }
+    }
+
+    let mut tidied: Vec<&str> = vec![];
+    for (i, line) in lines.iter().enumerate() {
+        let next = lines.get(i + 1).copied();
+        if *line == ">" && !next.is_some_and(quote_content) {
+            continue;
+        }
+        tidied.push(line);
+    }
+    while tidied.last().is_some_and(|line| line.is_empty()) {
+        tidied.pop();
+    }
+    tidied.join("\n")
+}
+
+#[test]
+fn converts_aws_doc_structures() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let markdown = |html: &str| Markdown::from(html.to_string()).0;
+    assert_eq!(
+        markdown("<p>Deletes a function.</p> <note> <p>This can't be undone.</p> </note> <p>See also.</p>"),
+        "Deletes a function.\n\n> **Note:**\n>\n> This can't be undone.\n\nSee also."
+    );
+    assert_eq!(
+        markdown("<dl> <dt>Active</dt> <dd> <p>Ready to invoke.</p> </dd> <dt>Pending</dt> <dd> <p>Being created.</p> </dd> </dl>"),
+        "* **Active**\n\n  Ready to invoke.\n\n* **Pending**\n\n  Being created."
+    );
+    assert_eq!(
+        markdown("<ul> <li> <p>One</p> <ul> <li> <p>Nested <code>code</code></p> </li> </ul> </li> </ul>"),
+        "* One\n\n  * Nested `code`"
+    );
+    assert_eq!(
+        markdown("<table> <tr> <th>Runtime</th> <th>Identifier</th> </tr> <tr> <td>Python 3.8</td> <td> <code>python3.8</code> </td> </tr> </table>"),
+        "| Runtime  | Identifier |\n|----------|------------|\n|Python 3.8| `python3.8`|"
+    );
+
+    Ok(())
+}
@@ -0,0 +1,266 @@
+use serde::Deserialize;
+use std::{collections::HashMap, path::Path};
+
+pub mod bundle;
+pub mod examples;
+pub mod extras;
+pub mod paginators;
+pub mod smoke;
+pub mod waiters;
+
+mod markdown;
+mod operation;
+mod shape;
+mod shape_id;
+
+pub use markdown::Markdown;
+pub use operation::{
+    AuthType, ChecksumAlgorithm, Endpoint, EndpointDiscovery, HttpBindings, HttpChecksum,
+    Operation, RequestCompression, UriSegment, UriTemplate,
+};
+pub use shape::{
+    Constraints, ErrorInfo, Location, LocationType, Retryable, Shape, ShapeMember, ShapeReference,
+    TimestampFormat, Traits, XmlNamespace,
+};
+pub use shape_id::ShapeId;
+
+use crate::{read_json, Err};
+use examples::{Example, Examples};
+use paginators::{Paginator, Paginators};
+use smoke::SmokeTests;
+use waiters::{Waiter, Waiters};
+
+#[derive(Debug, PartialEq, Deserialize)]
+pub struct BotocoreModel {
+    pub version: String,
+    pub metadata: Metadata,
+    pub operations: HashMap<String, Operation>,
+    pub shapes: HashMap<ShapeId, Shape>,
+    pub documentation: Markdown,
+    // Merged in from `paginators-1.json`, keyed by operation name.
+    #[serde(skip)]
+    pub paginators: HashMap<String, Paginator>,
+    // Merged in from `waiters-2.json`, keyed by waiter name.
+    #[serde(skip)]
+    pub waiters: HashMap<String, Waiter>,
+    // Merged in from `examples-1.json`, keyed by operation name.
+    #[serde(skip)]
+    pub examples: HashMap<String, Vec<Example>>,
+    // Loaded from `smoke.json`.
+    #[serde(skip)]
+    pub smoke_tests: Option<SmokeTests>,
+}
+
+impl BotocoreModel {
+    /// Parses a `service-2.json` file.
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self, Err> {
+        read_json(path)
+    }
+
+    /// The operation clients call to discover endpoints, if the service supports
+    /// endpoint discovery.
+    pub fn endpoint_operation(&self) -> Option<(&str, &Operation)> {
+        self.operations
+            .iter()
+            .find(|(_, op)| op.endpoint_operation)
+            .map(|(name, op)| (name.as_str(), op))
+    }
+
+    /// Parses a `paginators-1.json` file and merges its paginators into the model.
+    ///
+    /// Fails if a paginator refers to an operation the model doesn't define.
+    pub fn load_paginators(&mut self, path: impl AsRef<Path>) -> Result<(), Err> {
+        let paginators = read_json::<Paginators>(path)?;
+        for (name, paginator) in paginators.pagination {
+            if !self.operations.contains_key(&name) {
+                return Err(format!("paginator for unknown operation `{}`", name).into());
+            }
+            self.paginators.insert(name, paginator);
+        }
+
+        Ok(())
+    }
+
+    /// Parses a `waiters-2.json` file and merges its waiters into the model.
+    ///
+    /// Fails if a waiter polls an operation the model doesn't define.
+    pub fn load_waiters(&mut self, path: impl AsRef<Path>) -> Result<(), Err> {
+        let waiters = read_json::<Waiters>(path)?;
+        for (name, waiter) in waiters.waiters {
+            if !self.operations.contains_key(&waiter.operation) {
+                return Err(format!(
+                    "waiter `{}` polls unknown operation `{}`",
+                    name, waiter.operation
+                )
+                .into());
+            }
+            self.waiters.insert(name, waiter);
+        }
+
+        Ok(())
+    }
+
+    /// Parses an `examples-1.json` file and merges its examples into the model.
+    ///
+    /// Fails if an example is for an operation the model doesn't define.
+    pub fn load_examples(&mut self, path: impl AsRef<Path>) -> Result<(), Err> {
+        let examples = read_json::<Examples>(path)?;
+        for (name, examples) in examples.examples {
+            if !self.operations.contains_key(&name) {
+                return Err(format!("examples for unknown operation `{}`", name).into());
+            }
+            self.examples.insert(name, examples);
+        }
+
+        Ok(())
+    }
+
+    /// Parses a `smoke.json` file and attaches its tests to the model.
+    ///
+    /// Fails if a test calls an operation the model doesn't define.
+    pub fn load_smoke_tests(&mut self, path: impl AsRef<Path>) -> Result<(), Err> {
+        let smoke_tests = read_json::<SmokeTests>(path)?;
+        for test in &smoke_tests.test_cases {
+            if !self.operations.contains_key(&test.operation_name) {
+                return Err(
+                    format!("smoke test for unknown operation `{}`", test.operation_name).into(),
+                );
+            }
+        }
+        self.smoke_tests = Some(smoke_tests);
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Metadata {
+    pub api_version: String,
+    pub endpoint_prefix: String,
+    // Specifies the service protocol. Should be one of "json", "rest-json", "rest-xml", "query", or "ec2".
+    pub protocol: Protocol,
+    // A shorter name for the service. Often used to generate class names for SDKs. For example: "Amazon S3", which in the Ruby SDK would generate a client class in the `Aws::S3` namespace rather than `Aws::SimpleStorage`.
+    pub service_full_name: String,
+    pub service_id: String,
+    // A short name for the service, e.g. "Amazon SES". Not every model has one.
+    pub service_abbreviation: Option<String>,
+    pub signature_version: Signature,
+    // The service name requests are signed for, when it isn't the endpoint prefix.
+    pub signing_name: Option<String>,
+    // The awsJson protocol version, "1.0" or "1.1", for `Protocol::Json` services.
+    pub json_version: Option<String>,
+    // Prefixes operation names in the `X-Amz-Target` header of awsJson requests.
+    pub target_prefix: Option<String>,
+}
+#[derive(Debug, PartialEq, Deserialize)]
+pub enum Protocol {
+    #[serde(rename = "rest-json")]
+    RestJson,
+    #[serde(rename = "json")]
+    Json,
+    #[serde(rename = "rest-xml")]
+    RestXml,
+    #[serde(rename = "query")]
+    Query,
+    // EC2's dialect of the query protocol.
+    #[serde(rename = "ec2")]
+    Ec2,
+}
+
+#[derive(Debug, PartialEq, Eq, Deserialize, Clone, Copy)]
+pub enum Signature {
+    #[serde(rename = "v4")]
+    V4,
+    // Legacy S3 signing, predating SigV4.
+    #[serde(rename = "s3")]
+    S3,
+    // SigV4 with S3's quirks, such as not normalizing the URI path.
+    #[serde(rename = "s3v4")]
+    S3V4,
+    // SigV2, used by older query services like SimpleDB.
+    #[serde(rename = "v2")]
+    V2,
+    #[serde(rename = "bearer")]
+    Bearer,
+    #[serde(rename = "none")]
+    None,
+}
+
+#[test]
+fn merges_paginators() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let mut def = BotocoreModel::from_path("test-data/lambda/2015-03-31/service-2.json")?;
+    def.load_paginators("test-data/lambda/2015-03-31/paginators-1.json")?;
+    let paginator = &def.paginators["ListFunctions"];
+    assert_eq!(paginator.input_token, vec!["Marker"]);
+    assert_eq!(paginator.output_token, vec!["NextMarker"]);
+    assert_eq!(paginator.limit_key.as_deref(), Some("MaxItems"));
+    assert_eq!(paginator.result_key, vec!["Functions"]);
+
+    Ok(())
+}
+
+#[test]
+fn merges_waiters() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let mut def = BotocoreModel::from_path("test-data/lambda/2015-03-31/service-2.json")?;
+    def.load_waiters("test-data/lambda/2015-03-31/waiters-2.json")?;
+    let waiter = &def.waiters["FunctionExists"];
+    assert_eq!(waiter.operation, "GetFunction");
+    assert_eq!(waiter.acceptors[0].matcher, waiters::Matcher::Status);
+    assert_eq!(waiter.acceptors[0].expected, 200);
+    assert_eq!(
+        def.waiters["FunctionActive"].acceptors[0]
+            .argument
+            .as_deref(),
+        Some("State")
+    );
+
+    Ok(())
+}
+
+#[test]
+fn merges_examples() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let mut def = BotocoreModel::from_path("test-data/lambda/2015-03-31/service-2.json")?;
+    def.load_examples("test-data/lambda/2015-03-31/examples-1.json")?;
+    let example = &def.examples["AddPermission"][0];
+    assert_eq!(example.id, "add-permission-1474651469455");
+    assert_eq!(
+        example.input.as_ref().map(|input| &input["Principal"]),
+        Some(&serde_json::Value::from("s3.amazonaws.com"))
+    );
+
+    Ok(())
+}
+
+#[test]
+fn loads_smoke_tests() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let mut def = BotocoreModel::from_path("test-data/lambda/2015-03-31/service-2.json")?;
+    def.load_smoke_tests("test-data/lambda/2015-03-31/smoke.json")?;
+    let smoke_tests = def.smoke_tests.expect("smoke tests were loaded");
+    assert_eq!(smoke_tests.default_region, "us-west-2");
+    assert_eq!(smoke_tests.test_cases[1].operation_name, "Invoke");
+    assert!(smoke_tests.test_cases[1].error_expected_from_service);
+
+    Ok(())
+}
+
+#[test]
+fn parses_json_protocol_metadata() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let metadata = serde_json::from_str::<Metadata>(
+        r#"{
+            "apiVersion": "2012-08-10",
+            "endpointPrefix": "dynamodb",
+            "jsonVersion": "1.0",
+            "protocol": "json",
+            "serviceFullName": "Amazon DynamoDB",
+            "serviceId": "DynamoDB",
+            "signatureVersion": "v4",
+            "targetPrefix": "DynamoDB_20120810"
+        }"#,
+    )?;
+    assert_eq!(metadata.protocol, Protocol::Json);
+    assert_eq!(metadata.json_version.as_deref(), Some("1.0"));
+    assert_eq!(metadata.target_prefix.as_deref(), Some("DynamoDB_20120810"));
+
+    Ok(())
+}
@@ -0,0 +1,327 @@
+use super::{Markdown, ShapeReference, Signature};
+use crate::Err;
+use http::{method::Method, StatusCode};
+use serde::{Deserialize, Serialize};
+use std::{convert::TryFrom, fmt, str::FromStr};
+
+#[derive(Debug, PartialEq, Deserialize, Clone)]
+pub struct Operation {
+    pub name: String,
+    pub http: HttpBindings,
+    pub input: ShapeReference,
+    pub output: Option<ShapeReference>,
+    pub errors: Vec<ShapeReference>,
+    pub documentation: Markdown,
+    #[serde(default)]
+    pub deprecated: bool,
+    #[serde(rename = "deprecatedMessage")]
+    pub deprecated_message: Option<String>,
+    pub endpoint: Option<Endpoint>,
+    // Overrides how requests for this operation are signed. `None` means they're signed
+    // according to `Metadata::signature_version`.
+    #[serde(rename = "authtype")]
+    pub auth_type: Option<AuthType>,
+    // Requests must be sent to an endpoint discovered through the service's
+    // `endpoint_operation`.
+    #[serde(rename = "endpointdiscovery")]
+    pub endpoint_discovery: Option<EndpointDiscovery>,
+    // Set on the operation that discovers endpoints, e.g. `DescribeEndpoints`.
+    #[serde(rename = "endpointoperation", default)]
+    pub endpoint_operation: bool,
+    // Requests must carry a `Content-MD5` header.
+    #[serde(rename = "httpChecksumRequired", default)]
+    pub http_checksum_required: bool,
+    #[serde(rename = "httpChecksum")]
+    pub http_checksum: Option<HttpChecksum>,
+    #[serde(rename = "requestcompression")]
+    pub request_compression: Option<RequestCompression>,
+}
+
+impl Operation {
+    /// How requests for this operation are signed, given the service's default
+    /// `Metadata::signature_version`. An `authtype` on the operation takes precedence.
+    pub fn signature(&self, default: Signature) -> Signature {
+        match self.auth_type {
+            Some(AuthType::None) => Signature::None,
+            Some(AuthType::Bearer) => Signature::Bearer,
+            // Still signed, just without the body; see `AuthType::V4UnsignedBody`.
+            Some(AuthType::V4UnsignedBody) | None => default,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Deserialize, Clone, Default)]
+pub struct EndpointDiscovery {
+    // When unset, clients may fall back to the regular endpoint if discovery fails.
+    #[serde(default)]
+    pub required: bool,
+}
+
+/// Flexible checksum support: which algorithms a request can be checksummed with, and
+/// whether response checksums are validated.
+#[derive(Debug, PartialEq, Eq, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct HttpChecksum {
+    // Requests must carry a checksum even if the caller didn't pick an algorithm.
+    #[serde(default)]
+    pub request_checksum_required: bool,
+    // The input member the caller picks a checksum algorithm with.
+    pub request_algorithm_member: Option<String>,
+    // The input member the caller opts in to response validation with.
+    pub request_validation_mode_member: Option<String>,
+    // The algorithms response checksums may use, in order of preference.
+    #[serde(default)]
+    pub response_algorithms: Vec<ChecksumAlgorithm>,
+}
+
+/// The encodings, e.g. `gzip`, requests' bodies can be compressed with, in order of
+/// preference.
+#[derive(Debug, PartialEq, Eq, Deserialize, Clone, Default)]
+pub struct RequestCompression {
+    pub encodings: Vec<String>,
+}
+
+impl RequestCompression {
+    pub fn supports(&self, encoding: &str) -> bool {
+        self.encodings
+            .iter()
+            .any(|supported| supported.eq_ignore_ascii_case(encoding))
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Deserialize, Clone, Copy)]
+pub enum ChecksumAlgorithm {
+    #[serde(rename = "CRC32")]
+    Crc32,
+    #[serde(rename = "CRC32C")]
+    Crc32c,
+    #[serde(rename = "SHA1")]
+    Sha1,
+    #[serde(rename = "SHA256")]
+    Sha256,
+}
+
+#[derive(Debug, PartialEq, Eq, Deserialize, Clone, Copy)]
+pub enum AuthType {
+    // Requests aren't signed at all, e.g. `sts:AssumeRoleWithWebIdentity`.
+    #[serde(rename = "none")]
+    None,
+    // Requests are signed with SigV4, but the payload is left out of the signature.
+    #[serde(rename = "v4-unsigned-body")]
+    V4UnsignedBody,
+    // Requests carry a bearer token rather than a signature.
+    #[serde(rename = "bearer")]
+    Bearer,
+}
+
+#[derive(Debug, PartialEq, Eq, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Endpoint {
+    // Prepended to the resolved hostname. `{Label}`s are filled in from input members
+    // marked with `hostLabel`.
+    pub host_prefix: String,
+}
+
+impl Endpoint {
+    /// The names of the `{Label}`s in the host prefix, in order of appearance.
+    pub fn labels(&self) -> Vec<&str> {
+        self.host_prefix
+            .split('{')
+            .skip(1)
+            .filter_map(|s| s.split('}').next())
+            .collect()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct HttpBindingsTemp {
+    method: String,
+    request_uri: String,
+    response_code: Option<u16>,
+}
+
+#[derive(Debug, PartialEq, Eq, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+#[serde(try_from = "HttpBindingsTemp")]
+pub struct HttpBindings {
+    pub method: Method,
+    pub request_uri: UriTemplate,
+    pub response_code: Option<StatusCode>,
+}
+
+/// An operation's `requestUri`, e.g. `/{Bucket}/{Key+}?uploads`: a path of literal text and
+/// `{Label}`s, which are filled in from input members bound to the URI, and any literal query.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct UriTemplate {
+    pub path: Vec<UriSegment>,
+    // What follows the `?`, e.g. `uploads`, sent before any query parameters bound to input
+    // members.
+    pub query: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum UriSegment {
+    Literal(String),
+    // A greedy label, like `{Key+}`, can span path segments, so its `/`s aren't
+    // percent-encoded.
+    Label { name: String, greedy: bool },
+}
+
+impl UriTemplate {
+    /// The names of the labels in the path, in order of appearance.
+    pub fn labels(&self) -> Vec<&str> {
+        self.path
+            .iter()
+            .filter_map(|segment| match segment {
+                UriSegment::Label { name, .. } => Some(name.as_str()),
+                UriSegment::Literal(_) => None,
+            })
+            .collect()
+    }
+}
+
+impl FromStr for UriTemplate {
+    type Err = Err;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (mut rest, query) = match s.find('?') {
+            Some(at) => (&s[..at], Some(s[at + 1..].to_string())),
+            None => (s, None),
+        };
+        let mut path = Vec::new();
+        while let Some(start) = rest.find('{') {
+            if start > 0 {
+                path.push(UriSegment::Literal(rest[..start].to_string()));
+            }
+            let len = rest[start..]
+                .find('}')
+                .ok_or_else(|| format!("`{}` has an unclosed label", s))?;
+            let label = &rest[start + 1..start + len];
+            let (name, greedy) = match label.strip_suffix('+') {
+                Some(name) => (name, true),
+                None => (label, false),
+            };
+            if name.is_empty() || name.contains(&['{', '/'][..]) {
+                return Err(format!("`{}` has an invalid label `{{{}}}`", s, label).into());
+            }
+            path.push(UriSegment::Label {
+                name: name.to_string(),
+                greedy,
+            });
+            rest = &rest[start + len + 1..];
+        }
+        if rest.contains('}') {
+            return Err(format!("`{}` has an unopened label", s).into());
+        }
+        if !rest.is_empty() {
+            path.push(UriSegment::Literal(rest.to_string()));
+        }
+        Ok(UriTemplate { path, query })
+    }
+}
+
+impl fmt::Display for UriTemplate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for segment in &self.path {
+            match segment {
+                UriSegment::Literal(text) => f.write_str(text)?,
+                UriSegment::Label { name, greedy } => {
+                    write!(f, "{{{}{}}}", name, if *greedy { "+" } else { "" })?
+                }
+            }
+        }
+        if let Some(query) = &self.query {
+            write!(f, "?{}", query)?;
+        }
+        Ok(())
+    }
+}
+
+impl TryFrom<HttpBindingsTemp> for HttpBindings {
+    type Error = Err;
+    fn try_from(value: HttpBindingsTemp) -> Result<Self, Self::Error> {
+        let code: Option<StatusCode> = match value.response_code {
+            Some(v) => Some(StatusCode::from_u16(v)?),
+            None => None,
+        };
+
+        let method = Method::from_bytes(value.method.as_bytes())?;
+        let http = HttpBindings {
+            method,
+            request_uri: value.request_uri.parse()?,
+            response_code: code,
+        };
+
+        Ok(http)
+    }
+}
+
+#[test]
+fn parses_host_prefix_labels() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let endpoint =
+        serde_json::from_str::<Endpoint>(r#"{"hostPrefix": "{AccountId}.{Region}.data-"}"#)?;
+    assert_eq!(endpoint.labels(), vec!["AccountId", "Region"]);
+
+    Ok(())
+}
+
+#[test]
+fn parses_uri_templates() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let template = "/{Bucket}/{Key+}?uploads".parse::<UriTemplate>()?;
+    assert_eq!(
+        template.path,
+        vec![
+            UriSegment::Literal("/".to_string()),
+            UriSegment::Label {
+                name: "Bucket".to_string(),
+                greedy: false
+            },
+            UriSegment::Literal("/".to_string()),
+            UriSegment::Label {
+                name: "Key".to_string(),
+                greedy: true
+            },
+        ]
+    );
+    assert_eq!(template.query.as_deref(), Some("uploads"));
+    assert_eq!(template.labels(), vec!["Bucket", "Key"]);
+    assert_eq!(template.to_string(), "/{Bucket}/{Key+}?uploads");
+
+    let template = "/2015-03-31/functions/{FunctionName}/versions".parse::<UriTemplate>()?;
+    assert_eq!(template.labels(), vec!["FunctionName"]);
+    assert_eq!(template.query, None);
+
+    assert!("/{Bucket".parse::<UriTemplate>().is_err());
+    assert!("/Bucket}".parse::<UriTemplate>().is_err());
+    assert!("/{}".parse::<UriTemplate>().is_err());
+
+    Ok(())
+}
+
+#[test]
+fn parses_http_checksum() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let checksum = serde_json::from_str::<HttpChecksum>(
+        r#"{
+            "requestAlgorithmMember": "ChecksumAlgorithm",
+            "requestValidationModeMember": "ChecksumMode",
+            "responseAlgorithms": ["CRC32", "CRC32C", "SHA256", "SHA1"]
+        }"#,
+    )?;
+    assert!(!checksum.request_checksum_required);
+    assert_eq!(
+        checksum.request_algorithm_member.as_deref(),
+        Some("ChecksumAlgorithm")
+    );
+    assert_eq!(checksum.response_algorithms[1], ChecksumAlgorithm::Crc32c);
+
+    Ok(())
+}
+
+#[test]
+fn parses_request_compression() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let compression = serde_json::from_str::<RequestCompression>(r#"{"encodings": ["gzip"]}"#)?;
+    assert!(compression.supports("gzip"));
+    assert!(!compression.supports("br"));
+
+    Ok(())
+}
@@ -0,0 +1,51 @@
+use serde::{Deserialize, Deserializer};
+use std::collections::HashMap;
+
+/// The contents of a `paginators-1.json` file.
+#[derive(Debug, PartialEq, Deserialize)]
+pub struct Paginators {
+    // Keyed by operation name.
+    pub pagination: HashMap<String, Paginator>,
+}
+
+/// Describes how to page through the results of a single operation.
+///
+/// Tokens and keys are JMESPath expressions evaluated against the operation's input or
+/// output, though in practice most are plain member names (`NextMarker`) or simple paths
+/// (`Contents[-1].Key`). Operations with composite tokens list several expressions.
+#[derive(Debug, PartialEq, Eq, Deserialize, Clone)]
+pub struct Paginator {
+    // The input member that the next page's token is written to.
+    #[serde(deserialize_with = "one_or_many")]
+    pub input_token: Vec<String>,
+    // The output expression that yields the token for the next page.
+    #[serde(deserialize_with = "one_or_many")]
+    pub output_token: Vec<String>,
+    // The input member that limits the page size.
+    pub limit_key: Option<String>,
+    // The output expression that holds the items on the page.
+    #[serde(default, deserialize_with = "one_or_many")]
+    pub result_key: Vec<String>,
+    // An output expression that is true while pages remain, for operations with one.
+    pub more_results: Option<String>,
+    // Output expressions that aren't aggregated across pages.
+    #[serde(default)]
+    pub non_aggregate_keys: Vec<String>,
+}
+
+fn one_or_many<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        One(String),
+        Many(Vec<String>),
+    }
+
+    match OneOrMany::deserialize(deserializer)? {
+        OneOrMany::One(s) => Ok(vec![s]),
+        OneOrMany::Many(v) => Ok(v),
+    }
+}
@@ -0,0 +1,730 @@
+use super::{Markdown, ShapeId};
+use crate::Err;
+use http::StatusCode;
+use serde::{de, Deserialize, Deserializer};
+use serde_json::Value;
+use std::{collections::HashMap, convert::TryFrom};
+
+#[derive(Debug, PartialEq, Deserialize, Clone)]
+#[serde(tag = "type", remote = "Self")]
+pub enum Shape {
+    #[serde(rename = "structure")]
+    Structure {
+        members: HashMap<String, ShapeMember>,
+        documentation: Option<String>,
+        required: Option<Vec<String>>,
+        #[serde(default)]
+        exception: bool,
+        // Set on exceptions caused by the service rather than the caller.
+        #[serde(default)]
+        fault: bool,
+        error: Option<ErrorInfo>,
+        // Set on errors that are safe to retry.
+        retryable: Option<Retryable>,
+        // The member bound to the HTTP body in REST protocols.
+        payload: Option<String>,
+        #[serde(rename = "xmlNamespace")]
+        xml_namespace: Option<XmlNamespace>,
+        // Set on the structures that make up the events of an event stream.
+        #[serde(default)]
+        event: bool,
+        #[serde(flatten)]
+        traits: Traits,
+    },
+    // Botocore spells these as `"type": "structure"` with `"union": true`; see the
+    // `Deserialize` impl below.
+    #[serde(rename = "union")]
+    Union {
+        members: HashMap<String, ShapeMember>,
+        documentation: Option<String>,
+        #[serde(flatten)]
+        traits: Traits,
+    },
+    // A stream of events, each member being one kind of event. Like unions, these are
+    // structures with `"eventstream": true`.
+    #[serde(rename = "eventstream")]
+    EventStream {
+        members: HashMap<String, ShapeMember>,
+        documentation: Option<String>,
+        #[serde(flatten)]
+        traits: Traits,
+    },
+    #[serde(rename = "string")]
+    String {
+        // The closed set of values, when the string is really an enum.
+        #[serde(rename = "enum")]
+        enum_values: Option<Vec<String>>,
+        #[serde(flatten)]
+        constraints: Constraints,
+        #[serde(flatten)]
+        traits: Traits,
+    },
+    #[serde(rename = "map")]
+    Map {
+        key: ShapeMember,
+        value: ShapeMember,
+        #[serde(default)]
+        flattened: bool,
+        #[serde(flatten)]
+        constraints: Constraints,
+        #[serde(flatten)]
+        traits: Traits,
+    },
+    #[serde(rename = "list")]
+    List {
+        member: ShapeMember,
+        #[serde(default)]
+        flattened: bool,
+        #[serde(flatten)]
+        constraints: Constraints,
+        #[serde(flatten)]
+        traits: Traits,
+    },
+    // i32
+    #[serde(rename = "integer")]
+    Integer {
+        #[serde(flatten)]
+        constraints: Constraints,
+        #[serde(flatten)]
+        traits: Traits,
+    },
+    // i64
+    #[serde(rename = "long")]
+    Long {
+        #[serde(flatten)]
+        constraints: Constraints,
+        #[serde(flatten)]
+        traits: Traits,
+    },
+    #[serde(rename = "double")]
+    Double {
+        #[serde(flatten)]
+        constraints: Constraints<f64>,
+        #[serde(flatten)]
+        traits: Traits,
+    },
+    // f32
+    #[serde(rename = "float")]
+    Float {
+        #[serde(flatten)]
+        constraints: Constraints<f32>,
+        #[serde(flatten)]
+        traits: Traits,
+    },
+    #[serde(rename = "blob")]
+    Blob {
+        #[serde(flatten)]
+        constraints: Constraints,
+        #[serde(default)]
+        streaming: bool,
+        #[serde(flatten)]
+        traits: Traits,
+    },
+    #[serde(rename = "boolean")]
+    Boolean {
+        #[serde(flatten)]
+        traits: Traits,
+    },
+    // Untyped JSON, from Smithy models converted to botocore's format.
+    #[serde(rename = "document")]
+    Document {
+        #[serde(flatten)]
+        traits: Traits,
+    },
+    #[serde(rename = "timestamp")]
+    Timestamp {
+        #[serde(rename = "timestampFormat")]
+        timestamp_format: Option<TimestampFormat>,
+        #[serde(flatten)]
+        traits: Traits,
+    },
+}
+
+impl<'de> Deserialize<'de> for Shape {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let mut value = Value::deserialize(deserializer)?;
+        if let Some(shape) = value.as_object_mut() {
+            if shape.get("union") == Some(&Value::Bool(true)) {
+                shape.insert("type".to_string(), Value::from("union"));
+            } else if shape.get("eventstream") == Some(&Value::Bool(true)) {
+                shape.insert("type".to_string(), Value::from("eventstream"));
+            }
+        }
+        Shape::deserialize(value).map_err(de::Error::custom)
+    }
+}
+
+impl Shape {
+    /// The Rust type a shape maps to when it has a direct equivalent, or `None` for shapes
+    /// that need a generated type.
+    pub fn rust_primitive(&self) -> Option<&'static str> {
+        match self {
+            Shape::Integer { .. } => Some("i32"),
+            Shape::Long { .. } => Some("i64"),
+            Shape::Double { .. } => Some("f64"),
+            Shape::Float { .. } => Some("f32"),
+            Shape::Boolean { .. } => Some("bool"),
+            Shape::Document { .. } => Some("serde_json::Value"),
+            _ => None,
+        }
+    }
+
+    /// Where each member of a structure goes in the HTTP message, sorted by member name.
+    pub fn member_locations(&self) -> Vec<(String, Location)> {
+        let (members, payload) = match self {
+            Shape::Structure {
+                members, payload, ..
+            } => (members, payload.as_deref()),
+            _ => return vec![],
+        };
+        let mut locations = members
+            .iter()
+            .map(|(name, member)| {
+                let location = if Some(name.as_str()) == payload {
+                    Location::Payload
+                } else {
+                    member.location(name)
+                };
+                (name.clone(), location)
+            })
+            .collect::<Vec<_>>();
+        locations.sort_by(|a, b| a.0.cmp(&b.0));
+        locations
+    }
+
+    /// The retry behavior of an error shape, if it's retryable.
+    pub fn retryable(&self) -> Option<&Retryable> {
+        match self {
+            Shape::Structure { retryable, .. } => retryable.as_ref(),
+            _ => None,
+        }
+    }
+
+    /// The name of the member bound to the HTTP body, if this is a structure with one.
+    pub fn payload(&self) -> Option<&str> {
+        match self {
+            Shape::Structure { payload, .. } => payload.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// The traits common to every kind of shape.
+    pub fn traits(&self) -> &Traits {
+        match self {
+            Shape::Structure { traits, .. }
+            | Shape::Union { traits, .. }
+            | Shape::EventStream { traits, .. }
+            | Shape::String { traits, .. }
+            | Shape::Map { traits, .. }
+            | Shape::List { traits, .. }
+            | Shape::Integer { traits, .. }
+            | Shape::Long { traits, .. }
+            | Shape::Double { traits, .. }
+            | Shape::Float { traits, .. }
+            | Shape::Blob { traits, .. }
+            | Shape::Boolean { traits, .. }
+            | Shape::Document { traits, .. }
+            | Shape::Timestamp { traits, .. } => traits,
+        }
+    }
+
+    /// Whether this shape is an exception that operations can return.
+    pub fn is_error(&self) -> bool {
+        match self {
+            Shape::Structure {
+                exception, error, ..
+            } => *exception || error.is_some(),
+            _ => false,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Deserialize, Clone, Default)]
+pub struct Retryable {
+    // The error means the caller is being throttled, so retries should back off.
+    #[serde(default)]
+    pub throttling: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ErrorInfoTemp {
+    code: Option<String>,
+    http_status_code: Option<u16>,
+    #[serde(default)]
+    sender_fault: bool,
+}
+
+#[derive(Debug, PartialEq, Eq, Deserialize, Clone)]
+#[serde(try_from = "ErrorInfoTemp")]
+pub struct ErrorInfo {
+    // The wire error code, when it differs from the shape name.
+    pub code: Option<String>,
+    pub http_status_code: Option<StatusCode>,
+    pub sender_fault: bool,
+}
+
+impl TryFrom<ErrorInfoTemp> for ErrorInfo {
+    type Error = Err;
+    fn try_from(value: ErrorInfoTemp) -> Result<Self, Self::Error> {
+        let http_status_code = match value.http_status_code {
+            Some(v) => Some(StatusCode::from_u16(v)?),
+            None => None,
+        };
+
+        Ok(ErrorInfo {
+            code: value.code,
+            http_status_code,
+            sender_fault: value.sender_fault,
+        })
+    }
+}
+
+/// Length limits for strings, blobs, lists, and maps, or the value range for numbers.
+#[derive(Debug, PartialEq, Deserialize, Clone, Default)]
+pub struct Constraints<T = i64> {
+    pub min: Option<T>,
+    pub max: Option<T>,
+    // A regular expression string values must match.
+    pub pattern: Option<String>,
+}
+
+/// Traits that can be applied to both shapes and members.
+#[derive(Debug, PartialEq, Eq, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct Traits {
+    // Values must be kept out of logs and `Debug` output.
+    #[serde(default)]
+    pub sensitive: bool,
+    #[serde(default)]
+    pub deprecated: bool,
+    pub deprecated_message: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Eq, Deserialize, Clone, Copy)]
+pub enum TimestampFormat {
+    #[serde(rename = "iso8601")]
+    Iso8601,
+    #[serde(rename = "rfc822")]
+    Rfc822,
+    #[serde(rename = "unixTimestamp")]
+    UnixTimestamp,
+}
+
+#[derive(Debug, PartialEq, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ShapeMember {
+    #[serde(flatten)]
+    pub shape: ShapeReference,
+    pub documentation: Option<Markdown>,
+    // Where the member is bound in the HTTP request/response. `None` means the body.
+    pub location: Option<LocationType>,
+    // The name to use on the wire, whether that's a header, a query parameter, or an
+    // XML/JSON element. Defaults to the member name.
+    pub location_name: Option<String>,
+    pub streaming: Option<bool>,
+    // Streaming members that must be sent with a `Content-Length`, so can't be chunked.
+    #[serde(default)]
+    pub requires_length: bool,
+    pub timestamp_format: Option<TimestampFormat>,
+    // Clients fill these in with a fresh UUID when the caller leaves them unset.
+    #[serde(default)]
+    pub idempotency_token: bool,
+    #[serde(default)]
+    pub host_label: bool,
+    // On members of an event: bound to the event's payload, or to one of its headers.
+    #[serde(rename = "eventpayload", default)]
+    pub event_payload: bool,
+    #[serde(rename = "eventheader", default)]
+    pub event_header: bool,
+    // The string member holds arbitrary JSON.
+    #[serde(rename = "jsonvalue", default)]
+    pub json_value: bool,
+    // Marks a recursion point for aggregates, or a nullable value for numbers and booleans.
+    #[serde(rename = "box", default)]
+    pub boxed: bool,
+    // Identifies the resource discovered endpoints are cached for.
+    #[serde(rename = "endpointdiscoveryid", default)]
+    pub endpoint_discovery_id: bool,
+    // Serialize as an attribute of the enclosing XML element rather than a child element.
+    #[serde(default)]
+    pub xml_attribute: bool,
+    // Serialize list/map entries directly into the parent rather than in a wrapper element.
+    #[serde(default)]
+    pub flattened: bool,
+    pub xml_namespace: Option<XmlNamespace>,
+    // EC2's name for the member's query parameter, which takes precedence over its
+    // `locationName`.
+    pub query_name: Option<String>,
+    #[serde(flatten)]
+    pub traits: Traits,
+}
+
+// The raw `location` of a member, before it's combined with its `locationName`.
+#[derive(Debug, PartialEq, Eq, Deserialize, Clone, Copy)]
+pub enum LocationType {
+    #[serde(rename = "uri")]
+    Uri,
+    #[serde(rename = "querystring")]
+    QueryString,
+    #[serde(rename = "header")]
+    Header,
+    #[serde(rename = "headers")]
+    Headers,
+    #[serde(rename = "statusCode")]
+    StatusCode,
+}
+
+/// Where a member of an operation's input or output goes in the HTTP message.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Location {
+    // Substituted for `{Label}` in the request URI.
+    Uri(String),
+    QueryString(String),
+    Header(String),
+    // A `jsonvalue` member, serialized as JSON and base64-encoded into the header.
+    JsonHeader(String),
+    // Each entry of a map member becomes a header named `prefix` followed by the key,
+    // like S3's `x-amz-meta-*`.
+    Headers { prefix: String },
+    // The HTTP status code of the response.
+    StatusCode,
+    // The member is the entire HTTP body.
+    Payload,
+    // The member is serialized into the body under the given name.
+    Body(String),
+}
+
+impl ShapeMember {
+    /// Where this member, named `name` in its structure, goes in the HTTP message. This
+    /// doesn't know about payloads; see `Shape::member_locations`.
+    pub fn location(&self, name: &str) -> Location {
+        let wire_name = self.location_name.as_deref().unwrap_or(name).to_string();
+        match self.location {
+            Some(LocationType::Uri) => Location::Uri(wire_name),
+            Some(LocationType::QueryString) => Location::QueryString(wire_name),
+            Some(LocationType::Header) if self.json_value => Location::JsonHeader(wire_name),
+            Some(LocationType::Header) => Location::Header(wire_name),
+            Some(LocationType::Headers) => Location::Headers { prefix: wire_name },
+            Some(LocationType::StatusCode) => Location::StatusCode,
+            None => Location::Body(wire_name),
+        }
+    }
+
+    /// The timestamp format this member serializes with. A format on the member
+    /// overrides one on the targeted shape; `None` means the protocol default applies.
+    pub fn timestamp_format(&self, target: &Shape) -> Option<TimestampFormat> {
+        match target {
+            Shape::Timestamp {
+                timestamp_format, ..
+            } => self.timestamp_format.or(*timestamp_format),
+            _ => None,
+        }
+    }
+
+    /// Wraps `ty`, the Rust type of `target`, in whatever this member needs: `Box` at
+    /// recursion points, and `Option` when the member can be left unset.
+    pub fn field_type(&self, target: &Shape, ty: &str, required: bool) -> String {
+        let primitive = target.rust_primitive().is_some();
+        let ty = if self.boxed && !primitive {
+            format!("Box<{}>", ty)
+        } else {
+            ty.to_string()
+        };
+        if !required || (self.boxed && primitive) {
+            format!("Option<{}>", ty)
+        } else {
+            ty
+        }
+    }
+
+    /// Whether this member is streamed rather than buffered, either because it's marked
+    /// as such or because it targets a streaming blob.
+    pub fn is_streaming(&self, target: &Shape) -> bool {
+        self.streaming.unwrap_or(false)
+            || match target {
+                Shape::Blob { streaming, .. } => *streaming,
+                _ => false,
+            }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Deserialize, Clone)]
+pub struct XmlNamespace {
+    pub uri: String,
+    pub prefix: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Eq, Deserialize, Clone)]
+pub struct ShapeReference {
+    pub shape: ShapeId,
+    // The query protocols wrap an operation's output in an element of this name, e.g.
+    // `ListQueuesResult`, inside the response's root element.
+    #[serde(rename = "resultWrapper")]
+    pub result_wrapper: Option<String>,
+}
+
+#[test]
+fn parses_float_shapes() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let shape = r#"{"type": "float", "min": 0.0, "max": 100.0}"#;
+    let shape = serde_json::from_str::<Shape>(shape)?;
+    assert_eq!(
+        shape,
+        Shape::Float {
+            constraints: Constraints {
+                min: Some(0.0),
+                max: Some(100.0),
+                pattern: None,
+            },
+            traits: Traits::default(),
+        }
+    );
+    assert_eq!(shape.rust_primitive(), Some("f32"));
+
+    Ok(())
+}
+
+#[test]
+fn parses_string_enums() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let def = std::fs::read_to_string("test-data/lambda/2015-03-31/service-2.json")?;
+    let def = serde_json::from_str::<super::BotocoreModel>(&def)?;
+    match &def.shapes[&ShapeId::from("TracingMode")] {
+        Shape::String { enum_values, .. } => assert_eq!(
+            enum_values.as_deref(),
+            Some(&["Active".to_string(), "PassThrough".to_string()][..])
+        ),
+        other => panic!("expected a string shape, got {:?}", other),
+    }
+    match &def.shapes[&ShapeId::from("EnvironmentVariableName")] {
+        Shape::String {
+            constraints,
+            traits,
+            ..
+        } => {
+            assert_eq!(
+                constraints.pattern.as_deref(),
+                Some("[a-zA-Z]([a-zA-Z0-9_])+")
+            );
+            assert!(traits.sensitive);
+        }
+        other => panic!("expected a string shape, got {:?}", other),
+    }
+
+    Ok(())
+}
+
+#[test]
+fn parses_error_shapes() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let def = std::fs::read_to_string("test-data/lambda/2015-03-31/service-2.json")?;
+    let def = serde_json::from_str::<super::BotocoreModel>(&def)?;
+    let shape = &def.shapes[&ShapeId::from("EC2ThrottledException")];
+    assert!(shape.is_error());
+    match shape {
+        Shape::Structure { error, .. } => assert_eq!(
+            error.as_ref().and_then(|e| e.http_status_code),
+            Some(StatusCode::BAD_GATEWAY)
+        ),
+        other => panic!("expected a structure shape, got {:?}", other),
+    }
+    assert!(!def.shapes[&ShapeId::from("FunctionConfiguration")].is_error());
+
+    Ok(())
+}
+
+#[test]
+fn parses_union_shapes() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let shape = r#"{
+        "type": "structure",
+        "members": {
+            "S": { "shape": "StringAttributeValue" },
+            "N": { "shape": "NumberAttributeValue" }
+        },
+        "union": true
+    }"#;
+    match serde_json::from_str::<Shape>(shape)? {
+        Shape::Union { members, .. } => assert_eq!(members.len(), 2),
+        other => panic!("expected a union shape, got {:?}", other),
+    }
+
+    Ok(())
+}
+
+#[test]
+fn member_timestamp_format_overrides_shape() -> Result<(), Box<dyn std::error::Error + Send + Sync>>
+{
+    let target = serde_json::from_str::<Shape>(
+        r#"{"type": "timestamp", "timestampFormat": "unixTimestamp"}"#,
+    )?;
+    let member = serde_json::from_str::<ShapeMember>(
+        r#"{"shape": "Timestamp", "location": "header", "locationName": "Expires", "timestampFormat": "rfc822"}"#,
+    )?;
+    assert_eq!(
+        member.timestamp_format(&target),
+        Some(TimestampFormat::Rfc822)
+    );
+    let member = serde_json::from_str::<ShapeMember>(r#"{"shape": "Timestamp"}"#)?;
+    assert_eq!(
+        member.timestamp_format(&target),
+        Some(TimestampFormat::UnixTimestamp)
+    );
+
+    Ok(())
+}
+
+#[test]
+fn parses_sensitive_and_deprecated_traits() -> Result<(), Box<dyn std::error::Error + Send + Sync>>
+{
+    let def = std::fs::read_to_string("test-data/lambda/2015-03-31/service-2.json")?;
+    let def = serde_json::from_str::<super::BotocoreModel>(&def)?;
+    assert!(
+        def.shapes[&ShapeId::from("SensitiveString")]
+            .traits()
+            .sensitive
+    );
+    assert!(
+        def.shapes[&ShapeId::from("InvokeAsyncRequest")]
+            .traits()
+            .deprecated
+    );
+    assert!(
+        !def.shapes[&ShapeId::from("InvocationRequest")]
+            .traits()
+            .deprecated
+    );
+    assert!(def.operations["InvokeAsync"].deprecated);
+
+    let member = serde_json::from_str::<ShapeMember>(
+        r#"{"shape": "String", "deprecated": true, "deprecatedMessage": "Use Name instead"}"#,
+    )?;
+    assert_eq!(
+        member.traits.deprecated_message.as_deref(),
+        Some("Use Name instead")
+    );
+
+    Ok(())
+}
+
+#[test]
+fn parses_constraints() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let def = std::fs::read_to_string("test-data/lambda/2015-03-31/service-2.json")?;
+    let def = serde_json::from_str::<super::BotocoreModel>(&def)?;
+    match &def.shapes[&ShapeId::from("BatchSize")] {
+        Shape::Integer { constraints, .. } => {
+            assert_eq!((constraints.min, constraints.max), (Some(1), Some(10000)))
+        }
+        other => panic!("expected an integer shape, got {:?}", other),
+    }
+    match &def.shapes[&ShapeId::from("FunctionName")] {
+        Shape::String { constraints, .. } => {
+            assert_eq!((constraints.min, constraints.max), (Some(1), Some(140)))
+        }
+        other => panic!("expected a string shape, got {:?}", other),
+    }
+
+    Ok(())
+}
+
+#[test]
+fn parses_xml_traits() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let shape = r#"{
+        "type": "list",
+        "member": { "shape": "Tag", "locationName": "Tag" },
+        "flattened": true
+    }"#;
+    match serde_json::from_str::<Shape>(shape)? {
+        Shape::List {
+            member, flattened, ..
+        } => {
+            assert_eq!(member.location_name.as_deref(), Some("Tag"));
+            assert!(flattened);
+        }
+        other => panic!("expected a list shape, got {:?}", other),
+    }
+    let member = serde_json::from_str::<ShapeMember>(
+        r#"{"shape": "Type", "locationName": "xsi:type", "xmlAttribute": true}"#,
+    )?;
+    assert!(member.xml_attribute);
+    assert_eq!(
+        member.location("Type"),
+        Location::Body("xsi:type".to_string())
+    );
+
+    Ok(())
+}
+
+#[test]
+fn parses_retryable_errors() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let shape = serde_json::from_str::<Shape>(
+        r#"{
+            "type": "structure",
+            "members": { "message": { "shape": "String" } },
+            "error": { "code": "Throttling", "httpStatusCode": 400, "senderFault": true },
+            "exception": true,
+            "retryable": { "throttling": true }
+        }"#,
+    )?;
+    assert_eq!(shape.retryable(), Some(&Retryable { throttling: true }));
+
+    Ok(())
+}
+
+#[test]
+fn boxes_recursive_members() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let member = serde_json::from_str::<ShapeMember>(r#"{"shape": "Filter", "box": true}"#)?;
+    let structure = serde_json::from_str::<Shape>(r#"{"type": "structure", "members": {}}"#)?;
+    let integer = serde_json::from_str::<Shape>(r#"{"type": "integer"}"#)?;
+    assert_eq!(member.field_type(&structure, "Filter", true), "Box<Filter>");
+    assert_eq!(
+        member.field_type(&structure, "Filter", false),
+        "Option<Box<Filter>>"
+    );
+    assert_eq!(member.field_type(&integer, "i32", true), "Option<i32>");
+
+    Ok(())
+}
+
+#[test]
+fn parses_event_streams() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let stream = serde_json::from_str::<Shape>(
+        r#"{
+            "type": "structure",
+            "members": {
+                "Records": { "shape": "RecordsEvent" },
+                "End": { "shape": "EndEvent" }
+            },
+            "eventstream": true
+        }"#,
+    )?;
+    match stream {
+        Shape::EventStream { members, .. } => assert!(members.contains_key("Records")),
+        other => panic!("expected an event stream shape, got {:?}", other),
+    }
+    let event = serde_json::from_str::<Shape>(
+        r#"{
+            "type": "structure",
+            "members": { "Payload": { "shape": "Body", "eventpayload": true } },
+            "payload": "Payload",
+            "event": true
+        }"#,
+    )?;
+    match event {
+        Shape::Structure { event, members, .. } => {
+            assert!(event);
+            assert!(members["Payload"].event_payload);
+        }
+        other => panic!("expected a structure shape, got {:?}", other),
+    }
+
+    Ok(())
+}
+
+#[test]
+fn parses_document_shapes() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let shape = serde_json::from_str::<Shape>(r#"{"type": "document"}"#)?;
+    assert_eq!(shape.rust_primitive(), Some("serde_json::Value"));
+
+    Ok(())
+}
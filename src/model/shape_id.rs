@@ -0,0 +1,98 @@
+use serde::{Deserialize, Deserializer};
+use std::{
+    cmp::Ordering,
+    collections::HashMap,
+    fmt,
+    sync::{Mutex, OnceLock},
+};
+
+/// The name of a shape, interned so that copies are free and comparisons are O(1).
+///
+/// Every distinct name is leaked once and lives for the rest of the program, which is fine
+/// for a code generator: there are only so many shapes across every AWS model.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ShapeId(u32);
+
+#[derive(Default)]
+struct Interner {
+    ids: HashMap<&'static str, u32>,
+    names: Vec<&'static str>,
+}
+
+fn interner() -> &'static Mutex<Interner> {
+    static INTERNER: OnceLock<Mutex<Interner>> = OnceLock::new();
+    INTERNER.get_or_init(Default::default)
+}
+
+impl ShapeId {
+    pub fn new(name: &str) -> Self {
+        let mut interner = interner().lock().unwrap();
+        if let Some(&id) = interner.ids.get(name) {
+            return ShapeId(id);
+        }
+        let name: &'static str = Box::leak(name.to_string().into_boxed_str());
+        let id = interner.names.len() as u32;
+        interner.names.push(name);
+        interner.ids.insert(name, id);
+        ShapeId(id)
+    }
+
+    pub fn as_str(self) -> &'static str {
+        interner().lock().unwrap().names[self.0 as usize]
+    }
+}
+
+impl From<&str> for ShapeId {
+    fn from(name: &str) -> Self {
+        ShapeId::new(name)
+    }
+}
+
+// Ordered by name rather than by when the name was interned, so that anything sorted by
+// `ShapeId` comes out the same from run to run.
+impl Ord for ShapeId {
+    fn cmp(&self, other: &Self) -> Ordering {
+        if self == other {
+            Ordering::Equal
+        } else {
+            self.as_str().cmp(other.as_str())
+        }
+    }
+}
+
+impl PartialOrd for ShapeId {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Debug for ShapeId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("ShapeId").field(&self.as_str()).finish()
+    }
+}
+
+impl fmt::Display for ShapeId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for ShapeId {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let name = <std::borrow::Cow<'de, str>>::deserialize(deserializer)?;
+        Ok(ShapeId::new(&name))
+    }
+}
+
+#[test]
+fn interns_shape_names() {
+    let a = ShapeId::new("FunctionConfiguration");
+    let b = ShapeId::from("FunctionConfiguration");
+    assert_eq!(a, b);
+    assert_eq!(a.as_str(), "FunctionConfiguration");
+    assert!(ShapeId::new("Zebra") > ShapeId::new("Aardvark"));
+}
@@ -0,0 +1,23 @@
+use serde::Deserialize;
+use serde_json::Value;
+
+/// The contents of a `smoke.json` file.
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SmokeTests {
+    pub version: u32,
+    // The region to run the tests in.
+    pub default_region: String,
+    pub test_cases: Vec<SmokeTest>,
+}
+
+/// A single call to make against the live service.
+#[derive(Debug, PartialEq, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SmokeTest {
+    pub operation_name: String,
+    pub input: Value,
+    // Whether the call should return a modeled error, like calling `Invoke` on a
+    // function that doesn't exist, rather than succeed.
+    pub error_expected_from_service: bool,
+}
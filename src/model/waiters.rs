@@ -0,0 +1,63 @@
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// The contents of a `waiters-2.json` file.
+#[derive(Debug, PartialEq, Deserialize)]
+pub struct Waiters {
+    pub version: u32,
+    // Keyed by waiter name, e.g. `FunctionExists`.
+    pub waiters: HashMap<String, Waiter>,
+}
+
+/// Polls an operation until one of its acceptors reaches a terminal state.
+#[derive(Debug, PartialEq, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Waiter {
+    pub operation: String,
+    // Seconds between attempts.
+    pub delay: u32,
+    pub max_attempts: u32,
+    pub description: Option<String>,
+    // Evaluated in order after each attempt; the first match wins.
+    pub acceptors: Vec<Acceptor>,
+}
+
+#[derive(Debug, PartialEq, Deserialize, Clone)]
+pub struct Acceptor {
+    pub matcher: Matcher,
+    pub state: AcceptorState,
+    // A status code, error code, or the value a path expression should produce.
+    pub expected: Value,
+    // The JMESPath expression evaluated against the output by the path matchers.
+    pub argument: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Eq, Deserialize, Clone, Copy)]
+pub enum Matcher {
+    // `argument` evaluated against the output equals `expected`.
+    #[serde(rename = "path")]
+    Path,
+    // Every element of the list `argument` produces equals `expected`.
+    #[serde(rename = "pathAll")]
+    PathAll,
+    // Any element of the list `argument` produces equals `expected`.
+    #[serde(rename = "pathAny")]
+    PathAny,
+    // The HTTP status code equals `expected`.
+    #[serde(rename = "status")]
+    Status,
+    // The operation failed with the error code `expected`.
+    #[serde(rename = "error")]
+    Error,
+}
+
+#[derive(Debug, PartialEq, Eq, Deserialize, Clone, Copy)]
+pub enum AcceptorState {
+    #[serde(rename = "success")]
+    Success,
+    #[serde(rename = "failure")]
+    Failure,
+    #[serde(rename = "retry")]
+    Retry,
+}
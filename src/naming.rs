@@ -0,0 +1,450 @@
+use crate::{model::Metadata, Err};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+};
+
+// Strict and reserved keywords, across editions up to 2024.
+const KEYWORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "crate",
+    "do", "dyn", "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if", "impl",
+    "in", "let", "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref",
+    "return", "self", "Self", "static", "struct", "super", "trait", "true", "try", "type",
+    "typeof", "unsafe", "unsized", "use", "virtual", "where", "while", "yield",
+];
+
+// Keywords that can't be raw identifiers either.
+const NOT_RAW: &[&str] = &["crate", "self", "Self", "super"];
+
+/// Whether `name` is a Rust keyword, so can't be used as an identifier as is.
+pub fn is_keyword(name: &str) -> bool {
+    KEYWORDS.contains(&name)
+}
+
+/// Turns a shape or member name into a valid Rust identifier.
+///
+/// Characters that can't appear in an identifier become `_`, and a leading digit gets a `_`
+/// in front of it. Keywords become raw identifiers, e.g. `type` becomes `r#type`, except
+/// for the few that can't be, which get a trailing `_` instead: `self` becomes `self_`.
+pub fn rust_identifier(name: &str) -> String {
+    let mut ident = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect::<String>();
+    if ident.starts_with(|c: char| c.is_ascii_digit()) {
+        ident.insert(0, '_');
+    }
+
+    if NOT_RAW.contains(&ident.as_str()) {
+        ident.push('_');
+    } else if is_keyword(&ident) {
+        ident.insert_str(0, "r#");
+    }
+    ident
+}
+
+// Words AWS spells in mixed case, which would otherwise be split at the case change.
+const MIXED_CASE_WORDS: &[&str] = &["IoT", "IPv4", "IPv6", "iSCSI"];
+
+/// Splits a name into words at separators and case changes, keeping acronyms together:
+/// `DBInstance` is `DB` and `Instance`, and `ARNs` stays one word. Digits stick to the word
+/// before them, so `S3Bucket` is `S3` and `Bucket`.
+pub fn words(name: &str) -> Vec<&str> {
+    name.split(|c: char| !c.is_ascii_alphanumeric())
+        .flat_map(split_case)
+        .collect()
+}
+
+fn split_case(chunk: &str) -> Vec<&str> {
+    let bytes = chunk.as_bytes();
+    let len = bytes.len();
+    let mut words = vec![];
+    let mut start = 0;
+    while start < len {
+        let rest = &chunk[start..];
+        let mut end = match MIXED_CASE_WORDS
+            .iter()
+            .find(|word| rest.starts_with(*word) && !starts_lowercase(&rest[word.len()..]))
+        {
+            Some(word) => start + word.len(),
+            None if bytes[start].is_ascii_uppercase() => {
+                let run = start + count(rest, |b| b.is_ascii_uppercase());
+                if run - start == 1 || run == len || !bytes[run].is_ascii_lowercase() {
+                    // `Instance`, or an acronym at the end: `DB`, `ARN2`.
+                    run + count(&chunk[run..], |b| b.is_ascii_lowercase())
+                } else if bytes[run] == b's' && !starts_lowercase(&chunk[run + 1..]) {
+                    // A plural acronym: `ARNs`.
+                    run + 1
+                } else {
+                    // An acronym running into a word: `DB` in `DBInstance`.
+                    run - 1
+                }
+            }
+            None => start + count(rest, |b| b.is_ascii_lowercase()),
+        };
+        end += count(&chunk[end..], |b| b.is_ascii_digit());
+        words.push(&chunk[start..end]);
+        start = end;
+    }
+    words
+}
+
+fn count(s: &str, f: impl Fn(u8) -> bool) -> usize {
+    s.bytes().take_while(|&b| f(b)).count()
+}
+
+fn starts_lowercase(s: &str) -> bool {
+    s.starts_with(|c: char| c.is_ascii_lowercase())
+}
+
+/// `DBInstanceIdentifier` becomes `db_instance_identifier`.
+pub fn to_snake_case(name: &str) -> String {
+    words(name)
+        .iter()
+        .map(|word| word.to_ascii_lowercase())
+        .collect::<Vec<_>>()
+        .join("_")
+}
+
+/// `SCREAMING_SNAKE_CASE`, for constants: `DBInstance` becomes `DB_INSTANCE`.
+pub fn to_screaming_snake_case(name: &str) -> String {
+    words(name)
+        .iter()
+        .map(|word| word.to_ascii_uppercase())
+        .collect::<Vec<_>>()
+        .join("_")
+}
+
+/// `PascalCase`, with acronyms capitalized like words as the Rust API guidelines ask:
+/// `DBInstance` becomes `DbInstance`.
+pub fn to_pascal_case(name: &str) -> String {
+    words(name)
+        .iter()
+        .map(|word| {
+            let mut word = word.to_ascii_lowercase();
+            word[..1].make_ascii_uppercase();
+            word
+        })
+        .collect()
+}
+
+/// The Rust name for a field or function generated from a model name.
+pub fn field_name(name: &str) -> String {
+    rust_identifier(&to_snake_case(name))
+}
+
+/// The Rust name for a type generated from a model name.
+pub fn type_name(name: &str) -> String {
+    rust_identifier(&to_pascal_case(name))
+}
+
+/// Model names that map to the same Rust name, and couldn't be told apart.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct NameCollision {
+    pub rust_name: String,
+    // Sorted.
+    pub names: Vec<String>,
+}
+
+impl fmt::Display for NameCollision {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "`{}` all map to `{}`",
+            self.names.join("`, `"),
+            self.rust_name
+        )
+    }
+}
+
+impl std::error::Error for NameCollision {}
+
+/// Converts names that share a namespace, such as the members of a structure, the values
+/// of an enum, or the modules of a crate, making sure no two end up the same.
+///
+/// When several names convert to the same Rust name, the one already spelled that way keeps
+/// it, or failing that the one that sorts first. The rest get a number appended in name
+/// order: `Value` and `value` become `value_2` and `value`. If a numbered name is taken too,
+/// this gives up rather than keep guessing.
+pub fn assign_names<'n>(
+    names: impl IntoIterator<Item = &'n str>,
+    convert: impl Fn(&str) -> String,
+) -> Result<BTreeMap<&'n str, String>, NameCollision> {
+    let mut groups = BTreeMap::<String, Vec<&str>>::new();
+    for name in names {
+        let names = groups.entry(convert(name)).or_default();
+        if !names.contains(&name) {
+            names.push(name);
+        }
+    }
+
+    let mut assigned = BTreeMap::new();
+    let mut suffixed = vec![];
+    for (rust_name, names) in &groups {
+        let base = rust_name.trim_start_matches("r#");
+        let mut names = names.clone();
+        names.sort_unstable();
+        let keeper = names.iter().position(|name| *name == base).unwrap_or(0);
+        assigned.insert(names.remove(keeper), rust_name.clone());
+
+        // `value_2` for fields and modules, but `Value2` for types and variants.
+        let separator = if base.starts_with(|c: char| c.is_ascii_uppercase()) {
+            ""
+        } else {
+            "_"
+        };
+        for (i, name) in names.into_iter().enumerate() {
+            suffixed.push((name, format!("{}{}{}", base, separator, i + 2), rust_name));
+        }
+    }
+    for (name, rust_name, original) in suffixed {
+        if let Some(taken) = groups.get(&rust_name) {
+            let mut names = groups[original].clone();
+            names.extend(taken);
+            names.sort_unstable();
+            return Err(NameCollision {
+                rust_name,
+                names: names.into_iter().map(str::to_string).collect(),
+            });
+        }
+        assigned.insert(name, rust_name);
+    }
+
+    Ok(assigned)
+}
+
+/// The variant name for one of an enum's values, e.g. `t2.micro` becomes `T2Micro` and
+/// `us-east-1` becomes `UsEast1`. Returns `None` for values without any letters or digits,
+/// like `*`.
+///
+/// Numbers next to each other keep a `_` between them, and a leading one gets a `V` in front:
+/// `1.0` becomes `V1_0`. A value of `Unknown` becomes `UnknownValue`, leaving `Unknown` for
+/// the variant that holds values the model doesn't list.
+pub fn variant_name(value: &str) -> Option<String> {
+    let mut variant = String::new();
+    for word in words(value) {
+        let digit = |c: char| c.is_ascii_digit();
+        if variant.ends_with(digit) && word.starts_with(digit) {
+            variant.push('_');
+        }
+        variant.push_str(&to_pascal_case(word));
+    }
+    if variant.is_empty() {
+        return None;
+    }
+    if variant.starts_with(|c: char| c.is_ascii_digit()) {
+        variant.insert(0, 'V');
+    }
+    if variant == "Unknown" {
+        variant.push_str("Value");
+    }
+    Some(rust_identifier(&variant))
+}
+
+/// Variant names for all of an enum's values, keyed by value and kept apart with
+/// `assign_names`.
+///
+/// `overrides` names the variants for particular values, which is the only way to name
+/// values `variant_name` can't. Overridden names are used as is.
+pub fn enum_variants<'v>(
+    values: impl IntoIterator<Item = &'v str>,
+    overrides: &HashMap<String, String>,
+) -> Result<BTreeMap<&'v str, String>, Err> {
+    let mut variants = HashMap::new();
+    for value in values {
+        let variant = match overrides.get(value) {
+            Some(variant) => variant.clone(),
+            None => variant_name(value).ok_or_else(|| {
+                format!(
+                    "enum value `{}` can't be a variant name; give it one with an override",
+                    value
+                )
+            })?,
+        };
+        variants.insert(value, variant);
+    }
+    Ok(assign_names(variants.keys().copied(), |value| {
+        variants[value].clone()
+    })?)
+}
+
+/// What a service's generated crate and the items in it are called, following the official
+/// SDK: AWS Lambda is `aws-sdk-lambda`, with `lambda` as its module name and `Lambda`
+/// prefixing its service-level types.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ServiceNaming {
+    // Also the name of the directory the crate is generated into.
+    pub crate_name: String,
+    // How code refers to the crate, e.g. `aws_sdk_lambda`.
+    pub crate_ident: String,
+    pub module_name: String,
+    pub type_prefix: String,
+}
+
+impl ServiceNaming {
+    /// Names a service after its `service_id`, with spaces and punctuation dropped. Models
+    /// without one fall back to the service abbreviation, minus any "AWS" or "Amazon" in
+    /// front, and then to the endpoint prefix.
+    pub fn new(metadata: &Metadata) -> Self {
+        let abbreviation = metadata.service_abbreviation.as_deref().map(|name| {
+            name.trim_start_matches("AWS ")
+                .trim_start_matches("Amazon ")
+        });
+        let name = [
+            Some(metadata.service_id.as_str()),
+            abbreviation,
+            Some(metadata.endpoint_prefix.as_str()),
+        ]
+        .iter()
+        .flatten()
+        .find(|name| name.chars().any(|c| c.is_ascii_alphanumeric()))
+        .copied()
+        .unwrap_or_default();
+
+        let module = name
+            .chars()
+            .filter(char::is_ascii_alphanumeric)
+            .collect::<String>()
+            .to_ascii_lowercase();
+        ServiceNaming {
+            crate_name: format!("aws-sdk-{}", module),
+            crate_ident: format!("aws_sdk_{}", module),
+            module_name: rust_identifier(&module),
+            type_prefix: type_name(name),
+        }
+    }
+}
+
+#[test]
+fn escapes_keywords() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    assert_eq!(rust_identifier("type"), "r#type");
+    assert_eq!(rust_identifier("match"), "r#match");
+    assert_eq!(rust_identifier("async"), "r#async");
+    assert_eq!(rust_identifier("self"), "self_");
+    assert_eq!(rust_identifier("Self"), "Self_");
+    assert_eq!(rust_identifier("Type"), "Type");
+    assert_eq!(rust_identifier("FunctionName"), "FunctionName");
+    assert_eq!(rust_identifier("x-amz-meta"), "x_amz_meta");
+    assert_eq!(rust_identifier("3DES"), "_3DES");
+
+    Ok(())
+}
+
+#[test]
+fn converts_case() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let snake = |name| to_snake_case(name);
+    assert_eq!(snake("DBInstance"), "db_instance");
+    assert_eq!(snake("ARNs"), "arns");
+    assert_eq!(snake("ResourceARNs"), "resource_arns");
+    assert_eq!(snake("IoT"), "iot");
+    assert_eq!(snake("IoTAnalytics"), "iot_analytics");
+    assert_eq!(snake("FunctionARN"), "function_arn");
+    assert_eq!(snake("HTTPSListener"), "https_listener");
+    assert_eq!(snake("IPv6Address"), "ipv6_address");
+    assert_eq!(snake("S3Bucket"), "s3_bucket");
+    assert_eq!(snake("Ec2InstanceId"), "ec2_instance_id");
+    assert_eq!(snake("x-amz-meta"), "x_amz_meta");
+    assert_eq!(snake("max_items"), "max_items");
+
+    assert_eq!(to_pascal_case("DBInstance"), "DbInstance");
+    assert_eq!(to_pascal_case("list-functions"), "ListFunctions");
+    assert_eq!(to_screaming_snake_case("DBInstance"), "DB_INSTANCE");
+
+    assert_eq!(field_name("Type"), "r#type");
+    assert_eq!(type_name("self"), "Self_");
+
+    Ok(())
+}
+
+#[test]
+fn disambiguates_collisions() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let fields = assign_names(vec!["Value", "value", "Name"], field_name)?;
+    assert_eq!(fields["value"], "value");
+    assert_eq!(fields["Value"], "value_2");
+    assert_eq!(fields["Name"], "name");
+
+    let variants = assign_names(vec!["ACTIVE", "Active", "active"], type_name)?;
+    assert_eq!(variants["Active"], "Active");
+    assert_eq!(variants["ACTIVE"], "Active2");
+    assert_eq!(variants["active"], "Active3");
+
+    let fields = assign_names(vec!["Type", "type"], field_name)?;
+    assert_eq!(fields["type"], "r#type");
+    assert_eq!(fields["Type"], "type_2");
+
+    assert_eq!(
+        assign_names(vec!["Value", "value", "value_2"], field_name),
+        Err(NameCollision {
+            rust_name: "value_2".to_string(),
+            names: vec![
+                "Value".to_string(),
+                "value".to_string(),
+                "value_2".to_string()
+            ],
+        })
+    );
+
+    Ok(())
+}
+
+#[test]
+fn names_enum_variants() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    assert_eq!(variant_name("t2.micro").as_deref(), Some("T2Micro"));
+    assert_eq!(variant_name("us-east-1").as_deref(), Some("UsEast1"));
+    assert_eq!(variant_name("ACTIVE").as_deref(), Some("Active"));
+    assert_eq!(variant_name("nodejs12.x").as_deref(), Some("Nodejs12X"));
+    assert_eq!(variant_name("1.0").as_deref(), Some("V1_0"));
+    assert_eq!(variant_name("Unknown").as_deref(), Some("UnknownValue"));
+    assert_eq!(variant_name("Self").as_deref(), Some("Self_"));
+    assert_eq!(variant_name("*"), None);
+
+    let mut overrides = HashMap::new();
+    assert!(enum_variants(vec!["*", "ACTIVE"], &overrides).is_err());
+    overrides.insert("*".to_string(), "All".to_string());
+    let variants = enum_variants(vec!["*", "t2.micro", "t2-micro"], &overrides)?;
+    assert_eq!(variants["*"], "All");
+    assert_eq!(variants["t2-micro"], "T2Micro");
+    assert_eq!(variants["t2.micro"], "T2Micro2");
+
+    Ok(())
+}
+
+#[test]
+fn names_service_crates() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let def = crate::model::BotocoreModel::from_path("test-data/lambda/2015-03-31/service-2.json")?;
+    assert_eq!(
+        ServiceNaming::new(&def.metadata),
+        ServiceNaming {
+            crate_name: "aws-sdk-lambda".to_string(),
+            crate_ident: "aws_sdk_lambda".to_string(),
+            module_name: "lambda".to_string(),
+            type_prefix: "Lambda".to_string(),
+        }
+    );
+
+    let metadata = |service_id: &str| {
+        serde_json::from_str::<Metadata>(&format!(
+            r#"{{
+                "apiVersion": "2014-06-30",
+                "endpointPrefix": "email",
+                "protocol": "query",
+                "serviceAbbreviation": "Amazon SES",
+                "serviceFullName": "Amazon Simple Email Service",
+                "serviceId": "{}",
+                "signatureVersion": "v4"
+            }}"#,
+            service_id
+        ))
+    };
+    let naming = ServiceNaming::new(&metadata("Cognito Identity Provider")?);
+    assert_eq!(naming.crate_name, "aws-sdk-cognitoidentityprovider");
+    assert_eq!(naming.type_prefix, "CognitoIdentityProvider");
+    let naming = ServiceNaming::new(&metadata("S3 Control")?);
+    assert_eq!(naming.module_name, "s3control");
+    assert_eq!(naming.type_prefix, "S3Control");
+    let naming = ServiceNaming::new(&metadata("")?);
+    assert_eq!(naming.crate_name, "aws-sdk-ses");
+    assert_eq!(naming.type_prefix, "Ses");
+
+    Ok(())
+}
@@ -0,0 +1,304 @@
+use crate::{
+    graph::{Edge, ModelGraph},
+    model::{HttpBindings, Location, Markdown, Operation, Shape, ShapeId, ShapeMember},
+    visit::{walk_shape, ShapeVisitor},
+};
+use std::fmt;
+
+/// An operation with its input, output, and error shapes looked up, borrowed from the
+/// model they came from.
+#[derive(Debug, PartialEq)]
+pub struct ResolvedOperation<'a> {
+    pub name: &'a str,
+    pub http: &'a HttpBindings,
+    pub input: &'a Shape,
+    pub output: Option<&'a Shape>,
+    pub errors: Vec<(ShapeId, &'a Shape)>,
+    pub documentation: &'a Markdown,
+    // The input and output members bound to the HTTP body, if any. Everything else is
+    // bound to headers, the query string, the URI, or serialized into the body.
+    pub input_payload: Option<&'a str>,
+    pub output_payload: Option<&'a str>,
+    // Where every input and output member goes in the HTTP request and response.
+    pub input_locations: Vec<(String, Location)>,
+    pub output_locations: Vec<(String, Location)>,
+}
+
+impl ResolvedOperation<'_> {
+    /// The output member populated from the response's HTTP status code, if any.
+    pub fn status_code_member(&self) -> Option<&str> {
+        self.output_locations
+            .iter()
+            .find(|(_, location)| *location == Location::StatusCode)
+            .map(|(name, _)| name.as_str())
+    }
+}
+
+/// An operation refers to a shape the model doesn't define.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ResolveError {
+    pub operation: String,
+    pub missing_shape: ShapeId,
+}
+
+impl fmt::Display for ResolveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "operation `{}` refers to undefined shape `{}`",
+            self.operation, self.missing_shape
+        )
+    }
+}
+
+impl std::error::Error for ResolveError {}
+
+impl<'a> ModelGraph<'a> {
+    /// Looks up the input, output, and error shapes of an operation.
+    ///
+    /// Fails on the first of them the model doesn't define; `ModelGraph::validate` reports
+    /// every undefined shape in the model at once.
+    pub fn resolve(&self, op: &'a Operation) -> Result<ResolvedOperation<'a>, ResolveError> {
+        let shape = |id: ShapeId| {
+            self.shape(id).ok_or_else(|| ResolveError {
+                operation: op.name.clone(),
+                missing_shape: id,
+            })
+        };
+        let input = shape(op.input.shape)?;
+        let output = op.output.as_ref().map(|o| shape(o.shape)).transpose()?;
+        Ok(ResolvedOperation {
+            name: &op.name,
+            http: &op.http,
+            input,
+            output,
+            errors: op
+                .errors
+                .iter()
+                .map(|o| Ok((o.shape, shape(o.shape)?)))
+                .collect::<Result<_, ResolveError>>()?,
+            documentation: &op.documentation,
+            input_payload: input.payload(),
+            output_payload: output.and_then(Shape::payload),
+            input_locations: input.member_locations(),
+            output_locations: output.map(Shape::member_locations).unwrap_or_default(),
+        })
+    }
+}
+
+/// A shape whose members can be followed to the shapes they target, and so on, as deep as
+/// codegen needs to go. Recursive shapes can be followed forever; `ResolvedMember::back_edge`
+/// marks where they loop.
+#[derive(Clone, Copy)]
+pub struct ResolvedShape<'g, 'a> {
+    graph: &'g ModelGraph<'a>,
+    id: ShapeId,
+    shape: &'a Shape,
+}
+
+/// A member of a `ResolvedShape`, along with the shape it targets.
+#[derive(Debug, Clone, Copy)]
+pub struct ResolvedMember<'g, 'a> {
+    pub name: &'a str,
+    pub member: &'a ShapeMember,
+    pub target: ResolvedShape<'g, 'a>,
+    // See `Edge::back_edge`.
+    pub back_edge: bool,
+}
+
+impl<'g, 'a> ResolvedShape<'g, 'a> {
+    pub fn id(&self) -> ShapeId {
+        self.id
+    }
+
+    pub fn shape(&self) -> &'a Shape {
+        self.shape
+    }
+
+    /// The shape's members, in the same order as `ModelGraph::neighbors`.
+    pub fn members(&self) -> impl Iterator<Item = ResolvedMember<'g, 'a>> {
+        let graph = self.graph;
+        graph
+            .neighbors(self.id)
+            .iter()
+            .map(move |edge| ResolvedMember {
+                name: edge.name,
+                member: edge.member,
+                // `ModelGraph::resolve_shape` checked everything reachable from the root.
+                target: ResolvedShape {
+                    graph,
+                    id: edge.target,
+                    shape: graph
+                        .shape(edge.target)
+                        .expect("resolved shapes only reach defined shapes"),
+                },
+                back_edge: edge.back_edge,
+            })
+    }
+
+    /// The member called `name`, if the shape has one.
+    pub fn member(&self, name: &str) -> Option<ResolvedMember<'g, 'a>> {
+        self.members().find(|member| member.name == name)
+    }
+}
+
+// Printing the shape rather than the whole graph, which would include the entire model.
+impl fmt::Debug for ResolvedShape<'_, '_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ResolvedShape")
+            .field("id", &self.id)
+            .field("shape", self.shape)
+            .finish()
+    }
+}
+
+impl<'a> ModelGraph<'a> {
+    /// Resolves a shape and everything it references, directly or not.
+    ///
+    /// Fails with the first undefined shape that's reachable from `id`, if any, so that
+    /// following the members of the result never does.
+    pub fn resolve_shape(&self, id: ShapeId) -> Result<ResolvedShape<'_, 'a>, ShapeId> {
+        struct Undefined<'g, 'a>(&'g ModelGraph<'a>, Option<ShapeId>);
+
+        impl<'a> ShapeVisitor<'a> for Undefined<'_, 'a> {
+            fn visit_edge(&mut self, _from: ShapeId, edge: &Edge<'a>) {
+                if self.1.is_none() && self.0.shape(edge.target).is_none() {
+                    self.1 = Some(edge.target);
+                }
+            }
+        }
+
+        let shape = self.shape(id).ok_or(id)?;
+        let mut undefined = Undefined(self, None);
+        walk_shape(self, id, &mut undefined);
+        match undefined.1 {
+            Some(missing) => Err(missing),
+            None => Ok(ResolvedShape {
+                graph: self,
+                id,
+                shape,
+            }),
+        }
+    }
+}
+
+#[test]
+fn it_works() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let def = std::fs::read_to_string("test-data/lambda/2015-03-31/service-2.json")?;
+    let def = serde_json::from_str::<crate::model::BotocoreModel>(&def)?;
+    let graph = ModelGraph::new(&def);
+    let resolved = graph.resolve(&def.operations["CreateFunction"])?;
+    dbg!(&resolved);
+
+    Ok(())
+}
+
+#[test]
+fn resolves_payload_members() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let def = std::fs::read_to_string("test-data/lambda/2015-03-31/service-2.json")?;
+    let def = serde_json::from_str::<crate::model::BotocoreModel>(&def)?;
+    let graph = ModelGraph::new(&def);
+    let resolved = graph.resolve(&def.operations["InvokeAsync"])?;
+    assert_eq!(resolved.input_payload, Some("InvokeArgs"));
+    let member = match resolved.input {
+        Shape::Structure { members, .. } => &members["InvokeArgs"],
+        other => panic!("expected a structure shape, got {:?}", other),
+    };
+    assert!(member.is_streaming(&def.shapes[&ShapeId::from("BlobStream")]));
+
+    Ok(())
+}
+
+#[test]
+fn plans_member_locations() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let def = crate::model::BotocoreModel::from_path("test-data/lambda/2015-03-31/service-2.json")?;
+    let graph = ModelGraph::new(&def);
+    let resolved = graph.resolve(&def.operations["Invoke"])?;
+    let location = |locations: &[(String, Location)], name: &str| {
+        locations
+            .iter()
+            .find(|(member, _)| member == name)
+            .map(|(_, location)| location.clone())
+    };
+    assert_eq!(
+        location(&resolved.input_locations, "FunctionName"),
+        Some(Location::Uri("FunctionName".to_string()))
+    );
+    assert_eq!(
+        location(&resolved.input_locations, "Payload"),
+        Some(Location::Payload)
+    );
+    assert_eq!(
+        location(&resolved.output_locations, "LogResult"),
+        Some(Location::Header("X-Amz-Log-Result".to_string()))
+    );
+    assert_eq!(resolved.status_code_member(), Some("StatusCode"));
+
+    let member = serde_json::from_str::<crate::model::ShapeMember>(
+        r#"{"shape": "ClientContext", "location": "header", "locationName": "X-Amz-Client-Context", "jsonvalue": true}"#,
+    )?;
+    assert_eq!(
+        member.location("ClientContext"),
+        Location::JsonHeader("X-Amz-Client-Context".to_string())
+    );
+    let member = serde_json::from_str::<crate::model::ShapeMember>(
+        r#"{"shape": "Metadata", "location": "headers", "locationName": "x-amz-meta-"}"#,
+    )?;
+    assert_eq!(
+        member.location("Metadata"),
+        Location::Headers {
+            prefix: "x-amz-meta-".to_string()
+        }
+    );
+
+    Ok(())
+}
+
+#[test]
+fn reports_missing_shapes() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let mut def = crate::model::BotocoreModel::from_path("test-data/recursive.json")?;
+    def.shapes.remove(&ShapeId::from("ValidationException"));
+    let graph = ModelGraph::new(&def);
+    assert_eq!(
+        graph.resolve(&def.operations["PutItem"]),
+        Err(ResolveError {
+            operation: "PutItem".to_string(),
+            missing_shape: ShapeId::from("ValidationException"),
+        })
+    );
+
+    Ok(())
+}
+
+#[test]
+fn resolves_nested_shapes() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let mut def = crate::model::BotocoreModel::from_path("test-data/recursive.json")?;
+    let graph = ModelGraph::new(&def);
+    let value = graph
+        .resolve_shape(ShapeId::from("AttributeValue"))
+        .map_err(|id| format!("undefined shape `{}`", id))?;
+    let list = value.member("L").expect("AttributeValue has an L member");
+    assert!(!list.back_edge);
+    let element = list.target.member("member").expect("lists have a member");
+    assert!(element.back_edge);
+    assert_eq!(element.target.id(), value.id());
+    let string = element
+        .target
+        .member("S")
+        .expect("AttributeValue has an S member");
+    assert_eq!(
+        string.target.shape(),
+        &def.shapes[&ShapeId::from("StringAttributeValue")]
+    );
+
+    def.shapes.remove(&ShapeId::from("StringAttributeValue"));
+    let graph = ModelGraph::new(&def);
+    assert_eq!(
+        graph
+            .resolve_shape(ShapeId::from("PutItemInput"))
+            .map(|shape| shape.id()),
+        Err(ShapeId::from("StringAttributeValue"))
+    );
+
+    Ok(())
+}
@@ -0,0 +1,170 @@
+use crate::{
+    graph::{Edge, ModelGraph},
+    model::{Operation, Shape, ShapeId},
+};
+use std::collections::HashSet;
+
+/// Callbacks for a depth-first walk over a `ModelGraph`. Every method defaults to doing
+/// nothing, so visitors only implement the events they care about.
+pub trait ShapeVisitor<'a> {
+    /// Called the first time the walk reaches a shape. Returning `false` skips its members.
+    fn visit_shape(&mut self, _id: ShapeId, _shape: &'a Shape) -> bool {
+        true
+    }
+
+    /// Called after every member of a shape has been walked.
+    fn leave_shape(&mut self, _id: ShapeId, _shape: &'a Shape) {}
+
+    /// Called for each member reference, before the walk follows it.
+    fn visit_edge(&mut self, _from: ShapeId, _edge: &Edge<'a>) {}
+
+    /// Called instead of following a member reference that leads back to a shape the walk
+    /// is still inside of.
+    fn visit_cycle(&mut self, _from: ShapeId, _edge: &Edge<'a>) {}
+}
+
+/// Walks a shape and everything it references, depth-first in member order.
+///
+/// Each shape is visited at most once. References to shapes the model doesn't define are
+/// passed to `visit_edge` but not followed.
+pub fn walk_shape<'a, V>(graph: &ModelGraph<'a>, id: ShapeId, visitor: &mut V)
+where
+    V: ShapeVisitor<'a> + ?Sized,
+{
+    Walk::new(graph).shape(id, visitor);
+}
+
+/// Walks an operation's input, output, and errors, in that order, and everything they
+/// reference. Shapes shared between them are visited once.
+pub fn walk_operation<'a, V>(graph: &ModelGraph<'a>, op: &Operation, visitor: &mut V)
+where
+    V: ShapeVisitor<'a> + ?Sized,
+{
+    let mut walk = Walk::new(graph);
+    walk.shape(op.input.shape, visitor);
+    for reference in op.output.iter().chain(&op.errors) {
+        walk.shape(reference.shape, visitor);
+    }
+}
+
+/// Walks every shape in the model, starting from each one in name order that an earlier
+/// walk hasn't reached yet.
+pub fn walk_model<'a, V>(graph: &ModelGraph<'a>, visitor: &mut V)
+where
+    V: ShapeVisitor<'a> + ?Sized,
+{
+    let mut walk = Walk::new(graph);
+    for (id, _) in graph.shapes() {
+        walk.shape(id, visitor);
+    }
+}
+
+struct Walk<'g, 'a> {
+    graph: &'g ModelGraph<'a>,
+    visited: HashSet<ShapeId>,
+    // The shapes between the root and the shape being visited.
+    path: HashSet<ShapeId>,
+}
+
+impl<'g, 'a> Walk<'g, 'a> {
+    fn new(graph: &'g ModelGraph<'a>) -> Self {
+        Walk {
+            graph,
+            visited: HashSet::new(),
+            path: HashSet::new(),
+        }
+    }
+
+    fn shape<V>(&mut self, id: ShapeId, visitor: &mut V)
+    where
+        V: ShapeVisitor<'a> + ?Sized,
+    {
+        let shape = match self.graph.shape(id) {
+            Some(shape) if self.visited.insert(id) => shape,
+            _ => return,
+        };
+        if visitor.visit_shape(id, shape) {
+            self.path.insert(id);
+            for edge in self.graph.neighbors(id) {
+                if self.path.contains(&edge.target) {
+                    visitor.visit_cycle(id, edge);
+                } else {
+                    visitor.visit_edge(id, edge);
+                    self.shape(edge.target, visitor);
+                }
+            }
+            self.path.remove(&id);
+        }
+        visitor.leave_shape(id, shape);
+    }
+}
+
+#[test]
+fn walks_depth_first_around_cycles() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    #[derive(Default)]
+    struct Recorder {
+        visited: Vec<String>,
+        cycles: Vec<String>,
+    }
+
+    impl ShapeVisitor<'_> for Recorder {
+        fn visit_shape(&mut self, id: ShapeId, _shape: &Shape) -> bool {
+            self.visited.push(id.to_string());
+            true
+        }
+
+        fn visit_cycle(&mut self, from: ShapeId, edge: &Edge) {
+            self.cycles
+                .push(format!("{}.{} -> {}", from, edge.name, edge.target));
+        }
+    }
+
+    let def = crate::model::BotocoreModel::from_path("test-data/recursive.json")?;
+    let graph = ModelGraph::new(&def);
+    let mut recorder = Recorder::default();
+    walk_operation(&graph, &def.operations["PutItem"], &mut recorder);
+    assert_eq!(
+        recorder.visited,
+        vec![
+            "PutItemInput",
+            "AttributeMap",
+            "AttributeName",
+            "AttributeValue",
+            "ListAttributeValue",
+            "MapAttributeValue",
+            "StringAttributeValue",
+            "PutItemOutput",
+            "ValidationException",
+            "ErrorMessage",
+        ]
+    );
+    assert_eq!(
+        recorder.cycles,
+        vec![
+            "ListAttributeValue.member -> AttributeValue",
+            "MapAttributeValue.value -> AttributeValue",
+        ]
+    );
+
+    let mut recorder = Recorder::default();
+    walk_shape(&graph, ShapeId::from("ListAttributeValue"), &mut recorder);
+    assert_eq!(
+        recorder.visited,
+        vec![
+            "ListAttributeValue",
+            "AttributeValue",
+            "MapAttributeValue",
+            "AttributeName",
+            "StringAttributeValue",
+        ]
+    );
+    assert_eq!(
+        recorder.cycles,
+        vec![
+            "AttributeValue.L -> ListAttributeValue",
+            "MapAttributeValue.value -> AttributeValue",
+        ]
+    );
+
+    Ok(())
+}